@@ -0,0 +1,231 @@
+// Personal accomplishment report: bugs I resolved or closed in a month, from revision history
+use crate::azure_devops::{AzureDevOpsClient, Revision};
+use crate::bug_analysis::categorize_text;
+use crate::category_style::CategoryStyles;
+use crate::process::{self, ProcessModel};
+use crate::storage;
+use crate::wiql::WiqlQuery;
+use crate::AppConfig;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use tauri::AppHandle;
+
+/// Every fix found so far, keyed by bug id. Kept across runs so earlier months still report
+/// correctly after a bug is reassigned or drops out of the query.
+const FIXED_STORE: &str = "fixed_bugs";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixedBug {
+    pub id: u64,
+    pub title: String,
+    /// Category label at the time of the report.
+    pub category: String,
+    pub created_date: DateTime<Utc>,
+    /// When I moved it into a done state (the latest time, if it was reopened).
+    pub fixed_date: DateTime<Utc>,
+    pub state: String,
+}
+
+impl FixedBug {
+    pub fn days_to_fix(&self) -> f64 {
+        (self.fixed_date - self.created_date).num_minutes() as f64 / (24.0 * 60.0)
+    }
+}
+
+fn parse_date(date: Option<&str>) -> Option<DateTime<Utc>> {
+    date.and_then(|d| DateTime::parse_from_rfc3339(d).ok()).map(|d| d.with_timezone(&Utc))
+}
+
+/// The fix in a bug's revisions: the last transition into a done state made by one of `me`.
+fn find_fix(id: u64, revisions: &[Revision], me: &[String], styles: &CategoryStyles, process: &ProcessModel) -> Option<FixedBug> {
+    let is_done = |state: &str| process.is_done(state);
+    let fix = revisions.iter().enumerate().rev().find_map(|(i, rev)| {
+        let entered_done = is_done(&rev.state) && (i == 0 || !is_done(&revisions[i - 1].state));
+        let by_me = rev.changed_by.as_ref().is_some_and(|p| me.iter().any(|m| p.unique_name.eq_ignore_ascii_case(m)));
+        (entered_done && by_me).then_some(rev)
+    })?;
+    let latest = revisions.last()?;
+    // Reopened since: no longer a fix.
+    if !is_done(&latest.state) {
+        return None;
+    }
+    Some(FixedBug {
+        id,
+        title: latest.title.clone(),
+        category: styles.get(&categorize_text(&latest.title, latest.description.as_deref())).label,
+        created_date: parse_date(revisions.first()?.created_date.as_deref())?,
+        fixed_date: parse_date(fix.changed_date.as_deref())?,
+        state: latest.state.clone(),
+    })
+}
+
+/// First day of `month` (`YYYY-MM`, default: the current month) and of the month after it.
+pub fn month_bounds(month: Option<&str>) -> Result<(NaiveDate, NaiveDate), String> {
+    let start = match month.map(str::trim).filter(|m| !m.is_empty()) {
+        Some(m) => NaiveDate::parse_from_str(&format!("{}-01", m), "%Y-%m-%d").map_err(|_| format!("Invalid month '{}' (use YYYY-MM)", m))?,
+        None => Utc::now().date_naive().with_day(1).ok_or("Invalid current date")?,
+    };
+    let next = if start.month() == 12 { start.with_year(start.year() + 1).and_then(|d| d.with_month(1)) } else { start.with_month(start.month() + 1) };
+    Ok((start, next.ok_or("Invalid month")?))
+}
+
+/// Bugs I fixed in the month starting at `start`: refreshes the stored history from the revisions of
+/// bugs resolved or closed by me since then, and returns the stored fixes inside the month.
+pub fn fixed_bugs(app: &AppHandle, client: &AzureDevOpsClient, start: NaiveDate, end: NaiveDate) -> Result<Vec<FixedBug>, String> {
+    let me = client.config.my_identities();
+    let wiql = WiqlQuery::new()
+        .work_item_type("Bug")
+        .resolved_or_closed_by(&me)
+        .changed_since(&start.format("%Y-%m-%d").to_string())
+        .build();
+    let mut history: HashMap<u64, FixedBug> = storage::load_json(app, FIXED_STORE)?;
+    let process = process::model(client);
+    for id in client.run_wiql(&wiql)? {
+        let revisions = client.fetch_revisions(id)?;
+        match find_fix(id, &revisions, &me, &client.config.category_styles, &process) {
+            Some(fix) => history.insert(id, fix),
+            None => history.remove(&id),
+        };
+    }
+    storage::save_json(app, FIXED_STORE, &history)?;
+    let mut fixed: Vec<FixedBug> = history
+        .into_values()
+        .filter(|f| f.fixed_date.date_naive() >= start && f.fixed_date.date_naive() < end)
+        .collect();
+    fixed.sort_by_key(|f| f.fixed_date);
+    Ok(fixed)
+}
+
+/// Markdown summary for a performance review: totals, fixes per week, per category, and the list.
+pub fn markdown(fixed: &[FixedBug], who: &str, start: NaiveDate, bug_url: impl Fn(u64) -> String) -> String {
+    let mut md = format!("# Bugs fixed by {} — {}\n\n", who, start.format("%B %Y"));
+    if fixed.is_empty() {
+        md.push_str("No bugs resolved or closed this month.\n");
+        return md;
+    }
+    let mean = fixed.iter().map(FixedBug::days_to_fix).sum::<f64>() / fixed.len() as f64;
+    md.push_str(&format!("**{} bug(s) resolved or closed** · mean time to resolve **{:.1} days**\n\n", fixed.len(), mean));
+
+    let mut weeks: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+    for fix in fixed {
+        let day = fix.fixed_date.date_naive();
+        *weeks.entry(day - Duration::days(day.weekday().num_days_from_monday() as i64)).or_default() += 1;
+    }
+    md.push_str("## Per week\n\n| Week of | Fixed |\n|---|---|\n");
+    for (week, count) in weeks {
+        md.push_str(&format!("| {} | {} |\n", week.format("%Y-%m-%d"), count));
+    }
+
+    let mut categories: BTreeMap<&str, usize> = BTreeMap::new();
+    for fix in fixed {
+        *categories.entry(fix.category.as_str()).or_default() += 1;
+    }
+    let mut categories: Vec<(&str, usize)> = categories.into_iter().collect();
+    categories.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    md.push_str("\n## Categories\n\n| Category | Fixed |\n|---|---|\n");
+    for (category, count) in categories {
+        md.push_str(&format!("| {} | {} |\n", category, count));
+    }
+
+    md.push_str("\n## Bugs\n\n");
+    for fix in fixed {
+        md.push_str(&format!(
+            "- [#{}]({}) {} — {}, {} {} ({:.1} days)\n",
+            fix.id,
+            bug_url(fix.id),
+            fix.title.replace('\n', " "),
+            fix.category,
+            fix.state.to_lowercase(),
+            fix.fixed_date.format("%Y-%m-%d"),
+            fix.days_to_fix()
+        ));
+    }
+    md
+}
+
+/// Accomplishment report for `month` (`YYYY-MM`, default: this month) as Markdown.
+pub fn report(app: &AppHandle, month: Option<&str>) -> Result<String, String> {
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    let (start, end) = month_bounds(month)?;
+    let who = config.user_email.clone();
+    let client = AzureDevOpsClient::new(config);
+    let fixed = fixed_bugs(app, &client, start, end)?;
+    Ok(markdown(&fixed, &who, start, |id| client.config.bug_url(id)))
+}
+
+#[tauri::command]
+/// "Bugs fixed by me" Markdown report for `month` (`YYYY-MM`, default: this month).
+pub fn get_accomplishment_report(app: AppHandle, month: Option<String>) -> Result<String, String> {
+    report(&app, month.as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::azure_devops::Identity;
+
+    fn revision(rev: u64, state: &str, by: &str, day: u32) -> Revision {
+        Revision {
+            rev,
+            state: state.to_string(),
+            changed_by: Some(Identity { display_name: by.to_string(), unique_name: by.to_string(), avatar_url: None }),
+            changed_date: Some(format!("2026-03-{:02}T12:00:00Z", day)),
+            title: "App crash on save".to_string(),
+            description: None,
+            created_date: Some("2026-03-01T12:00:00Z".to_string()),
+        }
+    }
+
+    fn find(revisions: &[Revision]) -> Option<FixedBug> {
+        find_fix(7, revisions, &["me@contoso.com".to_string()], &CategoryStyles::from_env(), &ProcessModel::default())
+    }
+
+    #[test]
+    fn the_latest_fix_by_me_counts() {
+        let revisions = [
+            revision(1, "Active", "someone@contoso.com", 1),
+            revision(2, "Resolved", "ME@contoso.com", 3),
+            revision(3, "Active", "someone@contoso.com", 5),
+            revision(4, "Resolved", "me@contoso.com", 11),
+            revision(5, "Closed", "someone@contoso.com", 12),
+        ];
+        let fix = find(&revisions).unwrap();
+        assert_eq!(fix.fixed_date.date_naive(), NaiveDate::from_ymd_opt(2026, 3, 11).unwrap());
+        assert_eq!((fix.state.as_str(), fix.days_to_fix()), ("Closed", 10.0));
+        assert_eq!(fix.category, "Crash");
+    }
+
+    #[test]
+    fn fixes_by_others_or_reopened_bugs_do_not_count() {
+        assert!(find(&[revision(1, "Active", "me@contoso.com", 1), revision(2, "Resolved", "someone@contoso.com", 2)]).is_none());
+        assert!(find(&[revision(1, "Active", "me@contoso.com", 1), revision(2, "Resolved", "me@contoso.com", 2), revision(3, "Active", "someone@contoso.com", 3)]).is_none());
+    }
+
+    #[test]
+    fn months_are_bounded_by_their_first_days() {
+        let date = |y, m| NaiveDate::from_ymd_opt(y, m, 1).unwrap();
+        assert_eq!(month_bounds(Some("2026-03")).unwrap(), (date(2026, 3), date(2026, 4)));
+        assert_eq!(month_bounds(Some(" 2025-12 ")).unwrap(), (date(2025, 12), date(2026, 1)));
+        assert!(month_bounds(Some("March")).is_err());
+    }
+
+    #[test]
+    fn the_markdown_summarizes_weeks_and_categories() {
+        let fix = |id, day: u32, category: &str| FixedBug {
+            id,
+            title: format!("Bug\n{}", id),
+            category: category.to_string(),
+            created_date: "2026-03-01T00:00:00Z".parse().unwrap(),
+            fixed_date: format!("2026-03-{:02}T00:00:00Z", day).parse().unwrap(),
+            state: "Resolved".to_string(),
+        };
+        let start = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let md = markdown(&[fix(1, 3, "UI"), fix(2, 4, "Crash"), fix(3, 10, "Crash")], "me", start, |id| format!("https://bugs/{}", id));
+        assert!(md.starts_with("# Bugs fixed by me — March 2026\n\n**3 bug(s) resolved or closed** · mean time to resolve **4.7 days**"));
+        assert!(md.contains("| 2026-03-02 | 2 |\n| 2026-03-09 | 1 |"));
+        assert!(md.contains("| Crash | 2 |\n| UI | 1 |"));
+        assert!(md.contains("- [#1](https://bugs/1) Bug 1 — UI, resolved 2026-03-03 (2.0 days)"));
+        assert!(markdown(&[], "me", start, |id| id.to_string()).ends_with("No bugs resolved or closed this month.\n"));
+    }
+}
@@ -0,0 +1,130 @@
+// Opt-in achievements for backlog cleanup: streaks of days without questionable bugs and weekly
+// stale-bug cleanup badges, shown in the report footer
+use crate::azure_devops::Bug;
+use crate::bug_analysis::is_questionable;
+use crate::hygiene::{is_stale, HygieneScore};
+use crate::storage;
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+const ACHIEVEMENTS_STORE: &str = "achievements";
+/// Days of snapshots kept; the stale cleanup badge looks back one week.
+const HISTORY_DAYS: i64 = 30;
+const CLEANUP_WINDOW_DAYS: i64 = 7;
+/// Stale bugs cleaned up in a week needed for each cleanup badge, highest first.
+const CLEANUP_TIERS: [(usize, &str); 4] = [(25, "🏆"), (10, "🥇"), (5, "🥈"), (1, "🥉")];
+const HYGIENE_BADGE_SCORE: u32 = 90;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DaySnapshot {
+    date: NaiveDate,
+    questionable: usize,
+    stale: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct AchievementHistory {
+    days: Vec<DaySnapshot>,
+    best_streak: usize,
+}
+
+/// Consecutive days up to the last snapshot without questionable bugs; days without an analysis break the streak.
+fn current_streak(days: &[DaySnapshot]) -> usize {
+    let mut streak = 0;
+    let mut expected = days.last().map(|d| d.date);
+    for day in days.iter().rev() {
+        if Some(day.date) != expected || day.questionable > 0 {
+            break;
+        }
+        streak += 1;
+        expected = day.date.pred_opt();
+    }
+    streak
+}
+
+/// Bugs stale at some point in the last week that are now fixed or moving again.
+fn cleaned_stale(days: &[DaySnapshot], today: &DaySnapshot) -> usize {
+    let mut cleaned: Vec<u64> = days
+        .iter()
+        .filter(|d| d.date < today.date && d.date >= today.date - Duration::days(CLEANUP_WINDOW_DAYS))
+        .flat_map(|d| d.stale.iter().copied())
+        .filter(|id| !today.stale.contains(id))
+        .collect();
+    cleaned.sort_unstable();
+    cleaned.dedup();
+    cleaned.len()
+}
+
+/// Record today's backlog and return the badges earned, most notable first.
+pub fn update(app: &AppHandle, bugs: &[Bug], hygiene: &HygieneScore, stale_days: i64, now: DateTime<Utc>) -> Result<Vec<String>, String> {
+    let today = DaySnapshot {
+        date: now.with_timezone(&Local).date_naive(),
+        questionable: bugs.iter().filter(|b| is_questionable(b).is_some()).count(),
+        stale: bugs.iter().filter(|b| is_stale(b, stale_days, now)).map(|b| b.id).collect(),
+    };
+    let mut history: AchievementHistory = storage::load_json(app, ACHIEVEMENTS_STORE)?;
+    history.days.retain(|d| d.date != today.date && d.date > today.date - Duration::days(HISTORY_DAYS));
+    let cleaned = cleaned_stale(&history.days, &today);
+    history.days.push(today);
+    history.days.sort_by_key(|d| d.date);
+    let streak = current_streak(&history.days);
+    history.best_streak = history.best_streak.max(streak);
+    storage::save_json(app, ACHIEVEMENTS_STORE, &history)?;
+    Ok(badges(streak, history.best_streak, cleaned, hygiene.score))
+}
+
+fn badges(streak: usize, best_streak: usize, cleaned: usize, hygiene_score: u32) -> Vec<String> {
+    let mut badges = Vec::new();
+    match streak {
+        0 => {}
+        1 => badges.push("✨ No questionable bugs today".to_string()),
+        n => badges.push(format!("🔥 {}-day streak without questionable bugs", n)),
+    }
+    if best_streak > streak.max(1) {
+        badges.push(format!("🏅 Best streak: {} days", best_streak));
+    }
+    if let Some((_, medal)) = CLEANUP_TIERS.iter().find(|(min, _)| cleaned >= *min) {
+        badges.push(format!("{} Cleaned up {} stale bug(s) this week", medal, cleaned));
+    }
+    if hygiene_score >= HYGIENE_BADGE_SCORE {
+        badges.push(format!("💯 Backlog hygiene {}+", HYGIENE_BADGE_SCORE));
+    }
+    badges
+}
+
+pub fn footer_html(badges: &[String]) -> String {
+    let items: String = badges.iter().map(|b| format!("<span class='badge'>{}</span>", html_escape::encode_text(b))).collect();
+    format!("<footer class='achievements'>{}</footer>", items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(offset: i64, questionable: usize, stale: &[u64]) -> DaySnapshot {
+        DaySnapshot { date: NaiveDate::from_ymd_opt(2026, 10, 15).unwrap() + Duration::days(offset), questionable, stale: stale.to_vec() }
+    }
+
+    #[test]
+    fn streaks_count_consecutive_clean_days() {
+        assert_eq!(current_streak(&[]), 0);
+        assert_eq!(current_streak(&[day(-3, 0, &[]), day(-2, 2, &[]), day(-1, 0, &[]), day(0, 0, &[])]), 2);
+        // A day without an analysis breaks the streak.
+        assert_eq!(current_streak(&[day(-3, 0, &[]), day(-1, 0, &[]), day(0, 0, &[])]), 2);
+        assert_eq!(current_streak(&[day(-1, 0, &[]), day(0, 1, &[])]), 0);
+    }
+
+    #[test]
+    fn cleaned_counts_bugs_stale_this_week_that_no_longer_are() {
+        let history = [day(-10, 0, &[1]), day(-3, 0, &[2, 3]), day(-1, 0, &[3, 4])];
+        assert_eq!(cleaned_stale(&history, &day(0, 0, &[4])), 2);
+    }
+
+    #[test]
+    fn badges_reflect_streaks_cleanup_and_hygiene() {
+        assert!(badges(0, 0, 0, 50).is_empty());
+        assert_eq!(badges(1, 1, 0, 50), ["✨ No questionable bugs today"]);
+        assert_eq!(badges(3, 5, 12, 95), ["🔥 3-day streak without questionable bugs", "🏅 Best streak: 5 days", "🥇 Cleaned up 12 stale bug(s) this week", "💯 Backlog hygiene 90+"]);
+    }
+}
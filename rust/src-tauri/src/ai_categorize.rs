@@ -0,0 +1,110 @@
+// AI categorization alongside the keyword lists, and where the two disagree
+use crate::azure_devops::Bug;
+use crate::bug_analysis::{categorize_text, strip_html, BugCategory};
+use crate::{openai, storage, AppConfig};
+use log::warn;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::AppHandle;
+
+/// AI answers per bug, so a bug is only sent again when its title changes.
+const AI_CATEGORIES_STORE: &str = "ai_categories";
+/// Bugs per request.
+const BATCH_SIZE: usize = 25;
+/// Description characters sent per bug.
+const DESCRIPTION_CHARS: usize = 300;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedCategory {
+    title: String,
+    category: BugCategory,
+}
+
+/// A bug the keyword lists and the AI put in different categories.
+#[derive(Debug, Clone, Serialize)]
+pub struct Disagreement {
+    pub bug_id: u64,
+    pub title: String,
+    pub keyword: BugCategory,
+    pub ai: BugCategory,
+}
+
+fn category_key(category: &BugCategory) -> String {
+    format!("{:?}", category).to_lowercase()
+}
+
+/// Ask the AI for the categories of `bugs`; bugs missing from the reply are left out.
+fn ask(api_key: &str, model: &str, bugs: &[&Bug]) -> Result<HashMap<u64, BugCategory>, String> {
+    let categories: Vec<String> = BugCategory::ALL.iter().map(category_key).collect();
+    let mut prompt = format!(
+        "Categorize each bug as exactly one of: {}.\nReply with one line per bug in the form `<id>: <category>` and nothing else.\n\n",
+        categories.join(", ")
+    );
+    for bug in bugs {
+        let description: String = strip_html(bug.description.as_deref().unwrap_or("")).chars().take(DESCRIPTION_CHARS).collect();
+        prompt.push_str(&format!("{}: {}\n{}\n\n", bug.id, bug.title, description.trim()));
+    }
+    let reply = openai::chat_completion(api_key, model, "You categorize software bug reports.", &prompt, 20 * bugs.len() as u32)?;
+    let line = Regex::new(r"(?m)^\W*#?(\d+)\W+([A-Za-z]+)").unwrap();
+    Ok(line
+        .captures_iter(&reply)
+        .filter_map(|c| Some((c[1].parse().ok()?, c[2].parse().ok()?)))
+        .filter(|(id, _)| bugs.iter().any(|b| b.id == *id))
+        .collect())
+}
+
+/// AI categories of `bugs`, when BUGGER_AI_CATEGORIZATION is on and an OpenAI key is set; empty otherwise.
+/// Answers are cached, so only new or retitled bugs are sent. `prune` drops cached bugs not in `bugs`,
+/// so pass it only when they're every active bug (my own report), not an import or someone else's.
+pub fn categorize(app: &AppHandle, config: &AppConfig, bugs: &[Bug], prune: bool) -> HashMap<u64, BugCategory> {
+    let Some(api_key) = config.openai_api_key.as_deref().filter(|_| config.ai_categorization) else {
+        return HashMap::new();
+    };
+    let mut cache: HashMap<u64, CachedCategory> = storage::load_json(app, AI_CATEGORIES_STORE).unwrap_or_else(|e| {
+        warn!("Ignoring cached AI categories: {}", e);
+        HashMap::new()
+    });
+    let uncached: Vec<&Bug> = bugs.iter().filter(|b| cache.get(&b.id).is_none_or(|c| c.title != b.title)).collect();
+    for batch in uncached.chunks(BATCH_SIZE) {
+        match ask(api_key, &config.openai_model, batch) {
+            Ok(answers) => {
+                for bug in batch {
+                    if let Some(category) = answers.get(&bug.id) {
+                        cache.insert(bug.id, CachedCategory { title: bug.title.clone(), category: category.clone() });
+                    }
+                }
+            }
+            Err(e) => {
+                warn!("AI categorization failed: {}", e);
+                break;
+            }
+        }
+    }
+    // Bugs no longer active drop out of the cache, when `bugs` are all of them.
+    if prune {
+        cache.retain(|id, _| bugs.iter().any(|b| b.id == *id));
+    }
+    if let Err(e) = storage::save_json(app, AI_CATEGORIES_STORE, &cache) {
+        warn!("Failed to save AI categories: {}", e);
+    }
+    cache.into_iter().map(|(id, c)| (id, c.category)).collect()
+}
+
+/// Bugs whose keyword category differs from the AI's, most common keyword → AI pair first.
+pub fn disagreements(bugs: &[Bug], ai: &HashMap<u64, BugCategory>) -> Vec<Disagreement> {
+    let mut found: Vec<Disagreement> = bugs
+        .iter()
+        .filter_map(|bug| {
+            let ai = ai.get(&bug.id)?.clone();
+            let keyword = categorize_text(&bug.title, bug.description.as_deref());
+            (keyword != ai).then(|| Disagreement { bug_id: bug.id, title: bug.title.clone(), keyword, ai })
+        })
+        .collect();
+    let mut pair_counts: HashMap<(BugCategory, BugCategory), usize> = HashMap::new();
+    for d in &found {
+        *pair_counts.entry((d.keyword.clone(), d.ai.clone())).or_default() += 1;
+    }
+    found.sort_by_key(|d| (std::cmp::Reverse(pair_counts[&(d.keyword.clone(), d.ai.clone())]), category_key(&d.keyword), category_key(&d.ai), d.bug_id));
+    found
+}
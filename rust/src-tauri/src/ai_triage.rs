@@ -0,0 +1,156 @@
+// AI-assisted triage: a one-line summary, a suggested category and an actionability verdict per
+// bug, shown next to the keyword analysis in the report
+use crate::azure_devops::Bug;
+use crate::bug_analysis::{strip_html, BugCategory};
+use crate::{fnv, openai, storage, AppConfig};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::AppHandle;
+
+/// AI answers per bug, so a bug is only sent again when its title or description changes.
+const AI_TRIAGE_STORE: &str = "ai_triage";
+/// Bugs per request.
+const BATCH_SIZE: usize = 10;
+/// Description characters sent per bug.
+const DESCRIPTION_CHARS: usize = 1500;
+/// Bugs sent per report run; the rest follow on later runs.
+const MAX_PER_RUN: usize = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiTriage {
+    pub summary: String,
+    pub category: BugCategory,
+    pub actionable: bool,
+}
+
+impl AiTriage {
+    /// Summary line for a bug, noting where the AI's verdict differs from the keyword analysis.
+    pub fn html(&self, actionable: bool) -> String {
+        let disagreement = match (self.actionable, actionable) {
+            (false, true) => " · <b>AI: looks questionable</b>",
+            (true, false) => " · <b>AI: looks actionable</b>",
+            _ => "",
+        };
+        format!("<br><small>🤖 {}{}</small>", html_escape::encode_text(&self.summary), disagreement)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedTriage {
+    /// Hash of the title and description the answer is for.
+    source: u64,
+    triage: AiTriage,
+}
+
+fn source_hash(bug: &Bug) -> u64 {
+    fnv::hash(&[&bug.title, bug.description.as_deref().unwrap_or("")])
+}
+
+fn category_key(category: &BugCategory) -> String {
+    format!("{:?}", category).to_lowercase()
+}
+
+/// Parse one reply line: `<id> | <category> | <actionable|questionable> | <summary>`.
+fn parse_line(line: &str) -> Option<(u64, AiTriage)> {
+    let mut parts = line.trim().trim_start_matches(['-', '*', '#', ' ']).splitn(4, '|').map(str::trim);
+    let id = parts.next()?.trim_start_matches('#').parse().ok()?;
+    let category = parts.next()?.parse().ok()?;
+    let actionable = match parts.next()?.to_lowercase().as_str() {
+        "actionable" => true,
+        "questionable" => false,
+        _ => return None,
+    };
+    let summary = parts.next().filter(|s| !s.is_empty())?.to_string();
+    Some((id, AiTriage { summary, category, actionable }))
+}
+
+fn ask(api_key: &str, model: &str, bugs: &[&Bug]) -> Result<HashMap<u64, AiTriage>, String> {
+    let categories: Vec<String> = BugCategory::ALL.iter().map(category_key).collect();
+    let mut prompt = format!(
+        "For each bug, reply with one line in the form `<id> | <category> | <verdict> | <summary>` and nothing else.\n\
+         category: exactly one of {}.\n\
+         verdict: `actionable` if an engineer could start working on it, `questionable` if it is vague, lacks repro information, is a duplicate-looking test report or not a bug.\n\
+         summary: one short sentence saying what is wrong.\n\n",
+        categories.join(", ")
+    );
+    for bug in bugs {
+        let description: String = strip_html(bug.description.as_deref().unwrap_or("")).chars().take(DESCRIPTION_CHARS).collect();
+        prompt.push_str(&format!("{}: {}\n{}\n\n", bug.id, bug.title, description.trim()));
+    }
+    let reply = openai::chat_completion(api_key, model, "You triage software bug reports for the engineer who owns them.", &prompt, 60 * bugs.len() as u32)?;
+    Ok(reply.lines().filter_map(parse_line).filter(|(id, _)| bugs.iter().any(|b| b.id == *id)).collect())
+}
+
+/// AI triage of `bugs` when an OpenAI key is set and BUGGER_AI_TRIAGE is on; empty otherwise.
+/// Answers are cached until a bug's title or description changes. `prune` drops cached bugs not in
+/// `bugs`, so pass it only when they're every active bug (my own report).
+pub fn triage(app: &AppHandle, config: &AppConfig, bugs: &[Bug], prune: bool) -> HashMap<u64, AiTriage> {
+    let Some(api_key) = config.openai_api_key.as_deref().filter(|_| config.ai_triage) else {
+        return HashMap::new();
+    };
+    let mut cache: HashMap<u64, CachedTriage> = storage::load_json(app, AI_TRIAGE_STORE).unwrap_or_else(|e| {
+        warn!("Ignoring cached AI triage: {}", e);
+        HashMap::new()
+    });
+    let uncached: Vec<&Bug> = bugs.iter().filter(|b| cache.get(&b.id).is_none_or(|c| c.source != source_hash(b))).take(MAX_PER_RUN).collect();
+    for batch in uncached.chunks(BATCH_SIZE) {
+        match ask(api_key, &config.openai_model, batch) {
+            Ok(answers) => {
+                for bug in batch {
+                    if let Some(triage) = answers.get(&bug.id) {
+                        cache.insert(bug.id, CachedTriage { source: source_hash(bug), triage: triage.clone() });
+                    }
+                }
+            }
+            Err(e) => {
+                warn!("AI triage failed: {}", e);
+                break;
+            }
+        }
+    }
+    // Bugs no longer active drop out of the cache, when `bugs` are all of them.
+    if prune {
+        cache.retain(|id, _| bugs.iter().any(|b| b.id == *id));
+    }
+    if let Err(e) = storage::save_json(app, AI_TRIAGE_STORE, &cache) {
+        warn!("Failed to save AI triage: {}", e);
+    }
+    // An answer for an older version of a bug is still better than none until it's refreshed.
+    cache.into_iter().map(|(id, c)| (id, c.triage)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reply_lines_are_parsed() {
+        let (id, triage) = parse_line("- #42 | crash | Actionable | Crashes when saving a file").unwrap();
+        assert_eq!(id, 42);
+        assert_eq!(category_key(&triage.category), "crash");
+        assert!(triage.actionable);
+        assert_eq!(triage.summary, "Crashes when saving a file");
+        let (_, triage) = parse_line("7 | ui | questionable | Vague | no steps").unwrap();
+        assert!(!triage.actionable);
+        assert_eq!(triage.summary, "Vague | no steps");
+    }
+
+    #[test]
+    fn incomplete_or_unknown_lines_are_skipped() {
+        assert!(parse_line("Here are the bugs:").is_none());
+        assert!(parse_line("42 | crash | actionable |").is_none());
+        assert!(parse_line("42 | crash | maybe | Crashes").is_none());
+        assert!(parse_line("42 | not-a-category | actionable | Crashes").is_none());
+        assert!(parse_line("x42 | crash | actionable | Crashes").is_none());
+    }
+
+    #[test]
+    fn the_source_hash_follows_title_and_description() {
+        let bug = Bug::sample(1, "Crash");
+        let mut described = bug.clone();
+        described.description = Some("On save".to_string());
+        assert_eq!(source_hash(&bug), source_hash(&bug.clone()));
+        assert_ne!(source_hash(&bug), source_hash(&described));
+    }
+}
@@ -0,0 +1,165 @@
+// Anonymized exports: replace people and customer identifiers with stable pseudonyms
+use crate::azure_devops::{Bug, Identity};
+use crate::{env_list, fnv};
+
+/// Stable, case-insensitive pseudonym, so the same person gets the same one in every export.
+/// 32 bits of the hash keep collisions between people unlikely even in large organizations.
+pub fn pseudonym(kind: &str, value: &str) -> String {
+    format!("{}-{:08X}", kind, fnv::hash(&[kind, &value.to_lowercase()]) as u32)
+}
+
+/// Avatar images in report HTML, which would identify people by their picture.
+fn is_avatar(tag: &str) -> bool {
+    tag.starts_with("<img") && (tag.contains("class='avatar'") || tag.contains("class=\"avatar\""))
+}
+
+/// Replaces known identifiers (names, emails, customer field values) and any email address in free text.
+#[derive(Debug, Clone, Default)]
+pub struct Anonymizer {
+    replacements: Vec<(String, String)>,
+    /// Custom fields holding customer identifiers (BUGGER_ANONYMIZE_FIELDS).
+    sensitive_fields: Vec<String>,
+}
+
+impl Anonymizer {
+    /// Build from the people seen in the report and the customer fields of `bugs`.
+    pub fn new(bugs: &[Bug], people: &[Identity]) -> Self {
+        let sensitive_fields = env_list("BUGGER_ANONYMIZE_FIELDS");
+        let mut replacements = Vec::new();
+        for person in people.iter().chain(bugs.iter().flat_map(|b| b.assigned_to.iter().chain(b.created_by.iter()))) {
+            let alias = pseudonym("Person", &person.unique_name);
+            replacements.push((person.display_name.clone(), alias.clone()));
+            replacements.push((person.unique_name.clone(), alias));
+        }
+        for bug in bugs {
+            for field in &sensitive_fields {
+                if let Some(value) = bug.custom_fields.get(field) {
+                    replacements.push((value.clone(), pseudonym("Customer", value)));
+                }
+            }
+        }
+        replacements.retain(|(from, _)| from.trim().len() > 1);
+        // Longest first so "Jane Doe" is replaced before "Jane".
+        replacements.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+        replacements.dedup_by(|a, b| a.0 == b.0);
+        Anonymizer { replacements, sensitive_fields }
+    }
+
+    pub fn text(&self, text: &str) -> String {
+        let mut out = text.to_string();
+        for (from, to) in &self.replacements {
+            out = out.replace(from.as_str(), to);
+            // HTML output contains escaped versions of names with special characters.
+            let escaped = html_escape::encode_text(from);
+            if escaped != from.as_str() {
+                out = out.replace(escaped.as_ref(), to);
+            }
+        }
+        replace_emails(&out)
+    }
+
+    /// Anonymize HTML: names are replaced in text nodes only, so tags and attributes stay intact,
+    /// email addresses (e.g. in mailto links) everywhere, and avatar images are dropped.
+    pub fn html(&self, html: &str) -> String {
+        let mut out = String::with_capacity(html.len());
+        let mut rest = html;
+        while let Some((start, end)) = rest.find('<').and_then(|start| Some((start, start + rest[start..].find('>')? + 1))) {
+            out.push_str(&self.text(&rest[..start]));
+            let tag = &rest[start..end];
+            if !is_avatar(tag) {
+                out.push_str(&replace_emails(tag));
+            }
+            rest = &rest[end..];
+        }
+        out.push_str(&self.text(rest));
+        out
+    }
+
+    pub fn bug(&self, bug: &Bug) -> Bug {
+        let mut bug = bug.clone();
+        bug.title = self.text(&bug.title);
+        bug.description = bug.description.as_deref().map(|d| self.html(d));
+        bug.environment.device = bug.environment.device.as_deref().map(|d| self.text(d));
+        let alias = |a: Identity| {
+            let alias = pseudonym("Person", &a.unique_name);
+            Identity { display_name: alias.clone(), unique_name: alias, avatar_url: None }
+        };
+        bug.assigned_to = bug.assigned_to.map(alias);
+        bug.created_by = bug.created_by.map(alias);
+        for (name, value) in bug.custom_fields.iter_mut() {
+            *value = if self.sensitive_fields.contains(name) { pseudonym("Customer", value) } else { self.text(value) };
+        }
+        bug
+    }
+}
+
+/// Replace anything that looks like an email address with a pseudonym.
+fn replace_emails(text: &str) -> String {
+    let is_email_char = |c: char| c.is_alphanumeric() || "._%+-@".contains(c);
+    let mut out = String::with_capacity(text.len());
+    let mut token = String::new();
+    let flush = |token: &mut String, out: &mut String| {
+        let looks_like_email = token.split_once('@').is_some_and(|(user, domain)| !user.is_empty() && domain.contains('.'));
+        if looks_like_email {
+            out.push_str(&pseudonym("Person", token));
+        } else {
+            out.push_str(token);
+        }
+        token.clear();
+    };
+    for c in text.chars() {
+        if is_email_char(c) {
+            token.push(c);
+        } else {
+            flush(&mut token, &mut out);
+            out.push(c);
+        }
+    }
+    flush(&mut token, &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jane() -> Identity {
+        Identity { display_name: "Jane Doe".to_string(), unique_name: "jane@contoso.com".to_string(), avatar_url: Some("https://avatar/jane".to_string()) }
+    }
+
+    #[test]
+    fn pseudonyms_are_stable_and_case_insensitive() {
+        assert_eq!(pseudonym("Person", "jane@contoso.com"), pseudonym("Person", "Jane@Contoso.com"));
+        assert_ne!(pseudonym("Person", "jane@contoso.com"), pseudonym("Person", "john@contoso.com"));
+        assert_ne!(pseudonym("Person", "acme"), pseudonym("Customer", "acme"));
+        assert_eq!(pseudonym("Person", "jane@contoso.com").len(), "Person-".len() + 8);
+    }
+
+    #[test]
+    fn html_replaces_text_nodes_only_and_drops_avatars() {
+        let alias = pseudonym("Person", "jane@contoso.com");
+        let anonymizer = Anonymizer::new(&[], &[Identity { display_name: "span".to_string(), ..jane() }]);
+        let html = "<span title='x'>Assigned to span <img class='avatar' src='data:image/png;base64,AA' alt=''><a href='mailto:jane@contoso.com'>mail</a></span>";
+        assert_eq!(
+            anonymizer.html(html),
+            format!("<span title='x'>Assigned to {} <a href='mailto:{}'>mail</a></span>", alias, alias)
+        );
+        // A stray '<' without a closing '>' is text.
+        assert_eq!(anonymizer.html("1 < 2, ask span"), format!("1 < 2, ask {}", alias));
+    }
+
+    #[test]
+    fn bugs_lose_names_emails_and_avatars() {
+        let mut bug = Bug::sample(1, "Crash reported by Jane Doe");
+        bug.assigned_to = Some(jane());
+        bug.description = Some("<p>Ping jane@contoso.com</p>".to_string());
+        let anonymizer = Anonymizer::new(std::slice::from_ref(&bug), &[]);
+        let alias = pseudonym("Person", "jane@contoso.com");
+        let out = anonymizer.bug(&bug);
+        assert_eq!(out.title, format!("Crash reported by {}", alias));
+        assert_eq!(out.description.as_deref(), Some(format!("<p>Ping {}</p>", alias).as_str()));
+        let assignee = out.assigned_to.unwrap();
+        assert_eq!(assignee.display_name, alias);
+        assert_eq!(assignee.avatar_url, None);
+    }
+}
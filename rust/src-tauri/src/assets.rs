@@ -0,0 +1,192 @@
+// Avatar, work item type icon, description image and state color cache: fetched at most once,
+// stored on disk, embedded in the report
+use crate::azure_devops::{AzureDevOpsClient, Bug};
+use crate::storage;
+use base64::Engine;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::AppHandle;
+
+const ASSET_INDEX: &str = "assets";
+const STATE_COLORS: &str = "state_colors";
+/// Process metadata rarely changes; re-read state colors weekly.
+const STATE_COLORS_MAX_AGE_DAYS: i64 = 7;
+/// Pause between downloads so a large team report doesn't hammer the API.
+const FETCH_DELAY: Duration = Duration::from_millis(150);
+
+/// A cached image as a data URI.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedAsset {
+    data_uri: String,
+}
+
+/// Images available to the report, keyed by their original URL.
+#[derive(Debug, Clone, Default)]
+pub struct AssetCache {
+    assets: HashMap<String, String>,
+    /// Data URI of the Bug work item type icon, if known.
+    pub bug_icon: Option<String>,
+    /// Bug state name -> board color (hex without `#`).
+    pub state_colors: HashMap<String, String>,
+}
+
+/// State colors of the Bug type as last fetched.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct StateColors {
+    fetched_at: Option<DateTime<Utc>>,
+    colors: HashMap<String, String>,
+}
+
+impl AssetCache {
+    /// Embedded data URI for an image URL, if it has been cached.
+    pub fn get(&self, url: &str) -> Option<&str> {
+        self.assets.get(url).map(|s| s.as_str())
+    }
+
+    /// Avatar of a bug's assignee, if cached.
+    pub fn avatar_for(&self, bug: &Bug) -> Option<&str> {
+        bug.assigned_to.as_ref()?.avatar_url.as_deref().and_then(|u| self.get(u))
+    }
+
+    /// Rewrite `<img src>` attributes in description HTML to cached data URIs, so attachment
+    /// images that need authentication render in the report.
+    pub fn inline_images(&self, html: &str) -> String {
+        let mut out = html.to_string();
+        for src in image_sources(html) {
+            if let Some(uri) = self.get(&html_escape::decode_html_entities(&src)) {
+                out = out.replace(&src, uri);
+            }
+        }
+        out
+    }
+
+    /// State name as a badge in its Azure Boards color, or plain text if the color is unknown.
+    pub fn state_badge(&self, state: &str) -> String {
+        let name = html_escape::encode_text(state);
+        match self.state_colors.get(state) {
+            Some(color) => format!("<span class='state-badge'><span class='state-dot' style='background:#{} !important'></span>{}</span>", color, name),
+            None => name.into_owned(),
+        }
+    }
+}
+
+/// Raw `src` attribute values of `<img>` tags in `html`.
+fn image_sources(html: &str) -> Vec<String> {
+    let lower = html.to_ascii_lowercase();
+    let mut sources = Vec::new();
+    let mut rest = 0;
+    while let Some(tag_start) = lower[rest..].find("<img").map(|i| rest + i) {
+        let tag_end = lower[tag_start..].find('>').map_or(lower.len(), |i| tag_start + i);
+        let tag = &lower[tag_start..tag_end];
+        if let Some(attr) = tag.find("src=").map(|i| tag_start + i + 4) {
+            let quote = html[attr..].chars().next();
+            if let Some(q @ ('"' | '\'')) = quote {
+                if let Some(len) = html[attr + 1..tag_end].find(q) {
+                    sources.push(html[attr + 1..attr + 1 + len].to_string());
+                }
+            }
+        }
+        rest = tag_end;
+    }
+    sources
+}
+
+/// Only Azure DevOps URLs are fetched with the PAT; other images are left untouched.
+fn is_azure_devops_url(url: &str) -> bool {
+    let host = url.strip_prefix("https://").and_then(|u| u.split('/').next()).unwrap_or_default();
+    host == "dev.azure.com" || host.ends_with(".visualstudio.com") || host.ends_with(".dev.azure.com")
+}
+
+/// Cached Bug state colors, re-fetched once they are a week old. Colors that aren't plain hex are dropped.
+fn load_state_colors(app: &AppHandle, client: &AzureDevOpsClient) -> HashMap<String, String> {
+    let cached: StateColors = storage::load_json(app, STATE_COLORS).unwrap_or_default();
+    let fresh = cached.fetched_at.is_some_and(|t| Utc::now() - t < ChronoDuration::days(STATE_COLORS_MAX_AGE_DAYS));
+    if fresh {
+        return cached.colors;
+    }
+    match client.fetch_work_item_type_states("Bug") {
+        Ok(states) => {
+            let colors: HashMap<String, String> = states
+                .into_iter()
+                .map(|state| (state.name, state.color))
+                .filter(|(_, color)| !color.is_empty() && color.len() <= 8 && color.chars().all(|c| c.is_ascii_hexdigit()))
+                .collect();
+            if let Err(e) = storage::save_json(app, STATE_COLORS, &StateColors { fetched_at: Some(Utc::now()), colors: colors.clone() }) {
+                warn!("Failed to save state colors: {}", e);
+            }
+            colors
+        }
+        Err(e) => {
+            warn!("State colors unavailable: {}", e);
+            cached.colors
+        }
+    }
+}
+
+/// Load the on-disk cache and download any missing avatars, description images and the Bug icon, fetching at most
+/// `max_fetches` new images per run with a short delay between requests.
+pub fn prefetch(app: &AppHandle, client: &AzureDevOpsClient, bugs: &[Bug], max_fetches: usize) -> AssetCache {
+    let mut index: HashMap<String, CachedAsset> = storage::load_json(app, ASSET_INDEX).unwrap_or_else(|e| {
+        warn!("Ignoring unreadable asset cache: {}", e);
+        HashMap::new()
+    });
+    let icon_key = "icon:Bug".to_string();
+    let mut wanted: Vec<String> = bugs.iter().filter_map(|b| b.assigned_to.as_ref()?.avatar_url.clone()).collect();
+    wanted.extend(
+        bugs.iter()
+            .filter_map(|b| b.description.as_deref())
+            .flat_map(image_sources)
+            .map(|src| html_escape::decode_html_entities(&src).into_owned())
+            .filter(|url| is_azure_devops_url(url)),
+    );
+    wanted.sort();
+    wanted.dedup();
+    wanted.retain(|u| !index.contains_key(u));
+
+    let mut fetched = 0;
+    let mut changed = false;
+    if !index.contains_key(&icon_key) {
+        if let Ok(Some(url)) = client.fetch_work_item_type_icon_url("Bug") {
+            if let Some(asset) = download(client, &url) {
+                index.insert(icon_key.clone(), asset);
+                changed = true;
+            }
+            fetched += 1;
+        }
+    }
+    for url in &wanted {
+        if fetched >= max_fetches {
+            break;
+        }
+        std::thread::sleep(FETCH_DELAY);
+        fetched += 1;
+        if let Some(asset) = download(client, url) {
+            index.insert(url.clone(), asset);
+            changed = true;
+        }
+    }
+    if changed {
+        if let Err(e) = storage::save_json(app, ASSET_INDEX, &index) {
+            warn!("Failed to save asset cache: {}", e);
+        }
+    }
+
+    let bug_icon = index.get(&icon_key).map(|a| a.data_uri.clone());
+    let assets = index.into_iter().map(|(k, v)| (k, v.data_uri)).collect();
+    AssetCache { assets, bug_icon, state_colors: load_state_colors(app, client) }
+}
+
+fn download(client: &AzureDevOpsClient, url: &str) -> Option<CachedAsset> {
+    match client.fetch_bytes(url) {
+        Ok((bytes, content_type)) => Some(CachedAsset {
+            data_uri: format!("data:{};base64,{}", content_type, base64::engine::general_purpose::STANDARD.encode(bytes)),
+        }),
+        Err(e) => {
+            warn!("Failed to fetch {}: {}", url, e);
+            None
+        }
+    }
+}
@@ -0,0 +1,353 @@
+// Local audit log of every write action Bugger performs (or would perform, in dry-run) on work items
+use crate::azure_devops::AzureDevOpsClient;
+use crate::error::BuggerError;
+use crate::roles::{Role, Roles};
+use crate::{storage, AppConfig, AppState};
+use schemars::JsonSchema;
+use std::sync::atomic::{AtomicU64, Ordering};
+use chrono::{DateTime, Duration, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tauri::{AppHandle, Manager, State};
+
+const AUDIT_LOG: &str = "audit";
+/// The last dry-run detail logged per bug and action.
+const PREVIEW_STORE: &str = "audit_previews";
+
+/// The kinds of write Bugger performs on work items. Logged by `name`, and the role each one
+/// needs is decided by `Role::required_for`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteAction {
+    Comment,
+    Tag,
+    Reassign,
+    Attach,
+    LogTime,
+    FileBug,
+    Undo,
+    AutoCloseWarning,
+    AutoCloseResolve,
+    AutoAssign,
+    Escalate,
+    WriteBack,
+}
+
+impl WriteAction {
+    /// The name in the audit log, e.g. "auto-close-resolve".
+    pub fn name(self) -> &'static str {
+        match self {
+            WriteAction::Comment => "comment",
+            WriteAction::Tag => "tag",
+            WriteAction::Reassign => "reassign",
+            WriteAction::Attach => "attach",
+            WriteAction::LogTime => "log-time",
+            WriteAction::FileBug => "file-bug",
+            WriteAction::Undo => "undo",
+            WriteAction::AutoCloseWarning => "auto-close-warning",
+            WriteAction::AutoCloseResolve => "auto-close-resolve",
+            WriteAction::AutoAssign => "auto-assign",
+            WriteAction::Escalate => "escalate",
+            WriteAction::WriteBack => "write-back",
+        }
+    }
+}
+
+/// How to revert a write, captured when it was performed.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum UndoAction {
+    DeleteComment { comment_id: u64 },
+    /// Previous field values; `None` means the field was unset.
+    RestoreFields { fields: Vec<(String, Option<String>)> },
+    /// Remove a tag that was added, leaving tags added or removed since then alone.
+    RemoveTag { tag: String },
+}
+
+/// Result of a successful write: the API response and, for reversible operations, how to undo it.
+pub struct WriteOutcome {
+    pub response: Value,
+    pub undo: Option<UndoAction>,
+}
+
+impl WriteOutcome {
+    /// Outcome of posting a comment; undone by deleting the comment again.
+    pub fn comment(response: Value) -> Self {
+        let undo = response["id"].as_u64().map(|comment_id| UndoAction::DeleteComment { comment_id });
+        WriteOutcome { response, undo }
+    }
+}
+
+/// Update fields after capturing their current values, so the change can be undone.
+pub fn update_fields_reversible(client: &AzureDevOpsClient, id: u64, updates: &[(&str, &str)]) -> Result<WriteOutcome, BuggerError> {
+    let names: Vec<&str> = updates.iter().map(|(name, _)| *name).collect();
+    let previous = client.fetch_fields(id, &names)?;
+    let response = client.update_fields(id, updates)?;
+    Ok(WriteOutcome { response, undo: Some(UndoAction::RestoreFields { fields: previous }) })
+}
+
+/// `tags` (as in `System.Tags`) with `tag` added, or `None` if it's already there.
+fn with_tag(tags: &str, tag: &str) -> Option<String> {
+    let mut list: Vec<&str> = tags.split(';').map(str::trim).filter(|t| !t.is_empty()).collect();
+    if list.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+        return None;
+    }
+    list.push(tag);
+    Some(list.join("; "))
+}
+
+/// `tags` (as in `System.Tags`) without `tag`.
+fn without_tag(tags: &str, tag: &str) -> String {
+    tags.split(';').map(str::trim).filter(|t| !t.is_empty() && !t.eq_ignore_ascii_case(tag)).collect::<Vec<_>>().join("; ")
+}
+
+/// Add a tag to the bug's current tags; undone by removing just that tag again.
+pub fn add_tag_reversible(client: &AzureDevOpsClient, id: u64, tag: &str) -> Result<WriteOutcome, BuggerError> {
+    let current = client.fetch_fields(id, &["System.Tags"])?.pop().and_then(|(_, tags)| tags).unwrap_or_default();
+    let Some(tags) = with_tag(&current, tag) else {
+        return Ok(WriteOutcome { response: Value::Null, undo: None });
+    };
+    let response = client.update_fields(id, &[("System.Tags", &tags)])?;
+    Ok(WriteOutcome { response, undo: Some(UndoAction::RemoveTag { tag: tag.to_string() }) })
+}
+
+fn remove_tag(client: &AzureDevOpsClient, id: u64, tag: &str) -> Result<Value, BuggerError> {
+    let current = client.fetch_fields(id, &["System.Tags"])?.pop().and_then(|(_, tags)| tags).unwrap_or_default();
+    let tags = without_tag(&current, tag);
+    client.restore_fields(id, &[("System.Tags".to_string(), Some(tags).filter(|t| !t.is_empty()))])
+}
+
+/// A new entry id: the time and bug, plus a counter for entries within the same millisecond.
+fn entry_id(timestamp: DateTime<Utc>, bug_id: u64) -> String {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    format!("{}-{}-{}", timestamp.timestamp_millis(), bug_id, NEXT.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Whether the entry `entry_id` was already undone. Failed and simulated undos don't count.
+fn is_undone(entries: &[AuditEntry], entry_id: &str) -> bool {
+    entries.iter().any(|e| e.undoes.as_deref() == Some(entry_id) && e.error.is_none() && !e.dry_run)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AuditEntry {
+    /// Unique id used to reference the entry, e.g. when undoing it.
+    #[serde(default)]
+    pub id: String,
+    pub timestamp: DateTime<Utc>,
+    /// Who the action was performed as (the configured user email).
+    pub actor: String,
+    pub bug_id: u64,
+    /// Short action name, e.g. "comment" or "auto-close-resolve".
+    pub action: String,
+    pub detail: String,
+    pub dry_run: bool,
+    /// API response body for successful calls (absent in dry-run).
+    #[serde(default)]
+    pub response: Option<Value>,
+    /// API error, if the action failed.
+    pub error: Option<String>,
+    /// How to revert this action, for reversible operations that succeeded.
+    #[serde(default)]
+    pub undo: Option<UndoAction>,
+    /// For undo entries, the id of the entry that was reverted.
+    #[serde(default)]
+    pub undoes: Option<String>,
+}
+
+/// Whether a dry run of `action` on `bug_id` with this `detail` is new, i.e. not the same as the last
+/// one logged. Policies previewing on every refresh check this so the log doesn't fill up with repeats.
+pub fn is_new_preview(app: &AppHandle, bug_id: u64, action: WriteAction, detail: &str) -> bool {
+    let key = format!("{}:{}", bug_id, action.name());
+    let mut new = true;
+    let result = storage::update_json(app, PREVIEW_STORE, |previews: &mut HashMap<String, String>| {
+        new = previews.get(&key).is_none_or(|last| last != detail);
+        previews.insert(key, detail.to_string());
+    });
+    if let Err(e) = result {
+        warn!("Failed to check earlier dry runs for #{}: {}", bug_id, e);
+    }
+    new
+}
+
+/// Append an entry to the audit log. Failures are logged but never block the action itself.
+pub fn record(app: &AppHandle, entry: &AuditEntry) {
+    if let Err(e) = storage::append_jsonl(app, AUDIT_LOG, entry) {
+        warn!("Failed to write audit entry for #{}: {}", entry.bug_id, e);
+    }
+}
+
+/// Run a write action against Azure DevOps (skipped in dry-run or when the global simulate switch
+/// is on) and record it in the audit log. All work item mutations go through here, so this is also
+/// where the actor's role is checked.
+pub fn perform(
+    app: &AppHandle,
+    actor: &str,
+    bug_id: u64,
+    action: WriteAction,
+    detail: impl Into<String>,
+    dry_run: bool,
+    write: impl FnOnce() -> Result<WriteOutcome, BuggerError>,
+) -> Result<(), BuggerError> {
+    record_write(app, actor, bug_id, action, detail.into(), dry_run, None, write)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn record_write(
+    app: &AppHandle,
+    actor: &str,
+    bug_id: u64,
+    action: WriteAction,
+    detail: String,
+    dry_run: bool,
+    undoes: Option<String>,
+    write: impl FnOnce() -> Result<WriteOutcome, BuggerError>,
+) -> Result<(), BuggerError> {
+    let dry_run = dry_run || app.state::<AppState>().simulate.load(Ordering::Relaxed);
+    if !dry_run {
+        Roles::from_env().check(actor, Role::required_for(action), action.name())?;
+    }
+    let result = if dry_run { Ok(WriteOutcome { response: Value::Null, undo: None }) } else { write() };
+    let timestamp = Utc::now();
+    let (response, undo, error) = match &result {
+        Ok(outcome) => ((!outcome.response.is_null()).then(|| outcome.response.clone()), outcome.undo.clone(), None),
+        Err(e) => (None, None, Some(e.to_string())),
+    };
+    record(
+        app,
+        &AuditEntry {
+            id: entry_id(timestamp, bug_id),
+            timestamp,
+            actor: actor.to_string(),
+            bug_id,
+            action: action.name().to_string(),
+            detail,
+            dry_run,
+            response,
+            error,
+            undo,
+            undoes,
+        },
+    );
+    result.map(|_| ())
+}
+
+#[tauri::command]
+/// Returns audit log entries newest first, optionally filtered to one bug and capped at `limit`.
+pub fn get_audit_log(app: AppHandle, bug_id: Option<u64>, limit: Option<usize>) -> Result<Vec<AuditEntry>, String> {
+    let mut entries: Vec<AuditEntry> = storage::load_jsonl(&app, AUDIT_LOG)?;
+    entries.retain(|e| bug_id.is_none_or(|id| e.bug_id == id));
+    entries.reverse();
+    entries.truncate(limit.unwrap_or(usize::MAX));
+    Ok(entries)
+}
+
+#[tauri::command]
+/// Reverts a reversible audit entry via the API, if it is still within the undo window and hasn't been undone.
+pub fn undo_action(app: AppHandle, entry_id: String) -> Result<(), BuggerError> {
+    let config = AppConfig::from_env()?;
+    let window = Duration::minutes(config.undo_window_minutes);
+    let entries: Vec<AuditEntry> = storage::load_jsonl(&app, AUDIT_LOG)?;
+    let entry = entries.iter().find(|e| e.id == entry_id).ok_or_else(|| format!("No audit entry {}", entry_id))?;
+    if is_undone(&entries, &entry_id) {
+        return Err("This action has already been undone".into());
+    }
+    let undo = entry.undo.clone().ok_or("This action can't be undone")?;
+    if Utc::now() - entry.timestamp > window {
+        return Err(format!("The undo window of {} minutes has passed", config.undo_window_minutes).into());
+    }
+    let actor = config.user_email.clone();
+    let bug_id = entry.bug_id;
+    let client = AzureDevOpsClient::new(config);
+    record_write(&app, &actor, bug_id, WriteAction::Undo, format!("Undo {}", entry.action), false, Some(entry_id), || {
+        let response = match &undo {
+            UndoAction::DeleteComment { comment_id } => client.delete_comment(bug_id, *comment_id)?,
+            UndoAction::RestoreFields { fields } => client.restore_fields(bug_id, fields)?,
+            UndoAction::RemoveTag { tag } => remove_tag(&client, bug_id, tag)?,
+        };
+        Ok(WriteOutcome { response, undo: None })
+    })
+}
+
+#[tauri::command]
+/// Turns the global simulate switch on or off; returns the new value.
+pub fn set_simulation_mode(state: State<AppState>, enabled: bool) -> bool {
+    state.simulate.store(enabled, Ordering::Relaxed);
+    enabled
+}
+
+#[tauri::command]
+/// HTML preview of the writes that were simulated instead of executed, newest first.
+pub fn get_simulation_preview(app: AppHandle, state: State<AppState>) -> Result<String, String> {
+    let entries: Vec<AuditEntry> = storage::load_jsonl(&app, AUDIT_LOG)?;
+    let simulated: Vec<&AuditEntry> = entries.iter().rev().filter(|e| e.dry_run).collect();
+    let mut html = String::new();
+    html.push_str(&format!(
+        "<h2>🧪 Simulated Actions</h2><div class='warning'>Simulate mode is {}. These actions were logged but not sent to Azure DevOps.</div>",
+        if state.simulate.load(Ordering::Relaxed) { "ON" } else { "OFF" }
+    ));
+    if simulated.is_empty() {
+        html.push_str("<p>No simulated actions yet.</p>");
+        return Ok(html);
+    }
+    html.push_str("<table><tr><th>When</th><th>Bug</th><th>Action</th><th>Detail</th></tr>");
+    for e in simulated {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>#{}</td><td>{}</td><td>{}</td></tr>",
+            e.timestamp.format("%Y-%m-%d %H:%M"),
+            e.bug_id,
+            html_escape::encode_text(&e.action),
+            html_escape::encode_text(&e.detail)
+        ));
+    }
+    html.push_str("</table>");
+    Ok(html)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, undoes: Option<&str>, dry_run: bool, error: Option<&str>) -> AuditEntry {
+        AuditEntry {
+            id: id.to_string(),
+            timestamp: Utc::now(),
+            actor: "me@example.com".to_string(),
+            bug_id: 1,
+            action: "undo".to_string(),
+            detail: String::new(),
+            dry_run,
+            response: None,
+            error: error.map(str::to_string),
+            undo: None,
+            undoes: undoes.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn tags_are_added_once() {
+        assert_eq!(with_tag("ui; regression", "needs-info").as_deref(), Some("ui; regression; needs-info"));
+        assert_eq!(with_tag("", "needs-info").as_deref(), Some("needs-info"));
+        assert_eq!(with_tag("ui; Needs-Info", "needs-info"), None);
+    }
+
+    #[test]
+    fn removing_a_tag_keeps_the_others() {
+        assert_eq!(without_tag("ui; needs-info; added-later", "Needs-Info"), "ui; added-later");
+        assert_eq!(without_tag("needs-info", "needs-info"), "");
+    }
+
+    #[test]
+    fn entries_in_the_same_millisecond_get_different_ids() {
+        let now = Utc::now();
+        assert_ne!(entry_id(now, 7), entry_id(now, 7));
+    }
+
+    #[test]
+    fn only_a_real_successful_undo_counts() {
+        let original = entry("1-1-0", None, false, None);
+        assert!(!is_undone(&[original.clone(), entry("2-1-1", Some("1-1-0"), true, None)], "1-1-0"));
+        assert!(!is_undone(&[original.clone(), entry("2-1-1", Some("1-1-0"), false, Some("HTTP 500"))], "1-1-0"));
+        assert!(is_undone(&[original, entry("2-1-1", Some("1-1-0"), false, None)], "1-1-0"));
+    }
+}
@@ -0,0 +1,210 @@
+// Opt-in round-robin assignment of new unassigned bugs across a rotation, for team mode
+use crate::audit::{self, update_fields_reversible, WriteAction};
+use crate::azure_devops::{AzureDevOpsClient, Bug};
+use crate::error::BuggerError;
+use crate::out_of_office::{self, OutOfOffice};
+use crate::policy::PolicyMode;
+use crate::roles::Role;
+use crate::{storage, AppConfig, AppState};
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::env;
+use tauri::{AppHandle, State};
+
+/// Where the rotation continues from, so assignments stay fair across runs and restarts.
+const ROTATION_STORE: &str = "rotation";
+
+#[derive(Debug, Clone)]
+pub struct AutoAssignPolicy {
+    pub mode: PolicyMode,
+    /// Emails or unique names, in rotation order.
+    pub rotation: Vec<String>,
+    /// Only bugs created within this many days count as new.
+    pub max_age_days: i64,
+}
+
+impl AutoAssignPolicy {
+    /// Load from BUGGER_AUTOASSIGN (off | dry-run | on), BUGGER_AUTOASSIGN_ROTATION and
+    /// BUGGER_AUTOASSIGN_MAX_AGE_DAYS. Off unless explicitly enabled.
+    pub fn from_env() -> Self {
+        let mode = match env::var("BUGGER_AUTOASSIGN").unwrap_or_default().trim().to_lowercase().as_str() {
+            "on" | "true" => PolicyMode::On,
+            "dry-run" | "dryrun" => PolicyMode::DryRun,
+            _ => PolicyMode::Off,
+        };
+        AutoAssignPolicy {
+            mode,
+            rotation: crate::env_list("BUGGER_AUTOASSIGN_ROTATION"),
+            max_age_days: env::var("BUGGER_AUTOASSIGN_MAX_AGE_DAYS").ok().and_then(|v| v.parse().ok()).unwrap_or(7),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct RotationState {
+    /// Index into the rotation of the next person to assign.
+    next: usize,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct AssignAction {
+    pub bug_id: u64,
+    pub assignee: String,
+    pub dry_run: bool,
+    pub error: Option<String>,
+}
+
+/// Unassigned bugs created within the policy's window, oldest first.
+fn new_unassigned<'a>(bugs: &'a [Bug], policy: &AutoAssignPolicy, now: DateTime<Utc>) -> Vec<&'a Bug> {
+    let mut found: Vec<&Bug> = bugs
+        .iter()
+        .filter(|b| b.assigned_to.is_none())
+        .filter(|b| {
+            b.created_date
+                .as_deref()
+                .and_then(|d| DateTime::parse_from_rfc3339(d).ok())
+                .is_some_and(|d| d.with_timezone(&Utc) >= now - Duration::days(policy.max_age_days))
+        })
+        .collect();
+    found.sort_by_key(|b| (b.created_date.clone(), b.id));
+    found
+}
+
+/// Next person in the rotation who isn't out of office on `today`, and the position after them.
+/// The rotation only moves there once the assignment went through.
+fn next_assignee(policy: &AutoAssignPolicy, away: &OutOfOffice, state: &RotationState, today: NaiveDate) -> Option<(String, usize)> {
+    (0..policy.rotation.len())
+        .map(|offset| (state.next + offset) % policy.rotation.len())
+        .find(|&i| !away.is_out(&policy.rotation[i], today))
+        .map(|i| (policy.rotation[i].clone(), (i + 1) % policy.rotation.len()))
+}
+
+/// Move `state` past `assignments` more people, skipping those out of office on `today`.
+fn advance(policy: &AutoAssignPolicy, away: &OutOfOffice, state: &mut RotationState, today: NaiveDate, assignments: usize) {
+    for _ in 0..assignments {
+        match next_assignee(policy, away, state, today) {
+            Some((_, next)) => state.next = next,
+            None => return,
+        }
+    }
+}
+
+/// Assign the new unassigned bugs round-robin (or, in dry-run, only log it). Every assignment is
+/// written to the audit log, repeated dry runs only once; a failed assignment or a dry run leaves the
+/// rotation where it was.
+pub fn run_auto_assign(app: &AppHandle, client: &AzureDevOpsClient, bugs: &[Bug], policy: &AutoAssignPolicy, dry_run: bool) -> Vec<AssignAction> {
+    let mut state: RotationState = storage::load_json(app, ROTATION_STORE).unwrap_or_else(|e| {
+        warn!("Ignoring saved rotation position: {}", e);
+        RotationState::default()
+    });
+    let away = out_of_office::current(app, &client.config);
+    let today = Local::now().date_naive();
+    let mut actions = Vec::new();
+    let mut assigned = 0;
+    for bug in new_unassigned(bugs, policy, Utc::now()) {
+        let Some((assignee, next)) = next_assignee(policy, &away, &state, today) else {
+            warn!("Auto-assign: everyone in the rotation is out of office");
+            break;
+        };
+        let detail = format!("Assigned to {}", assignee);
+        if dry_run && !audit::is_new_preview(app, bug.id, WriteAction::AutoAssign, &detail) {
+            state.next = next;
+            actions.push(AssignAction { bug_id: bug.id, assignee, dry_run, error: None });
+            continue;
+        }
+        let result = audit::perform(app, &client.config.user_email, bug.id, WriteAction::AutoAssign, detail, dry_run, || {
+            update_fields_reversible(client, bug.id, &[("System.AssignedTo", assignee.as_str())])
+        });
+        info!("Auto-assign #{} to {} (dry run: {})", bug.id, assignee, dry_run);
+        if result.is_ok() {
+            state.next = next;
+            assigned += usize::from(!dry_run);
+        }
+        actions.push(AssignAction { bug_id: bug.id, assignee, dry_run, error: result.err().map(String::from) });
+    }
+    if assigned > 0 {
+        // Advanced from the saved position, which a teammate's run may have moved meanwhile.
+        if let Err(e) = storage::update_json(app, ROTATION_STORE, |saved: &mut RotationState| advance(policy, &away, saved, today, assigned)) {
+            warn!("Failed to save rotation position: {}", e);
+        }
+    }
+    actions
+}
+
+#[tauri::command]
+/// Runs the round-robin auto-assignment now. Needs team mode and a rotation; `dry_run` defaults to
+/// the configured mode, and running while the policy is off is always a dry run.
+pub fn run_auto_assign_policy(app: AppHandle, state: State<AppState>, dry_run: Option<bool>) -> Result<Vec<AssignAction>, BuggerError> {
+    let config = AppConfig::from_env()?;
+    config.provider.require_azure_devops("Auto-assignment")?;
+    if !config.query_filters.is_team_mode() {
+        return Err(BuggerError::Config("Auto-assignment needs team mode (BUGGER_TEAM_MODE and BUGGER_TEAM_AREA_PATHS)".to_string()));
+    }
+    let policy = config.auto_assign.clone();
+    if policy.rotation.is_empty() {
+        return Err(BuggerError::Config("No rotation configured (BUGGER_AUTOASSIGN_ROTATION)".to_string()));
+    }
+    let dry_run = policy.mode != PolicyMode::On || dry_run.unwrap_or(false);
+    if !dry_run {
+        config.roles.check(&config.user_email, Role::Admin, "running auto-assignment")?;
+    }
+    let bugs = state.bugs_or_fetch()?;
+    Ok(run_auto_assign(&app, &AzureDevOpsClient::new(config), &bugs, &policy, dry_run))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::out_of_office::Absence;
+
+    fn policy() -> AutoAssignPolicy {
+        AutoAssignPolicy { mode: PolicyMode::On, rotation: vec!["ann".to_string(), "bob".to_string(), "cy".to_string()], max_age_days: 7 }
+    }
+
+    #[test]
+    fn next_assignee_skips_people_who_are_away() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 15).unwrap();
+        let state = RotationState { next: 0 };
+        assert_eq!(next_assignee(&policy(), &OutOfOffice::default(), &state, today), Some(("ann".to_string(), 1)));
+        let away = OutOfOffice { absences: vec![Absence { who: "ANN".to_string(), from: today, until: today, configured: true }] };
+        assert_eq!(next_assignee(&policy(), &away, &state, today), Some(("bob".to_string(), 2)));
+        assert_eq!(next_assignee(&policy(), &away, &RotationState { next: 2 }, today), Some(("cy".to_string(), 0)));
+        let everyone = OutOfOffice {
+            absences: policy().rotation.iter().map(|who| Absence { who: who.clone(), from: today, until: today, configured: true }).collect(),
+        };
+        assert_eq!(next_assignee(&policy(), &everyone, &state, today), None);
+    }
+
+    #[test]
+    fn the_rotation_advances_from_the_saved_position() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 15).unwrap();
+        let away = OutOfOffice { absences: vec![Absence { who: "cy".to_string(), from: today, until: today, configured: true }] };
+        // A teammate's run moved the rotation to bob meanwhile: two more assignments go to bob and ann.
+        let mut saved = RotationState { next: 1 };
+        advance(&policy(), &away, &mut saved, today, 2);
+        assert_eq!(saved.next, 1);
+        let mut saved = RotationState { next: 0 };
+        advance(&policy(), &away, &mut saved, today, 1);
+        assert_eq!(saved.next, 1);
+    }
+
+    #[test]
+    fn only_recent_unassigned_bugs_are_new() {
+        let now = Utc::now();
+        let created = |days: i64| Some((now - Duration::days(days)).to_rfc3339());
+        let mut old = Bug::sample(1, "Old");
+        old.created_date = created(30);
+        let mut newer = Bug::sample(2, "Newer");
+        newer.created_date = created(1);
+        let mut older = Bug::sample(3, "Older");
+        older.created_date = created(2);
+        let mut assigned = Bug::sample(4, "Assigned");
+        assigned.created_date = created(1);
+        assigned.assigned_to = Some(crate::azure_devops::Identity { display_name: "Ann".to_string(), unique_name: "ann".to_string(), avatar_url: None });
+        let bugs = [old, newer, older, assigned];
+        let ids: Vec<u64> = new_unassigned(&bugs, &policy(), now).iter().map(|b| b.id).collect();
+        assert_eq!(ids, vec![3, 2]);
+    }
+}
@@ -1,21 +1,243 @@
 use base64::Engine; // Needed for .encode()
-use reqwest::blocking::Client;
-use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use reqwest::blocking::{Client, Request, RequestBuilder};
+use reqwest::{Method, StatusCode};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE, RANGE, RETRY_AFTER};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use crate::AppConfig;
+use std::collections::BTreeMap;
+use std::hash::{BuildHasher, Hash, Hasher, RandomState};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use crate::environment::Environment;
+use crate::error::BuggerError;
+use crate::process::{self, StateCategory};
+use crate::wiql::QueryFilters;
+use crate::{http_trace, AppConfig};
+use std::time::{Duration, Instant};
+
+/// Most ids `workitemsbatch` accepts in one request.
+const BATCH_SIZE: usize = 200;
+/// Most ids a WIQL query returns; larger results need paging.
+const WIQL_LIMIT: usize = 20_000;
+
+/// First backoff delay; each further retry doubles it.
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+/// Longest wait before a retry, also when the server's Retry-After asks for more.
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// Fingerprint of the primary PAT that was rejected, after which requests fail over to the secondary
+/// one. A new primary PAT (saved in settings or the keychain) is tried again.
+static REJECTED_PAT: Mutex<Option<u64>> = Mutex::new(None);
+
+fn fingerprint(pat: &str) -> u64 {
+    let mut hasher = std::hash::DefaultHasher::new();
+    pat.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Whether `pat` is a primary PAT rejected this session (shown as invalid in the settings status).
+pub fn primary_pat_rejected(pat: &str) -> bool {
+    REJECTED_PAT.lock().is_ok_and(|rejected| *rejected == Some(fingerprint(pat)))
+}
+
+/// Whether a reply is worth retrying: throttling always (the request wasn't processed), other
+/// transient server errors only for requests that are safe to repeat.
+fn is_retryable(status: StatusCode, request: &Request) -> bool {
+    let transient = matches!(status.as_u16(), 500 | 502 | 503 | 504);
+    // The WIQL and batch endpoints only read, despite being POSTs.
+    let safe = !matches!(*request.method(), Method::POST | Method::PATCH)
+        || request.url().path().ends_with("/_apis/wit/wiql")
+        || request.url().path().ends_with("/_apis/wit/workitemsbatch");
+    status == StatusCode::TOO_MANY_REQUESTS || transient && safe
+}
+
+/// The wait a Retry-After header asks for, in seconds or as an HTTP date.
+fn retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some((at.with_timezone(&chrono::Utc) - chrono::Utc::now()).to_std().unwrap_or_default())
+}
+
+/// Exponential backoff for retry `attempt` (0-based) with jitter: between half and all of the delay.
+fn backoff(attempt: u32) -> Duration {
+    let delay = RETRY_BASE_DELAY.saturating_mul(1 << attempt.min(16)).min(RETRY_MAX_DELAY);
+    let jitter = (RandomState::new().hash_one(attempt) % 1000) as f64 / 1000.0;
+    delay.mul_f64(0.5 + jitter / 2.0)
+}
+
+/// Azure DevOps answers a bad PAT with 401, or with 203 and a sign-in page.
+pub fn is_auth_failure(status: StatusCode) -> bool {
+    status == StatusCode::UNAUTHORIZED || status == StatusCode::NON_AUTHORITATIVE_INFORMATION
+}
+
+/// Basic auth header value for a personal access token.
+fn basic_auth(pat: &str) -> Result<HeaderValue, BuggerError> {
+    let value = format!("Basic {}", base64::engine::general_purpose::STANDARD.encode(format!(":{}", pat)));
+    HeaderValue::from_str(&value).map_err(|e| BuggerError::Config(format!("Invalid header value: {}", e)))
+}
+
+/// Send a request with `pat` and return only the status code, for checking credentials before they are saved.
+/// A JSON `body` makes it a POST.
+pub fn probe(url: &str, pat: &str, body: Option<&Value>) -> Result<StatusCode, BuggerError> {
+    let client = Client::new();
+    let request = match body {
+        Some(body) => client
+            .post(url)
+            .header(CONTENT_TYPE, "application/json")
+            .body(serde_json::to_vec(body).map_err(|e| BuggerError::Other(format!("JSON serialize error: {}", e)))?),
+        None => client.get(url),
+    };
+    let resp = request.header(AUTHORIZATION, basic_auth(pat)?).send().map_err(|e| BuggerError::Network(format!("Request error: {}", e)))?;
+    Ok(resp.status())
+}
+
+/// A fully read response, so it can be recorded before being parsed.
+struct Reply {
+    status: StatusCode,
+    content_type: Option<String>,
+    /// Requested wait before retrying, from a Retry-After header.
+    retry_after: Option<Duration>,
+    body: Vec<u8>,
+}
+
+impl Reply {
+    fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
 
 pub struct AzureDevOpsClient {
     pub config: AppConfig,
     client: Client,
+    /// Longest wait slept through before a retry; a longer Retry-After ends the request instead.
+    max_retry_wait: Duration,
+    /// Requests this client retried after throttling or a transient server error.
+    retried: AtomicU32,
+    /// The Retry-After of the last reply not retried because it asked for more than `max_retry_wait`.
+    throttled_for: Mutex<Option<Duration>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Bug {
     pub id: u64,
     pub title: String,
     pub state: String,
     pub created_date: Option<String>,
+    /// Last time any field changed.
+    #[serde(default)]
+    pub changed_date: Option<String>,
+    /// When the bug entered its current state.
+    #[serde(default)]
+    pub state_changed_date: Option<String>,
     pub description: Option<String>,
+    pub severity: Option<String>,
+    pub board_column: Option<String>,
+    pub parent_id: Option<u64>,
+    #[serde(default)]
+    pub comment_count: u64,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub assigned_to: Option<Identity>,
+    /// The reporter.
+    #[serde(default)]
+    pub created_by: Option<Identity>,
+    #[serde(default)]
+    pub area_path: Option<String>,
+    /// Extra fields from BUGGER_CUSTOM_FIELDS, keyed by reference name (e.g. "Microsoft.VSTS.Build.FoundIn").
+    #[serde(default)]
+    pub custom_fields: BTreeMap<String, String>,
+    /// OS, build, browser and device mentioned in the title or description.
+    #[serde(default)]
+    pub environment: Environment,
+}
+
+#[cfg(test)]
+impl Bug {
+    /// An active bug with nothing but an id and a title, for tests to fill in.
+    pub fn sample(id: u64, title: &str) -> Bug {
+        Bug {
+            id,
+            title: title.to_string(),
+            state: "Active".to_string(),
+            created_date: None,
+            changed_date: None,
+            state_changed_date: None,
+            description: None,
+            severity: None,
+            board_column: None,
+            parent_id: None,
+            comment_count: 0,
+            tags: Vec::new(),
+            assigned_to: None,
+            created_by: None,
+            area_path: None,
+            custom_fields: Default::default(),
+            environment: Default::default(),
+        }
+    }
+}
+
+/// An Azure DevOps user as returned in identity fields such as `System.AssignedTo`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Identity {
+    pub display_name: String,
+    pub unique_name: String,
+    pub avatar_url: Option<String>,
+}
+
+impl Identity {
+    fn from_value(value: &Value) -> Option<Self> {
+        Some(Identity {
+            display_name: value["displayName"].as_str()?.to_string(),
+            unique_name: value["uniqueName"].as_str().unwrap_or("").to_string(),
+            avatar_url: value["_links"]["avatar"]["href"].as_str().or(value["imageUrl"].as_str()).map(|s| s.to_string()),
+        })
+    }
+}
+
+/// A discussion comment on a work item (text is HTML).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Comment {
+    pub text: String,
+    pub author: String,
+    pub author_email: String,
+    pub created_date: Option<String>,
+}
+
+/// One revision of a work item: the fields as they were after a change.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Revision {
+    pub rev: u64,
+    pub state: String,
+    pub changed_by: Option<Identity>,
+    pub changed_date: Option<String>,
+    pub title: String,
+    pub description: Option<String>,
+    pub created_date: Option<String>,
+}
+
+/// A workflow state of a work item type.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkItemState {
+    pub name: String,
+    /// Board color, hex without `#`.
+    pub color: String,
+    /// Azure DevOps state category: Proposed, InProgress, Resolved, Completed or Removed.
+    pub category: String,
+}
+
+/// Minimal view of a non-bug work item such as a parent feature or epic.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkItemSummary {
+    pub id: u64,
+    pub title: String,
+    pub work_item_type: String,
+    pub parent_id: Option<u64>,
 }
 
 impl AzureDevOpsClient {
@@ -23,39 +245,179 @@ impl AzureDevOpsClient {
         AzureDevOpsClient {
             config,
             client: Client::new(),
+            max_retry_wait: RETRY_MAX_DELAY,
+            retried: AtomicU32::new(0),
+            throttled_for: Mutex::new(None),
         }
     }
 
-    pub fn fetch_active_bugs(&self) -> Result<Vec<u64>, String> {
-        let url = format!(
-            "https://dev.azure.com/{}/{}/_apis/wit/wiql?api-version=7.0",
-            self.config.org, self.config.project
-        );
-        let query = serde_json::json!({
-            "query": format!(
-                "SELECT [System.Id] FROM WorkItems WHERE [System.WorkItemType] = 'Bug' AND [System.State] <> 'Closed' AND [System.AssignedTo] = '{}' ORDER BY [System.CreatedDate] DESC",
-                self.config.user_email
-            )
-        });
+    /// Give up on throttled requests asking to wait longer than `wait`, e.g. while others wait on this one.
+    pub fn with_max_retry_wait(mut self, wait: Duration) -> Self {
+        self.max_retry_wait = wait;
+        self
+    }
+
+    /// Requests retried by this client.
+    pub fn retried(&self) -> u32 {
+        self.retried.load(Ordering::SeqCst)
+    }
+
+    /// How long the server asked to wait on the last request given up for waiting too long, if any.
+    pub fn throttled_for(&self) -> Option<Duration> {
+        self.throttled_for.lock().ok().and_then(|t| *t)
+    }
+
+    fn auth_headers(pat: &str) -> Result<HeaderMap, BuggerError> {
         let mut headers = HeaderMap::new();
-        let pat = format!("Basic {}", base64::engine::general_purpose::STANDARD.encode(format!(":{}", self.config.azure_devops_pat)));
-        headers.insert(AUTHORIZATION, HeaderValue::from_str(&pat).map_err(|e| format!("Invalid header value: {}", e))?);
+        headers.insert(AUTHORIZATION, basic_auth(pat)?);
         headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
-        let body = serde_json::to_vec(&query).map_err(|e| format!("JSON serialize error: {}", e))?;
-        let resp = self
-            .client
-            .post(&url)
-            .headers(headers)
-            .body(body)
-            .send()
-            .map_err(|e| format!("Request error: {}", e))?;
+        Ok(headers)
+    }
+
+    /// Send a request built by `build` from the auth headers. If the primary PAT is rejected and a
+    /// secondary PAT is configured, the request is retried with it and the rest of the session uses it.
+    fn send(&self, build: impl Fn(HeaderMap) -> RequestBuilder) -> Result<Reply, BuggerError> {
+        let secondary = self.config.secondary_pat.as_deref();
+        let pat = match secondary {
+            Some(secondary) if primary_pat_rejected(&self.config.azure_devops_pat) => secondary,
+            _ => self.config.azure_devops_pat.as_str(),
+        };
+        let reply = self.execute(build(Self::auth_headers(pat)?))?;
+        match secondary {
+            Some(secondary) if is_auth_failure(reply.status) && pat != secondary => {
+                log::warn!("Primary Azure DevOps PAT was rejected ({}); failing over to the secondary PAT", reply.status);
+                if let Ok(mut rejected) = REJECTED_PAT.lock() {
+                    *rejected = Some(fingerprint(pat));
+                }
+                self.execute(build(Self::auth_headers(secondary)?))
+            }
+            _ => Ok(reply),
+        }
+    }
+
+    /// Execute a request, retrying throttled (429) and transient 5xx replies up to `http_retries`
+    /// times. Each retry waits as long as the server's Retry-After asks, or else backs off exponentially.
+    fn execute(&self, builder: RequestBuilder) -> Result<Reply, BuggerError> {
+        let request = builder.build().map_err(|e| BuggerError::Config(format!("Request error: {}", e)))?;
+        let mut attempt = 0;
+        loop {
+            let reply = self.execute_once(request.try_clone().ok_or(BuggerError::Other("Request body can't be resent".to_string()))?)?;
+            if attempt >= self.config.http_retries || !is_retryable(reply.status, &request) {
+                return Ok(reply);
+            }
+            let wait = reply.retry_after.map_or_else(|| backoff(attempt), |after| after.min(RETRY_MAX_DELAY));
+            if wait > self.max_retry_wait {
+                if let Ok(mut throttled) = self.throttled_for.lock() {
+                    *throttled = Some(wait);
+                }
+                return Ok(reply);
+            }
+            log::warn!("Azure DevOps replied {} to {} {}; retrying in {:.1}s", reply.status, request.method(), request.url().path(), wait.as_secs_f64());
+            self.retried.fetch_add(1, Ordering::SeqCst);
+            std::thread::sleep(wait);
+            attempt += 1;
+        }
+    }
+
+    /// Execute and fully read one request, recording it when HTTP tracing is enabled.
+    fn execute_once(&self, request: Request) -> Result<Reply, BuggerError> {
+        let (method, url) = (request.method().to_string(), request.url().to_string());
+        let request_body = request.body().and_then(|b| b.as_bytes()).map(|b| String::from_utf8_lossy(b).into_owned());
+        let started = Instant::now();
+        let resp = self.client.execute(request).map_err(|e| BuggerError::Network(format!("Request error: {}", e)))?;
         let status = resp.status();
-        let resp_text = resp.text().map_err(|e| format!("Response text error: {}", e))?;
-        if !status.is_success() {
+        let content_type = resp.headers().get(CONTENT_TYPE).and_then(|v| v.to_str().ok()).map(str::to_string);
+        let retry_after = resp.headers().get(RETRY_AFTER).and_then(|v| v.to_str().ok()).and_then(retry_after);
+        let body = resp.bytes().map_err(|e| BuggerError::Network(format!("Response body error: {}", e)))?.to_vec();
+        let reply = Reply { status, content_type, retry_after, body };
+        if http_trace::is_enabled() {
+            let secrets: Vec<&str> = std::iter::once(self.config.azure_devops_pat.as_str())
+                .chain(self.config.secondary_pat.as_deref())
+                .chain(self.config.openai_api_key.as_deref())
+                .collect();
+            let is_text = reply.content_type.as_deref().is_none_or(|t| t.contains("json") || t.starts_with("text/"));
+            http_trace::record(&http_trace::HttpExchange {
+                timestamp: chrono::Utc::now().to_rfc3339(),
+                method,
+                url: http_trace::sanitize(&url, &secrets),
+                status: status.as_u16(),
+                duration_ms: started.elapsed().as_millis(),
+                request_body: request_body.map(|b| http_trace::sanitize(&b, &secrets)),
+                response_body: if is_text { http_trace::sanitize(&reply.text(), &secrets) } else { format!("[{} bytes]", reply.body.len()) },
+            });
+        }
+        Ok(reply)
+    }
+
+    /// POST a JSON body to an Azure DevOps endpoint and parse the JSON response.
+    fn post_json(&self, url: &str, body_json: &Value) -> Result<Value, BuggerError> {
+        let body = serde_json::to_vec(body_json).map_err(|e| BuggerError::Other(format!("JSON serialize error: {}", e)))?;
+        let resp = self.send(|headers| self.client.post(url).headers(headers).body(body.clone()))?;
+        Self::parse_response(resp)
+    }
+
+    /// GET an Azure DevOps endpoint and parse the JSON response.
+    fn get_json(&self, url: &str) -> Result<Value, BuggerError> {
+        let resp = self.send(|headers| self.client.get(url).headers(headers))?;
+        Self::parse_response(resp)
+    }
+
+    fn parse_response(reply: Reply) -> Result<Value, BuggerError> {
+        let status = reply.status;
+        let resp_text = reply.text();
+        // A 203 sign-in page is a success status, but the token was rejected.
+        if is_auth_failure(status) || !status.is_success() {
             println!("Azure DevOps API error ({}): {}", status, resp_text);
-            return Err(format!("Azure DevOps API error ({}): {}", status, resp_text));
+            return Err(BuggerError::from_status("Azure DevOps", status, &resp_text));
+        }
+        serde_json::from_str(&resp_text).map_err(|e| BuggerError::Parse(format!("JSON error: {}\nRaw response: {}", e, resp_text)))
+    }
+
+    fn batch_url(&self) -> String {
+        format!(
+            "{}/{}/{}/_apis/wit/workitemsbatch?api-version=7.0",
+            self.config.base_url, self.config.org, self.config.project
+        )
+    }
+
+    /// Fetch `fields` of `ids` in batches of at most BATCH_SIZE, up to `fetch_concurrency` batches at a
+    /// time, returning the work items of all batches in id-list order. The first failure stops the rest.
+    fn post_batch(&self, ids: &[u64], fields: &[&str]) -> Result<Vec<Value>, BuggerError> {
+        let chunks: Vec<&[u64]> = ids.chunks(BATCH_SIZE).collect();
+        let url = self.batch_url();
+        let next = AtomicUsize::new(0);
+        let failed = AtomicBool::new(false);
+        let fetch_pages = || {
+            let mut pages = Vec::new();
+            while !failed.load(Ordering::SeqCst) {
+                let i = next.fetch_add(1, Ordering::SeqCst);
+                let Some(chunk) = chunks.get(i) else { break };
+                let page = self.post_json(&url, &serde_json::json!({ "ids": chunk, "fields": fields })).map(|json| json["value"].as_array().cloned().unwrap_or_default());
+                failed.fetch_or(page.is_err(), Ordering::SeqCst);
+                pages.push((i, page));
+            }
+            pages
+        };
+        let workers = self.config.fetch_concurrency.clamp(1, chunks.len().max(1));
+        let mut pages: Vec<(usize, Result<Vec<Value>, BuggerError>)> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers).map(|_| scope.spawn(fetch_pages)).collect();
+            handles.into_iter().flat_map(|h| h.join().unwrap_or_else(|e| std::panic::resume_unwind(e))).collect()
+        });
+        pages.sort_by_key(|(i, _)| *i);
+        let mut items = Vec::new();
+        for (_, page) in pages {
+            items.extend(page?);
         }
-        let json: Value = serde_json::from_str(&resp_text).map_err(|e| format!("JSON error: {}\nRaw response: {}", e, resp_text))?;
+        Ok(items)
+    }
+
+    /// One WIQL request, capped at WIQL_LIMIT results.
+    fn query_ids(&self, wiql: &str) -> Result<Vec<u64>, BuggerError> {
+        let url = format!(
+            "{}/{}/{}/_apis/wit/wiql?$top={}&api-version=7.0",
+            self.config.base_url, self.config.org, self.config.project, WIQL_LIMIT
+        );
+        let json = self.post_json(&url, &serde_json::json!({ "query": wiql }))?;
         let ids = json["workItems"]
             .as_array()
             .unwrap_or(&vec![])
@@ -65,64 +427,1012 @@ impl AzureDevOpsClient {
         Ok(ids)
     }
 
-    pub fn fetch_bug_details(&self, ids: &[u64]) -> Result<Vec<Bug>, String> {
-        if ids.is_empty() {
-            return Ok(vec![]);
+    /// Runs a WIQL query and returns the matching work item IDs. Results past the WIQL limit are
+    /// fetched page by page in id order and appended.
+    pub fn run_wiql(&self, wiql: &str) -> Result<Vec<u64>, BuggerError> {
+        let mut ids = self.query_ids(wiql)?;
+        if ids.len() < WIQL_LIMIT {
+            return Ok(ids);
+        }
+        log::warn!("WIQL query returned {} or more work items; fetching the rest page by page", WIQL_LIMIT);
+        let mut seen: std::collections::HashSet<u64> = ids.iter().copied().collect();
+        let mut after = 0;
+        loop {
+            let page = self.query_ids(&crate::wiql::after_id(wiql, after))?;
+            ids.extend(page.iter().filter(|id| seen.insert(**id)));
+            match page.last() {
+                Some(last) if page.len() == WIQL_LIMIT => after = *last,
+                _ => return Ok(ids),
+            }
+        }
+    }
+
+    /// The configured query filters. Unless BUGGER_EXCLUDED_STATES is set, the completed and removed
+    /// states of the project's process are excluded.
+    pub fn query_filters(&self) -> QueryFilters {
+        let mut filters = self.config.query_filters.clone();
+        if !filters.excluded_states_configured {
+            let finished = process::model(self).states(&[StateCategory::Completed, StateCategory::Removed]);
+            if !finished.is_empty() {
+                filters.excluded_states = finished;
+            }
         }
+        filters
+    }
+
+    /// WIQL for the configured bug query (custom BUGGER_WIQL with macros expanded, if set).
+    pub fn active_bugs_wiql(&self) -> String {
+        self.query_filters().wiql(&self.config.my_identities(), &self.config.project)
+    }
+
+    pub fn fetch_active_bugs(&self) -> Result<Vec<u64>, BuggerError> {
+        self.run_wiql(&self.active_bugs_wiql())
+    }
+
+    /// Fields requested for every bug: the ones `Bug` is built from plus the configured custom fields.
+    fn bug_fields(&self) -> Vec<&str> {
+        let mut fields: Vec<&str> = vec![
+            "System.Id",
+            "System.Title",
+            "System.State",
+            "System.CreatedDate",
+            "System.ChangedDate",
+            "Microsoft.VSTS.Common.StateChangeDate",
+            "System.Description",
+            "Microsoft.VSTS.Common.Severity",
+            "System.BoardColumn",
+            "System.Parent",
+            "System.CommentCount",
+            "System.Tags",
+            "System.AssignedTo",
+            "System.CreatedBy",
+            "System.AreaPath",
+        ];
+        fields.extend(self.config.custom_fields.iter().map(|f| f.as_str()));
+        fields
+    }
+
+    pub fn fetch_bug_details(&self, ids: &[u64]) -> Result<Vec<Bug>, BuggerError> {
+        let items = self.post_batch(ids, &self.bug_fields())?;
+        let mut bugs = vec![];
+        for item in &items {
+            let fields = item["fields"].as_object();
+            if let Some(id) = item["id"].as_u64() {
+                let title = fields.and_then(|f| f.get("System.Title")).and_then(|v| v.as_str()).unwrap_or("").to_string();
+                let state = fields.and_then(|f| f.get("System.State")).and_then(|v| v.as_str()).unwrap_or("").to_string();
+                let created_date = fields.and_then(|f| f.get("System.CreatedDate")).and_then(|v| v.as_str()).map(|s| s.to_string());
+                let changed_date = fields.and_then(|f| f.get("System.ChangedDate")).and_then(|v| v.as_str()).map(|s| s.to_string());
+                let state_changed_date = fields.and_then(|f| f.get("Microsoft.VSTS.Common.StateChangeDate")).and_then(|v| v.as_str()).map(|s| s.to_string());
+                let description = fields.and_then(|f| f.get("System.Description")).and_then(|v| v.as_str()).map(|s| s.to_string());
+                let severity = fields.and_then(|f| f.get("Microsoft.VSTS.Common.Severity")).and_then(|v| v.as_str()).map(|s| s.to_string());
+                let board_column = fields.and_then(|f| f.get("System.BoardColumn")).and_then(|v| v.as_str()).map(|s| s.to_string());
+                let parent_id = fields.and_then(|f| f.get("System.Parent")).and_then(|v| v.as_u64());
+                let comment_count = fields.and_then(|f| f.get("System.CommentCount")).and_then(|v| v.as_u64()).unwrap_or(0);
+                let assigned_to = fields.and_then(|f| f.get("System.AssignedTo")).and_then(Identity::from_value);
+                let created_by = fields.and_then(|f| f.get("System.CreatedBy")).and_then(Identity::from_value);
+                let area_path = fields.and_then(|f| f.get("System.AreaPath")).and_then(|v| v.as_str()).map(|s| s.to_string());
+                let tags = fields
+                    .and_then(|f| f.get("System.Tags"))
+                    .and_then(|v| v.as_str())
+                    .map(|t| t.split(';').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+                    .unwrap_or_default();
+                let custom_fields = self
+                    .config
+                    .custom_fields
+                    .iter()
+                    .filter_map(|name| {
+                        let value = field_value_to_string(fields?.get(name)?)?;
+                        Some((name.clone(), value))
+                    })
+                    .collect();
+                let environment = Environment::extract(&format!("{}\n{}", title, description.as_deref().unwrap_or("")));
+                bugs.push(Bug {
+                    id,
+                    title,
+                    state,
+                    created_date,
+                    changed_date,
+                    state_changed_date,
+                    description,
+                    severity,
+                    board_column,
+                    parent_id,
+                    comment_count,
+                    tags,
+                    assigned_to,
+                    created_by,
+                    area_path,
+                    custom_fields,
+                    environment,
+                });
+            } else {
+                println!("Warning: Missing or invalid bug ID in response item: {:?}", item);
+            }
+        }
+        Ok(bugs)
+    }
+
+    /// Fetch every revision of a work item, oldest first.
+    pub fn fetch_revisions(&self, id: u64) -> Result<Vec<Revision>, BuggerError> {
         let url = format!(
-            "https://dev.azure.com/{}/{}/_apis/wit/workitemsbatch?api-version=7.0",
-            self.config.org, self.config.project
+            "{}/{}/{}/_apis/wit/workItems/{}/revisions?api-version=7.0",
+            self.config.base_url, self.config.org, self.config.project, id
         );
-        let body_json = serde_json::json!({
-            "ids": ids,
-            "fields": [
-                "System.Id",
-                "System.Title",
-                "System.State",
-                "System.CreatedDate",
-                "System.Description"
-            ]
-        });
-        let mut headers = HeaderMap::new();
-        let pat = format!("Basic {}", base64::engine::general_purpose::STANDARD.encode(format!(":{}", self.config.azure_devops_pat)));
-        headers.insert(AUTHORIZATION, HeaderValue::from_str(&pat).unwrap());
-        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
-        let body = serde_json::to_vec(&body_json).map_err(|e| format!("JSON serialize error: {}", e))?;
-        let resp = self
-            .client
-            .post(&url)
-            .headers(headers)
-            .body(body)
-            .send()
-            .map_err(|e| format!("Request error: {}", e))?;
-        let status = resp.status();
-        let resp_text = resp.text().map_err(|e| format!("Response text error: {}", e))?;
-        if !status.is_success() {
-            println!("Azure DevOps API error ({}): {}", status, resp_text);
-            return Err(format!("Azure DevOps API error ({}): {}", status, resp_text));
+        let json = self.get_json(&url)?;
+        let items = json["value"].as_array().cloned().unwrap_or_default();
+        Ok(items
+            .iter()
+            .map(|r| {
+                let field = |name: &str| r["fields"][name].as_str().map(|s| s.to_string());
+                Revision {
+                    rev: r["rev"].as_u64().unwrap_or(0),
+                    state: field("System.State").unwrap_or_default(),
+                    changed_by: Identity::from_value(&r["fields"]["System.ChangedBy"]),
+                    changed_date: field("System.ChangedDate"),
+                    title: field("System.Title").unwrap_or_default(),
+                    description: field("System.Description"),
+                    created_date: field("System.CreatedDate"),
+                }
+            })
+            .collect())
+    }
+
+    /// Fetch the discussion comments on a work item, oldest first.
+    pub fn fetch_comments(&self, id: u64) -> Result<Vec<Comment>, BuggerError> {
+        let url = format!(
+            "{}/{}/{}/_apis/wit/workItems/{}/comments?order=asc&api-version=7.0-preview.3",
+            self.config.base_url, self.config.org, self.config.project, id
+        );
+        let json = self.get_json(&url)?;
+        let items = json["comments"].as_array().cloned().unwrap_or_default();
+        Ok(items
+            .iter()
+            .map(|c| Comment {
+                text: c["text"].as_str().unwrap_or("").to_string(),
+                author: c["createdBy"]["displayName"].as_str().unwrap_or("").to_string(),
+                author_email: c["createdBy"]["uniqueName"].as_str().unwrap_or("").to_string(),
+                created_date: c["createdDate"].as_str().map(|s| s.to_string()),
+            })
+            .collect())
+    }
+
+    /// Files attached to a work item, as (file name, download URL).
+    pub fn fetch_attachments(&self, id: u64) -> Result<Vec<(String, String)>, BuggerError> {
+        let url = format!(
+            "{}/{}/{}/_apis/wit/workitems/{}?$expand=relations&api-version=7.0",
+            self.config.base_url, self.config.org, self.config.project, id
+        );
+        let json = self.get_json(&url)?;
+        Ok(json["relations"]
+            .as_array()
+            .unwrap_or(&vec![])
+            .iter()
+            .filter(|r| r["rel"] == "AttachedFile")
+            .filter_map(|r| Some((r["attributes"]["name"].as_str()?.to_string(), r["url"].as_str()?.to_string())))
+            .collect())
+    }
+
+    /// Download at most the first `max_bytes` of a resource (ranged request; extra bytes are discarded
+    /// if the server ignores the range).
+    pub fn fetch_prefix(&self, url: &str, max_bytes: usize) -> Result<Vec<u8>, BuggerError> {
+        let range = format!("bytes=0-{}", max_bytes.saturating_sub(1));
+        let reply = self.send(|headers| self.client.get(url).headers(headers).header(RANGE, range.as_str()))?;
+        if !reply.status.is_success() {
+            return Err(BuggerError::from_status("Download", reply.status, url));
         }
-        let json: Value = serde_json::from_str(&resp_text).map_err(|e| format!("JSON error: {}\nRaw response: {}", e, resp_text))?;
-        let mut bugs = vec![];
-        if let Some(items) = json["value"].as_array() {
-            for item in items {
+        let mut body = reply.body;
+        body.truncate(max_bytes);
+        Ok(body)
+    }
+
+    /// Icon URL for a work item type (e.g. "Bug"), from the project's type metadata.
+    pub fn fetch_work_item_type_icon_url(&self, work_item_type: &str) -> Result<Option<String>, BuggerError> {
+        let url = format!(
+            "{}/{}/{}/_apis/wit/workitemtypes/{}?api-version=7.0",
+            self.config.base_url, self.config.org, self.config.project, work_item_type
+        );
+        let json = self.get_json(&url)?;
+        Ok(json["icon"]["url"].as_str().map(|s| s.to_string()))
+    }
+
+    /// States of a work item type with their board colors and categories, in workflow order.
+    pub fn fetch_work_item_type_states(&self, work_item_type: &str) -> Result<Vec<WorkItemState>, BuggerError> {
+        let url = format!(
+            "{}/{}/{}/_apis/wit/workitemtypes/{}/states?api-version=7.0",
+            self.config.base_url, self.config.org, self.config.project, work_item_type
+        );
+        let json = self.get_json(&url)?;
+        let states = json["value"]
+            .as_array()
+            .unwrap_or(&vec![])
+            .iter()
+            .filter_map(|s| {
+                Some(WorkItemState {
+                    name: s["name"].as_str()?.to_string(),
+                    color: s["color"].as_str().unwrap_or_default().to_string(),
+                    category: s["category"].as_str().unwrap_or_default().to_string(),
+                })
+            })
+            .collect();
+        Ok(states)
+    }
+
+    /// Name of the project's process (e.g. "Agile", "Scrum", "CMMI", or an inherited process).
+    pub fn fetch_process_name(&self) -> Result<Option<String>, BuggerError> {
+        let url = format!(
+            "{}/{}/_apis/projects/{}/properties?keys=System.ProcessTemplateType&api-version=7.1-preview.1",
+            self.config.base_url, self.config.org, self.config.project
+        );
+        let json = self.get_json(&url)?;
+        let Some(process_id) = json["value"].as_array().and_then(|v| v.first()).and_then(|p| p["value"].as_str()).map(|s| s.to_string()) else {
+            return Ok(None);
+        };
+        let url = format!("{}/{}/_apis/work/processes/{}?api-version=7.1", self.config.base_url, self.config.org, process_id);
+        Ok(self.get_json(&url)?["name"].as_str().map(|s| s.to_string()))
+    }
+
+    /// Description HTML of a team's Bug work item template: the one named `name`, else the first.
+    /// `None` when the team has no Bug templates.
+    pub fn fetch_bug_template(&self, team: &str, name: Option<&str>) -> Result<Option<String>, BuggerError> {
+        let base = format!("{}/{}/{}/{}/_apis/wit/templates", self.config.base_url, self.config.org, self.config.project, team);
+        let list = self.get_json(&format!("{}?workitemtypename=Bug&api-version=7.1", base))?;
+        let templates = list["value"].as_array().cloned().unwrap_or_default();
+        let chosen = match name {
+            Some(name) => templates.iter().find(|t| t["name"].as_str().is_some_and(|n| n.eq_ignore_ascii_case(name))),
+            None => templates.first(),
+        };
+        let Some(id) = chosen.and_then(|t| t["id"].as_str()) else {
+            return Ok(None);
+        };
+        let template = self.get_json(&format!("{}/{}?api-version=7.1", base, id))?;
+        let fields = &template["fields"];
+        Ok(fields["System.Description"].as_str().or(fields["Microsoft.VSTS.TCM.ReproSteps"].as_str()).map(|s| s.to_string()))
+    }
+
+    /// Download a binary resource (avatar, icon) with the PAT; returns the bytes and content type.
+    pub fn fetch_bytes(&self, url: &str) -> Result<(Vec<u8>, String), BuggerError> {
+        let reply = self.send(|headers| self.client.get(url).headers(headers))?;
+        if !reply.status.is_success() {
+            return Err(BuggerError::from_status("Download", reply.status, url));
+        }
+        let content_type = reply.content_type.unwrap_or_else(|| "image/png".to_string());
+        Ok((reply.body, content_type))
+    }
+
+    /// Post a new discussion comment (HTML) on a work item.
+    pub fn add_comment(&self, id: u64, text: &str) -> Result<Value, BuggerError> {
+        let url = format!(
+            "{}/{}/{}/_apis/wit/workItems/{}/comments?api-version=7.0-preview.3",
+            self.config.base_url, self.config.org, self.config.project, id
+        );
+        self.post_json(&url, &serde_json::json!({ "text": text }))
+    }
+
+    /// Current values of the given fields on one work item (`None` when unset).
+    /// Identity fields come back as unique names, so the values can be written back.
+    pub fn fetch_fields(&self, id: u64, names: &[&str]) -> Result<Vec<(String, Option<String>)>, BuggerError> {
+        let url = format!(
+            "{}/{}/{}/_apis/wit/workitems/{}?fields={}&api-version=7.0",
+            self.config.base_url,
+            self.config.org,
+            self.config.project,
+            id,
+            names.join(",")
+        );
+        let json = self.get_json(&url)?;
+        Ok(names
+            .iter()
+            .map(|name| {
+                let value = json["fields"].get(*name);
+                let unique_name = value.and_then(|v| v["uniqueName"].as_str()).map(str::to_string);
+                (name.to_string(), unique_name.or_else(|| value.and_then(field_value_to_string)))
+            })
+            .collect())
+    }
+
+    /// Set or clear work item fields; `None` removes the field value.
+    pub fn restore_fields(&self, id: u64, values: &[(String, Option<String>)]) -> Result<Value, BuggerError> {
+        let patch: Vec<Value> = values
+            .iter()
+            .map(|(field, value)| match value {
+                Some(v) => serde_json::json!({ "op": "add", "path": format!("/fields/{}", field), "value": v }),
+                None => serde_json::json!({ "op": "remove", "path": format!("/fields/{}", field) }),
+            })
+            .collect();
+        self.patch_work_item(id, &patch)
+    }
+
+    /// Delete a discussion comment.
+    pub fn delete_comment(&self, id: u64, comment_id: u64) -> Result<Value, BuggerError> {
+        let url = format!(
+            "{}/{}/{}/_apis/wit/workItems/{}/comments/{}?api-version=7.0-preview.3",
+            self.config.base_url, self.config.org, self.config.project, id, comment_id
+        );
+        let reply = self.send(|headers| self.client.delete(&url).headers(headers))?;
+        if !reply.status.is_success() {
+            return Err(BuggerError::from_status("Azure DevOps", reply.status, &reply.text()));
+        }
+        Ok(Value::Null)
+    }
+
+    /// Upload a file to the project's attachment store; the response's `url` links it to work items.
+    pub fn upload_attachment(&self, file_name: &str, bytes: &[u8]) -> Result<Value, BuggerError> {
+        let url = format!("{}/{}/{}/_apis/wit/attachments?api-version=7.0", self.config.base_url, self.config.org, self.config.project);
+        let resp = self.send(|mut headers| {
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/octet-stream"));
+            self.client.post(&url).query(&[("fileName", file_name)]).headers(headers).body(bytes.to_vec())
+        })?;
+        Self::parse_response(resp)
+    }
+
+    /// Attach an uploaded file (its attachment `url`) to a work item.
+    pub fn link_attachment(&self, id: u64, attachment_url: &str, comment: Option<&str>) -> Result<Value, BuggerError> {
+        let relation = serde_json::json!({
+            "op": "add",
+            "path": "/relations/-",
+            "value": { "rel": "AttachedFile", "url": attachment_url, "attributes": { "comment": comment.unwrap_or("") } }
+        });
+        self.patch_work_item(id, &[relation])
+    }
+
+    /// Set work item fields via a JSON Patch document (e.g. `System.State`).
+    pub fn update_fields(&self, id: u64, updates: &[(&str, &str)]) -> Result<Value, BuggerError> {
+        let patch: Vec<Value> = updates
+            .iter()
+            .map(|(field, value)| serde_json::json!({ "op": "add", "path": format!("/fields/{}", field), "value": value }))
+            .collect();
+        self.patch_work_item(id, &patch)
+    }
+
+    /// Create a work item of the given type with the given field values.
+    pub fn create_work_item(&self, work_item_type: &str, fields: &[(&str, &str)]) -> Result<Value, BuggerError> {
+        let url = format!(
+            "{}/{}/{}/_apis/wit/workitems/${}?api-version=7.0",
+            self.config.base_url, self.config.org, self.config.project, work_item_type
+        );
+        let patch: Vec<Value> = fields
+            .iter()
+            .map(|(field, value)| serde_json::json!({ "op": "add", "path": format!("/fields/{}", field), "value": value }))
+            .collect();
+        let body = serde_json::to_vec(&patch).map_err(|e| BuggerError::Other(format!("JSON serialize error: {}", e)))?;
+        let resp = self.send(|mut headers| {
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json-patch+json"));
+            self.client.post(&url).headers(headers).body(body.clone())
+        })?;
+        Self::parse_response(resp)
+    }
+
+    fn patch_work_item(&self, id: u64, patch: &[Value]) -> Result<Value, BuggerError> {
+        let url = format!(
+            "{}/{}/{}/_apis/wit/workitems/{}?api-version=7.0",
+            self.config.base_url, self.config.org, self.config.project, id
+        );
+        let body = serde_json::to_vec(&patch).map_err(|e| BuggerError::Other(format!("JSON serialize error: {}", e)))?;
+        let resp = self.send(|mut headers| {
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json-patch+json"));
+            self.client.patch(&url).headers(headers).body(body.clone())
+        })?;
+        Self::parse_response(resp)
+    }
+
+    /// Who resolved each of `ids` (work items without a resolver are skipped).
+    pub fn fetch_resolvers(&self, ids: &[u64]) -> Result<Vec<(u64, Identity)>, BuggerError> {
+        let items = self.post_batch(ids, &["System.Id", "Microsoft.VSTS.Common.ResolvedBy"])?;
+        Ok(items
+            .iter()
+            .filter_map(|item| Some((item["id"].as_u64()?, Identity::from_value(&item["fields"]["Microsoft.VSTS.Common.ResolvedBy"])?)))
+            .collect())
+    }
+
+    /// Fetch id/title/type/parent for arbitrary work items (used to walk up to features and epics).
+    pub fn fetch_work_item_summaries(&self, ids: &[u64]) -> Result<Vec<WorkItemSummary>, BuggerError> {
+        let items = self.post_batch(ids, &["System.Id", "System.Title", "System.WorkItemType", "System.Parent"])?;
+        Ok(items
+            .iter()
+            .filter_map(|item| {
                 let fields = item["fields"].as_object();
-                if let Some(id) = item["id"].as_u64() {
-                    let title = fields.and_then(|f| f.get("System.Title")).and_then(|v| v.as_str()).unwrap_or("").to_string();
-                    let state = fields.and_then(|f| f.get("System.State")).and_then(|v| v.as_str()).unwrap_or("").to_string();
-                    let created_date = fields.and_then(|f| f.get("System.CreatedDate")).and_then(|v| v.as_str()).map(|s| s.to_string());
-                    let description = fields.and_then(|f| f.get("System.Description")).and_then(|v| v.as_str()).map(|s| s.to_string());
-                    bugs.push(Bug {
-                        id,
-                        title,
-                        state,
-                        created_date,
-                        description,
-                    });
-                } else {
-                    println!("Warning: Missing or invalid bug ID in response item: {:?}", item);
+                Some(WorkItemSummary {
+                    id: item["id"].as_u64()?,
+                    title: fields.and_then(|f| f.get("System.Title")).and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    work_item_type: fields.and_then(|f| f.get("System.WorkItemType")).and_then(|v| v.as_str()).unwrap_or("").to_string(),
+                    parent_id: fields.and_then(|f| f.get("System.Parent")).and_then(|v| v.as_u64()),
+                })
+            })
+            .collect())
+    }
+}
+
+/// Render a work item field value as text: strings as-is, numbers/bools formatted,
+/// identities by display name. Nulls and other shapes are skipped.
+fn field_value_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Object(o) => o.get("displayName").and_then(|v| v.as_str()).map(|s| s.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+    use serde_json::json;
+
+    const ORG: &str = "contoso";
+    const PROJECT: &str = "Fabrikam";
+
+    /// Configuration for the test organization with the API at `base_url`. Built here rather than
+    /// from the environment so tests don't depend on a local .env or each other's variables.
+    pub(crate) fn test_config(base_url: &str) -> AppConfig {
+        AppConfig {
+            base_url: base_url.to_string(),
+            org: ORG.to_string(),
+            project: PROJECT.to_string(),
+            user_email: "me@contoso.com".to_string(),
+            user_aliases: Vec::new(),
+            azure_devops_pat: "primary-pat".to_string(),
+            secondary_pat: None,
+            http_retries: 4,
+            fetch_concurrency: 6,
+            openai_api_key: None,
+            openai_model: "gpt-4o".to_string(),
+            ai_enabled: false,
+            ai_categorization: false,
+            ai_triage: false,
+            refresh_interval_minutes: None,
+            refresh_schedule: None,
+            policy_schedule: None,
+            notification_rules: crate::notifications::NotificationRules::from_env(),
+            quiet_hours: None,
+            custom_fields: vec!["Microsoft.VSTS.Build.FoundIn".to_string()],
+            build_field: "Microsoft.VSTS.Build.FoundIn".to_string(),
+            hot_comment_threshold: 8,
+            summary_comment_threshold: 10,
+            waiting_on_others_days: 3,
+            stale_days: 30,
+            focus_top: None,
+            achievements: false,
+            auto_close: crate::policy::AutoClosePolicy::from_env(),
+            auto_assign: crate::auto_assign::AutoAssignPolicy::from_env(),
+            out_of_office: crate::out_of_office::OutOfOffice::default(),
+            escalation: crate::escalation::EscalationPolicy::from_env(),
+            write_back: crate::write_back::WriteBackPolicy::from_env(),
+            roles: crate::roles::Roles::from_env(),
+            undo_window_minutes: 60,
+            bug_cache: false,
+            asset_fetch_limit: 0,
+            power: crate::power::PowerPolicy::from_env(),
+            description_max_chars: 20_000,
+            log_scan: crate::log_scan::LogScanConfig::from_env(),
+            error_codes: crate::error_codes::ErrorCodes::from_env(),
+            checklist: crate::checklist::Checklist::from_env(),
+            bug_template: crate::template::TemplateConfig::from_env(),
+            repo_roots: crate::code_refs::RepoRoots::from_env(),
+            sla: crate::calendar::SlaPolicy::from_env(),
+            calendar_path: None,
+            owners_path: None,
+            team_metrics: false,
+            export_team_metrics: false,
+            export_stamp: crate::export::ExportStamp::default(),
+            category_styles: crate::category_style::CategoryStyles::from_env(),
+            query_filters: QueryFilters::from_env(),
+            provider: crate::provider::ProviderConfig::AzureDevOps,
+        }
+    }
+
+    /// A client for the test organization that talks to `server` instead of dev.azure.com.
+    fn client(server: &MockServer) -> AzureDevOpsClient {
+        AzureDevOpsClient::new(test_config(&server.base_url()))
+    }
+
+    fn api_path(rest: &str) -> String {
+        format!("/{}/{}/_apis/wit/{}", ORG, PROJECT, rest)
+    }
+
+    fn auth(pat: &str) -> String {
+        basic_auth(pat).unwrap().to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn wiql_returns_matching_ids() {
+        let server = MockServer::start();
+        let wiql = server.mock(|when, then| {
+            when.method(POST)
+                .path(api_path("wiql"))
+                .query_param("api-version", "7.0")
+                .header("authorization", auth("primary-pat"))
+                .json_body(json!({ "query": "SELECT [System.Id] FROM WorkItems" }));
+            then.status(200).json_body(json!({ "workItems": [{ "id": 3, "url": "" }, { "id": 1, "url": "" }] }));
+        });
+        let ids = client(&server).run_wiql("SELECT [System.Id] FROM WorkItems").unwrap();
+        wiql.assert();
+        assert_eq!(ids, vec![3, 1]);
+    }
+
+    #[test]
+    fn a_sign_in_page_is_an_auth_error() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(POST).path(api_path("wiql"));
+            then.status(203).header("content-type", "text/html").body("<html>Sign in</html>");
+        });
+        let result = client(&server).run_wiql("SELECT [System.Id] FROM WorkItems");
+        assert!(matches!(result, Err(BuggerError::Auth(_))), "{:?}", result);
+    }
+
+    #[test]
+    fn batch_fields_are_parsed_into_bugs() {
+        let server = MockServer::start();
+        let batch = server.mock(|when, then| {
+            when.method(POST).path(api_path("workitemsbatch")).json_body_partial(r#"{ "ids": [42] }"#);
+            then.status(200).json_body(json!({
+                "count": 1,
+                "value": [{
+                    "id": 42,
+                    "fields": {
+                        "System.Title": "Crash on start with Windows 11 build 22631",
+                        "System.State": "Active",
+                        "System.CreatedDate": "2026-09-01T10:00:00Z",
+                        "System.ChangedDate": "2026-10-01T10:00:00Z",
+                        "Microsoft.VSTS.Common.StateChangeDate": "2026-09-02T10:00:00Z",
+                        "System.Description": "<div>Steps</div>",
+                        "Microsoft.VSTS.Common.Severity": "2 - High",
+                        "System.Parent": 7,
+                        "System.CommentCount": 4,
+                        "System.Tags": "regression; ui ",
+                        "System.AssignedTo": { "displayName": "Me", "uniqueName": "me@contoso.com", "imageUrl": "https://avatar/me" },
+                        "System.AreaPath": "Fabrikam\\Shell",
+                        "Microsoft.VSTS.Build.FoundIn": 20261001
+                    }
+                }, { "fields": {} }]
+            }));
+        });
+        let bugs = client(&server).fetch_bug_details(&[42]).unwrap();
+        batch.assert();
+        assert_eq!(bugs.len(), 1, "items without an id are skipped");
+        let bug = &bugs[0];
+        assert_eq!(bug.title, "Crash on start with Windows 11 build 22631");
+        assert_eq!(bug.state, "Active");
+        assert_eq!(bug.changed_date.as_deref(), Some("2026-10-01T10:00:00Z"));
+        assert_eq!(bug.state_changed_date.as_deref(), Some("2026-09-02T10:00:00Z"));
+        assert_eq!(bug.severity.as_deref(), Some("2 - High"));
+        assert_eq!(bug.parent_id, Some(7));
+        assert_eq!(bug.comment_count, 4);
+        assert_eq!(bug.tags, vec!["regression", "ui"]);
+        assert_eq!(bug.assigned_to.as_ref().map(|a| a.unique_name.as_str()), Some("me@contoso.com"));
+        assert_eq!(bug.area_path.as_deref(), Some("Fabrikam\\Shell"));
+        assert_eq!(bug.custom_fields.get("Microsoft.VSTS.Build.FoundIn").map(String::as_str), Some("20261001"));
+        assert!(bug.board_column.is_none() && bug.created_by.is_none());
+    }
+
+    #[test]
+    fn large_id_lists_are_fetched_in_batches() {
+        let server = MockServer::start();
+        let batch = server.mock(|when, then| {
+            when.method(POST).path(api_path("workitemsbatch"));
+            then.status(200).json_body(json!({ "value": [{ "id": 1, "fields": { "System.Title": "t" } }] }));
+        });
+        let ids: Vec<u64> = (1..=450).collect();
+        let bugs = client(&server).fetch_bug_details(&ids).unwrap();
+        batch.assert_hits(3);
+        assert_eq!(bugs.len(), 3, "the batches' results are combined");
+    }
+
+    #[test]
+    fn concurrent_batches_are_merged_in_id_order() {
+        let server = MockServer::start();
+        for first in [1, 201, 401] {
+            server.mock(|when, then| {
+                when.method(POST).path(api_path("workitemsbatch")).body_contains(format!("\"ids\":[{},", first));
+                then.status(200).json_body(json!({ "value": [{ "id": first, "fields": { "System.Title": "t" } }] }));
+            });
+        }
+        let mut client = client(&server);
+        client.config.fetch_concurrency = 3;
+        let ids: Vec<u64> = (1..=450).collect();
+        let bugs = client.fetch_bug_details(&ids).unwrap();
+        assert_eq!(bugs.iter().map(|b| b.id).collect::<Vec<_>>(), [1, 201, 401]);
+    }
+
+    #[test]
+    fn wiql_results_past_the_limit_are_paged_by_id() {
+        let server = MockServer::start();
+        let first: Vec<Value> = (1..=WIQL_LIMIT as u64).map(|id| json!({ "id": id })).collect();
+        let capped = server.mock(|when, then| {
+            when.method(POST).path(api_path("wiql")).query_param("$top", "20000").matches(|req| {
+                !String::from_utf8_lossy(req.body.as_deref().unwrap_or_default()).contains("[System.Id] >")
+            });
+            then.status(200).json_body(json!({ "workItems": first }));
+        });
+        let rest = server.mock(|when, then| {
+            when.method(POST).path(api_path("wiql")).body_contains("[System.Id] > 0 AND");
+            then.status(200).json_body(json!({ "workItems": [{ "id": 20000 }, { "id": 20001 }] }));
+        });
+        let ids = client(&server).run_wiql("SELECT [System.Id] FROM WorkItems WHERE [System.State] = 'Active'").unwrap();
+        capped.assert();
+        rest.assert();
+        assert_eq!(ids.len(), WIQL_LIMIT + 1, "ids already seen aren't repeated");
+        assert_eq!(ids.last(), Some(&20001));
+    }
+
+    #[test]
+    fn no_ids_means_no_batch_request() {
+        let server = MockServer::start();
+        let batch = server.mock(|when, then| {
+            when.method(POST).path(api_path("workitemsbatch"));
+            then.status(200).json_body(json!({ "value": [] }));
+        });
+        assert!(client(&server).fetch_bug_details(&[]).unwrap().is_empty());
+        assert!(client(&server).fetch_resolvers(&[]).unwrap().is_empty());
+        batch.assert_hits(0);
+    }
+
+    #[test]
+    fn comments_are_read_in_order() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path(api_path("workItems/42/comments")).query_param("order", "asc");
+            then.status(200).json_body(json!({
+                "totalCount": 2,
+                "comments": [
+                    { "text": "Can you attach logs?", "createdBy": { "displayName": "Me", "uniqueName": "me@contoso.com" }, "createdDate": "2026-10-01T09:00:00Z" },
+                    { "text": "Attached.", "createdBy": { "displayName": "Reporter", "uniqueName": "rep@contoso.com" }, "createdDate": "2026-10-02T09:00:00Z" }
+                ]
+            }));
+        });
+        let comments = client(&server).fetch_comments(42).unwrap();
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].author_email, "me@contoso.com");
+        assert_eq!(comments[1].text, "Attached.");
+        assert_eq!(comments[1].created_date.as_deref(), Some("2026-10-02T09:00:00Z"));
+    }
+
+    #[test]
+    fn posting_a_comment_sends_the_html() {
+        let server = MockServer::start();
+        let post = server.mock(|when, then| {
+            when.method(POST).path(api_path("workItems/42/comments")).json_body(json!({ "text": "Fixed in <b>main</b>" }));
+            then.status(200).json_body(json!({ "id": 99, "text": "Fixed in <b>main</b>" }));
+        });
+        let response = client(&server).add_comment(42, "Fixed in <b>main</b>").unwrap();
+        post.assert();
+        assert_eq!(response["id"], 99);
+    }
+
+    #[test]
+    fn field_updates_are_sent_as_json_patch() {
+        let server = MockServer::start();
+        let patch = server.mock(|when, then| {
+            when.method(httpmock::Method::PATCH)
+                .path(api_path("workitems/42"))
+                .header("content-type", "application/json-patch+json")
+                .json_body(json!([{ "op": "add", "path": "/fields/System.State", "value": "Resolved" }]));
+            then.status(200).json_body(json!({ "id": 42, "rev": 5 }));
+        });
+        client(&server).update_fields(42, &[("System.State", "Resolved")]).unwrap();
+        patch.assert();
+    }
+
+    #[test]
+    fn attachments_are_uploaded_then_linked() {
+        let server = MockServer::start();
+        let upload = server.mock(|when, then| {
+            when.method(POST)
+                .path(api_path("attachments"))
+                .query_param("fileName", "screenshot.png")
+                .header("content-type", "application/octet-stream")
+                .body("PNG");
+            then.status(201).json_body(json!({ "id": "a1", "url": "https://dev.azure.com/contoso/_apis/wit/attachments/a1" }));
+        });
+        let link = server.mock(|when, then| {
+            when.method(httpmock::Method::PATCH).path(api_path("workitems/42")).json_body(json!([{
+                "op": "add",
+                "path": "/relations/-",
+                "value": { "rel": "AttachedFile", "url": "https://dev.azure.com/contoso/_apis/wit/attachments/a1", "attributes": { "comment": "Repro" } }
+            }]));
+            then.status(200).json_body(json!({ "id": 42, "rev": 6 }));
+        });
+        let client = client(&server);
+        let uploaded = client.upload_attachment("screenshot.png", b"PNG").unwrap();
+        client.link_attachment(42, uploaded["url"].as_str().unwrap(), Some("Repro")).unwrap();
+        upload.assert();
+        link.assert();
+    }
+
+    #[test]
+    fn api_errors_carry_status_and_body() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(POST).path(api_path("wiql"));
+            then.status(400).body("{\"message\":\"TF51005: The query references a field that does not exist.\"}");
+        });
+        let err = client(&server).run_wiql("SELECT [Nope] FROM WorkItems").unwrap_err();
+        assert!(matches!(err, BuggerError::Api { status: 400, .. }), "{:?}", err);
+        assert!(err.to_string().contains("TF51005"), "{}", err);
+    }
+
+    #[test]
+    fn rejected_pat_and_missing_project_are_told_apart() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(POST).path(api_path("wiql"));
+            then.status(401).body("Access denied");
+        });
+        server.mock(|when, then| {
+            when.method(GET).path(api_path("workItems/42/comments"));
+            then.status(404).body("{\"message\":\"TF200016: The following project does not exist: Fabrikam.\"}");
+        });
+        let client = client(&server);
+        assert!(matches!(client.run_wiql("SELECT [System.Id] FROM WorkItems"), Err(BuggerError::Auth(_))));
+        assert!(matches!(client.fetch_comments(42), Err(BuggerError::Config(_))));
+    }
+
+    #[test]
+    fn throttling_is_retried_then_reported_as_an_error() {
+        let server = MockServer::start();
+        let batch = server.mock(|when, then| {
+            when.method(POST).path(api_path("workitemsbatch"));
+            then.status(429).header("Retry-After", "0").body("Request was blocked due to exceeding usage of resource");
+        });
+        let mut client = client(&server);
+        client.config.http_retries = 2;
+        let err = client.fetch_bug_details(&[1]).unwrap_err();
+        assert!(matches!(err, BuggerError::RateLimit(_)), "{:?}", err);
+        assert!(err.to_string().contains("429"), "{}", err);
+        batch.assert_hits(3);
+        assert_eq!(client.retried(), 2);
+        assert_eq!(client.throttled_for(), None);
+    }
+
+    #[test]
+    fn a_longer_retry_after_than_allowed_is_not_waited_for() {
+        let server = MockServer::start();
+        let batch = server.mock(|when, then| {
+            when.method(POST).path(api_path("workitemsbatch"));
+            then.status(429).header("Retry-After", "30");
+        });
+        let client = client(&server).with_max_retry_wait(Duration::from_secs(5));
+        assert!(matches!(client.fetch_bug_details(&[1]), Err(BuggerError::RateLimit(_))));
+        batch.assert_hits(1);
+        assert_eq!(client.retried(), 0);
+        assert_eq!(client.throttled_for(), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn server_errors_are_retried_only_when_the_request_can_be_repeated() {
+        let server = MockServer::start();
+        let comments = server.mock(|when, then| {
+            when.method(GET).path(api_path("workItems/42/comments"));
+            then.status(503).body("Service Unavailable");
+        });
+        let patch = server.mock(|when, then| {
+            when.method(httpmock::Method::PATCH).path(api_path("workitems/42"));
+            then.status(500).body("Internal Server Error");
+        });
+        let mut client = client(&server);
+        client.config.http_retries = 1;
+        assert!(matches!(client.fetch_comments(42), Err(BuggerError::Api { status: 503, .. })));
+        comments.assert_hits(2);
+        // A failed update may still have been applied, so it isn't sent again.
+        assert!(client.update_fields(42, &[("System.State", "Resolved")]).is_err());
+        patch.assert_hits(1);
+    }
+
+    #[test]
+    fn a_throttled_request_succeeds_once_the_retry_goes_through() {
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        let server = MockServer::start();
+        // Mocks are matched in the order they were created: the first call is throttled, the retry isn't.
+        let throttled = server.mock(|when, then| {
+            when.method(POST).path(api_path("wiql")).matches(|_| CALLS.fetch_add(1, Ordering::SeqCst) == 0);
+            then.status(429).header("Retry-After", "0");
+        });
+        let ok = server.mock(|when, then| {
+            when.method(POST).path(api_path("wiql"));
+            then.status(200).json_body(json!({ "workItems": [{ "id": 7 }] }));
+        });
+        let client = client(&server);
+        assert_eq!(client.run_wiql("SELECT [System.Id] FROM WorkItems").unwrap(), [7]);
+        throttled.assert_hits(1);
+        ok.assert_hits(1);
+        assert_eq!(client.retried(), 1);
+    }
+
+    #[test]
+    fn only_the_throttled_batch_is_fetched_again() {
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        let server = MockServer::start();
+        let throttled = server.mock(|when, then| {
+            when.method(POST).path(api_path("workitemsbatch")).matches(|req| {
+                String::from_utf8_lossy(req.body.as_deref().unwrap_or_default()).contains("\"ids\":[201,") && CALLS.fetch_add(1, Ordering::SeqCst) == 0
+            });
+            then.status(429).header("Retry-After", "0");
+        });
+        let mut batches = Vec::new();
+        for (first, last) in [(1, 200), (201, 400), (401, 450)] {
+            batches.push(server.mock(|when, then| {
+                when.method(POST).path(api_path("workitemsbatch")).body_contains(format!("\"ids\":[{},", first)).body_contains(format!(",{}]", last));
+                then.status(200).json_body(json!({ "value": [{ "id": first, "fields": { "System.Title": "t" } }] }));
+            }));
+        }
+        let mut client = client(&server);
+        client.config.fetch_concurrency = 1;
+        let ids: Vec<u64> = (1..=450).collect();
+        let bugs = client.fetch_bug_details(&ids).unwrap();
+        assert_eq!(bugs.iter().map(|b| b.id).collect::<Vec<_>>(), [1, 201, 401]);
+        throttled.assert_hits(1);
+        for batch in &batches {
+            batch.assert_hits(1);
+        }
+        assert_eq!(client.retried(), 1);
+    }
+
+    #[test]
+    fn a_failing_batch_fails_the_whole_fetch() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(POST).path(api_path("workitemsbatch")).body_contains("\"ids\":[401,");
+            then.status(400).body("{\"message\":\"TF401232: Work item 401 does not exist.\"}");
+        });
+        server.mock(|when, then| {
+            when.method(POST).path(api_path("workitemsbatch"));
+            then.status(200).json_body(json!({ "value": [] }));
+        });
+        let ids: Vec<u64> = (1..=450).collect();
+        let err = client(&server).fetch_bug_details(&ids).unwrap_err();
+        assert!(err.to_string().contains("TF401232"), "{}", err);
+    }
+
+    #[test]
+    fn bug_links_follow_the_configured_server() {
+        assert_eq!(test_config("https://dev.azure.com").bug_url(42), "https://dev.azure.com/contoso/Fabrikam/_workitems/edit/42");
+        assert_eq!(test_config("https://tfs.example.com/tfs").bug_url(42), "https://tfs.example.com/tfs/contoso/Fabrikam/_workitems/edit/42");
+    }
+
+    #[test]
+    fn retry_after_is_read_as_seconds_or_a_date() {
+        assert_eq!(retry_after("30"), Some(Duration::from_secs(30)));
+        assert_eq!(retry_after("Sun, 06 Nov 1994 08:49:37 GMT"), Some(Duration::ZERO));
+        assert_eq!(retry_after("soon"), None);
+        assert!(backoff(0) >= RETRY_BASE_DELAY / 2 && backoff(0) <= RETRY_BASE_DELAY);
+        assert!(backoff(10) <= RETRY_MAX_DELAY);
+    }
+
+    #[test]
+    fn invalid_json_is_an_error() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path(api_path("workItems/42/comments"));
+            then.status(200).body("<html>Sign in</html>");
+        });
+        let err = client(&server).fetch_comments(42).unwrap_err();
+        assert!(matches!(err, BuggerError::Parse(_)), "{:?}", err);
+    }
+
+    #[test]
+    fn rejected_primary_pat_fails_over_to_secondary() {
+        let server = MockServer::start();
+        let primary = server.mock(|when, then| {
+            when.method(GET).path(api_path("workItems/42/comments")).header("authorization", auth("primary-pat"));
+            then.status(401);
+        });
+        let secondary = server.mock(|when, then| {
+            when.method(GET).path(api_path("workItems/42/comments")).header("authorization", auth("secondary-pat"));
+            then.status(200).json_body(json!({ "comments": [] }));
+        });
+        let mut client = client(&server);
+        client.config.secondary_pat = Some("secondary-pat".to_string());
+        assert!(client.fetch_comments(42).unwrap().is_empty());
+        // The rest of the session goes straight to the secondary PAT.
+        assert!(client.fetch_comments(42).unwrap().is_empty());
+        primary.assert_hits(1);
+        secondary.assert_hits(2);
+        assert!(primary_pat_rejected("primary-pat"));
+        // A replaced primary PAT is tried again.
+        client.config.azure_devops_pat = "renewed-pat".to_string();
+        let renewed = server.mock(|when, then| {
+            when.method(GET).path(api_path("workItems/42/comments")).header("authorization", auth("renewed-pat"));
+            then.status(200).json_body(json!({ "comments": [] }));
+        });
+        assert!(client.fetch_comments(42).unwrap().is_empty());
+        renewed.assert();
+        assert!(!primary_pat_rejected("renewed-pat"));
+    }
+
+    /// Live smoke tests against the real organization in `.env`, to catch API changes or process
+    /// customizations that break parsing before a report silently goes wrong. Run with
+    /// `cargo test live_ -- --ignored`; BUGGER_SMOKE_WORK_ITEM picks the work item (default: my first bug).
+    mod live {
+        use super::super::*;
+        use crate::process::{self, StateCategory};
+        use chrono::DateTime;
+
+        #[derive(Clone, Copy)]
+        enum Kind {
+            Text,
+            Date,
+            Number,
+            Identity,
+        }
+
+        /// What parsing assumes about each field, and whether every bug has it.
+        const CONTRACT: [(&str, Kind, bool); 14] = [
+            ("System.Title", Kind::Text, true),
+            ("System.State", Kind::Text, true),
+            ("System.CreatedDate", Kind::Date, true),
+            ("System.ChangedDate", Kind::Date, true),
+            ("Microsoft.VSTS.Common.StateChangeDate", Kind::Date, false),
+            ("System.Description", Kind::Text, false),
+            ("Microsoft.VSTS.Common.Severity", Kind::Text, false),
+            ("System.BoardColumn", Kind::Text, false),
+            ("System.Parent", Kind::Number, false),
+            ("System.CommentCount", Kind::Number, false),
+            ("System.Tags", Kind::Text, false),
+            ("System.AssignedTo", Kind::Identity, false),
+            ("System.CreatedBy", Kind::Identity, true),
+            ("System.AreaPath", Kind::Text, true),
+        ];
+
+        fn violation(value: &Value, kind: Kind) -> Option<String> {
+            let ok = match kind {
+                Kind::Text => value.is_string(),
+                Kind::Date => value.as_str().is_some_and(|d| DateTime::parse_from_rfc3339(d).is_ok()),
+                Kind::Number => value.is_u64(),
+                Kind::Identity => value["displayName"].is_string() && value["uniqueName"].is_string(),
+            };
+            (!ok).then(|| format!("unexpected shape {}", value))
+        }
+
+        #[test]
+        #[ignore = "needs real credentials: cargo test live_ -- --ignored"]
+        fn live_work_item_matches_parsing_assumptions() {
+            let config = AppConfig::from_env().expect("live smoke test needs AZURE_DEVOPS_* settings in .env");
+            let client = AzureDevOpsClient::new(config);
+            let id = match std::env::var("BUGGER_SMOKE_WORK_ITEM") {
+                Ok(id) => id.trim().parse().expect("BUGGER_SMOKE_WORK_ITEM must be a work item id"),
+                Err(_) => *client.fetch_active_bugs().expect("bug query failed").first().expect("no active bugs; set BUGGER_SMOKE_WORK_ITEM"),
+            };
+            let mut problems = Vec::new();
+
+            let raw = client.post_json(&client.batch_url(), &serde_json::json!({ "ids": [id], "fields": client.bug_fields() })).expect("batch request failed");
+            let fields = &raw["value"][0]["fields"];
+            assert!(fields.is_object(), "no fields in batch response: {}", raw);
+            for (name, kind, required) in CONTRACT {
+                match fields.get(name) {
+                    None if required => problems.push(format!("{} is missing", name)),
+                    None => {}
+                    Some(value) => problems.extend(violation(value, kind).map(|v| format!("{}: {}", name, v))),
                 }
             }
+
+            let bug = client.fetch_bug_details(&[id]).expect("bug details failed").pop().expect("work item not returned");
+            if Some(bug.title.as_str()) != fields["System.Title"].as_str() || Some(bug.state.as_str()) != fields["System.State"].as_str() {
+                problems.push("parsed title or state differs from the raw fields".to_string());
+            }
+            for name in &client.config.custom_fields {
+                if fields.get(name).is_some_and(|v| !v.is_null()) && !bug.custom_fields.contains_key(name) {
+                    problems.push(format!("custom field {} is present but wasn't parsed", name));
+                }
+            }
+
+            let model = process::model(&client);
+            if model.states.is_empty() {
+                problems.push("Bug states of the process couldn't be read".to_string());
+            } else if !model.states.iter().any(|(name, _)| name.eq_ignore_ascii_case(&bug.state)) {
+                problems.push(format!("state '{}' isn't a Bug state of the process", bug.state));
+            }
+            if model.states(&[StateCategory::Completed]).is_empty() {
+                problems.push("process has no Completed state, so closed bugs can't be excluded".to_string());
+            }
+
+            let comments = client.fetch_comments(id).expect("comments request failed");
+            if comments.len() as u64 != bug.comment_count && bug.comment_count <= 200 {
+                problems.push(format!("{} comment(s) read but CommentCount is {}", comments.len(), bug.comment_count));
+            }
+
+            assert!(problems.is_empty(), "Azure DevOps API drift on #{}:\n- {}", id, problems.join("\n- "));
         }
-        Ok(bugs)
     }
 }
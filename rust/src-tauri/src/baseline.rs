@@ -0,0 +1,153 @@
+// Release baselines: snapshot the bug list under a name and compare the current list against it later
+use crate::azure_devops::{AzureDevOpsClient, Bug};
+use crate::process::{self, ProcessModel};
+use crate::{storage, AppConfig, AppState};
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+const BASELINE_STORE: &str = "baselines";
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BaselineBug {
+    pub id: u64,
+    pub title: String,
+    pub state: String,
+}
+
+impl From<&Bug> for BaselineBug {
+    fn from(bug: &Bug) -> Self {
+        BaselineBug { id: bug.id, title: bug.title.clone(), state: bug.state.clone() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Baseline {
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub bugs: Vec<BaselineBug>,
+}
+
+/// A baseline without its bug list, for pickers.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct BaselineInfo {
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub bug_count: usize,
+}
+
+impl From<&Baseline> for BaselineInfo {
+    fn from(baseline: &Baseline) -> Self {
+        BaselineInfo { name: baseline.name.clone(), created_at: baseline.created_at, bug_count: baseline.bugs.len() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct BaselineComparison {
+    pub baseline: BaselineInfo,
+    /// In the list now but not at the baseline.
+    pub opened_since: Vec<BaselineBug>,
+    /// At the baseline, gone from the list now and resolved or completed.
+    pub resolved_since: Vec<BaselineBug>,
+    /// At the baseline, gone from the list now but not done (reassigned, moved or removed).
+    pub left_since: Vec<BaselineBug>,
+    /// In the list at the baseline and now, with the current state.
+    pub still_open: Vec<BaselineBug>,
+}
+
+fn load(app: &AppHandle) -> Result<Vec<Baseline>, String> {
+    storage::load_json(app, BASELINE_STORE)
+}
+
+/// Split the difference between `baseline` and `current`; `departed` holds the current state of bugs
+/// that left the list (bugs missing from it count as left).
+pub fn compare(baseline: &Baseline, current: &[Bug], departed: &[Bug], process: &ProcessModel) -> BaselineComparison {
+    let was_there = |id: u64| baseline.bugs.iter().any(|b| b.id == id);
+    let mut comparison = BaselineComparison {
+        baseline: BaselineInfo::from(baseline),
+        opened_since: current.iter().filter(|b| !was_there(b.id)).map(BaselineBug::from).collect(),
+        resolved_since: Vec::new(),
+        left_since: Vec::new(),
+        still_open: current.iter().filter(|b| was_there(b.id)).map(BaselineBug::from).collect(),
+    };
+    for old in baseline.bugs.iter().filter(|old| !current.iter().any(|b| b.id == old.id)) {
+        let now = departed.iter().find(|b| b.id == old.id).map(BaselineBug::from).unwrap_or_else(|| old.clone());
+        if process.is_done(&now.state) {
+            comparison.resolved_since.push(now);
+        } else {
+            comparison.left_since.push(now);
+        }
+    }
+    comparison
+}
+
+#[tauri::command]
+/// Saves the current bug list as baseline `name` (e.g. "2.4 release"), replacing one with the same name.
+pub fn mark_baseline(app: AppHandle, state: State<AppState>, name: String) -> Result<BaselineInfo, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Baseline name is required".to_string());
+    }
+    let bugs = state.bugs_or_fetch()?;
+    let baseline = Baseline { name: name.clone(), created_at: Utc::now(), bugs: bugs.iter().map(BaselineBug::from).collect() };
+    let mut baselines = load(&app)?;
+    baselines.retain(|b| b.name != name);
+    baselines.push(baseline.clone());
+    storage::save_json(&app, BASELINE_STORE, &baselines)?;
+    Ok(BaselineInfo::from(&baseline))
+}
+
+#[tauri::command]
+/// Saved baselines, newest first.
+pub fn list_baselines(app: AppHandle) -> Result<Vec<BaselineInfo>, String> {
+    let mut baselines: Vec<BaselineInfo> = load(&app)?.iter().map(BaselineInfo::from).collect();
+    baselines.sort_by_key(|b| std::cmp::Reverse(b.created_at));
+    Ok(baselines)
+}
+
+#[tauri::command]
+pub fn delete_baseline(app: AppHandle, name: String) -> Result<(), String> {
+    let mut baselines = load(&app)?;
+    baselines.retain(|b| b.name != name);
+    storage::save_json(&app, BASELINE_STORE, &baselines)
+}
+
+#[tauri::command]
+/// Compares the current bug list against baseline `name`: opened, resolved and left since, and still open.
+pub fn compare_to_baseline(app: AppHandle, state: State<AppState>, name: String) -> Result<BaselineComparison, String> {
+    let baseline = load(&app)?.into_iter().find(|b| b.name == name).ok_or_else(|| format!("No baseline named '{}'", name))?;
+    let current = state.bugs_or_fetch()?;
+    let gone: Vec<u64> = baseline.bugs.iter().map(|b| b.id).filter(|id| !current.iter().any(|b| b.id == *id)).collect();
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    let client = AzureDevOpsClient::new(config);
+    let departed = client.fetch_bug_details(&gone)?;
+    Ok(compare(&baseline, &current, &departed, &process::model(&client)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bug(id: u64, state: &str) -> Bug {
+        let mut bug = Bug::sample(id, &format!("Bug {}", id));
+        bug.state = state.to_string();
+        bug
+    }
+
+    #[test]
+    fn changes_since_the_baseline_are_split() {
+        let baseline = Baseline { name: "2.4".to_string(), created_at: Utc::now(), bugs: [1, 2, 3, 4].iter().map(|id| BaselineBug::from(&bug(*id, "Active"))).collect() };
+        let current = [bug(1, "Committed"), bug(5, "New")];
+        // 2 was resolved, 3 moved to another team's area and 4 couldn't be fetched.
+        let departed = [bug(2, "Resolved"), bug(3, "Active")];
+        let comparison = compare(&baseline, &current, &departed, &ProcessModel::default());
+        let ids = |bugs: &[BaselineBug]| bugs.iter().map(|b| b.id).collect::<Vec<_>>();
+        assert_eq!(ids(&comparison.opened_since), [5]);
+        assert_eq!(ids(&comparison.resolved_since), [2]);
+        assert_eq!(ids(&comparison.left_since), [3, 4]);
+        assert_eq!(ids(&comparison.still_open), [1]);
+        assert_eq!(comparison.still_open[0].state, "Committed");
+        assert_eq!(comparison.baseline.bug_count, 4);
+    }
+}
@@ -0,0 +1,169 @@
+// Likely owners of code-referencing bugs: recent authors and commits of the mentioned files, from git
+use crate::code_refs::CodeRef;
+use log::warn;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+/// Recent commits looked at per file.
+const COMMITS_PER_FILE: usize = 5;
+/// Owners and commits shown per bug.
+const MAX_OWNERS: usize = 3;
+const MAX_COMMITS: usize = 3;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Commit {
+    pub hash: String,
+    pub author: String,
+    pub email: String,
+    pub date: String,
+    pub subject: String,
+}
+
+impl Commit {
+    pub fn short_hash(&self) -> &str {
+        &self.hash[..self.hash.len().min(8)]
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Owner {
+    pub name: String,
+    pub email: String,
+    /// Recent commits to the mentioned files, plus the last change to each mentioned line.
+    pub score: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OwnerSuggestion {
+    pub owners: Vec<Owner>,
+    /// Most recent commits touching the mentioned files, newest first.
+    pub commits: Vec<Commit>,
+}
+
+/// Repository root of a clone that `code_ref.local` is in.
+fn repo_dir(code_ref: &CodeRef) -> Option<&Path> {
+    let depth = code_ref.relative.split('/').count();
+    code_ref.local.ancestors().nth(depth)
+}
+
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = match Command::new("git").arg("-C").arg(dir).args(args).output() {
+        Ok(output) => output,
+        Err(e) => {
+            warn!("Failed to run git: {}", e);
+            return None;
+        }
+    };
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn recent_commits(dir: &Path, relative: &str) -> Vec<Commit> {
+    let count = format!("-n{}", COMMITS_PER_FILE);
+    let Some(log) = git(dir, &["log", &count, "--date=short", "--format=%H%x1f%an%x1f%ae%x1f%ad%x1f%s", "--", relative]) else {
+        return Vec::new();
+    };
+    log.lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\x1f');
+            Some(Commit {
+                hash: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                email: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                subject: fields.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Author (name, email) of the last change to `line`.
+fn line_author(dir: &Path, relative: &str, line: u32) -> Option<(String, String)> {
+    let range = format!("{},{}", line, line);
+    let blame = git(dir, &["blame", "--porcelain", "-L", &range, "--", relative])?;
+    let field = |key: &str| blame.lines().find_map(|l| l.strip_prefix(key)).map(|v| v.trim().to_string());
+    let email = field("author-mail ")?.trim_matches(['<', '>']).to_string();
+    // Uncommitted local edits.
+    if email == "not.committed.yet" {
+        return None;
+    }
+    Some((field("author ")?, email))
+}
+
+/// Likely owners of the files (and lines) a bug mentions, by recent commits and blame.
+pub fn suggest_owners(code_refs: &[CodeRef]) -> OwnerSuggestion {
+    let mut owners: HashMap<String, Owner> = HashMap::new();
+    let mut credit = |name: &str, email: &str| {
+        owners
+            .entry(email.to_lowercase())
+            .or_insert_with(|| Owner { name: name.to_string(), email: email.to_string(), score: 0 })
+            .score += 1;
+    };
+    let mut commits: Vec<Commit> = Vec::new();
+    for (i, code_ref) in code_refs.iter().enumerate() {
+        let Some(dir) = repo_dir(code_ref) else {
+            continue;
+        };
+        // A file mentioned at several lines counts its history once.
+        if code_refs[..i].iter().all(|r| r.local != code_ref.local) {
+            for commit in recent_commits(dir, &code_ref.relative) {
+                credit(&commit.author, &commit.email);
+                if !commits.iter().any(|c| c.hash == commit.hash) {
+                    commits.push(commit);
+                }
+            }
+        }
+        if let Some((name, email)) = code_ref.line.and_then(|line| line_author(dir, &code_ref.relative, line)) {
+            credit(&name, &email);
+        }
+    }
+    let mut owners: Vec<Owner> = owners.into_values().collect();
+    owners.sort_by(|a, b| b.score.cmp(&a.score).then(a.name.cmp(&b.name)));
+    owners.truncate(MAX_OWNERS);
+    commits.sort_by(|a, b| b.date.cmp(&a.date));
+    commits.truncate(MAX_COMMITS);
+    OwnerSuggestion { owners, commits }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn commit(dir: &Path, author: &str, date: &str, content: &str) {
+        fs::write(dir.join("src/config.rs"), content).unwrap();
+        let env = [("GIT_AUTHOR_DATE", date), ("GIT_COMMITTER_DATE", date)];
+        for args in [vec!["add", "-A"], vec!["-c", &format!("user.name={}", author), "-c", &format!("user.email={}@contoso.com", author.to_lowercase()), "commit", "-qm", &format!("Change by {}", author)]] {
+            let status = Command::new("git").arg("-C").arg(dir).args(&args).envs(env).status().unwrap();
+            assert!(status.success());
+        }
+    }
+
+    #[test]
+    fn recent_authors_and_line_blame_suggest_owners() {
+        let dir = std::env::temp_dir().join(format!("bugger-blame-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("src")).unwrap();
+        assert!(Command::new("git").arg("-C").arg(&dir).args(["init", "-q"]).status().unwrap().success());
+        commit(&dir, "Ana", "2026-01-05T10:00:00Z", "one\ntwo\n");
+        commit(&dir, "Ana", "2026-01-06T10:00:00Z", "one\ntwo\nthree\n");
+        commit(&dir, "Bo", "2026-01-07T10:00:00Z", "one\nTWO\nthree\n");
+        let code_ref = |line| CodeRef { repo: "app".to_string(), relative: "src/config.rs".to_string(), local: dir.join("src/config.rs"), line };
+
+        let suggestion = suggest_owners(&[code_ref(Some(2)), code_ref(Some(3))]);
+        let owners: Vec<_> = suggestion.owners.iter().map(|o| (o.email.as_str(), o.score)).collect();
+        // History counts once for the file; each mentioned line credits its last author.
+        assert_eq!(owners, [("ana@contoso.com", 3), ("bo@contoso.com", 2)]);
+        let commits: Vec<_> = suggestion.commits.iter().map(|c| (c.author.as_str(), c.date.as_str())).collect();
+        assert_eq!(commits, [("Bo", "2026-01-07"), ("Ana", "2026-01-06"), ("Ana", "2026-01-05")]);
+        assert_eq!(suggestion.commits[0].short_hash().len(), 8);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn files_outside_a_repository_suggest_nobody() {
+        let code_ref = CodeRef { repo: "app".to_string(), relative: "a/b.rs".to_string(), local: "/b.rs".into(), line: Some(1) };
+        assert!(suggest_owners(&[code_ref]).owners.is_empty());
+    }
+}
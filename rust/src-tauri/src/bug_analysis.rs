@@ -1,7 +1,14 @@
 // Bug analysis and categorization logic ported from Python
 use crate::azure_devops::Bug;
+use crate::env_list;
+use crate::rules::{self, RuleSeverity};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::sync::{OnceLock, RwLock};
+use unicode_segmentation::UnicodeSegmentation;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum QuestionableCategory {
     EmptyMinimalDescription,
     DeadLinks,
@@ -16,6 +23,64 @@ pub struct AnalysisResult {
     pub questionable: Vec<(Bug, QuestionableCategory)>,
 }
 
+/// Reduce Azure DevOps rich text to plain text: drop tags, decode common entities, collapse whitespace.
+pub fn strip_html(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => {
+                in_tag = true;
+                text.push(' ');
+            }
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Chinese, Japanese and Korean characters, where one character carries about a word's worth of content.
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32, 0x1100..=0x11FF | 0x3040..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xAC00..=0xD7AF | 0xF900..=0xFAFF | 0x20000..=0x2FA1F)
+}
+
+/// How much readable content a plain text has, counted in user-perceived characters.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TextContent {
+    /// Grapheme clusters with a letter or digit; emoji, punctuation and whitespace don't count,
+    /// combining marks count with their base character.
+    pub graphemes: usize,
+    pub cjk_graphemes: usize,
+    pub words: usize,
+}
+
+impl TextContent {
+    pub fn of(text: &str) -> Self {
+        let mut content = TextContent { words: text.unicode_words().count(), ..Default::default() };
+        for grapheme in text.graphemes(true).filter(|g| g.chars().any(char::is_alphanumeric)) {
+            content.graphemes += 1;
+            if grapheme.chars().any(is_cjk) {
+                content.cjk_graphemes += 1;
+            }
+        }
+        content
+    }
+
+    /// Mostly Chinese, Japanese or Korean, so length thresholds for alphabetic text don't apply.
+    pub fn is_mostly_cjk(&self) -> bool {
+        self.cjk_graphemes * 2 > self.graphemes
+    }
+}
+
 pub fn analyze_bugs(bugs: Vec<Bug>) -> AnalysisResult {
     let mut actionable = Vec::new();
     let mut questionable = Vec::new();
@@ -29,28 +94,67 @@ pub fn analyze_bugs(bugs: Vec<Bug>) -> AnalysisResult {
     AnalysisResult { actionable, questionable }
 }
 
-pub fn is_questionable(bug: &Bug) -> Option<QuestionableCategory> {
-    let desc = bug.description.as_deref().unwrap_or("").trim();
-    if desc.is_empty() {
-        return Some(QuestionableCategory::EmptyMinimalDescription);
-    }
-    if desc.len() < 8 {
-        return Some(QuestionableCategory::SingleWordDescription);
-    }
-    if desc.chars().all(|c| !c.is_alphanumeric()) {
-        return Some(QuestionableCategory::SpecialCharactersSoup);
-    }
-    if desc == bug.title {
-        return Some(QuestionableCategory::DuplicateTitleDescription);
-    }
-    if desc.contains("http") && desc.contains("404") {
-        return Some(QuestionableCategory::DeadLinks);
+/// A questionable bug with why it isn't actionable.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct QuestionableBug {
+    pub bug: Bug,
+    pub reason: QuestionableCategory,
+}
+
+/// Actionable bugs of one category, by id.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CategoryGroup {
+    pub category: BugCategory,
+    pub bug_ids: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct ReportStats {
+    pub total: usize,
+    pub actionable: usize,
+    pub questionable: usize,
+    /// Azure DevOps requests retried after throttling or a transient server error while fetching.
+    pub retried: u32,
+}
+
+/// The analysis as data, for the frontend to render however it likes.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct BugReport {
+    pub actionable: Vec<Bug>,
+    pub questionable: Vec<QuestionableBug>,
+    /// Non-empty categories, in `BugCategory::ALL` order.
+    pub categories: Vec<CategoryGroup>,
+    pub stats: ReportStats,
+}
+
+impl From<AnalysisResult> for BugReport {
+    fn from(analysis: AnalysisResult) -> Self {
+        let categorized = categorize_bugs(&analysis.actionable);
+        let categories = BugCategory::ALL
+            .into_iter()
+            .filter_map(|category| {
+                let bugs = categorized.get(&category)?;
+                Some(CategoryGroup { bug_ids: bugs.iter().map(|b| b.id).collect(), category })
+            })
+            .collect();
+        let stats = ReportStats {
+            total: analysis.actionable.len() + analysis.questionable.len(),
+            actionable: analysis.actionable.len(),
+            questionable: analysis.questionable.len(),
+            retried: 0,
+        };
+        let questionable = analysis.questionable.into_iter().map(|(bug, reason)| QuestionableBug { bug, reason }).collect();
+        BugReport { actionable: analysis.actionable, questionable, categories, stats }
     }
-    None
+}
+
+/// Reason from the first enabled rule at Questionable severity that matches (see `rules`).
+pub fn is_questionable(bug: &Bug) -> Option<QuestionableCategory> {
+    rules::matching(bug, RuleSeverity::Questionable).first().map(|rule| rule.category())
 }
 
 // Categorization logic (simple keyword-based)
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
 pub enum BugCategory {
     Crash,
     Performance,
@@ -64,33 +168,229 @@ pub enum BugCategory {
     Other,
 }
 
+impl BugCategory {
+    pub const ALL: [BugCategory; 10] = [
+        BugCategory::Crash,
+        BugCategory::Performance,
+        BugCategory::Security,
+        BugCategory::FileSystem,
+        BugCategory::Memory,
+        BugCategory::Driver,
+        BugCategory::Boot,
+        BugCategory::UI,
+        BugCategory::Network,
+        BugCategory::Other,
+    ];
+}
+
+impl std::str::FromStr for BugCategory {
+    type Err = String;
+
+    /// Parse a category name as written in config (case-insensitive, e.g. "crash", "FileSystem").
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use BugCategory::*;
+        match s.trim().to_lowercase().as_str() {
+            "crash" => Ok(Crash),
+            "performance" => Ok(Performance),
+            "security" => Ok(Security),
+            "filesystem" => Ok(FileSystem),
+            "memory" => Ok(Memory),
+            "driver" => Ok(Driver),
+            "boot" => Ok(Boot),
+            "ui" => Ok(UI),
+            "network" => Ok(Network),
+            "other" => Ok(Other),
+            _ => Err(format!("Unknown bug category: {}", s)),
+        }
+    }
+}
+
 pub fn categorize_bugs(bugs: &[Bug]) -> std::collections::HashMap<BugCategory, Vec<&Bug>> {
-    use BugCategory::*;
     let mut map: std::collections::HashMap<BugCategory, Vec<&Bug>> = std::collections::HashMap::new();
     for bug in bugs {
-        let text = format!("{} {}", bug.title.to_lowercase(), bug.description.as_deref().unwrap_or("").to_lowercase());
-        let cat = if text.contains("crash") || text.contains("bsod") || text.contains("exception") || text.contains("fault") || text.contains("bugcheck") {
-            Crash
-        } else if text.contains("slow") || text.contains("hang") || text.contains("freeze") || text.contains("performance") || text.contains("timeout") || text.contains("unresponsive") {
-            Performance
-        } else if text.contains("security") || text.contains("permission") || text.contains("access") || text.contains("privilege") || text.contains("auth") || text.contains("token") {
-            Security
-        } else if text.contains("file") || text.contains("disk") || text.contains("storage") || text.contains("ntfs") || text.contains("fat32") || text.contains("corruption") {
-            FileSystem
-        } else if text.contains("memory") || text.contains("leak") || text.contains("heap") || text.contains("allocation") || text.contains("out of memory") || text.contains("oom") {
-            Memory
-        } else if text.contains("driver") || text.contains("device") || text.contains("hardware") || text.contains("pnp") || text.contains("plug and play") {
-            Driver
-        } else if text.contains("boot") || text.contains("startup") || text.contains("start") || text.contains("initialization") || text.contains("init") || text.contains("loading") {
-            Boot
-        } else if text.contains("ui") || text.contains("button") || text.contains("window") || text.contains("dialog") || text.contains("menu") || text.contains("screen") {
-            UI
-        } else if text.contains("network") || text.contains("connect") || text.contains("disconnect") || text.contains("timeout") || text.contains("tcp") || text.contains("udp") {
-            Network
-        } else {
-            Other
-        };
-        map.entry(cat).or_default().push(bug);
+        map.entry(categorize_bug(bug)).or_default().push(bug);
     }
     map
 }
+
+/// Explicit category labels: a tag prefix (BUGGER_CATEGORY_TAG_PREFIX, e.g. `cat:` for `cat:network`)
+/// and area paths mapped to categories (BUGGER_CATEGORY_AREAS, e.g. `Contoso\Networking=network`).
+#[derive(Debug, Clone, Default)]
+pub struct CategoryTaxonomy {
+    pub tag_prefix: Option<String>,
+    pub areas: Vec<(String, BugCategory)>,
+}
+
+impl CategoryTaxonomy {
+    pub fn from_env() -> Self {
+        let areas = env_list("BUGGER_CATEGORY_AREAS")
+            .iter()
+            .filter_map(|entry| {
+                let (area, category) = entry.rsplit_once('=')?;
+                Some((area.trim().trim_end_matches('\\').to_string(), category.parse().ok()?))
+            })
+            .collect();
+        CategoryTaxonomy {
+            tag_prefix: env::var("BUGGER_CATEGORY_TAG_PREFIX").ok().filter(|p| !p.trim().is_empty()),
+            areas,
+        }
+    }
+
+    /// The bug's labelled category: a prefixed tag first, then the most specific mapped area path.
+    pub fn category(&self, bug: &Bug) -> Option<BugCategory> {
+        let from_tag = self.tag_prefix.as_ref().and_then(|prefix| {
+            bug.tags.iter().find_map(|tag| {
+                let label = tag.get(..prefix.len())?;
+                if !label.eq_ignore_ascii_case(prefix) {
+                    return None;
+                }
+                tag[prefix.len()..].parse().ok()
+            })
+        });
+        from_tag.or_else(|| {
+            let area = bug.area_path.as_deref()?.to_lowercase();
+            self.areas
+                .iter()
+                .filter(|(mapped, _)| {
+                    let mapped = mapped.to_lowercase();
+                    area == mapped || area.starts_with(&format!("{}\\", mapped))
+                })
+                .max_by_key(|(mapped, _)| mapped.len())
+                .map(|(_, category)| category.clone())
+        })
+    }
+}
+
+fn taxonomy() -> &'static RwLock<CategoryTaxonomy> {
+    static TAXONOMY: OnceLock<RwLock<CategoryTaxonomy>> = OnceLock::new();
+    TAXONOMY.get_or_init(|| RwLock::new(CategoryTaxonomy::from_env()))
+}
+
+/// Read the category taxonomy from the environment again. Called once per report so changed
+/// settings apply without re-reading them for every bug.
+pub fn reload_taxonomy() {
+    if let Ok(mut taxonomy) = taxonomy().write() {
+        *taxonomy = CategoryTaxonomy::from_env();
+    }
+}
+
+/// Category for a single bug: its explicit label (tag or area path) when there is one, else keywords.
+pub fn categorize_bug(bug: &Bug) -> BugCategory {
+    let labelled = taxonomy().read().ok().and_then(|taxonomy| taxonomy.category(bug));
+    labelled.unwrap_or_else(|| categorize_text(&bug.title, bug.description.as_deref()))
+}
+
+/// Keyword-based category from a title and description, e.g. for historical revisions.
+pub fn categorize_text(title: &str, description: Option<&str>) -> BugCategory {
+    use BugCategory::*;
+    let text = format!("{} {}", title.to_lowercase(), description.unwrap_or("").to_lowercase());
+    if text.contains("crash") || text.contains("bsod") || text.contains("exception") || text.contains("fault") || text.contains("bugcheck") {
+        Crash
+    } else if text.contains("slow") || text.contains("hang") || text.contains("freeze") || text.contains("performance") || text.contains("timeout") || text.contains("unresponsive") {
+        Performance
+    } else if text.contains("security") || text.contains("permission") || text.contains("access") || text.contains("privilege") || text.contains("auth") || text.contains("token") {
+        Security
+    } else if text.contains("file") || text.contains("disk") || text.contains("storage") || text.contains("ntfs") || text.contains("fat32") || text.contains("corruption") {
+        FileSystem
+    } else if text.contains("memory") || text.contains("leak") || text.contains("heap") || text.contains("allocation") || text.contains("out of memory") || text.contains("oom") {
+        Memory
+    } else if text.contains("driver") || text.contains("device") || text.contains("hardware") || text.contains("pnp") || text.contains("plug and play") {
+        Driver
+    } else if text.contains("boot") || text.contains("startup") || text.contains("start") || text.contains("initialization") || text.contains("init") || text.contains("loading") {
+        Boot
+    } else if text.contains("ui") || text.contains("button") || text.contains("window") || text.contains("dialog") || text.contains("menu") || text.contains("screen") {
+        UI
+    } else if text.contains("network") || text.contains("connect") || text.contains("disconnect") || text.contains("timeout") || text.contains("tcp") || text.contains("udp") {
+        Network
+    } else {
+        Other
+    }
+}
+
+/// Rank of common Azure Boards columns so groups read left-to-right like the board.
+fn board_column_rank(column: &str) -> usize {
+    const ORDER: [&str; 8] = ["new", "approved", "committed", "active", "in progress", "resolved", "done", "closed"];
+    ORDER.iter().position(|c| c.eq_ignore_ascii_case(column)).unwrap_or(ORDER.len())
+}
+
+/// Group bugs by board column in board order; bugs not on a board are grouped under "(No column)" last.
+pub fn group_by_board_column<'a>(bugs: impl IntoIterator<Item = &'a Bug>) -> Vec<(String, Vec<&'a Bug>)> {
+    let mut groups: Vec<(String, Vec<&Bug>)> = Vec::new();
+    for bug in bugs {
+        let column = bug.board_column.clone().unwrap_or_else(|| "(No column)".to_string());
+        match groups.iter_mut().find(|(c, _)| *c == column) {
+            Some((_, list)) => list.push(bug),
+            None => groups.push((column, vec![bug])),
+        }
+    }
+    groups.sort_by(|(a, _), (b, _)| {
+        let unassigned = |c: &str| c == "(No column)";
+        unassigned(a).cmp(&unassigned(b)).then(board_column_rank(a).cmp(&board_column_rank(b))).then(a.cmp(b))
+    });
+    groups
+}
+
+/// Group bugs by the OS build (without revision) found in their text, newest build first; bugs
+/// without a build are grouped under "(Unknown)" last.
+pub fn group_by_os_build<'a>(bugs: impl IntoIterator<Item = &'a Bug>) -> Vec<(String, Vec<&'a Bug>)> {
+    let mut groups: Vec<(String, Vec<&Bug>)> = Vec::new();
+    for bug in bugs {
+        let build = bug.environment.build_number().unwrap_or("(Unknown)").to_string();
+        match groups.iter_mut().find(|(b, _)| *b == build) {
+            Some((_, list)) => list.push(bug),
+            None => groups.push((build, vec![bug])),
+        }
+    }
+    groups.sort_by_key(|(build, _)| std::cmp::Reverse(build.parse::<u64>().ok()));
+    groups
+}
+
+/// Crash and driver bugs are hard to act on without knowing where they happened.
+pub fn needs_environment(bug: &Bug, category: &BugCategory) -> bool {
+    matches!(category, BugCategory::Crash | BugCategory::Driver) && bug.environment.is_missing()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn taxonomy() -> CategoryTaxonomy {
+        CategoryTaxonomy {
+            tag_prefix: Some("cat:".to_string()),
+            areas: vec![("Contoso".to_string(), BugCategory::UI), ("Contoso\\Networking".to_string(), BugCategory::Network)],
+        }
+    }
+
+    #[test]
+    fn prefixed_tags_win_over_area_paths() {
+        let mut bug = Bug::sample(1, "Button misaligned");
+        bug.area_path = Some("Contoso\\Networking".to_string());
+        bug.tags = vec!["CAT:crash".to_string()];
+        assert_eq!(taxonomy().category(&bug), Some(BugCategory::Crash));
+    }
+
+    #[test]
+    fn the_most_specific_area_path_is_used() {
+        let mut bug = Bug::sample(1, "Slow page");
+        bug.area_path = Some("contoso\\networking\\DNS".to_string());
+        assert_eq!(taxonomy().category(&bug), Some(BugCategory::Network));
+        bug.area_path = Some("Contoso\\NetworkingTools".to_string());
+        assert_eq!(taxonomy().category(&bug), Some(BugCategory::UI));
+        bug.area_path = Some("Fabrikam".to_string());
+        assert_eq!(taxonomy().category(&bug), None);
+    }
+
+    #[test]
+    fn reports_group_actionable_bugs_in_category_order() {
+        let analysis = AnalysisResult {
+            actionable: vec![Bug::sample(1, "Network drops"), Bug::sample(2, "App crash on start"), Bug::sample(3, "Crash in driver")],
+            questionable: vec![(Bug::sample(4, "x"), QuestionableCategory::SingleWordDescription)],
+        };
+        let report = BugReport::from(analysis);
+        let groups: Vec<_> = report.categories.iter().map(|g| (g.category.clone(), g.bug_ids.clone())).collect();
+        assert_eq!(groups, [(BugCategory::Crash, vec![2, 3]), (BugCategory::Network, vec![1])]);
+        assert_eq!((report.stats.total, report.stats.actionable, report.stats.questionable, report.stats.retried), (4, 3, 1, 0));
+        assert_eq!(report.questionable[0].bug.id, 4);
+        assert_eq!(report.questionable[0].reason, QuestionableCategory::SingleWordDescription);
+    }
+}
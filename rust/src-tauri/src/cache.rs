@@ -0,0 +1,220 @@
+// Local SQLite cache of fetched bugs: after the first full fetch, a refresh asks Azure DevOps only for
+// the ids matching the query and the details of work items changed since the last sync
+use crate::azure_devops::{AzureDevOpsClient, Bug};
+use crate::error::BuggerError;
+use crate::{storage, wiql, AppState};
+use chrono::{DateTime, Days, Utc};
+use log::{info, warn};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use tauri::{AppHandle, Manager, State};
+
+#[derive(Default)]
+pub struct BugCache {
+    path: OnceLock<PathBuf>,
+    /// Set by `force_full_refresh`: the next fetch ignores what's cached.
+    force_full: AtomicBool,
+}
+
+/// The bugs of the last sync, by id.
+struct Synced {
+    bugs: HashMap<u64, Bug>,
+    at: DateTime<Utc>,
+}
+
+/// What the cached bugs were fetched with; when it changes the cache starts over.
+fn cache_key(client: &AzureDevOpsClient, wiql: &str) -> String {
+    let config = &client.config;
+    format!("{}/{}/{}\n{}\n{}", config.base_url, config.org, config.project, config.custom_fields.join(","), wiql)
+}
+
+fn open(path: &Path) -> Result<Connection, String> {
+    let db = Connection::open(path).map_err(|e| format!("Failed to open bug cache {}: {}", path.display(), e))?;
+    create_tables(&db)?;
+    Ok(db)
+}
+
+fn create_tables(db: &Connection) -> Result<(), String> {
+    db.execute_batch(
+        "CREATE TABLE IF NOT EXISTS bugs (id INTEGER PRIMARY KEY, bug TEXT NOT NULL);
+         CREATE TABLE IF NOT EXISTS sync (key TEXT NOT NULL, synced_at TEXT NOT NULL);",
+    )
+    .map_err(|e| format!("Bug cache error: {}", e))
+}
+
+/// The cached bugs and when they were synced, if they were fetched with `key`.
+fn load(db: &Connection, key: &str) -> Result<Option<Synced>, String> {
+    let error = |e: rusqlite::Error| format!("Bug cache error: {}", e);
+    let synced: Option<(String, String)> = db.query_row("SELECT key, synced_at FROM sync", [], |row| Ok((row.get(0)?, row.get(1)?))).optional().map_err(error)?;
+    let Some(synced_at) = synced.filter(|(k, _)| k == key).and_then(|(_, at)| DateTime::parse_from_rfc3339(&at).ok()) else {
+        return Ok(None);
+    };
+    let mut query = db.prepare("SELECT bug FROM bugs").map_err(error)?;
+    let rows = query.query_map([], |row| row.get::<_, String>(0)).map_err(error)?;
+    let mut bugs = HashMap::new();
+    for row in rows {
+        // A bug that no longer parses (e.g. after an update) is simply fetched again.
+        if let Ok(bug) = serde_json::from_str::<Bug>(&row.map_err(error)?) {
+            bugs.insert(bug.id, bug);
+        }
+    }
+    Ok(Some(Synced { bugs, at: synced_at.with_timezone(&Utc) }))
+}
+
+/// Replace the cache with `bugs`, synced at `synced_at` with `key`.
+fn save(db: &mut Connection, key: &str, bugs: &[Bug], synced_at: DateTime<Utc>) -> Result<(), String> {
+    let error = |e: rusqlite::Error| format!("Bug cache error: {}", e);
+    let tx = db.transaction().map_err(error)?;
+    tx.execute("DELETE FROM bugs", []).map_err(error)?;
+    tx.execute("DELETE FROM sync", []).map_err(error)?;
+    {
+        let mut insert = tx.prepare("INSERT INTO bugs (id, bug) VALUES (?1, ?2)").map_err(error)?;
+        for bug in bugs {
+            let json = serde_json::to_string(bug).map_err(|e| format!("JSON serialize error: {}", e))?;
+            insert.execute(params![bug.id as i64, json]).map_err(error)?;
+        }
+    }
+    tx.execute("INSERT INTO sync (key, synced_at) VALUES (?1, ?2)", params![key, synced_at.to_rfc3339()]).map_err(error)?;
+    tx.commit().map_err(error)
+}
+
+/// The ids to fetch in full: all of them without a cache, otherwise those changed since the sync
+/// and those not cached yet.
+fn stale(ids: &[u64], cached: Option<&Synced>, changed: &HashSet<u64>) -> Vec<u64> {
+    match cached {
+        Some(synced) => ids.iter().copied().filter(|id| changed.contains(id) || !synced.bugs.contains_key(id)).collect(),
+        None => ids.to_vec(),
+    }
+}
+
+/// The bugs with `ids`, in that order: freshly fetched ones, else cached ones. Cached bugs the query
+/// no longer matches are dropped.
+fn merge(ids: &[u64], mut fresh: HashMap<u64, Bug>, mut cached: HashMap<u64, Bug>) -> Vec<Bug> {
+    ids.iter().filter_map(|id| fresh.remove(id).or_else(|| cached.remove(id))).collect()
+}
+
+impl BugCache {
+    /// Keep the cache in the app data directory.
+    pub fn init(app: &AppHandle) {
+        match storage::store_path(app, "bug_cache") {
+            Ok(path) => {
+                let _ = app.state::<AppState>().bug_cache.path.set(path.with_extension("sqlite"));
+            }
+            Err(e) => warn!("Bug cache unavailable: {}", e),
+        }
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.path.get().is_some()
+    }
+
+    /// What's cached for `key`, unless a full refresh was asked for.
+    fn cached(&self, db: &Connection, key: &str) -> Result<Option<Synced>, String> {
+        if self.force_full.swap(false, Ordering::SeqCst) {
+            return Ok(None);
+        }
+        load(db, key)
+    }
+
+    /// The bugs of the configured query. Only work items that are new to the cache or changed since
+    /// the last sync are fetched in full; the rest come from the cache.
+    pub fn fetch(&self, client: &AzureDevOpsClient) -> Result<Vec<Bug>, BuggerError> {
+        let path = self.path.get().ok_or("Bug cache unavailable")?;
+        let mut db = open(path)?;
+        let wiql = client.active_bugs_wiql();
+        let key = cache_key(client, &wiql);
+        let cached = self.cached(&db, &key)?;
+        let started = Utc::now();
+        let ids = client.run_wiql(&wiql)?;
+        let changed: HashSet<u64> = match &cached {
+            Some(synced) => {
+                // A day earlier, as changes in the last hours of a day can fall on the next one in another time zone.
+                let since = synced.at.date_naive().checked_sub_days(Days::new(1)).unwrap_or(synced.at.date_naive());
+                client.run_wiql(&wiql::changed_since(&wiql, since))?.into_iter().collect()
+            }
+            None => HashSet::new(),
+        };
+        let stale = stale(&ids, cached.as_ref(), &changed);
+        let fresh: HashMap<u64, Bug> = client.fetch_bug_details(&stale)?.into_iter().map(|b| (b.id, b)).collect();
+        let bugs = merge(&ids, fresh, cached.map(|synced| synced.bugs).unwrap_or_default());
+        info!("Bug cache: fetched {} of {} bugs", stale.len(), bugs.len());
+        if let Err(e) = save(&mut db, &key, &bugs, started) {
+            warn!("Failed to update the bug cache: {}", e);
+        }
+        Ok(bugs)
+    }
+}
+
+#[tauri::command]
+/// Refetches every bug instead of only the changed ones, replacing the local cache.
+pub fn force_full_refresh(state: State<AppState>) -> Result<(), BuggerError> {
+    state.bug_cache.force_full.store(true, Ordering::SeqCst);
+    state.refresh_bugs()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn db() -> Connection {
+        let db = Connection::open_in_memory().unwrap();
+        create_tables(&db).unwrap();
+        db
+    }
+
+    fn bugs(ids: &[u64], title: &str) -> HashMap<u64, Bug> {
+        ids.iter().map(|&id| (id, Bug::sample(id, title))).collect()
+    }
+
+    fn synced(ids: &[u64]) -> Synced {
+        Synced { bugs: bugs(ids, "cached"), at: Utc::now() }
+    }
+
+    #[test]
+    fn only_changed_and_new_bugs_are_fetched() {
+        let changed = HashSet::from([2, 9]);
+        assert_eq!(stale(&[1, 2, 3, 4], Some(&synced(&[1, 2, 3])), &changed), vec![2, 4]);
+        assert_eq!(stale(&[1, 2, 3], None, &changed), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn fresh_bugs_replace_cached_ones_and_unmatched_ones_are_dropped() {
+        let merged = merge(&[3, 1, 4], bugs(&[1, 4], "fresh"), bugs(&[1, 2, 3], "cached"));
+        let titles: Vec<(u64, &str)> = merged.iter().map(|b| (b.id, b.title.as_str())).collect();
+        assert_eq!(titles, vec![(3, "cached"), (1, "fresh"), (4, "fresh")]);
+    }
+
+    #[test]
+    fn the_cache_is_kept_for_the_same_key_only() {
+        let mut db = db();
+        let saved: Vec<Bug> = vec![Bug::sample(1, "one"), Bug::sample(2, "two")];
+        save(&mut db, "org/project\nquery", &saved, Utc::now()).unwrap();
+        let loaded = load(&db, "org/project\nquery").unwrap().unwrap();
+        assert_eq!(loaded.bugs.len(), 2);
+        assert_eq!(loaded.bugs[&2].title, "two");
+        assert!(load(&db, "org/project\nanother query").unwrap().is_none());
+    }
+
+    #[test]
+    fn a_save_replaces_what_was_cached() {
+        let mut db = db();
+        save(&mut db, "key", &[Bug::sample(1, "one"), Bug::sample(2, "two")], Utc::now()).unwrap();
+        save(&mut db, "key", &[Bug::sample(3, "three")], Utc::now()).unwrap();
+        let ids: HashSet<u64> = load(&db, "key").unwrap().unwrap().bugs.into_keys().collect();
+        assert_eq!(ids, HashSet::from([3]));
+    }
+
+    #[test]
+    fn a_forced_full_refresh_ignores_the_cache_once() {
+        let mut db = db();
+        save(&mut db, "key", &[Bug::sample(1, "one")], Utc::now()).unwrap();
+        let cache = BugCache::default();
+        cache.force_full.store(true, Ordering::SeqCst);
+        assert!(cache.cached(&db, "key").unwrap().is_none());
+        assert!(cache.cached(&db, "key").unwrap().is_some());
+    }
+}
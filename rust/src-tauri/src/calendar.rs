@@ -0,0 +1,291 @@
+// Calendar export: SLA deadlines and follow-up reminders for my bugs as an iCalendar (.ics) file
+use crate::azure_devops::{AzureDevOpsClient, Bug, Comment};
+use crate::discussion::fetch_threads;
+use crate::waiting::find_waiting_on_others;
+use crate::{storage, AppConfig, AppState};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use tauri::{AppHandle, State};
+
+/// Every event exported so far, so changed events get a higher SEQUENCE and vanished ones are cancelled.
+const EVENT_STORE: &str = "calendar_events";
+/// Cancelled events stay in the file this long, so calendars that imported them see the cancellation.
+const CANCELLED_KEEP_DAYS: i64 = 30;
+
+/// Days to fix a bug per severity (BUGGER_SLA_DAYS, e.g. `1=1,2=7,3=30,4=90`), keyed by the
+/// severity's leading digit ("2 - High" → 2). Bugs without a severity get no deadline.
+#[derive(Debug, Clone)]
+pub struct SlaPolicy {
+    pub days: BTreeMap<u32, i64>,
+}
+
+impl SlaPolicy {
+    pub fn from_env() -> Self {
+        let mut days = BTreeMap::from([(1, 1), (2, 7), (3, 30), (4, 90)]);
+        for entry in env::var("BUGGER_SLA_DAYS").unwrap_or_default().split(',') {
+            let Some((severity, value)) = entry.split_once('=') else {
+                continue;
+            };
+            match (severity.trim().parse(), value.trim().parse::<i64>()) {
+                (Ok(severity), Ok(value)) if value > 0 => {
+                    days.insert(severity, value);
+                }
+                _ => warn!("Ignoring invalid BUGGER_SLA_DAYS entry '{}'", entry),
+            }
+        }
+        SlaPolicy { days }
+    }
+
+    /// When `bug` is due under the SLA, if it has a severity and a creation date.
+    pub fn due_date(&self, bug: &Bug) -> Option<NaiveDate> {
+        let severity = bug.severity.as_deref()?.trim().chars().next()?.to_digit(10)?;
+        let created = DateTime::parse_from_rfc3339(bug.created_date.as_deref()?).ok()?;
+        Some((created.with_timezone(&Utc) + Duration::days(*self.days.get(&severity)?)).date_naive())
+    }
+}
+
+/// One all-day calendar entry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub date: NaiveDate,
+    pub summary: String,
+    pub description: String,
+    pub url: String,
+    /// Reminder trigger relative to the start of the day, e.g. `-P1D` or `PT9H`.
+    pub alarm: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredEvent {
+    event: CalendarEvent,
+    sequence: u32,
+    cancelled_at: Option<DateTime<Utc>>,
+}
+
+fn is_mine(bug: &Bug, me: &[String]) -> bool {
+    bug.assigned_to.as_ref().is_some_and(|a| me.iter().any(|m| a.unique_name.eq_ignore_ascii_case(m)))
+}
+
+/// SLA deadlines for my bugs, plus a follow-up reminder for each question of mine nobody has answered,
+/// due BUGGER_WAITING_DAYS after I asked.
+pub fn events(
+    bugs: &[Bug],
+    threads: &HashMap<u64, Vec<Comment>>,
+    config: &AppConfig,
+    now: DateTime<Utc>,
+    bug_url: impl Fn(u64) -> String,
+) -> Vec<CalendarEvent> {
+    let me = config.my_identities();
+    let mine: Vec<&Bug> = bugs.iter().filter(|b| is_mine(b, &me)).collect();
+    let mut events: Vec<CalendarEvent> = mine
+        .iter()
+        .filter_map(|bug| {
+            let due = config.sla.due_date(bug)?;
+            Some(CalendarEvent {
+                uid: format!("bugger-sla-{}@{}", bug.id, config.org),
+                date: due,
+                summary: format!("SLA due: #{} {}", bug.id, bug.title),
+                description: format!("Severity: {}\nState: {}", bug.severity.as_deref().unwrap_or_default(), bug.state),
+                url: bug_url(bug.id),
+                alarm: "-P1D".to_string(),
+            })
+        })
+        .collect();
+    let my_threads: HashMap<u64, Vec<Comment>> = threads
+        .iter()
+        .filter(|(id, _)| mine.iter().any(|b| b.id == **id))
+        .map(|(id, comments)| (*id, comments.clone()))
+        .collect();
+    for item in find_waiting_on_others(&my_threads, &me, 0, now) {
+        let Some(bug) = mine.iter().find(|b| b.id == item.bug_id) else {
+            continue;
+        };
+        let asked = my_threads[&item.bug_id]
+            .last()
+            .and_then(|c| DateTime::parse_from_rfc3339(c.created_date.as_deref()?).ok())
+            .map(|d| d.with_timezone(&Utc).date_naive())
+            .unwrap_or_else(|| now.date_naive() - Duration::days(item.days_waiting));
+        events.push(CalendarEvent {
+            uid: format!("bugger-followup-{}@{}", bug.id, config.org),
+            date: asked + Duration::days(config.waiting_on_others_days),
+            summary: format!("Follow up with {}: #{} {}", item.counterpart, bug.id, bug.title),
+            description: format!("Asked on {}: {}\nState: {}", asked.format("%Y-%m-%d"), item.excerpt, bug.state),
+            url: bug_url(bug.id),
+            alarm: "PT9H".to_string(),
+        });
+    }
+    events.sort_by(|a, b| a.date.cmp(&b.date).then(a.uid.cmp(&b.uid)));
+    events
+}
+
+/// Escape a TEXT value (RFC 5545 §3.3.11).
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace("\r\n", "\\n").replace('\n', "\\n")
+}
+
+/// Fold a content line at 75 octets without splitting characters (RFC 5545 §3.1).
+fn fold(line: &str) -> String {
+    let mut out = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+    out
+}
+
+fn vevent(stored: &StoredEvent, now: DateTime<Utc>) -> String {
+    let event = &stored.event;
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", event.uid),
+        format!("DTSTAMP:{}", now.format("%Y%m%dT%H%M%SZ")),
+        format!("SEQUENCE:{}", stored.sequence),
+        format!("DTSTART;VALUE=DATE:{}", event.date.format("%Y%m%d")),
+        format!("DTEND;VALUE=DATE:{}", (event.date + Duration::days(1)).format("%Y%m%d")),
+        format!("SUMMARY:{}", escape(&event.summary)),
+        format!("DESCRIPTION:{}", escape(&format!("{}\n{}", event.description, event.url))),
+        format!("URL:{}", event.url),
+        "TRANSP:TRANSPARENT".to_string(),
+    ];
+    if stored.cancelled_at.is_some() {
+        lines.push("STATUS:CANCELLED".to_string());
+    } else {
+        lines.push("STATUS:CONFIRMED".to_string());
+        lines.extend([
+            "BEGIN:VALARM".to_string(),
+            "ACTION:DISPLAY".to_string(),
+            format!("DESCRIPTION:{}", escape(&event.summary)),
+            format!("TRIGGER:{}", event.alarm),
+            "END:VALARM".to_string(),
+        ]);
+    }
+    lines.push("END:VEVENT".to_string());
+    lines.iter().map(|l| fold(l)).collect()
+}
+
+/// Merge `events` into the stored ones and render the calendar: changed events get the next SEQUENCE,
+/// events that disappeared (bug resolved, question answered) are kept as cancelled for a while.
+pub fn calendar(app: &AppHandle, events: Vec<CalendarEvent>, now: DateTime<Utc>) -> Result<String, String> {
+    let mut stored: BTreeMap<String, StoredEvent> = storage::load_json(app, EVENT_STORE)?;
+    for entry in stored.values_mut() {
+        if entry.cancelled_at.is_none() && !events.iter().any(|e| e.uid == entry.event.uid) {
+            entry.cancelled_at = Some(now);
+            entry.sequence += 1;
+        }
+    }
+    for event in events {
+        match stored.get_mut(&event.uid) {
+            Some(entry) if entry.event != event || entry.cancelled_at.is_some() => {
+                entry.event = event;
+                entry.sequence += 1;
+                entry.cancelled_at = None;
+            }
+            Some(_) => {}
+            None => {
+                stored.insert(event.uid.clone(), StoredEvent { event, sequence: 0, cancelled_at: None });
+            }
+        }
+    }
+    stored.retain(|_, e| e.cancelled_at.is_none_or(|at| at > now - Duration::days(CANCELLED_KEEP_DAYS)));
+    storage::save_json(app, EVENT_STORE, &stored)?;
+
+    let mut ics = String::new();
+    for line in ["BEGIN:VCALENDAR", "VERSION:2.0", "PRODID:-//Bugger//Bug calendar//EN", "CALSCALE:GREGORIAN", "METHOD:PUBLISH", "X-WR-CALNAME:Bugger"] {
+        ics.push_str(&fold(line));
+    }
+    for entry in stored.values() {
+        ics.push_str(&vevent(entry, now));
+    }
+    ics.push_str(&fold("END:VCALENDAR"));
+    Ok(ics)
+}
+
+/// Rewrite the calendar feed at BUGGER_CALENDAR_PATH, if configured, so subscribed calendars pick up changes.
+pub fn update_feed(app: &AppHandle, config: &AppConfig, bugs: &[Bug], threads: &HashMap<u64, Vec<Comment>>) {
+    let Some(path) = &config.calendar_path else {
+        return;
+    };
+    let now = Utc::now();
+    let events = events(bugs, threads, config, now, |id| config.bug_url(id));
+    match calendar(app, events, now).and_then(|ics| std::fs::write(path, ics).map_err(|e| format!("Failed to write {}: {}", path, e))) {
+        Ok(()) => info!("Calendar feed updated: {}", path),
+        Err(e) => warn!("Calendar feed update failed: {}", e),
+    }
+}
+
+#[tauri::command]
+/// Writes SLA deadlines and follow-up reminders for my bugs to `path` as an .ics file.
+pub fn export_calendar(app: AppHandle, state: State<AppState>, path: String) -> Result<(), String> {
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    let bugs = state.bugs_or_fetch()?;
+    let now = Utc::now();
+    let client = AzureDevOpsClient::new(config);
+    let threads = fetch_threads(&client, &bugs);
+    let ics = calendar(&app, events(&bugs, &threads, &client.config, now, |id| client.config.bug_url(id)), now)?;
+    std::fs::write(&path, ics).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::azure_devops::tests::test_config;
+    use crate::azure_devops::Identity;
+
+    #[test]
+    fn sla_deadlines_are_listed_for_my_bugs_only() {
+        let mut config = test_config("https://dev.azure.com");
+        config.sla = SlaPolicy { days: BTreeMap::from([(2, 7)]) };
+        let assigned = |id, to: &str| {
+            let mut bug = Bug::sample(id, "Save fails");
+            bug.severity = Some("2 - High".to_string());
+            bug.created_date = Some("2026-03-01T10:00:00Z".to_string());
+            bug.assigned_to = Some(Identity { display_name: to.to_string(), unique_name: to.to_string(), avatar_url: None });
+            bug
+        };
+        let bugs = [assigned(1, "ME@contoso.com"), assigned(2, "someone@contoso.com")];
+        let events = events(&bugs, &HashMap::new(), &config, Utc::now(), |id| format!("https://bugs/{}", id));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].uid, "bugger-sla-1@contoso");
+        assert_eq!(events[0].date, NaiveDate::from_ymd_opt(2026, 3, 8).unwrap());
+        assert_eq!((events[0].summary.as_str(), events[0].alarm.as_str()), ("SLA due: #1 Save fails", "-P1D"));
+    }
+
+    #[test]
+    fn text_is_escaped_and_long_lines_folded() {
+        assert_eq!(escape("a,b;c\\d\r\ne\nf"), r"a\,b\;c\\d\ne\nf");
+        let folded = fold(&format!("SUMMARY:{}", "é".repeat(40)));
+        let lines: Vec<&str> = folded.trim_end_matches("\r\n").split("\r\n").collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|l| l.len() <= 75));
+        assert!(lines[1].starts_with(' '));
+    }
+
+    #[test]
+    fn cancelled_events_have_no_alarm() {
+        let event = CalendarEvent {
+            uid: "bugger-sla-1@contoso".to_string(),
+            date: NaiveDate::from_ymd_opt(2026, 3, 8).unwrap(),
+            summary: "SLA due: #1 Save fails".to_string(),
+            description: "Severity: 2 - High".to_string(),
+            url: "https://bugs/1".to_string(),
+            alarm: "-P1D".to_string(),
+        };
+        let now = Utc::now();
+        let confirmed = vevent(&StoredEvent { event: event.clone(), sequence: 0, cancelled_at: None }, now);
+        assert!(confirmed.contains("DTSTART;VALUE=DATE:20260308\r\nDTEND;VALUE=DATE:20260309\r\n"));
+        assert!(confirmed.contains("STATUS:CONFIRMED\r\nBEGIN:VALARM") && confirmed.contains("TRIGGER:-P1D"));
+        let cancelled = vevent(&StoredEvent { event, sequence: 2, cancelled_at: Some(now) }, now);
+        assert!(cancelled.contains("SEQUENCE:2") && cancelled.contains("STATUS:CANCELLED"));
+        assert!(!cancelled.contains("VALARM"));
+    }
+}
@@ -0,0 +1,111 @@
+// Category presentation (emoji, color, display name), configurable per category
+use crate::bug_analysis::BugCategory;
+use log::warn;
+use std::collections::HashMap;
+use std::env;
+
+#[derive(Debug, Clone)]
+pub struct CategoryStyle {
+    pub emoji: String,
+    /// CSS color, e.g. "#d7263d" or "teal".
+    pub color: String,
+    pub label: String,
+}
+
+/// Presentation for every category: built-in defaults, overridden by BUGGER_CATEGORY_<NAME>
+/// ("emoji|color|Display name"; empty parts keep the default), e.g. BUGGER_CATEGORY_CRASH="🔥||Crashes".
+#[derive(Debug, Clone)]
+pub struct CategoryStyles(HashMap<BugCategory, CategoryStyle>);
+
+fn default_style(category: &BugCategory) -> CategoryStyle {
+    use BugCategory::*;
+    let (emoji, color) = match category {
+        Crash => ("💥", "#d7263d"),
+        Performance => ("🐢", "#fbb13c"),
+        Security => ("🔒", "#1e90ff"),
+        FileSystem => ("💾", "#6c3483"),
+        Memory => ("🧠", "#16a085"),
+        Driver => ("🔌", "#b9770e"),
+        Boot => ("🚀", "#34495e"),
+        UI => ("🖥️", "#e67e22"),
+        Network => ("🌐", "#2980b9"),
+        Other => ("📦", "#888"),
+    };
+    CategoryStyle { emoji: emoji.to_string(), color: color.to_string(), label: format!("{:?}", category) }
+}
+
+/// Colors end up in inline styles, so only `#hex` and plain color names are accepted.
+fn is_safe_color(color: &str) -> bool {
+    let name = color.strip_prefix('#').unwrap_or(color);
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+impl CategoryStyles {
+    pub fn from_env() -> Self {
+        let styles = BugCategory::ALL
+            .iter()
+            .map(|category| {
+                let mut style = default_style(category);
+                let var = format!("BUGGER_CATEGORY_{}", format!("{:?}", category).to_uppercase());
+                if let Ok(value) = env::var(&var) {
+                    let mut parts = value.splitn(3, '|').map(str::trim);
+                    if let Some(emoji) = parts.next().filter(|e| !e.is_empty()) {
+                        style.emoji = emoji.to_string();
+                    }
+                    match parts.next().filter(|c| !c.is_empty()) {
+                        Some(color) if is_safe_color(color) => style.color = color.to_string(),
+                        Some(color) => warn!("Ignoring invalid color '{}' in {}", color, var),
+                        None => {}
+                    }
+                    if let Some(label) = parts.next().filter(|l| !l.is_empty()) {
+                        style.label = label.to_string();
+                    }
+                }
+                (category.clone(), style)
+            })
+            .collect();
+        CategoryStyles(styles)
+    }
+
+    pub fn get(&self, category: &BugCategory) -> CategoryStyle {
+        self.0.get(category).cloned().unwrap_or_else(|| default_style(category))
+    }
+
+    /// "emoji label" as plain text, for notifications and exports.
+    pub fn display(&self, category: &BugCategory) -> String {
+        let style = self.get(category);
+        format!("{} {}", style.emoji, style.label)
+    }
+
+    /// Colored category heading for the report, with an optional count.
+    pub fn html(&self, category: &BugCategory, count: Option<usize>) -> String {
+        let style = self.get(category);
+        let count = count.map(|n| format!(" ({})", n)).unwrap_or_default();
+        format!(
+            "<span class='category' style='color:{} !important'>{} {}{}</span>",
+            style.color,
+            html_escape::encode_text(&style.emoji),
+            html_escape::encode_text(&style.label),
+            count
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn custom_emoji_and_labels_are_escaped_in_html() {
+        let mut styles = CategoryStyles::from_env();
+        styles.0.insert(BugCategory::Crash, CategoryStyle { emoji: "<img src=x>".to_string(), color: "teal".to_string(), label: "A & B".to_string() });
+        let html = styles.html(&BugCategory::Crash, Some(3));
+        assert_eq!(html, "<span class='category' style='color:teal !important'>&lt;img src=x&gt; A &amp; B (3)</span>");
+    }
+
+    #[test]
+    fn only_hex_and_named_colors_are_safe() {
+        assert!(is_safe_color("#d7263d") && is_safe_color("teal"));
+        assert!(!is_safe_color("#") && !is_safe_color("red;background:url(x)") && !is_safe_color("red !important"));
+    }
+}
@@ -0,0 +1,194 @@
+// Weighted actionability checklist: what a bug report contains, scored per bug and shown as a badge
+use crate::azure_devops::Bug;
+use crate::stacktrace;
+use log::warn;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::env;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+pub enum Check {
+    Repro,
+    Environment,
+    ExpectedBehavior,
+    Logs,
+}
+
+impl Check {
+    const ALL: [Check; 4] = [Check::Repro, Check::Environment, Check::ExpectedBehavior, Check::Logs];
+
+    /// Name used in BUGGER_CHECKLIST and on the badge.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Check::Repro => "repro",
+            Check::Environment => "environment",
+            Check::ExpectedBehavior => "expected",
+            Check::Logs => "logs",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Check::Repro => "Repro steps",
+            Check::Environment => "Environment",
+            Check::ExpectedBehavior => "Expected vs. actual behavior",
+            Check::Logs => "Logs, dump or screenshot",
+        }
+    }
+}
+
+fn repro_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?im)steps to reproduce|repro(?:duction)? steps|\brepro:|to reproduce|<ol\b|^\s*(?:step\s*)?1[.):]\s").unwrap())
+}
+
+fn expected_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?i)expected (?:behaviou?r|result|outcome)|actual (?:behaviou?r|result)|\bexpected:|\bshould (?:be|have|show|not|work|display|return)\b").unwrap())
+}
+
+fn logs_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?i)<img\b|_apis/wit/attachments|\.(?:log|dmp|etl|evtx|txt|png|jpe?g|gif|har)\b|screenshot|memory dump|crash dump").unwrap())
+}
+
+/// Checks and their weights: BUGGER_CHECKLIST as `repro=3,environment=2,expected=2,logs=1`.
+/// Checks left out (or weighted 0) are not scored. Defaults to every check with weight 1.
+#[derive(Debug, Clone)]
+pub struct Checklist {
+    items: Vec<(Check, u32)>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ChecklistScore {
+    pub passed: Vec<Check>,
+    pub missing: Vec<Check>,
+    pub score: u32,
+    pub max: u32,
+}
+
+impl Checklist {
+    pub fn from_env() -> Self {
+        let items = match env::var("BUGGER_CHECKLIST") {
+            Ok(value) if !value.trim().is_empty() => value
+                .split(',')
+                .filter_map(|entry| {
+                    let (name, weight) = entry.split_once('=').unwrap_or((entry, "1"));
+                    let check = Check::ALL.into_iter().find(|c| c.key().eq_ignore_ascii_case(name.trim()));
+                    match (check, weight.trim().parse::<u32>()) {
+                        (Some(check), Ok(weight)) => (weight > 0).then_some((check, weight)),
+                        _ => {
+                            warn!("Ignoring checklist entry '{}'", entry.trim());
+                            None
+                        }
+                    }
+                })
+                .collect(),
+            _ => Check::ALL.iter().map(|c| (*c, 1)).collect(),
+        };
+        Checklist { items }
+    }
+
+    fn passes(check: Check, bug: &Bug, text: &str, has_logs: bool) -> bool {
+        match check {
+            Check::Repro => repro_re().is_match(text),
+            Check::Environment => !bug.environment.is_missing() || bug.environment.browser.is_some(),
+            Check::ExpectedBehavior => expected_re().is_match(text),
+            Check::Logs => has_logs || logs_re().is_match(text) || stacktrace::parse(text).is_some(),
+        }
+    }
+
+    /// Score `bug`; `has_logs` is set when attached logs were found by the attachment scan.
+    pub fn score(&self, bug: &Bug, has_logs: bool) -> ChecklistScore {
+        let text = bug.description.as_deref().unwrap_or("");
+        let mut result = ChecklistScore { passed: Vec::new(), missing: Vec::new(), score: 0, max: 0 };
+        for (check, weight) in &self.items {
+            result.max += weight;
+            if Self::passes(*check, bug, text, has_logs) {
+                result.score += weight;
+                result.passed.push(*check);
+            } else {
+                result.missing.push(*check);
+            }
+        }
+        result
+    }
+}
+
+impl ChecklistScore {
+    pub fn percent(&self) -> u32 {
+        (self.score * 100).checked_div(self.max).unwrap_or(100)
+    }
+
+    /// Badge with the score and a ✓/✗ per check; the tooltip lists what is missing.
+    pub fn badge(&self) -> String {
+        let color = match self.percent() {
+            75.. => "#2e7d32",
+            40..=74 => "#b9770e",
+            _ => "#c0392b",
+        };
+        let mut checks: Vec<(usize, String)> = self
+            .passed
+            .iter()
+            .map(|c| (Check::ALL.iter().position(|a| a == c).unwrap_or(0), format!("✓{}", c.key())))
+            .chain(self.missing.iter().map(|c| (Check::ALL.iter().position(|a| a == c).unwrap_or(0), format!("✗{}", c.key()))))
+            .collect();
+        checks.sort();
+        let missing: Vec<&str> = self.missing.iter().map(|c| c.label()).collect();
+        let title = if missing.is_empty() { "Checklist complete".to_string() } else { format!("Missing: {}", missing.join(", ")) };
+        format!(
+            "<span class='checklist-badge' style='border-color:{c} !important;color:{c} !important' title='{}'>📝 {}% {}</span>",
+            html_escape::encode_single_quoted_attribute(&title),
+            self.percent(),
+            checks.into_iter().map(|(_, c)| c).collect::<Vec<_>>().join(" "),
+            c = color
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environment::Environment;
+
+    fn bug(description: &str) -> Bug {
+        let mut bug = Bug::sample(1, "Save fails");
+        bug.description = Some(description.to_string());
+        bug.environment = Environment::extract(description);
+        bug
+    }
+
+    #[test]
+    fn a_complete_report_passes_every_check() {
+        let checklist = Checklist { items: Check::ALL.iter().map(|c| (*c, 1)).collect() };
+        let score = checklist.score(&bug("Steps to reproduce: click Save. Expected result: saved. OS: Windows 11. See save.log"), false);
+        assert_eq!(score.passed, Check::ALL);
+        assert!(score.missing.is_empty());
+        assert_eq!(score.percent(), 100);
+        assert!(score.badge().contains("title='Checklist complete'"));
+    }
+
+    #[test]
+    fn weights_decide_the_score() {
+        let checklist = Checklist { items: vec![(Check::Repro, 3), (Check::Logs, 1)] };
+        let score = checklist.score(&bug("<ol><li>Open a file</li><li>Click Save</li></ol>"), false);
+        assert_eq!((score.score, score.max, score.percent()), (3, 4, 75));
+        assert_eq!(score.missing, [Check::Logs]);
+        assert_eq!(checklist.score(&bug("Nothing useful"), true).passed, [Check::Logs]);
+    }
+
+    #[test]
+    fn the_badge_lists_what_is_missing() {
+        let checklist = Checklist { items: vec![(Check::Repro, 1), (Check::ExpectedBehavior, 1)] };
+        let badge = checklist.score(&bug("It should work"), false).badge();
+        assert!(badge.contains("title='Missing: Repro steps'"));
+        assert!(badge.contains("📝 50% ✗repro ✓expected"));
+    }
+
+    #[test]
+    fn an_empty_checklist_scores_full() {
+        assert_eq!(Checklist { items: Vec::new() }.score(&bug(""), false).percent(), 100);
+    }
+}
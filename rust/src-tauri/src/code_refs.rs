@@ -0,0 +1,191 @@
+// Code references: file paths and repo links in bug text, resolved against local clones and opened in the editor
+use crate::env_list;
+use crate::stacktrace::text_lines;
+use crate::AppConfig;
+use log::warn;
+use regex::Regex;
+use serde::Serialize;
+use std::env;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// Local clones to resolve paths against (BUGGER_REPO_ROOTS, e.g. `Contoso.App=C:\src\app,tools=D:\tools`),
+/// named after their Azure DevOps repository.
+#[derive(Debug, Clone, Default)]
+pub struct RepoRoots {
+    pub roots: Vec<(String, PathBuf)>,
+}
+
+impl RepoRoots {
+    pub fn from_env() -> Self {
+        let roots = env_list("BUGGER_REPO_ROOTS")
+            .iter()
+            .filter_map(|entry| match entry.split_once('=') {
+                Some((name, path)) if !name.trim().is_empty() && !path.trim().is_empty() => Some((name.trim().to_string(), PathBuf::from(path.trim()))),
+                _ => {
+                    warn!("Ignoring invalid BUGGER_REPO_ROOTS entry '{}' (use name=path)", entry);
+                    None
+                }
+            })
+            .collect();
+        RepoRoots { roots }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.roots.is_empty()
+    }
+
+    /// The first clone (limited to `repo` when the bug named one) containing the longest trailing part
+    /// of `path`, e.g. `C:\agent\_work\1\s\src\Config.cs` → `<root>\src\Config.cs`.
+    fn resolve(&self, path: &str, repo: Option<&str>) -> Option<(String, String, PathBuf)> {
+        let parts: Vec<&str> = path.split(['/', '\\']).filter(|p| !p.is_empty() && !p.ends_with(':')).collect();
+        if parts.contains(&"..") {
+            return None;
+        }
+        let roots = self.roots.iter().filter(|(name, _)| repo.is_none_or(|r| name.eq_ignore_ascii_case(r)));
+        for start in 0..parts.len() {
+            let relative = parts[start..].join("/");
+            for (name, root) in roots.clone() {
+                let local = root.join(&relative);
+                if local.is_file() {
+                    return Some((name.clone(), relative, local));
+                }
+            }
+        }
+        None
+    }
+
+    /// Whether `path` lies inside one of the configured clones.
+    pub fn contains(&self, path: &Path) -> bool {
+        let Ok(path) = path.canonicalize() else {
+            return false;
+        };
+        self.roots.iter().any(|(_, root)| root.canonicalize().is_ok_and(|root| path.starts_with(root)))
+    }
+}
+
+/// A file mentioned in a bug that exists in a local clone.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CodeRef {
+    pub repo: String,
+    /// Path inside the repository, with forward slashes.
+    pub relative: String,
+    pub local: PathBuf,
+    pub line: Option<u32>,
+}
+
+impl CodeRef {
+    /// e.g. "src/Config.cs:42".
+    pub fn label(&self) -> String {
+        match self.line {
+            Some(line) => format!("{}:{}", self.relative, line),
+            None => self.relative.clone(),
+        }
+    }
+}
+
+/// Azure DevOps file links: `.../_git/<repo>?path=/src/x.cs&line=42`.
+fn repo_link() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r#"_git/([^/?#\s"'<>]+)[^\s"'<>]*?[?&]path=([^&#\s"'<>]+)(?:[^\s"'<>]*?[?&]line=(\d+))?"#).unwrap())
+}
+
+/// Paths with at least one directory and a source file extension, with an optional line number
+/// (`x.cs:42`, `x.cs(42,7)`, `x.cs:line 42`, `x.py", line 42`).
+fn file_path() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r#"(?i)((?:[a-z]:)?(?:[\w.\-]+[/\\])+[\w.\-]+\.(?:rs|cs|cpp|cc|cxx|hpp|hxx|c|h|tsx|ts|jsx|js|py|java|kt|go|rb|swift|mm|m|ps1|psm1|xaml|vb|fs|idl|inf|sql|sh|json|ya?ml|toml|xml|props|targets|csproj|vcxproj))\b(?::line (\d+)|", line (\d+)|:(\d+)|\((\d+)(?:,\d+)?\))?"#,
+        )
+        .unwrap()
+    })
+}
+
+/// Files referenced in `text` (title and description) that exist in a configured clone, in order of mention.
+pub fn find(text: &str, roots: &RepoRoots) -> Vec<CodeRef> {
+    if roots.is_empty() {
+        return Vec::new();
+    }
+    let text = text_lines(text).join("\n");
+    let mut mentions: Vec<(usize, Option<String>, String, Option<u32>)> = repo_link()
+        .captures_iter(&text)
+        .map(|c| {
+            let path = c[2].replace("%2F", "/").replace("%2f", "/").replace("%20", " ");
+            (c.get(0).map_or(0, |m| m.start()), Some(c[1].to_string()), path, c.get(3).and_then(|l| l.as_str().parse().ok()))
+        })
+        .collect();
+    for c in file_path().captures_iter(&text) {
+        let start = c.get(0).map_or(0, |m| m.start());
+        // Already covered by a repo link's path parameter.
+        if mentions.iter().any(|(_, repo, path, _)| repo.is_some() && path.ends_with(&c[1].replace('\\', "/"))) {
+            continue;
+        }
+        let line = (2..=5).find_map(|i| c.get(i)).and_then(|l| l.as_str().parse().ok());
+        mentions.push((start, None, c[1].to_string(), line));
+    }
+    mentions.sort_by_key(|m| m.0);
+    let mut refs: Vec<CodeRef> = Vec::new();
+    for (_, repo, path, line) in mentions {
+        let Some((repo, relative, local)) = roots.resolve(&path, repo.as_deref()) else {
+            continue;
+        };
+        let code_ref = CodeRef { repo, relative, local, line };
+        if !refs.contains(&code_ref) {
+            refs.push(code_ref);
+        }
+    }
+    refs
+}
+
+/// Whether `cmd /C` passes `arg` through as plain text: no operators, escapes, quotes or variable
+/// expansion.
+fn is_shell_safe(arg: &str) -> bool {
+    !arg.chars().any(|c| matches!(c, '&' | '|' | '^' | '<' | '>' | '%' | '!' | '"' | '(' | ')' | '\n' | '\r'))
+}
+
+#[tauri::command]
+/// Opens `path` (at `line`) in the editor from BUGGER_EDITOR_COMMAND (default `code`) with `--goto`.
+/// Only files inside the configured repo roots can be opened.
+pub fn open_in_editor(path: String, line: Option<u32>) -> Result<(), String> {
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    let local = PathBuf::from(&path);
+    if local.components().any(|c| c == Component::ParentDir) || !config.repo_roots.contains(&local) {
+        return Err(format!("{} is not inside a configured repo root", path));
+    }
+    let editor = env::var("BUGGER_EDITOR_COMMAND").unwrap_or_else(|_| "code".to_string());
+    let target = format!("{}:{}", path, line.unwrap_or(1));
+    // `code` is a .cmd script on Windows, which only the shell can run; the shell would also act on
+    // metacharacters in a file name taken from a bug.
+    if cfg!(windows) && !is_shell_safe(&target) {
+        return Err(format!("{} can't be opened: its name contains shell metacharacters", path));
+    }
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", &editor]);
+        command
+    } else {
+        Command::new(&editor)
+    };
+    command
+        .args(["--goto", &target])
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("Failed to start {}: {}", editor, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shell_metacharacters_in_paths_are_refused() {
+        assert!(is_shell_safe(r"C:\src\app\main.rs:12"));
+        assert!(is_shell_safe("/home/me/src/app main.rs:1"));
+        assert!(!is_shell_safe(r"C:\src\a&calc.exe&.rs:1"));
+        assert!(!is_shell_safe("x|y.rs"));
+        assert!(!is_shell_safe("%COMSPEC%.rs"));
+        assert!(!is_shell_safe("a^b.rs"));
+    }
+}
@@ -0,0 +1,205 @@
+// Side-by-side comparison of two bug sets (two people, teams or area paths): counts, ages and hygiene
+use crate::azure_devops::{AzureDevOpsClient, Bug};
+use crate::bug_analysis::{categorize_bug, is_questionable, BugCategory};
+use crate::category_style::CategoryStyles;
+use crate::triage::bug_age_days;
+use crate::wiql::WiqlQuery;
+use crate::{template, AppConfig};
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Age buckets (upper bound in days, label) for the age distribution.
+const AGE_BUCKETS: [(i64, &str); 5] = [(7, "< 1 week"), (30, "1–4 weeks"), (90, "1–3 months"), (365, "3–12 months"), (i64::MAX, "> 1 year")];
+
+/// One side of a comparison: active bugs matching every given filter.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ComparisonFilter {
+    /// Column heading; defaults to the filters themselves.
+    pub label: Option<String>,
+    /// Area path, including its children.
+    pub area_path: Option<String>,
+    /// Assignee's email.
+    pub assigned_to: Option<String>,
+    pub tags: Vec<String>,
+}
+
+impl ComparisonFilter {
+    fn label(&self) -> String {
+        self.label.clone().unwrap_or_else(|| {
+            let parts: Vec<String> = self.area_path.iter().chain(&self.assigned_to).cloned().chain(self.tags.iter().map(|t| format!("tag:{}", t))).collect();
+            if parts.is_empty() { "All bugs".to_string() } else { parts.join(" · ") }
+        })
+    }
+
+    fn wiql(&self, excluded_states: &[String]) -> String {
+        let mut query = WiqlQuery::new().work_item_type("Bug").states_not_in(excluded_states);
+        if let Some(area) = &self.area_path {
+            query = query.area_under(area);
+        }
+        if let Some(who) = &self.assigned_to {
+            query = query.assigned_to(who);
+        }
+        query.tagged(&self.tags).build()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct SideStats {
+    pub label: String,
+    pub total: usize,
+    pub actionable: usize,
+    /// Share of bugs that are actionable.
+    pub actionable_percent: u32,
+    /// Bug count per age bucket, in AGE_BUCKETS order.
+    pub age_buckets: Vec<(String, usize)>,
+    pub median_age_days: i64,
+    pub average_checklist_percent: u32,
+    /// Null when no bug template is configured or found.
+    pub template_compliance_percent: Option<u32>,
+    pub by_category: Vec<(BugCategory, usize)>,
+}
+
+fn percent(part: usize, total: usize) -> u32 {
+    (part * 100).checked_div(total).unwrap_or(100) as u32
+}
+
+fn average(values: impl Iterator<Item = u32>) -> u32 {
+    let values: Vec<u32> = values.collect();
+    values.iter().sum::<u32>().checked_div(values.len() as u32).unwrap_or(100)
+}
+
+pub fn stats(label: String, bugs: &[Bug], config: &AppConfig, template_sections: &[String], now: DateTime<Utc>) -> SideStats {
+    let mut ages: Vec<i64> = bugs.iter().map(|b| bug_age_days(b, now)).collect();
+    ages.sort_unstable();
+    let actionable = bugs.iter().filter(|b| is_questionable(b).is_none()).count();
+    let mut lower = 0;
+    let age_buckets = AGE_BUCKETS
+        .iter()
+        .map(|(upper, name)| {
+            let count = ages.iter().filter(|a| **a >= lower && **a < *upper).count();
+            lower = *upper;
+            (name.to_string(), count)
+        })
+        .collect();
+    SideStats {
+        label,
+        total: bugs.len(),
+        actionable,
+        actionable_percent: percent(actionable, bugs.len()),
+        age_buckets,
+        median_age_days: ages.get(ages.len() / 2).copied().unwrap_or(0),
+        average_checklist_percent: average(bugs.iter().map(|b| config.checklist.score(b, false).percent())),
+        template_compliance_percent: (!template_sections.is_empty()).then(|| average(bugs.iter().map(|b| template::check(b, template_sections).percent()))),
+        by_category: BugCategory::ALL
+            .iter()
+            .map(|c| (c.clone(), bugs.iter().filter(|b| categorize_bug(b) == *c).count()))
+            .filter(|(_, n)| *n > 0)
+            .collect(),
+    }
+}
+
+/// Side-by-side table; each row is one metric.
+pub fn html(a: &SideStats, b: &SideStats, styles: &CategoryStyles) -> String {
+    let mut rows: Vec<(String, String, String)> = vec![
+        ("Active bugs".to_string(), a.total.to_string(), b.total.to_string()),
+        ("Actionable".to_string(), format!("{} ({}%)", a.actionable, a.actionable_percent), format!("{} ({}%)", b.actionable, b.actionable_percent)),
+        ("Median age (days)".to_string(), a.median_age_days.to_string(), b.median_age_days.to_string()),
+        ("Avg. checklist score".to_string(), format!("{}%", a.average_checklist_percent), format!("{}%", b.average_checklist_percent)),
+    ];
+    if a.template_compliance_percent.is_some() {
+        let show = |s: &SideStats| s.template_compliance_percent.map(|p| format!("{}%", p)).unwrap_or_default();
+        rows.push(("Template compliance".to_string(), show(a), show(b)));
+    }
+    for (i, (bucket, count)) in a.age_buckets.iter().enumerate() {
+        rows.push((format!("Age {}", bucket), count.to_string(), b.age_buckets[i].1.to_string()));
+    }
+    for category in BugCategory::ALL.iter() {
+        let count = |s: &SideStats| s.by_category.iter().find(|(c, _)| c == category).map_or(0, |(_, n)| *n);
+        if count(a) + count(b) > 0 {
+            rows.push((styles.html(category, None), count(a).to_string(), count(b).to_string()));
+        }
+    }
+    let mut html = format!(
+        "<h2>⚖️ Comparison</h2><table class='comparison'><tr><th></th><th>{}</th><th>{}</th></tr>",
+        html_escape::encode_text(&a.label),
+        html_escape::encode_text(&b.label)
+    );
+    for (metric, left, right) in rows {
+        html.push_str(&format!("<tr><td>{}</td><td>{}</td><td>{}</td></tr>", metric, left, right));
+    }
+    html.push_str("</table>");
+    html
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Comparison {
+    pub a: SideStats,
+    pub b: SideStats,
+    pub html: String,
+}
+
+#[tauri::command]
+/// Compares the active bugs matching two filters (e.g. two area paths or two people) side by side.
+pub fn compare_filters(a: ComparisonFilter, b: ComparisonFilter) -> Result<Comparison, String> {
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    let client = AzureDevOpsClient::new(config);
+    let excluded_states = client.query_filters().excluded_states;
+    let template_sections = client.config.bug_template.load(&client);
+    let now = Utc::now();
+    let side = |filter: &ComparisonFilter| -> Result<SideStats, String> {
+        let ids = client.run_wiql(&filter.wiql(&excluded_states))?;
+        let bugs = client.fetch_bug_details(&ids)?;
+        Ok(stats(filter.label(), &bugs, &client.config, &template_sections, now))
+    };
+    let (a, b) = (side(&a)?, side(&b)?);
+    let html = html(&a, &b, &client.config.category_styles);
+    Ok(Comparison { a, b, html })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::azure_devops::tests::test_config;
+    use chrono::Duration;
+
+    fn aged(id: u64, title: &str, days: i64, now: DateTime<Utc>) -> Bug {
+        let mut bug = Bug::sample(id, title);
+        bug.description = Some(format!("{} reproduces several times a day here.", title));
+        bug.created_date = Some((now - Duration::days(days)).to_rfc3339());
+        bug
+    }
+
+    #[test]
+    fn labels_default_to_the_filters() {
+        let filter = ComparisonFilter { area_path: Some("Contoso\\Shell".to_string()), assigned_to: Some("ana@contoso.com".to_string()), tags: vec!["regression".to_string()], label: None };
+        assert_eq!(filter.label(), "Contoso\\Shell · ana@contoso.com · tag:regression");
+        assert_eq!(ComparisonFilter::default().label(), "All bugs");
+    }
+
+    #[test]
+    fn ages_categories_and_actionability_are_counted() {
+        let now = Utc::now();
+        let mut questionable = Bug::sample(4, "Network drops");
+        questionable.created_date = Some((now - Duration::days(400)).to_rfc3339());
+        let bugs = [aged(1, "App crash", 2, now), aged(2, "Network disconnects", 20, now), aged(3, "Crash on save", 45, now), questionable];
+        let side = stats("Shell".to_string(), &bugs, &test_config("https://dev.azure.com"), &[], now);
+        assert_eq!((side.total, side.actionable, side.actionable_percent, side.median_age_days), (4, 3, 75, 45));
+        let buckets: Vec<usize> = side.age_buckets.iter().map(|(_, n)| *n).collect();
+        assert_eq!(buckets, [1, 1, 1, 0, 1]);
+        assert_eq!(side.by_category, [(BugCategory::Crash, 2), (BugCategory::Network, 2)]);
+        assert_eq!(side.template_compliance_percent, None);
+    }
+
+    #[test]
+    fn empty_sides_compare_side_by_side() {
+        let config = test_config("https://dev.azure.com");
+        let a = stats("<A>".to_string(), &[], &config, &[], Utc::now());
+        assert_eq!((a.actionable_percent, a.average_checklist_percent), (100, 100));
+        let html = html(&a, &stats("B".to_string(), &[], &config, &[], Utc::now()), &config.category_styles);
+        assert!(html.contains("<th>&lt;A&gt;</th><th>B</th>"));
+        assert!(html.contains("<tr><td>Active bugs</td><td>0</td><td>0</td></tr>"));
+        assert!(!html.contains("Template compliance"));
+    }
+}
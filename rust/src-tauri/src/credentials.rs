@@ -0,0 +1,225 @@
+// Secrets in the OS keychain (Windows Credential Manager, macOS Keychain, the Secret Service on Linux)
+// instead of plaintext `.env`; the environment is the fallback
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+/// Keychain service the secrets are stored under, one entry per credential.
+const SERVICE: &str = "Bugger";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Credential {
+    AzureDevopsPat,
+    /// Used when the first PAT is rejected, e.g. while rotating tokens.
+    AzureDevopsPatSecondary,
+    OpenaiApiKey,
+}
+
+/// Where a credential currently comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialSource {
+    Keychain,
+    Environment,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CredentialStatus {
+    pub name: Credential,
+    /// `None` when it isn't set anywhere.
+    pub source: Option<CredentialSource>,
+}
+
+impl Credential {
+    pub const ALL: [Credential; 3] = [Credential::AzureDevopsPat, Credential::AzureDevopsPatSecondary, Credential::OpenaiApiKey];
+
+    /// The environment variable it falls back to, also its keychain account name.
+    pub fn env_name(self) -> &'static str {
+        match self {
+            Credential::AzureDevopsPat => "AZURE_DEVOPS_PAT",
+            Credential::AzureDevopsPatSecondary => "AZURE_DEVOPS_PAT_SECONDARY",
+            Credential::OpenaiApiKey => "OPENAI_API_KEY",
+        }
+    }
+
+    fn entry(self) -> Result<keyring::Entry, String> {
+        keyring::Entry::new(SERVICE, self.env_name()).map_err(|e| format!("Keychain error: {}", e))
+    }
+}
+
+/// Where secrets are kept: the OS keychain, or memory in tests.
+pub trait SecretStore: Send + Sync {
+    fn get(&self, credential: Credential) -> Result<Option<String>, String>;
+    fn set(&self, credential: Credential, secret: &str) -> Result<(), String>;
+    fn delete(&self, credential: Credential) -> Result<(), String>;
+}
+
+struct Keychain;
+
+impl SecretStore for Keychain {
+    fn get(&self, credential: Credential) -> Result<Option<String>, String> {
+        match credential.entry()?.get_password() {
+            Ok(secret) => Ok(Some(secret).filter(|s| !s.is_empty())),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    fn set(&self, credential: Credential, secret: &str) -> Result<(), String> {
+        credential.entry()?.set_password(secret).map_err(|e| e.to_string())
+    }
+
+    fn delete(&self, credential: Credential) -> Result<(), String> {
+        match credential.entry()?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+/// Credentials from a secret store, with the environment as the fallback.
+pub struct Credentials {
+    store: Box<dyn SecretStore>,
+    /// Values read so far; lookups happen on every config load, so each is read once.
+    cache: Mutex<HashMap<Credential, Option<String>>>,
+}
+
+impl Credentials {
+    pub fn new(store: Box<dyn SecretStore>) -> Self {
+        Credentials { store, cache: Mutex::new(HashMap::new()) }
+    }
+
+    fn read(&self, credential: Credential) -> Option<String> {
+        self.store.get(credential).unwrap_or_else(|e| {
+            log::warn!("Could not read {} from the keychain: {}", credential.env_name(), e);
+            None
+        })
+    }
+
+    fn stored(&self, credential: Credential) -> Option<String> {
+        let Ok(mut cache) = self.cache.lock() else {
+            return self.read(credential);
+        };
+        cache.entry(credential).or_insert_with(|| self.read(credential)).clone()
+    }
+
+    /// A setting from the environment, except that credentials come from the store first.
+    pub fn var(&self, name: &str) -> Option<String> {
+        let stored = Credential::ALL.into_iter().find(|c| c.env_name() == name).and_then(|c| self.stored(c));
+        stored.or_else(|| crate::settings::var(name).filter(|v| !v.trim().is_empty()))
+    }
+
+    pub fn store(&self, credential: Credential, secret: &str) -> Result<(), String> {
+        let secret = secret.trim();
+        if secret.is_empty() {
+            return Err(format!("{} is empty", credential.env_name()));
+        }
+        self.store.set(credential, secret).map_err(|e| format!("Could not store {} in the keychain: {}", credential.env_name(), e))?;
+        self.cache.lock().map_err(|e| e.to_string())?.insert(credential, Some(secret.to_string()));
+        Ok(())
+    }
+
+    pub fn clear(&self, credential: Credential) -> Result<(), String> {
+        self.store.delete(credential).map_err(|e| format!("Could not remove {} from the keychain: {}", credential.env_name(), e))?;
+        self.cache.lock().map_err(|e| e.to_string())?.insert(credential, None);
+        Ok(())
+    }
+
+    pub fn status(&self) -> Vec<CredentialStatus> {
+        Credential::ALL
+            .into_iter()
+            .map(|name| {
+                let source = if self.stored(name).is_some() {
+                    Some(CredentialSource::Keychain)
+                } else if crate::settings::var(name.env_name()).is_some_and(|v| !v.trim().is_empty()) {
+                    Some(CredentialSource::Environment)
+                } else {
+                    None
+                };
+                CredentialStatus { name, source }
+            })
+            .collect()
+    }
+}
+
+/// The app's credentials, kept in the OS keychain.
+fn keychain() -> &'static Credentials {
+    static CREDENTIALS: OnceLock<Credentials> = OnceLock::new();
+    CREDENTIALS.get_or_init(|| Credentials::new(Box::new(Keychain)))
+}
+
+/// A setting from the environment, except that credentials come from the keychain first.
+pub fn var(name: &str) -> Option<String> {
+    keychain().var(name)
+}
+
+/// Store a credential in the keychain.
+pub fn store(credential: Credential, secret: &str) -> Result<(), String> {
+    keychain().store(credential, secret)
+}
+
+#[tauri::command]
+/// Stores a credential in the OS keychain, where it takes precedence over `.env`.
+pub fn set_credential(name: Credential, value: String) -> Result<(), String> {
+    store(name, &value)
+}
+
+#[tauri::command]
+/// Removes a credential from the OS keychain and from this session, e.g. to stop using the OpenAI
+/// key. One in `.env` applies again on the next start.
+pub fn clear_credential(name: Credential) -> Result<(), String> {
+    keychain().clear(name)?;
+    crate::settings::set_var(name.env_name(), None);
+    Ok(())
+}
+
+#[tauri::command]
+/// Where each credential comes from, without revealing it.
+pub fn get_credential_status() -> Vec<CredentialStatus> {
+    keychain().status()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Memory(Mutex<HashMap<Credential, String>>);
+
+    impl SecretStore for Memory {
+        fn get(&self, credential: Credential) -> Result<Option<String>, String> {
+            Ok(self.0.lock().unwrap().get(&credential).cloned())
+        }
+
+        fn set(&self, credential: Credential, secret: &str) -> Result<(), String> {
+            self.0.lock().unwrap().insert(credential, secret.to_string());
+            Ok(())
+        }
+
+        fn delete(&self, credential: Credential) -> Result<(), String> {
+            self.0.lock().unwrap().remove(&credential);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn stored_secrets_are_used_until_cleared() {
+        let credentials = Credentials::new(Box::<Memory>::default());
+        assert_eq!(credentials.var("AZURE_DEVOPS_PAT_SECONDARY"), None);
+        credentials.store(Credential::AzureDevopsPatSecondary, "  next-pat ").unwrap();
+        assert_eq!(credentials.var("AZURE_DEVOPS_PAT_SECONDARY").as_deref(), Some("next-pat"));
+        let status = credentials.status();
+        assert_eq!(status.iter().find(|s| s.name == Credential::AzureDevopsPatSecondary).unwrap().source, Some(CredentialSource::Keychain));
+        credentials.clear(Credential::AzureDevopsPatSecondary).unwrap();
+        assert_eq!(credentials.var("AZURE_DEVOPS_PAT_SECONDARY"), None);
+    }
+
+    #[test]
+    fn empty_secrets_are_refused() {
+        let credentials = Credentials::new(Box::<Memory>::default());
+        assert!(credentials.store(Credential::OpenaiApiKey, "   ").is_err());
+        assert_eq!(credentials.stored(Credential::OpenaiApiKey), None);
+    }
+}
@@ -0,0 +1,146 @@
+// Azure DevOps dashboard widget payload: the health score and headline counts as compact JSON, or an
+// HTML fragment for the markdown widget, written by a scheduled output so the team dashboard stays current
+use crate::azure_devops::Bug;
+use crate::bug_analysis::{categorize_bug, is_questionable, BugCategory};
+use crate::calendar::SlaPolicy;
+use crate::checklist::{Checklist, ChecklistScore};
+use crate::hygiene;
+use crate::{AppConfig, AppState};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use log::warn;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::HashMap;
+use tauri::{AppHandle, State};
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CategoryCount {
+    pub category: BugCategory,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DashboardWidget {
+    pub generated_at: DateTime<Utc>,
+    /// Backlog hygiene score, 0 (unhealthy) to 100 (healthy).
+    pub health_score: u32,
+    /// The score of the last day before today an analysis ran, for the trend.
+    pub previous_health_score: Option<u32>,
+    pub total: usize,
+    pub actionable: usize,
+    pub questionable: usize,
+    pub stale: usize,
+    pub past_sla: usize,
+    /// Non-empty categories, in `BugCategory::ALL` order.
+    pub categories: Vec<CategoryCount>,
+}
+
+/// The widget payload for `bugs`. Days (the SLA deadline, the previous score) are local, like the
+/// hygiene history.
+pub fn widget(app: &AppHandle, config: &AppConfig, bugs: &[Bug], now: DateTime<Utc>) -> DashboardWidget {
+    let today = now.with_timezone(&Local).date_naive();
+    let previous_health_score = hygiene::previous(app, today).unwrap_or_else(|e| {
+        warn!("Hygiene history unavailable: {}", e);
+        None
+    });
+    summarize(bugs, &config.checklist, &config.sla, config.stale_days, now, today, previous_health_score)
+}
+
+fn summarize(bugs: &[Bug], checklist: &Checklist, sla: &SlaPolicy, stale_days: i64, now: DateTime<Utc>, today: NaiveDate, previous_health_score: Option<u32>) -> DashboardWidget {
+    // Attached logs aren't scanned for the widget; the checklist counts them as missing.
+    let checklists: HashMap<u64, ChecklistScore> = bugs.iter().map(|bug| (bug.id, checklist.score(bug, false))).collect();
+    let score = hygiene::score(bugs, &checklists, sla, stale_days, now);
+    let questionable = bugs.iter().filter(|b| is_questionable(b).is_some()).count();
+    let mut counts: HashMap<BugCategory, usize> = HashMap::new();
+    for bug in bugs {
+        *counts.entry(categorize_bug(bug)).or_default() += 1;
+    }
+    DashboardWidget {
+        generated_at: now,
+        health_score: score.score,
+        previous_health_score,
+        total: bugs.len(),
+        actionable: bugs.len() - questionable,
+        questionable,
+        stale: bugs.iter().filter(|b| hygiene::is_stale(b, stale_days, now)).count(),
+        past_sla: bugs.iter().filter(|b| sla.due_date(b).is_some_and(|due| due < today)).count(),
+        categories: BugCategory::ALL.into_iter().filter_map(|category| Some(CategoryCount { count: *counts.get(&category)?, category })).collect(),
+    }
+}
+
+/// Self-contained HTML fragment (inline styles only, as the markdown widget has no stylesheet).
+pub fn html(widget: &DashboardWidget) -> String {
+    let trend = match widget.previous_health_score.map(|p| widget.health_score as i64 - p as i64) {
+        Some(delta) if delta > 0 => format!(" <span style=\"color:#2e7d32\">▲ {}</span>", delta),
+        Some(delta) if delta < 0 => format!(" <span style=\"color:#c62828\">▼ {}</span>", -delta),
+        _ => String::new(),
+    };
+    let categories = widget.categories.iter().map(|c| format!("{:?} {}", c.category, c.count)).collect::<Vec<_>>().join(" · ");
+    format!(
+        "<div style=\"font-family:sans-serif\"><div><span style=\"font-size:2em;font-weight:bold\">{}</span>/100{} <b>Backlog health</b></div>\
+         <div>{} active · {} actionable · {} questionable · {} stale · {} past SLA</div>\
+         <div style=\"color:#666\">{}</div>\
+         <div style=\"color:#999;font-size:0.8em\">Bugger, {}</div></div>",
+        widget.health_score,
+        trend,
+        widget.total,
+        widget.actionable,
+        widget.questionable,
+        widget.stale,
+        widget.past_sla,
+        categories,
+        widget.generated_at.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+    )
+}
+
+#[tauri::command]
+/// The dashboard widget payload for the current bugs.
+pub fn get_dashboard_widget(app: AppHandle, state: State<AppState>) -> Result<DashboardWidget, String> {
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    let bugs = state.bugs_or_fetch()?;
+    Ok(widget(&app, &config, &bugs, Utc::now()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn summary(bugs: &[Bug], now: DateTime<Utc>, previous: Option<u32>) -> DashboardWidget {
+        let sla = SlaPolicy { days: BTreeMap::from([(1, 1)]) };
+        summarize(bugs, &Checklist::from_env(), &sla, 30, now, now.date_naive(), previous)
+    }
+
+    #[test]
+    fn widget_counts_categories_staleness_and_sla() {
+        let now = Utc::now();
+        let mut crash = Bug::sample(1, "App crash on startup");
+        crash.severity = Some("1 - Critical".to_string());
+        crash.created_date = Some((now - chrono::Duration::days(3)).to_rfc3339());
+        let mut old = Bug::sample(2, "Login page crash");
+        old.created_date = Some((now - chrono::Duration::days(60)).to_rfc3339());
+        let widget = summary(&[crash, old], now, None);
+        assert_eq!(widget.total, 2);
+        assert_eq!(widget.actionable + widget.questionable, 2);
+        assert_eq!(widget.past_sla, 1);
+        assert_eq!(widget.stale, 1);
+        assert_eq!(widget.categories.iter().map(|c| c.count).sum::<usize>(), 2);
+        assert!(widget.categories.iter().all(|c| c.count > 0));
+        assert!(widget.health_score <= 100);
+    }
+
+    #[test]
+    fn html_shows_the_score_trend_and_counts() {
+        let mut widget = summary(&[Bug::sample(1, "App crash on startup")], Utc::now(), None);
+        widget.health_score = 80;
+        assert!(!html(&widget).contains('▲') && !html(&widget).contains('▼'));
+        widget.previous_health_score = Some(75);
+        assert!(html(&widget).contains("▲ 5"));
+        widget.previous_health_score = Some(90);
+        let out = html(&widget);
+        assert!(out.contains("▼ 10"));
+        assert!(out.contains(">80</span>/100"));
+        assert!(out.contains("1 active"));
+        assert!(!out.contains("class="), "the markdown widget has no stylesheet");
+    }
+}
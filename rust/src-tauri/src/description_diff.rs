@@ -0,0 +1,145 @@
+// Description edits between runs: a word diff of each changed description, so re-triaging a bug
+// only means reading what's new
+use crate::azure_devops::Bug;
+use crate::bug_analysis::strip_html;
+use crate::storage;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use std::collections::HashMap;
+use tauri::AppHandle;
+
+const SNAPSHOT_STORE: &str = "description_snapshots";
+const CHANGES_STORE: &str = "description_changes";
+/// How long a change stays in the report after it's first seen.
+const SHOW_DAYS: i64 = 7;
+/// Unchanged runs longer than this many words are shortened to their ends.
+const CONTEXT_WORDS: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DiffTag {
+    Same,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DescriptionChange {
+    pub detected: DateTime<Utc>,
+    pub segments: Vec<(DiffTag, String)>,
+}
+
+/// Word-level diff of two plain-text descriptions, with adjacent changes of the same kind merged.
+pub fn word_diff(old: &str, new: &str) -> Vec<(DiffTag, String)> {
+    let diff = TextDiff::from_words(old, new);
+    let mut segments: Vec<(DiffTag, String)> = Vec::new();
+    for change in diff.iter_all_changes() {
+        let tag = match change.tag() {
+            ChangeTag::Equal => DiffTag::Same,
+            ChangeTag::Insert => DiffTag::Added,
+            ChangeTag::Delete => DiffTag::Removed,
+        };
+        match segments.last_mut() {
+            Some((last, text)) if *last == tag => text.push_str(change.value()),
+            _ => segments.push((tag, change.value().to_string())),
+        }
+    }
+    segments
+}
+
+/// Keep the first and last few words of a long unchanged run.
+fn shorten(text: &str) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if words.len() <= CONTEXT_WORDS * 2 {
+        return text.to_string();
+    }
+    format!("{} … {}", words[..CONTEXT_WORDS].join(" "), words[words.len() - CONTEXT_WORDS..].join(" "))
+}
+
+/// Compare each bug's description with the last run's, store new changes and return the recent
+/// ones for bugs still in the list. Snapshots are only kept for the bugs in the list.
+pub fn update(app: &AppHandle, bugs: &[Bug], now: DateTime<Utc>) -> Result<HashMap<u64, DescriptionChange>, String> {
+    let mut snapshots: HashMap<u64, String> = storage::load_json(app, SNAPSHOT_STORE)?;
+    let mut changes: HashMap<u64, DescriptionChange> = storage::load_json(app, CHANGES_STORE)?;
+    record(&mut snapshots, &mut changes, bugs, now);
+    storage::save_json(app, SNAPSHOT_STORE, &snapshots)?;
+    storage::save_json(app, CHANGES_STORE, &changes)?;
+    changes.retain(|id, _| bugs.iter().any(|b| b.id == *id));
+    Ok(changes)
+}
+
+fn record(snapshots: &mut HashMap<u64, String>, changes: &mut HashMap<u64, DescriptionChange>, bugs: &[Bug], now: DateTime<Utc>) {
+    for bug in bugs {
+        let text = strip_html(bug.description.as_deref().unwrap_or("")).trim().to_string();
+        // Bugs seen for the first time have nothing to compare against yet.
+        if let Some(old) = snapshots.get(&bug.id).filter(|old| **old != text) {
+            changes.insert(bug.id, DescriptionChange { detected: now, segments: word_diff(old, &text) });
+        }
+        snapshots.insert(bug.id, text);
+    }
+    snapshots.retain(|id, _| bugs.iter().any(|b| b.id == *id));
+    changes.retain(|_, c| c.detected > now - Duration::days(SHOW_DAYS));
+}
+
+pub fn html(change: &DescriptionChange) -> String {
+    let body: String = change
+        .segments
+        .iter()
+        .map(|(tag, text)| match tag {
+            DiffTag::Same => html_escape::encode_text(&shorten(text)).into_owned(),
+            DiffTag::Added => format!("<ins>{}</ins>", html_escape::encode_text(text)),
+            DiffTag::Removed => format!("<del>{}</del>", html_escape::encode_text(text)),
+        })
+        .collect();
+    format!(
+        "<br><details class='description-diff'><summary>✏️ Description updated {} (view changes)</summary><div style='white-space:pre-wrap'>{}</div></details>",
+        change.detected.with_timezone(&chrono::Local).format("%Y-%m-%d"),
+        body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bug(id: u64, description: &str) -> Bug {
+        let mut bug = Bug::sample(id, "Crash");
+        bug.description = Some(description.to_string());
+        bug
+    }
+
+    #[test]
+    fn words_are_diffed_with_runs_merged() {
+        assert_eq!(
+            word_diff("Crash on login page", "Crash on the login screen"),
+            [
+                (DiffTag::Same, "Crash on ".to_string()),
+                (DiffTag::Added, "the ".to_string()),
+                (DiffTag::Same, "login ".to_string()),
+                (DiffTag::Removed, "page".to_string()),
+                (DiffTag::Added, "screen".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn changes_are_recorded_and_snapshots_of_gone_bugs_dropped() {
+        let now = Utc::now();
+        let (mut snapshots, mut changes) = (HashMap::new(), HashMap::new());
+        record(&mut snapshots, &mut changes, &[bug(1, "<p>Crash</p>"), bug(2, "Hang")], now);
+        assert!(changes.is_empty(), "nothing to compare against yet");
+        record(&mut snapshots, &mut changes, &[bug(1, "<p>Crash on save</p>")], now);
+        assert_eq!(snapshots.keys().collect::<Vec<_>>(), [&1]);
+        assert_eq!(changes[&1].segments.last(), Some(&(DiffTag::Added, " on save".to_string())));
+        record(&mut snapshots, &mut changes, &[bug(1, "<p>Crash on save</p>")], now + Duration::days(SHOW_DAYS + 1));
+        assert!(changes.is_empty(), "old changes expire");
+    }
+
+    #[test]
+    fn long_unchanged_runs_are_shortened() {
+        let text = (1..=40).map(|n| n.to_string()).collect::<Vec<_>>().join(" ");
+        let short = shorten(&text);
+        assert!(short.starts_with("1 2 ") && short.ends_with(" 39 40") && short.contains(" … "));
+        assert_eq!(shorten("a few words"), "a few words");
+    }
+}
@@ -0,0 +1,103 @@
+// Discussion analysis for hot bugs: escalating frustration and circular "cannot repro" threads
+use crate::azure_devops::{AzureDevOpsClient, Bug, Comment};
+use crate::bug_analysis::strip_html;
+use log::warn;
+use serde::Serialize;
+use std::collections::HashMap;
+
+const FRUSTRATION_MARKERS: &[&str] = &[
+    "still", "again", "any update", "asap", "urgent", "blocking", "blocker", "frustrat", "escalat", "unacceptable",
+    "when will", "ping", "???", "!!",
+];
+const NO_REPRO_MARKERS: &[&str] = &[
+    "cannot repro", "can't repro", "cant repro", "unable to repro", "not reproducible", "could not reproduce",
+    "couldn't reproduce", "no repro", "works for me",
+];
+const STILL_REPROS_MARKERS: &[&str] = &["still repro", "still happening", "still happens", "still see", "still broken", "happened again"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiscussionInsight {
+    pub bug_id: u64,
+    /// Frustration score per comment, oldest first.
+    pub timeline: Vec<u32>,
+    /// Later comments are noticeably more frustrated than earlier ones.
+    pub escalating: bool,
+    /// The thread bounces between "cannot repro" and "still happens".
+    pub circular_repro: bool,
+}
+
+impl DiscussionInsight {
+    /// More comments won't help; someone should set up a call.
+    pub fn needs_conversation(&self) -> bool {
+        self.escalating || self.circular_repro
+    }
+}
+
+fn frustration_score(text: &str) -> u32 {
+    FRUSTRATION_MARKERS.iter().map(|m| text.matches(m).count() as u32).sum()
+}
+
+#[derive(PartialEq)]
+enum ReproClaim {
+    NoRepro,
+    StillRepros,
+}
+
+fn repro_claim(text: &str) -> Option<ReproClaim> {
+    if STILL_REPROS_MARKERS.iter().any(|m| text.contains(m)) {
+        Some(ReproClaim::StillRepros)
+    } else if NO_REPRO_MARKERS.iter().any(|m| text.contains(m)) {
+        Some(ReproClaim::NoRepro)
+    } else {
+        None
+    }
+}
+
+/// Analyze a comment thread (oldest first).
+pub fn analyze_discussion(bug_id: u64, comments: &[Comment]) -> DiscussionInsight {
+    let texts: Vec<String> = comments.iter().map(|c| strip_html(&c.text).to_lowercase()).collect();
+    let timeline: Vec<u32> = texts.iter().map(|t| frustration_score(t)).collect();
+
+    let half = timeline.len() / 2;
+    let average = |scores: &[u32]| if scores.is_empty() { 0.0 } else { scores.iter().sum::<u32>() as f64 / scores.len() as f64 };
+    let (early, late) = (average(&timeline[..half]), average(&timeline[half..]));
+    let escalating = late >= 1.0 && late > early * 1.5;
+
+    let claims: Vec<ReproClaim> = texts.iter().filter_map(|t| repro_claim(t)).collect();
+    let flips = claims.windows(2).filter(|w| w[0] != w[1]).count();
+    let circular_repro = flips >= 2;
+
+    DiscussionInsight { bug_id, timeline, escalating, circular_repro }
+}
+
+/// Fetch comment threads for every bug that has comments, keyed by bug id.
+pub fn fetch_threads(client: &AzureDevOpsClient, bugs: &[Bug]) -> HashMap<u64, Vec<Comment>> {
+    bugs.iter()
+        .filter(|b| b.comment_count > 0)
+        .filter_map(|b| match client.fetch_comments(b.id) {
+            Ok(comments) => Some((b.id, comments)),
+            Err(e) => {
+                warn!("Failed to fetch comments for #{}: {}", b.id, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Analyze threads with at least `threshold` comments and return the ones that need a conversation.
+pub fn find_hot_discussions(threads: &HashMap<u64, Vec<Comment>>, threshold: u64) -> Vec<DiscussionInsight> {
+    let mut insights: Vec<DiscussionInsight> = threads
+        .iter()
+        .filter(|(_, comments)| comments.len() as u64 >= threshold)
+        .map(|(id, comments)| analyze_discussion(*id, comments))
+        .filter(|i| i.needs_conversation())
+        .collect();
+    insights.sort_by_key(|i| i.bug_id);
+    insights
+}
+
+/// Render a frustration timeline as a compact sparkline.
+pub fn sparkline(timeline: &[u32]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    timeline.iter().map(|s| BARS[(*s as usize).min(BARS.len() - 1)]).collect()
+}
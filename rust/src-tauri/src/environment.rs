@@ -0,0 +1,169 @@
+// Environment details (OS, build, browser, device) extracted from bug titles and descriptions
+use crate::stacktrace::text_lines;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// Longest labeled value kept (e.g. "Device: ..."), so a pasted paragraph doesn't become a field.
+const MAX_VALUE_CHARS: usize = 60;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Environment {
+    /// e.g. "Windows 11 23H2", "macOS 14.2", "Android 14".
+    pub os: Option<String>,
+    /// Windows build with optional revision, e.g. "22631.3007".
+    pub os_build: Option<String>,
+    /// e.g. "Edge 120.0.2210".
+    pub browser: Option<String>,
+    /// e.g. "Surface Pro 9".
+    pub device: Option<String>,
+}
+
+/// `Label: value` lines, the way bug templates usually ask for environment details.
+fn labeled() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?i)^\s*(os build|os version|operating system|os|build|browser|device|model|hardware)\s*[:=]\s*(.+)$").unwrap()
+    })
+}
+
+fn os_name() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"(?i)\b(Windows Server \d{4}(?: R2)?|Windows (?:11|10|8\.1|7)(?: (?:Home|Pro|Enterprise|Education|IoT|LTSC|S))?(?: \d{2}H\d)?|macOS(?: \d+(?:\.\d+)*)?|Ubuntu(?: \d+\.\d+)?|Debian(?: \d+)?|Fedora(?: \d+)?|iOS \d+(?:\.\d+)*|iPadOS \d+(?:\.\d+)*|Android \d+(?:\.\d+)*|ChromeOS)\b",
+        )
+        .unwrap()
+    })
+}
+
+/// "OS Build 22631.3007", "build 19045" or a full "10.0.22631.3007" version.
+fn os_build() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?i)(?:\bbuild[\s:#]*|\b10\.0\.)(\d{5}(?:\.\d+)?)\b").unwrap())
+}
+
+fn browser() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?i)\b(Microsoft |Google |Mozilla )?(Edge|Chrome|Firefox|Safari)\b(?:\s*/\s*|\s+(?:version\s+|v)?)?(\d+(?:\.\d+)*)?").unwrap()
+    })
+}
+
+fn device() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"(?i)\b(Surface (?:Pro|Laptop(?: Studio| Go)?|Book|Go|Studio)(?: \d+)?|iPhone(?: \d+)?(?: Pro(?: Max)?)?|iPad(?: Pro| Air| mini)?|Pixel \d+(?: Pro)?|Galaxy [A-Z]\d+|ThinkPad [A-Z]\d+|XPS \d+)\b",
+        )
+        .unwrap()
+    })
+}
+
+fn truncate(value: &str) -> String {
+    value.trim().chars().take(MAX_VALUE_CHARS).collect()
+}
+
+fn find_browser(text: &str) -> Option<String> {
+    browser().captures_iter(text).find_map(|c| {
+        let name = match c[2].to_lowercase().as_str() {
+            "edge" => "Edge",
+            "chrome" => "Chrome",
+            "firefox" => "Firefox",
+            _ => "Safari",
+        };
+        // "Edge" and "Safari" are ordinary words too; without a vendor or version they don't count.
+        let version = c.get(3).map(|v| v.as_str());
+        let unambiguous = c.get(1).is_some() || version.is_some() || matches!(name, "Chrome" | "Firefox");
+        unambiguous.then(|| version.map(|v| format!("{} {}", name, v)).unwrap_or_else(|| name.to_string()))
+    })
+}
+
+impl Environment {
+    /// Extract environment details from `text` (HTML or plain). Labeled lines ("OS: ...") win over
+    /// mentions in running text.
+    pub fn extract(text: &str) -> Self {
+        let mut env = Environment::default();
+        let lines = text_lines(text);
+        for line in &lines {
+            let Some(c) = labeled().captures(line) else { continue };
+            let value = &c[2];
+            match c[1].to_lowercase().as_str() {
+                "os build" | "build" => {
+                    env.os_build = env.os_build.take().or_else(|| os_build().captures(&format!("build {}", value)).map(|b| b[1].to_string()));
+                }
+                "os version" | "operating system" | "os" => {
+                    env.os = env.os.take().or_else(|| Some(os_name().find(value).map_or_else(|| truncate(value), |m| m.as_str().to_string())));
+                    env.os_build = env.os_build.take().or_else(|| os_build().captures(value).map(|b| b[1].to_string()));
+                }
+                "browser" => env.browser = env.browser.take().or_else(|| find_browser(value).or_else(|| Some(truncate(value)))),
+                _ => env.device = env.device.take().or_else(|| Some(truncate(value))),
+            }
+        }
+        let plain = lines.join("\n");
+        env.os = env.os.or_else(|| os_name().find(&plain).map(|m| m.as_str().to_string()));
+        env.os_build = env.os_build.or_else(|| os_build().captures(&plain).map(|c| c[1].to_string()));
+        env.browser = env.browser.or_else(|| find_browser(&plain));
+        env.device = env.device.or_else(|| device().find(&plain).map(|m| m.as_str().to_string()));
+        env
+    }
+
+    /// Nothing that identifies where the bug happened (browser alone doesn't count).
+    pub fn is_missing(&self) -> bool {
+        self.os.is_none() && self.os_build.is_none() && self.device.is_none()
+    }
+
+    /// Build number without the revision ("22631.3007" → "22631"), for grouping.
+    pub fn build_number(&self) -> Option<&str> {
+        self.os_build.as_deref().map(|b| b.split('.').next().unwrap_or(b))
+    }
+
+    /// One-line summary, e.g. "Windows 11 · build 22631.3007 · Surface Pro 9".
+    pub fn summary(&self) -> String {
+        let mut parts: Vec<String> = Vec::new();
+        parts.extend(self.os.clone());
+        parts.extend(self.os_build.as_ref().map(|b| format!("build {}", b)));
+        parts.extend(self.browser.clone());
+        parts.extend(self.device.clone());
+        parts.join(" · ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labeled_lines_are_extracted() {
+        let env = Environment::extract("<p>OS: Windows 11 Pro 23H2</p><p>OS Build: 22631.3007</p><p>Browser: Microsoft Edge 120.0.2210</p><p>Device: Contoso Tablet X</p>");
+        assert_eq!(env.os.as_deref(), Some("Windows 11 Pro 23H2"));
+        assert_eq!(env.os_build.as_deref(), Some("22631.3007"));
+        assert_eq!(env.browser.as_deref(), Some("Edge 120.0.2210"));
+        assert_eq!(env.device.as_deref(), Some("Contoso Tablet X"));
+        assert_eq!(env.build_number(), Some("22631"));
+        assert_eq!(env.summary(), "Windows 11 Pro 23H2 · build 22631.3007 · Edge 120.0.2210 · Contoso Tablet X");
+    }
+
+    #[test]
+    fn running_text_mentions_are_found() {
+        let env = Environment::extract("Happens on my Surface Pro 9 with version 10.0.19045.3803 in Chrome/119.0");
+        assert_eq!(env.device.as_deref(), Some("Surface Pro 9"));
+        assert_eq!(env.os_build.as_deref(), Some("19045.3803"));
+        assert_eq!(env.browser.as_deref(), Some("Chrome 119.0"));
+        assert!(env.os.is_none());
+    }
+
+    #[test]
+    fn browser_names_that_are_ordinary_words_need_a_version() {
+        assert!(Environment::extract("Move the window to the edge of the screen").browser.is_none());
+        assert_eq!(Environment::extract("Broken in Safari 17.2").browser.as_deref(), Some("Safari 17.2"));
+    }
+
+    #[test]
+    fn a_browser_alone_leaves_the_environment_missing() {
+        assert!(Environment::extract("Firefox shows a blank page").is_missing());
+        assert!(!Environment::extract("Only on Android 14").is_missing());
+        assert!(Environment::default().summary().is_empty());
+    }
+}
@@ -0,0 +1,157 @@
+// Typed errors, so the frontend can tell a bad token from a network outage or a missing project and
+// suggest the right fix; layers that still use `String` convert both ways
+use reqwest::StatusCode;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::{Serialize, Serializer};
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum BuggerError {
+    /// The token was rejected or lacks a scope.
+    #[error("{0}")]
+    Auth(String),
+    /// The server couldn't be reached or the connection broke.
+    #[error("{0}")]
+    Network(String),
+    #[error("{0}")]
+    RateLimit(String),
+    /// The response wasn't what the API should send.
+    #[error("{0}")]
+    Parse(String),
+    /// Settings are missing or point at something that doesn't exist, like the project.
+    #[error("{0}")]
+    Config(String),
+    /// Any other error status from the API.
+    #[error("{message}")]
+    Api { status: u16, message: String },
+    #[error("{0}")]
+    Other(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    Auth,
+    Network,
+    RateLimit,
+    Parse,
+    Config,
+    Api,
+    Other,
+}
+
+/// What the frontend receives.
+#[derive(Serialize, JsonSchema)]
+#[serde(rename = "BuggerError")]
+struct Payload {
+    kind: ErrorKind,
+    message: String,
+    /// What to do about it, if there's something to suggest.
+    hint: Option<&'static str>,
+}
+
+impl BuggerError {
+    /// The error for an unsuccessful HTTP response from `service` (e.g. "Azure DevOps").
+    pub fn from_status(service: &str, status: StatusCode, body: &str) -> Self {
+        let message = format!("{} API error ({}): {}", service, status, body);
+        match status.as_u16() {
+            // Azure DevOps answers a bad PAT with 203 and a sign-in page.
+            203 | 401 | 403 => BuggerError::Auth(message),
+            429 => BuggerError::RateLimit(message),
+            // TF200016: the project doesn't exist.
+            404 if body.contains("TF200016") => BuggerError::Config(message),
+            code => BuggerError::Api { status: code, message },
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            BuggerError::Auth(_) => ErrorKind::Auth,
+            BuggerError::Network(_) => ErrorKind::Network,
+            BuggerError::RateLimit(_) => ErrorKind::RateLimit,
+            BuggerError::Parse(_) => ErrorKind::Parse,
+            BuggerError::Config(_) => ErrorKind::Config,
+            BuggerError::Api { .. } => ErrorKind::Api,
+            BuggerError::Other(_) => ErrorKind::Other,
+        }
+    }
+
+    /// The same kind of error, with `context` in front of the message.
+    pub fn context(self, context: &str) -> Self {
+        let with = |message: String| format!("{}: {}", context, message);
+        match self {
+            BuggerError::Auth(m) => BuggerError::Auth(with(m)),
+            BuggerError::Network(m) => BuggerError::Network(with(m)),
+            BuggerError::RateLimit(m) => BuggerError::RateLimit(with(m)),
+            BuggerError::Parse(m) => BuggerError::Parse(with(m)),
+            BuggerError::Config(m) => BuggerError::Config(with(m)),
+            BuggerError::Api { status, message } => BuggerError::Api { status, message: with(message) },
+            BuggerError::Other(m) => BuggerError::Other(with(m)),
+        }
+    }
+
+    pub fn hint(&self) -> Option<&'static str> {
+        match self.kind() {
+            ErrorKind::Auth => Some("Check that the access token is valid, hasn't expired and has the Work Items (Read & write) scope."),
+            ErrorKind::Network => Some("Check the network connection, VPN or proxy, and the organization URL."),
+            ErrorKind::RateLimit => Some("The server is throttling requests; wait a minute and try again."),
+            ErrorKind::Parse => Some("The server sent an unexpected response; BUGGER_HTTP_TRACE=1 records it for a closer look."),
+            ErrorKind::Config => Some("Open Settings and check the organization, project and the other required values."),
+            ErrorKind::Api | ErrorKind::Other => None,
+        }
+    }
+}
+
+/// Layers that report errors as text (storage, locks) have nothing more specific to say.
+impl From<String> for BuggerError {
+    fn from(message: String) -> Self {
+        BuggerError::Other(message)
+    }
+}
+
+impl From<&str> for BuggerError {
+    fn from(message: &str) -> Self {
+        BuggerError::Other(message.to_string())
+    }
+}
+
+/// `anyhow` errors come from loading configuration, so they're missing or invalid settings unless
+/// they wrap an I/O, HTTP or JSON error.
+impl From<anyhow::Error> for BuggerError {
+    fn from(e: anyhow::Error) -> Self {
+        if let Some(e) = e.downcast_ref::<reqwest::Error>() {
+            return BuggerError::Network(e.to_string());
+        }
+        if let Some(e) = e.downcast_ref::<serde_json::Error>() {
+            return BuggerError::Parse(e.to_string());
+        }
+        if e.downcast_ref::<std::io::Error>().is_some() {
+            return BuggerError::Other(e.to_string());
+        }
+        BuggerError::Config(e.to_string())
+    }
+}
+
+impl From<BuggerError> for String {
+    fn from(e: BuggerError) -> Self {
+        e.to_string()
+    }
+}
+
+impl Serialize for BuggerError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Payload { kind: self.kind(), message: self.to_string(), hint: self.hint() }.serialize(serializer)
+    }
+}
+
+impl JsonSchema for BuggerError {
+    fn schema_name() -> String {
+        "BuggerError".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        Payload::json_schema(gen)
+    }
+}
@@ -0,0 +1,183 @@
+// Knowledge base of Windows bugcheck codes, NTSTATUS/exception codes and HRESULTs found in bug text
+use crate::bug_analysis::strip_html;
+use log::warn;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::env;
+use std::sync::OnceLock;
+
+/// Built-in entries: (code, symbolic name, explanation).
+const BUILT_IN: [(u32, &str, &str); 33] = [
+    // Bugchecks (stop codes)
+    (0x0A, "IRQL_NOT_LESS_OR_EQUAL", "Kernel code touched pageable or invalid memory at raised IRQL; usually a driver bug"),
+    (0x19, "BAD_POOL_HEADER", "Pool header corrupted; typically a driver overrunning a pool allocation"),
+    (0x1A, "MEMORY_MANAGEMENT", "Memory manager detected corruption; faulty RAM or a driver corrupting memory"),
+    (0x1E, "KMODE_EXCEPTION_NOT_HANDLED", "Kernel-mode code raised an exception nobody handled"),
+    (0x3B, "SYSTEM_SERVICE_EXCEPTION", "Exception while executing a system call; check the faulting driver in the stack"),
+    (0x50, "PAGE_FAULT_IN_NONPAGED_AREA", "Invalid memory referenced in kernel mode; often a freed or bad pointer in a driver"),
+    (0x7B, "INACCESSIBLE_BOOT_DEVICE", "Boot volume could not be mounted; storage driver or disk configuration problem"),
+    (0x7E, "SYSTEM_THREAD_EXCEPTION_NOT_HANDLED", "A system thread raised an unhandled exception; the first parameter is the exception code"),
+    (0x9F, "DRIVER_POWER_STATE_FAILURE", "A driver did not complete a power IRP in time (sleep/resume hang)"),
+    (0xC2, "BAD_POOL_CALLER", "Invalid pool request, e.g. freeing memory twice or at the wrong IRQL"),
+    (0xD1, "DRIVER_IRQL_NOT_LESS_OR_EQUAL", "A driver accessed pageable or invalid memory at raised IRQL"),
+    (0xEF, "CRITICAL_PROCESS_DIED", "A critical system process (e.g. csrss, wininit) terminated"),
+    (0x124, "WHEA_UNCORRECTABLE_ERROR", "Hardware reported a fatal error (CPU, memory, bus); rarely a software bug"),
+    (0x133, "DPC_WATCHDOG_VIOLATION", "A DPC or the system at DISPATCH_LEVEL ran too long; usually a driver spinning"),
+    (0x139, "KERNEL_SECURITY_CHECK_FAILURE", "Kernel detected corruption of a critical data structure (e.g. a LIST_ENTRY)"),
+    // NTSTATUS / exception codes
+    (0x80000003, "STATUS_BREAKPOINT", "A breakpoint or assertion was hit outside a debugger"),
+    (0xC0000005, "STATUS_ACCESS_VIOLATION", "Read or write of an invalid address; null or dangling pointer"),
+    (0xC000001D, "STATUS_ILLEGAL_INSTRUCTION", "CPU executed an invalid instruction; corrupted code or unsupported CPU feature"),
+    (0xC0000094, "STATUS_INTEGER_DIVIDE_BY_ZERO", "Integer division by zero"),
+    (0xC00000FD, "STATUS_STACK_OVERFLOW", "Thread stack exhausted; usually unbounded recursion"),
+    (0xC0000135, "STATUS_DLL_NOT_FOUND", "A required DLL could not be found at process start"),
+    (0xC0000374, "STATUS_HEAP_CORRUPTION", "Heap manager detected corruption; use-after-free or buffer overrun"),
+    (0xC0000409, "STATUS_STACK_BUFFER_OVERRUN", "Fail-fast: stack cookie check failed or __fastfail was called"),
+    (0xE0434352, "CLR_EXCEPTION", "Unhandled .NET exception; the managed stack has the details"),
+    // HRESULTs
+    (0x80004001, "E_NOTIMPL", "Not implemented"),
+    (0x80004002, "E_NOINTERFACE", "Requested COM interface is not supported"),
+    (0x80004005, "E_FAIL", "Unspecified failure"),
+    (0x8000FFFF, "E_UNEXPECTED", "Catastrophic or unexpected failure"),
+    (0x80070002, "ERROR_FILE_NOT_FOUND", "The system cannot find the file specified"),
+    (0x80070005, "E_ACCESSDENIED", "Access denied; check permissions or elevation"),
+    (0x8007000E, "E_OUTOFMEMORY", "Not enough memory to complete the operation"),
+    (0x80070057, "E_INVALIDARG", "One or more arguments are invalid"),
+    (0x800705B4, "ERROR_TIMEOUT", "The operation timed out"),
+];
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct KnownCode {
+    pub code: u32,
+    pub name: String,
+    pub explanation: String,
+}
+
+impl KnownCode {
+    /// Bugchecks are conventionally written short ("0x3B"), everything else as 8 hex digits.
+    pub fn display_code(&self) -> String {
+        if self.code <= 0xFFFF {
+            format!("0x{:X}", self.code)
+        } else {
+            format!("0x{:08X}", self.code)
+        }
+    }
+}
+
+/// Code lookup: built-in entries plus BUGGER_ERROR_CODES (`0xCODE=NAME|Explanation;;...`, the name is
+/// optional), which add codes or override built-in explanations.
+#[derive(Debug, Clone)]
+pub struct ErrorCodes {
+    by_code: HashMap<u32, KnownCode>,
+}
+
+fn parse_code(text: &str) -> Option<u32> {
+    let hex = text.trim().strip_prefix("0x").or_else(|| text.trim().strip_prefix("0X"))?;
+    u32::from_str_radix(hex, 16).ok()
+}
+
+/// Full-width codes, e.g. 0xC0000005 or 0x0000003B.
+fn full_code() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\b0[xX]([0-9a-fA-F]{8})\b").unwrap())
+}
+
+/// Short bugcheck codes only count next to a bugcheck keyword ("BugCheck 3B", "stop code 0xD1").
+fn bugcheck_code() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?i)\b(?:bug\s?check|stop code)(?:\s+code)?[\s:]+(?:0x)?([0-9a-f]{1,8})\b").unwrap())
+}
+
+fn symbolic_name() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\b[A-Z][A-Z0-9]*_[A-Z0-9_]+\b").unwrap())
+}
+
+impl ErrorCodes {
+    pub fn from_env() -> Self {
+        let mut by_code: HashMap<u32, KnownCode> = BUILT_IN
+            .iter()
+            .map(|(code, name, explanation)| (*code, KnownCode { code: *code, name: name.to_string(), explanation: explanation.to_string() }))
+            .collect();
+        for entry in env::var("BUGGER_ERROR_CODES").unwrap_or_default().split(";;").filter(|e| !e.trim().is_empty()) {
+            let Some((code, rest)) = entry.split_once('=') else {
+                warn!("Ignoring error code entry without '=': {}", entry);
+                continue;
+            };
+            let Some(code) = parse_code(code) else {
+                warn!("Ignoring error code entry with invalid hex code: {}", entry);
+                continue;
+            };
+            let (name, explanation) = match rest.split_once('|') {
+                Some((name, explanation)) => (name.trim().to_string(), explanation.trim().to_string()),
+                None => (by_code.get(&code).map(|k| k.name.clone()).unwrap_or_default(), rest.trim().to_string()),
+            };
+            by_code.insert(code, KnownCode { code, name, explanation });
+        }
+        ErrorCodes { by_code }
+    }
+
+    /// Known codes mentioned in `text` (HTML or plain), by value or symbolic name, in order of first mention.
+    pub fn explain(&self, text: &str) -> Vec<KnownCode> {
+        let text = strip_html(text);
+        let mut mentions: Vec<(usize, u32)> = Vec::new();
+        for c in full_code().captures_iter(&text).chain(bugcheck_code().captures_iter(&text)) {
+            if let Ok(code) = u32::from_str_radix(&c[1], 16) {
+                mentions.push((c.get(0).map_or(0, |m| m.start()), code));
+            }
+        }
+        for m in symbolic_name().find_iter(&text) {
+            if let Some(known) = self.by_code.values().find(|k| k.name == m.as_str()) {
+                mentions.push((m.start(), known.code));
+            }
+        }
+        mentions.sort();
+        let mut found: Vec<KnownCode> = Vec::new();
+        for (_, code) in mentions {
+            if let Some(known) = self.by_code.get(&code) {
+                if !found.iter().any(|k| k.code == code) {
+                    found.push(known.clone());
+                }
+            }
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(found: Vec<KnownCode>) -> Vec<String> {
+        found.iter().map(|k| k.display_code()).collect()
+    }
+
+    #[test]
+    fn codes_are_explained_in_order_of_first_mention() {
+        let errors = ErrorCodes::from_env();
+        let text = "<p>Failed with 0x80070005, then crashed: 0xc0000005.</p><p>STATUS_ACCESS_VIOLATION again</p>";
+        assert_eq!(codes(errors.explain(text)), ["0x80070005", "0xC0000005"]);
+    }
+
+    #[test]
+    fn short_bugchecks_need_a_bugcheck_keyword() {
+        let errors = ErrorCodes::from_env();
+        assert_eq!(codes(errors.explain("Stop code: 0xD1 after resume")), ["0xD1"]);
+        assert_eq!(codes(errors.explain("BugCheck D1, {0, 2, 0, 0}")), ["0xD1"]);
+        assert!(errors.explain("Section D1 of the spec").is_empty());
+    }
+
+    #[test]
+    fn symbolic_names_are_recognized() {
+        assert_eq!(codes(ErrorCodes::from_env().explain("Hit DRIVER_IRQL_NOT_LESS_OR_EQUAL twice")), ["0xD1"]);
+    }
+
+    #[test]
+    fn only_prefixed_hex_codes_parse() {
+        assert_eq!(parse_code(" 0XdeadBEEF "), Some(0xDEADBEEF));
+        assert_eq!(parse_code("C0000005"), None);
+        assert_eq!(parse_code("0xZZ"), None);
+    }
+}
@@ -0,0 +1,320 @@
+// Opt-in escalation chain: bugs sitting too long in a state notify someone by email or get a tag,
+// one step at a time as the days add up
+use crate::audit::{self, add_tag_reversible, WriteAction, WriteOutcome};
+use crate::azure_devops::{AzureDevOpsClient, Bug};
+use crate::outputs::send_email;
+use crate::policy::PolicyMode;
+use crate::roles::Role;
+use crate::error::BuggerError;
+use crate::{storage, AppConfig, AppState};
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::env;
+use tauri::{AppHandle, State};
+
+/// When each escalation step was taken, so steps run once per stay in a state.
+const ESCALATION_STORE: &str = "escalations";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EscalationStep {
+    /// Email this address.
+    Notify(String),
+    AddTag(String),
+}
+
+impl EscalationStep {
+    fn describe(&self) -> String {
+        match self {
+            EscalationStep::Notify(who) => format!("notify {}", who),
+            EscalationStep::AddTag(tag) => format!("tag '{}'", tag),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EscalationRule {
+    pub state: String,
+    pub days: i64,
+    pub steps: Vec<EscalationStep>,
+}
+
+impl EscalationRule {
+    pub fn label(&self) -> String {
+        format!("{} > {} days", self.state, self.days)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EscalationPolicy {
+    pub mode: PolicyMode,
+    pub rules: Vec<EscalationRule>,
+}
+
+/// One rule, e.g. "Active>10=notify:lead@contoso.com,tag:escalated".
+fn parse_rule(entry: &str) -> Option<EscalationRule> {
+    let (condition, steps) = entry.split_once('=')?;
+    let (state, days) = condition.rsplit_once('>')?;
+    let steps: Vec<EscalationStep> = steps
+        .split(',')
+        .map(str::trim)
+        .filter_map(|step| match step.split_once(':') {
+            Some(("notify", who)) if !who.trim().is_empty() => Some(EscalationStep::Notify(who.trim().to_string())),
+            Some(("tag", tag)) if !tag.trim().is_empty() => Some(EscalationStep::AddTag(tag.trim().to_string())),
+            _ => {
+                warn!("Ignoring escalation step '{}', expected notify:<email> or tag:<tag>", step);
+                None
+            }
+        })
+        .collect();
+    let days = days.trim().parse().ok()?;
+    let state = state.trim();
+    (!state.is_empty() && !steps.is_empty()).then(|| EscalationRule { state: state.to_string(), days, steps })
+}
+
+impl EscalationPolicy {
+    /// Load from BUGGER_ESCALATION (off | dry-run | on) and BUGGER_ESCALATION_RULES (rules separated
+    /// by ";;"). Off unless explicitly enabled.
+    pub fn from_env() -> Self {
+        let mode = match env::var("BUGGER_ESCALATION").unwrap_or_default().trim().to_lowercase().as_str() {
+            "on" | "true" => PolicyMode::On,
+            "dry-run" | "dryrun" => PolicyMode::DryRun,
+            _ => PolicyMode::Off,
+        };
+        let rules = env::var("BUGGER_ESCALATION_RULES")
+            .unwrap_or_default()
+            .split(";;")
+            .filter(|e| !e.trim().is_empty())
+            .filter_map(|entry| {
+                let rule = parse_rule(entry);
+                if rule.is_none() {
+                    warn!("Ignoring escalation rule '{}', expected <state>><days>=<step>,...", entry);
+                }
+                rule
+            })
+            .collect();
+        EscalationPolicy { mode, rules }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct EscalationAction {
+    pub bug_id: u64,
+    pub rule: String,
+    pub step: String,
+    pub dry_run: bool,
+    pub error: Option<String>,
+}
+
+/// A bug past at least one escalation rule, for the report.
+#[derive(Debug, Clone, Serialize)]
+pub struct Escalation {
+    pub bug_id: u64,
+    pub title: String,
+    pub days_in_state: i64,
+    /// The furthest rule reached.
+    pub rule: String,
+    /// When that rule's steps were taken; `None` while dry-running or not run yet.
+    pub escalated_at: Option<DateTime<Utc>>,
+}
+
+fn days_in_state(bug: &Bug, now: DateTime<Utc>) -> Option<i64> {
+    let entered = bug.state_changed_date.as_deref().or(bug.created_date.as_deref())?;
+    DateTime::parse_from_rfc3339(entered).ok().map(|d| (now - d.with_timezone(&Utc)).num_days())
+}
+
+/// Identifies a rule for one stay of a bug in a state, so leaving and re-entering escalates again.
+fn step_key(bug: &Bug, rule: &EscalationRule) -> String {
+    format!("{}|{}|{}", bug.id, rule.label(), bug.state_changed_date.as_deref().unwrap_or(""))
+}
+
+/// Rules `bug` is past, shortest first.
+fn due_rules<'a>(bug: &Bug, policy: &'a EscalationPolicy, now: DateTime<Utc>) -> Vec<&'a EscalationRule> {
+    let Some(days) = days_in_state(bug, now) else {
+        return Vec::new();
+    };
+    let mut due: Vec<&EscalationRule> = policy.rules.iter().filter(|r| r.state.eq_ignore_ascii_case(&bug.state) && days > r.days).collect();
+    due.sort_by_key(|r| r.days);
+    due
+}
+
+fn apply_step(client: &AzureDevOpsClient, bug: &Bug, rule: &EscalationRule, step: &EscalationStep, now: DateTime<Utc>) -> Result<WriteOutcome, BuggerError> {
+    match step {
+        EscalationStep::Notify(who) => {
+            let url = client.config.bug_url(bug.id);
+            let html = format!(
+                "<p><a href=\"{}\">#{} {}</a> has been {} for {} days (escalation rule: {}).</p>",
+                url,
+                bug.id,
+                html_escape::encode_text(&bug.title),
+                html_escape::encode_text(&bug.state),
+                days_in_state(bug, now).unwrap_or(0),
+                html_escape::encode_text(&rule.label())
+            );
+            send_email(&client.config, std::slice::from_ref(who), &format!("Escalation: #{} {}", bug.id, bug.title), html)?;
+            Ok(WriteOutcome { response: Value::Null, undo: None })
+        }
+        EscalationStep::AddTag(tag) => add_tag_reversible(client, bug.id, tag),
+    }
+}
+
+/// Take the due escalation steps not taken yet (or, in dry-run, only log them). Every step is
+/// written to the audit log, repeated dry runs only once; tags already on the bug are skipped.
+pub fn run_escalations(app: &AppHandle, client: &AzureDevOpsClient, bugs: &[Bug], policy: &EscalationPolicy, dry_run: bool) -> Vec<EscalationAction> {
+    let mut taken: HashMap<String, DateTime<Utc>> = storage::load_json(app, ESCALATION_STORE).unwrap_or_else(|e| {
+        warn!("Ignoring escalation history: {}", e);
+        HashMap::new()
+    });
+    let now = Utc::now();
+    // Emails wait for working hours; their rule isn't taken until they're sent.
+    let quiet = client.config.quiet_hours.as_ref().is_some_and(|q| !q.is_working_time(now));
+    let mut actions = Vec::new();
+    for bug in bugs {
+        let pending: Vec<(&EscalationRule, Vec<&EscalationStep>)> = due_rules(bug, policy, now)
+            .into_iter()
+            .filter(|rule| !taken.contains_key(&step_key(bug, rule)))
+            .map(|rule| {
+                let steps = rule.steps.iter().filter(|step| !matches!(step, EscalationStep::AddTag(tag) if bug.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))).collect();
+                (rule, steps)
+            })
+            .collect();
+        let detail = |rule: &EscalationRule, step: &EscalationStep| format!("{}: {}", rule.label(), step.describe());
+        // Previews are compared per bug, all of its pending steps at once.
+        let preview = pending.iter().flat_map(|(rule, steps)| steps.iter().map(|step| detail(rule, step))).collect::<Vec<_>>().join("; ");
+        if dry_run && !preview.is_empty() && !audit::is_new_preview(app, bug.id, WriteAction::Escalate, &preview) {
+            for (rule, steps) in &pending {
+                actions.extend(steps.iter().map(|step| EscalationAction { bug_id: bug.id, rule: rule.label(), step: step.describe(), dry_run, error: None }));
+            }
+            continue;
+        }
+        for (rule, steps) in pending {
+            let mut failed = false;
+            let mut held = false;
+            for step in steps {
+                if quiet && !dry_run && matches!(step, EscalationStep::Notify(_)) {
+                    info!("Holding escalation email for #{} until working hours", bug.id);
+                    held = true;
+                    continue;
+                }
+                let detail = detail(rule, step);
+                let result = audit::perform(app, &client.config.user_email, bug.id, WriteAction::Escalate, detail, dry_run, || apply_step(client, bug, rule, step, now));
+                info!("Escalate #{} ({}, dry run: {})", bug.id, step.describe(), dry_run);
+                failed |= result.is_err();
+                actions.push(EscalationAction { bug_id: bug.id, rule: rule.label(), step: step.describe(), dry_run, error: result.err().map(String::from) });
+            }
+            // Failed and held steps are retried on the next run.
+            if !dry_run && !failed && !held {
+                taken.insert(step_key(bug, rule), now);
+            }
+        }
+    }
+    if !dry_run {
+        // Merged with what teammates escalated meanwhile; only bugs still in one of the escalated
+        // states are remembered.
+        let saved = storage::update_json(app, ESCALATION_STORE, |saved: &mut HashMap<String, DateTime<Utc>>| {
+            saved.extend(taken);
+            saved.retain(|key, _| bugs.iter().any(|b| policy.rules.iter().any(|r| step_key(b, r) == *key)));
+        });
+        if let Err(e) = saved {
+            warn!("Failed to save escalation history: {}", e);
+        }
+    }
+    actions
+}
+
+/// Bugs past an escalation rule, longest in their state first.
+pub fn escalated(app: &AppHandle, bugs: &[Bug], policy: &EscalationPolicy, now: DateTime<Utc>) -> Vec<Escalation> {
+    if policy.mode == PolicyMode::Off {
+        return Vec::new();
+    }
+    let taken: HashMap<String, DateTime<Utc>> = storage::load_json(app, ESCALATION_STORE).unwrap_or_default();
+    let mut found: Vec<Escalation> = bugs
+        .iter()
+        .filter_map(|bug| {
+            let rule = *due_rules(bug, policy, now).last()?;
+            Some(Escalation {
+                bug_id: bug.id,
+                title: bug.title.clone(),
+                days_in_state: days_in_state(bug, now)?,
+                rule: rule.label(),
+                escalated_at: taken.get(&step_key(bug, rule)).copied(),
+            })
+        })
+        .collect();
+    found.sort_by(|a, b| b.days_in_state.cmp(&a.days_in_state).then(a.bug_id.cmp(&b.bug_id)));
+    found
+}
+
+pub fn section_html(escalations: &[Escalation], bug_url: impl Fn(u64) -> String) -> String {
+    let mut html = String::from("<h2>🚨 Escalations</h2><ul>");
+    for e in escalations {
+        let when = match e.escalated_at {
+            Some(at) => format!("escalated {}", at.with_timezone(&chrono::Local).format("%Y-%m-%d")),
+            None => "not escalated yet".to_string(),
+        };
+        html.push_str(&format!(
+            "<li><a href=\"{}\" target=\"_blank\">#{}</a> {} — {} day(s) in state ({}), {}</li>",
+            bug_url(e.bug_id),
+            e.bug_id,
+            html_escape::encode_text(&e.title),
+            e.days_in_state,
+            html_escape::encode_text(&e.rule),
+            when
+        ));
+    }
+    html.push_str("</ul>");
+    html
+}
+
+#[tauri::command]
+/// Runs the escalation rules now. `dry_run` defaults to the configured mode; running while
+/// escalation is off is always a dry run.
+pub fn run_escalation_policy(app: AppHandle, state: State<AppState>, dry_run: Option<bool>) -> Result<Vec<EscalationAction>, BuggerError> {
+    let config = AppConfig::from_env()?;
+    config.provider.require_azure_devops("The escalation policy")?;
+    let policy = config.escalation.clone();
+    let dry_run = policy.mode != PolicyMode::On || dry_run.unwrap_or(false);
+    if !dry_run {
+        config.roles.check(&config.user_email, Role::Admin, "running the escalation policy")?;
+    }
+    let bugs = state.bugs_or_fetch()?;
+    Ok(run_escalations(&app, &AzureDevOpsClient::new(config), &bugs, &policy, dry_run))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn rules_parse_steps_and_skip_bad_ones() {
+        let rule = parse_rule("Active>10=notify:lead@contoso.com, tag:escalated, shout:loud").unwrap();
+        assert_eq!(rule.state, "Active");
+        assert_eq!(rule.days, 10);
+        assert_eq!(rule.steps, vec![EscalationStep::Notify("lead@contoso.com".to_string()), EscalationStep::AddTag("escalated".to_string())]);
+        assert!(parse_rule("Active>ten=tag:x").is_none());
+        assert!(parse_rule("Active>10=shout:loud").is_none());
+        assert!(parse_rule(">10=tag:x").is_none());
+    }
+
+    #[test]
+    fn due_rules_are_those_past_their_days_in_the_bugs_state() {
+        let now = Utc::now();
+        let policy = EscalationPolicy {
+            mode: PolicyMode::On,
+            rules: vec![parse_rule("active>10=tag:late").unwrap(), parse_rule("Active>3=tag:slow").unwrap(), parse_rule("New>1=tag:new").unwrap()],
+        };
+        let mut bug = Bug::sample(1, "Stuck");
+        bug.state = "Active".to_string();
+        bug.state_changed_date = Some((now - Duration::days(5)).to_rfc3339());
+        let labels = |bug: &Bug| due_rules(bug, &policy, now).iter().map(|r| r.label()).collect::<Vec<_>>();
+        assert_eq!(labels(&bug), vec!["Active > 3 days"]);
+        bug.state_changed_date = Some((now - Duration::days(12)).to_rfc3339());
+        assert_eq!(labels(&bug), vec!["Active > 3 days", "active > 10 days"]);
+        bug.state_changed_date = None;
+        assert!(labels(&bug).is_empty());
+    }
+}
@@ -0,0 +1,103 @@
+// Fine-grained update events pushed to the frontend instead of a full HTML report on every refresh
+use crate::azure_devops::Bug;
+use crate::bug_analysis::{categorize_bug, BugCategory};
+use crate::refresh::diff_bugs;
+use crate::AppState;
+use log::warn;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use tauri::ipc::Channel;
+use tauri::State;
+
+/// Bumped whenever the shape of `BugEvent` changes, so the frontend can detect incompatible payloads.
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BugEvent {
+    Added { bug: Bug, category: BugCategory },
+    Updated { bug: Bug },
+    Removed { id: u64 },
+    CategoryChanged { id: u64, from: BugCategory, to: BugCategory },
+}
+
+/// One batch of events from a single fetch. `sequence` increases by one per batch; a gap means the
+/// subscriber missed updates and should resubscribe for a fresh snapshot.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct UpdateBatch {
+    pub version: u32,
+    pub sequence: u64,
+    /// True for the initial snapshot sent on subscribe.
+    pub snapshot: bool,
+    pub events: Vec<BugEvent>,
+}
+
+/// Events turning `previous` into `current`.
+pub fn events_between(previous: &[Bug], current: &[Bug]) -> Vec<BugEvent> {
+    let diff = diff_bugs(previous, current);
+    let old: HashMap<u64, &Bug> = previous.iter().map(|b| (b.id, b)).collect();
+    let mut events: Vec<BugEvent> = diff.added.into_iter().map(|bug| BugEvent::Added { category: categorize_bug(&bug), bug }).collect();
+    for bug in diff.updated {
+        let (from, to) = (old.get(&bug.id).map(|b| categorize_bug(b)), categorize_bug(&bug));
+        let id = bug.id;
+        events.push(BugEvent::Updated { bug });
+        if let Some(from) = from.filter(|f| *f != to) {
+            events.push(BugEvent::CategoryChanged { id, from, to });
+        }
+    }
+    events.extend(diff.removed.into_iter().map(|id| BugEvent::Removed { id }));
+    events
+}
+
+/// Send the changes between two fetches to every subscriber, dropping subscribers that have gone away.
+pub fn publish(state: &AppState, previous: &[Bug], current: &[Bug]) {
+    let events = events_between(previous, current);
+    if events.is_empty() {
+        return;
+    }
+    let Ok(mut subscribers) = state.subscribers.lock() else {
+        return;
+    };
+    let sequence = state.update_sequence.fetch_add(1, Ordering::SeqCst) + 1;
+    let update = UpdateBatch { version: EVENT_SCHEMA_VERSION, sequence, snapshot: false, events };
+    subscribers.retain(|channel| match channel.send(update.clone()) {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("Dropping update subscriber: {}", e);
+            false
+        }
+    });
+}
+
+#[tauri::command]
+/// Subscribes `on_update` to bug events. The current bug list is sent first as a snapshot of `added` events.
+pub fn subscribe_updates(state: State<AppState>, on_update: Channel<UpdateBatch>) -> Result<(), String> {
+    let bugs = state.bugs.lock().map_err(|e| e.to_string())?.clone();
+    let snapshot = UpdateBatch {
+        version: EVENT_SCHEMA_VERSION,
+        sequence: state.update_sequence.load(Ordering::SeqCst),
+        snapshot: true,
+        events: events_between(&[], &bugs),
+    };
+    on_update.send(snapshot).map_err(|e| e.to_string())?;
+    state.subscribers.lock().map_err(|e| e.to_string())?.push(on_update);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_cover_added_updated_recategorized_and_removed_bugs() {
+        let previous = [Bug::sample(1, "Button is misaligned"), Bug::sample(2, "Gone soon")];
+        let current = [Bug::sample(1, "App crash on startup"), Bug::sample(3, "Brand new")];
+        let events = events_between(&previous, &current);
+        let kinds: Vec<String> = events.iter().map(|e| serde_json::to_value(e).unwrap()["type"].as_str().unwrap().to_string()).collect();
+        assert_eq!(kinds, ["added", "updated", "category_changed", "removed"]);
+        assert!(matches!(events[3], BugEvent::Removed { id: 2 }));
+        assert!(events_between(&current, &current).is_empty());
+    }
+}
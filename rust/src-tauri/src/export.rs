@@ -0,0 +1,182 @@
+// Report export: standalone HTML files with optional confidentiality banner and watermark, plus CSV/JSON bug lists
+use crate::accomplishments;
+use crate::anonymize::Anonymizer;
+use crate::azure_devops::Bug;
+use crate::bug_analysis::categorize_bug;
+use crate::category_style::CategoryStyles;
+use crate::{team, AppConfig, AppState};
+use chrono::Utc;
+use std::env;
+use tauri::{AppHandle, State};
+
+/// App stylesheet, inlined so exported reports look the same outside the app.
+const REPORT_CSS: &str = include_str!("../../src/styles.css");
+
+/// Stamp applied to exported reports (BUGGER_CONFIDENTIALITY_NOTICE, BUGGER_WATERMARK, BUGGER_PROFILE).
+#[derive(Debug, Clone, Default)]
+pub struct ExportStamp {
+    /// Notice shown in a banner at the top and bottom, e.g. "Confidential — internal use only".
+    pub notice: Option<String>,
+    /// Repeat the notice as a faint diagonal watermark behind the report.
+    pub watermark: bool,
+    pub profile: String,
+}
+
+impl ExportStamp {
+    pub fn from_env() -> Self {
+        ExportStamp {
+            notice: env::var("BUGGER_CONFIDENTIALITY_NOTICE").ok().filter(|n| !n.trim().is_empty()),
+            watermark: crate::env_flag("BUGGER_WATERMARK", false),
+            profile: env::var("BUGGER_PROFILE").unwrap_or_else(|_| "default".to_string()),
+        }
+    }
+}
+
+/// Escape `value` for a double-quoted CSS string inside `<style>`: anything but letters, digits and
+/// spaces becomes a hex escape, so quotes, newlines and `</style>` can't end the string or the element.
+fn css_string(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == ' ' { c.to_string() } else { format!("\\{:X} ", c as u32) })
+        .collect()
+}
+
+/// Wrap report HTML in a standalone document stamped with generation time, profile and user.
+pub fn standalone_report(report_html: &str, stamp: &ExportStamp, user: &str) -> String {
+    let provenance = format!(
+        "Generated {} by {} · profile: {}",
+        Utc::now().format("%Y-%m-%d %H:%M UTC"),
+        html_escape::encode_text(user),
+        html_escape::encode_text(&stamp.profile)
+    );
+    let banner = match &stamp.notice {
+        Some(notice) => format!("<div class='confidential-banner'><b>{}</b><br><small>{}</small></div>", html_escape::encode_text(notice), provenance),
+        None => format!("<div class='provenance'><small>{}</small></div>", provenance),
+    };
+    let watermark_css = match (&stamp.notice, stamp.watermark) {
+        (Some(notice), true) => format!(
+            "body::before {{ content: \"{}\"; position: fixed; top: 40%; left: 0; width: 100%; text-align: center; font-size: 5em; opacity: 0.07; transform: rotate(-30deg); pointer-events: none; z-index: 1000; }}",
+            css_string(notice)
+        ),
+        _ => String::new(),
+    };
+    format!(
+        "<!doctype html><html lang=\"en\"><head><meta charset=\"UTF-8\"><title>Bug Report</title><style>{}\n.confidential-banner {{ background: #b00020; color: #fff; padding: 0.5em; text-align: center; }}\n.provenance {{ color: #666; text-align: center; }}\n{}</style></head><body><main class=\"container\">{}<div class=\"report-area\">{}</div>{}</main></body></html>",
+        REPORT_CSS, watermark_css, banner, report_html, banner
+    )
+}
+
+/// Anonymizer covering the people in the last report and the given bugs.
+fn anonymizer(state: &AppState, bugs: &[Bug]) -> Result<Anonymizer, String> {
+    let people = state.report_people.lock().map_err(|e| e.to_string())?.clone();
+    Ok(Anonymizer::new(bugs, &people))
+}
+
+#[tauri::command]
+/// Writes the most recent report to `path` as a standalone, stamped HTML file.
+/// With `anonymize`, names, emails and customer identifiers are replaced by stable pseudonyms.
+pub fn export_report_html(state: State<AppState>, path: String, anonymize: Option<bool>) -> Result<(), String> {
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    let mut report = state
+        .last_report
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .ok_or("No report to export yet — run an analysis first")?;
+    if !config.export_team_metrics {
+        report = team::strip_section(&report);
+    }
+    let mut user = config.user_email.clone();
+    if anonymize.unwrap_or(false) {
+        let anonymizer = anonymizer(&state, &state.bugs_or_fetch()?)?;
+        report = anonymizer.html(&report);
+        user = anonymizer.text(&user);
+    }
+    let html = standalone_report(&report, &config.export_stamp, &user);
+    std::fs::write(&path, html).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+/// Quote a CSV cell when it contains a separator, quote or newline.
+pub fn csv_cell(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn bugs_csv(bugs: &[Bug], custom_fields: &[String], styles: &CategoryStyles) -> String {
+    let mut header = vec![
+        "id", "title", "state", "category", "severity", "board_column", "assigned_to", "created_date", "tags", "os", "os_build", "browser", "device",
+    ];
+    header.extend(custom_fields.iter().map(String::as_str));
+    let mut out = header.iter().map(|h| csv_cell(h)).collect::<Vec<_>>().join(",");
+    out.push('\n');
+    for bug in bugs {
+        let mut row = vec![
+            bug.id.to_string(),
+            bug.title.clone(),
+            bug.state.clone(),
+            styles.get(&categorize_bug(bug)).label,
+            bug.severity.clone().unwrap_or_default(),
+            bug.board_column.clone().unwrap_or_default(),
+            bug.assigned_to.as_ref().map(|a| a.display_name.clone()).unwrap_or_default(),
+            bug.created_date.clone().unwrap_or_default(),
+            bug.tags.join("; "),
+            bug.environment.os.clone().unwrap_or_default(),
+            bug.environment.os_build.clone().unwrap_or_default(),
+            bug.environment.browser.clone().unwrap_or_default(),
+            bug.environment.device.clone().unwrap_or_default(),
+        ];
+        row.extend(custom_fields.iter().map(|f| bug.custom_fields.get(f).cloned().unwrap_or_default()));
+        out.push_str(&row.iter().map(|c| csv_cell(c)).collect::<Vec<_>>().join(","));
+        out.push('\n');
+    }
+    out
+}
+
+#[tauri::command]
+/// Writes the current bug list to `path` as `csv` or `json`, optionally anonymized.
+pub fn export_bugs(state: State<AppState>, path: String, format: String, anonymize: Option<bool>) -> Result<(), String> {
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    let mut bugs = state.bugs_or_fetch()?;
+    if anonymize.unwrap_or(false) {
+        let anonymizer = anonymizer(&state, &bugs)?;
+        bugs = bugs.iter().map(|b| anonymizer.bug(b)).collect();
+    }
+    let contents = match format.to_lowercase().as_str() {
+        "csv" => bugs_csv(&bugs, &config.custom_fields, &config.category_styles),
+        "json" => serde_json::to_string_pretty(&bugs).map_err(|e| format!("JSON serialize error: {}", e))?,
+        other => return Err(format!("Unsupported export format '{}' (use csv or json)", other)),
+    };
+    std::fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+#[tauri::command]
+/// Writes the "bugs fixed by me" report for `month` (`YYYY-MM`, default: this month) to `path` as Markdown.
+pub fn export_accomplishment_report(app: AppHandle, path: String, month: Option<String>) -> Result<(), String> {
+    let markdown = accomplishments::report(&app, month.as_deref())?;
+    std::fs::write(&path, markdown).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watermark_notice_cannot_escape_the_style_element() {
+        assert_eq!(css_string("Top secret"), "Top secret");
+        assert_eq!(css_string("a\"b\\c\n"), "a\\22 b\\5C c\\A ");
+        let stamp = ExportStamp { notice: Some("x\"; }</style><script>alert(1)</script>".to_string()), watermark: true, profile: "default".to_string() };
+        let html = standalone_report("<p>report</p>", &stamp, "me");
+        assert_eq!(html.matches("</style>").count(), 1);
+        assert!(!html.contains("<script>"));
+    }
+
+    #[test]
+    fn csv_cells_are_quoted_when_needed() {
+        assert_eq!(csv_cell("plain"), "plain");
+        assert_eq!(csv_cell("a,b"), "\"a,b\"");
+        assert_eq!(csv_cell("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -0,0 +1,122 @@
+// Atom feed of newly detected bugs, served in local server mode
+use crate::azure_devops::Bug;
+use crate::bug_analysis::{categorize_bug, BugCategory};
+use crate::{storage, AppConfig};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::AppHandle;
+
+/// When each bug was first seen, keyed by id, so feed entries keep their date across restarts.
+const FEED_STORE: &str = "feed_entries";
+/// Most entries kept (and served), newest first.
+const MAX_ENTRIES: usize = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedEntry {
+    pub id: u64,
+    pub title: String,
+    pub state: String,
+    pub category: BugCategory,
+    pub severity: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Assigned to me when first seen (in team mode the list also holds other people's bugs).
+    pub mine: bool,
+    pub first_seen: DateTime<Utc>,
+}
+
+/// Narrowing applied to the feed from the URL query, e.g. `?category=crash,security&severity=1&tag=regression`.
+/// Only my bugs unless `scope=team`.
+#[derive(Debug, Clone, Default)]
+pub struct FeedFilter {
+    pub categories: Vec<BugCategory>,
+    /// Severity prefixes ("1" matches "1 - Critical").
+    pub severities: Vec<String>,
+    pub tags: Vec<String>,
+    pub team: bool,
+}
+
+impl FeedFilter {
+    pub fn from_query(query: &BTreeMap<String, String>) -> Result<Self, String> {
+        let list = |key: &str| -> Vec<String> {
+            query.get(key).map(|v| v.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()).unwrap_or_default()
+        };
+        Ok(FeedFilter {
+            categories: list("category").iter().map(|c| c.parse()).collect::<Result<_, _>>()?,
+            severities: list("severity"),
+            tags: list("tag"),
+            team: query.get("scope").is_some_and(|s| s.eq_ignore_ascii_case("team")),
+        })
+    }
+
+    fn matches(&self, entry: &FeedEntry) -> bool {
+        (self.team || entry.mine)
+            && (self.categories.is_empty() || self.categories.contains(&entry.category))
+            && (self.severities.is_empty()
+                || entry.severity.as_deref().is_some_and(|sev| self.severities.iter().any(|s| sev.to_lowercase().starts_with(&s.to_lowercase()))))
+            && (self.tags.is_empty() || entry.tags.iter().any(|t| self.tags.iter().any(|f| f.eq_ignore_ascii_case(t))))
+    }
+}
+
+/// Add bugs not seen before to the feed history and return it. On the very first run bugs are dated
+/// by creation, so subscribing doesn't show the whole backlog as new today.
+pub fn record(app: &AppHandle, config: &AppConfig, bugs: &[Bug]) -> Result<Vec<FeedEntry>, String> {
+    let mut entries: BTreeMap<u64, FeedEntry> = storage::load_json(app, FEED_STORE)?;
+    let first_run = entries.is_empty();
+    let me = config.my_identities();
+    let now = Utc::now();
+    for bug in bugs.iter().filter(|b| !entries.contains_key(&b.id)).collect::<Vec<_>>() {
+        let created = bug.created_date.as_deref().and_then(|d| DateTime::parse_from_rfc3339(d).ok()).map(|d| d.with_timezone(&Utc));
+        entries.insert(
+            bug.id,
+            FeedEntry {
+                id: bug.id,
+                title: bug.title.clone(),
+                state: bug.state.clone(),
+                category: categorize_bug(bug),
+                severity: bug.severity.clone(),
+                tags: bug.tags.clone(),
+                mine: bug.assigned_to.as_ref().is_some_and(|a| me.iter().any(|m| a.unique_name.eq_ignore_ascii_case(m))),
+                first_seen: if first_run { created.unwrap_or(now) } else { now },
+            },
+        );
+    }
+    let mut newest: Vec<FeedEntry> = entries.into_values().collect();
+    newest.sort_by_key(|e| std::cmp::Reverse(e.first_seen));
+    newest.truncate(MAX_ENTRIES);
+    let kept: BTreeMap<u64, FeedEntry> = newest.iter().map(|e| (e.id, e.clone())).collect();
+    storage::save_json(app, FEED_STORE, &kept)?;
+    Ok(newest)
+}
+
+/// Atom document for the entries passing `filter`; `self_url` is the feed's own address.
+pub fn atom(entries: &[FeedEntry], filter: &FeedFilter, config: &AppConfig, self_url: &str) -> String {
+    let entries: Vec<&FeedEntry> = entries.iter().filter(|e| filter.matches(e)).collect();
+    let updated = entries.iter().map(|e| e.first_seen).max().unwrap_or_else(Utc::now);
+    let mut xml = format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\"><title>Bugger — new bugs</title><id>urn:bugger:{}:{}:feed</id><updated>{}</updated><link rel=\"self\" href=\"{}\"/>",
+        html_escape::encode_text(&config.org),
+        html_escape::encode_text(&config.project),
+        updated.to_rfc3339(),
+        html_escape::encode_double_quoted_attribute(self_url)
+    );
+    for entry in entries {
+        let url = config.bug_url(entry.id);
+        let mut summary = vec![config.category_styles.get(&entry.category).label, entry.state.clone()];
+        summary.extend(entry.severity.clone());
+        xml.push_str(&format!(
+            "<entry><id>urn:bugger:{}:{}:bug:{}</id><title>#{} {}</title><link href=\"{}\"/><updated>{}</updated><summary>{}</summary></entry>",
+            html_escape::encode_text(&config.org),
+            html_escape::encode_text(&config.project),
+            entry.id,
+            entry.id,
+            html_escape::encode_text(&entry.title),
+            html_escape::encode_double_quoted_attribute(&url),
+            entry.first_seen.to_rfc3339(),
+            html_escape::encode_text(&summary.join(" · "))
+        ));
+    }
+    xml.push_str("</feed>");
+    xml
+}
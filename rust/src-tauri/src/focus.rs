@@ -0,0 +1,94 @@
+// Focus mode: cap the report to the top N actionable bugs by triage score plus urgent ones,
+// since a short report gets acted on and a long one gets ignored
+use crate::azure_devops::Bug;
+use crate::bug_analysis::AnalysisResult;
+use crate::calendar::SlaPolicy;
+use crate::triage::{ranked, TriageWeights};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashSet;
+
+/// Bugs due under the SLA within this many days count as urgent.
+const URGENT_DUE_DAYS: i64 = 2;
+
+/// What focus mode left out, for the report's banner and stats.
+#[derive(Debug, Clone)]
+pub struct FocusSummary {
+    pub top: usize,
+    pub total_actionable: usize,
+    pub total_questionable: usize,
+    pub shown: usize,
+}
+
+impl FocusSummary {
+    pub fn hidden(&self) -> usize {
+        self.total_actionable + self.total_questionable - self.shown
+    }
+
+    pub fn html(&self) -> String {
+        format!(
+            "<div class='focus-banner'>🎯 <b>Focus mode:</b> the top {} actionable bug(s) by triage score plus urgent ones. {} more bug(s) hidden.</div>",
+            self.top,
+            self.hidden()
+        )
+    }
+}
+
+/// Urgent: severity 1, past or close to its SLA due date, or someone is waiting on my reply.
+pub fn is_urgent(bug: &Bug, sla: &SlaPolicy, waiting_on_me: &HashSet<u64>, now: DateTime<Utc>) -> bool {
+    bug.severity.as_deref().is_some_and(|s| s.trim().starts_with('1'))
+        || sla.due_date(bug).is_some_and(|due| due <= now.date_naive() + Duration::days(URGENT_DUE_DAYS))
+        || waiting_on_me.contains(&bug.id)
+}
+
+/// Keep the `top` highest-scoring actionable bugs and every urgent bug, in place.
+pub fn apply(analysis: &mut AnalysisResult, top: usize, weights: &TriageWeights, sla: &SlaPolicy, waiting_on_me: &HashSet<u64>, now: DateTime<Utc>) -> FocusSummary {
+    let total_actionable = analysis.actionable.len();
+    let total_questionable = analysis.questionable.len();
+    let keep: HashSet<u64> = ranked(&analysis.actionable, now, weights).iter().take(top).map(|(bug, _)| bug.id).collect();
+    analysis.actionable.retain(|b| keep.contains(&b.id) || is_urgent(b, sla, waiting_on_me, now));
+    analysis.questionable.retain(|(b, _)| is_urgent(b, sla, waiting_on_me, now));
+    FocusSummary { top, total_actionable, total_questionable, shown: analysis.actionable.len() + analysis.questionable.len() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bug_analysis::QuestionableCategory;
+    use std::collections::BTreeMap;
+
+    fn sla() -> SlaPolicy {
+        SlaPolicy { days: BTreeMap::from([(2, 7)]) }
+    }
+
+    #[test]
+    fn urgent_means_severity_one_due_soon_or_waiting_on_me() {
+        let now = Utc::now();
+        let none = HashSet::new();
+        let mut bug = Bug::sample(1, "Crash");
+        assert!(!is_urgent(&bug, &sla(), &none, now));
+        assert!(is_urgent(&bug, &sla(), &HashSet::from([1]), now));
+        bug.severity = Some("1 - Critical".to_string());
+        assert!(is_urgent(&bug, &sla(), &none, now));
+        bug.severity = Some("2 - High".to_string());
+        bug.created_date = Some((now - Duration::days(6)).to_rfc3339());
+        assert!(is_urgent(&bug, &sla(), &none, now), "due tomorrow");
+        bug.created_date = Some(now.to_rfc3339());
+        assert!(!is_urgent(&bug, &sla(), &none, now), "due in a week");
+    }
+
+    #[test]
+    fn focus_keeps_the_top_bugs_and_every_urgent_one() {
+        let now = Utc::now();
+        let actionable: Vec<Bug> = (1..=5).map(|id| Bug::sample(id, "Something is broken")).collect();
+        let questionable = vec![(Bug::sample(6, "?"), QuestionableCategory::EmptyMinimalDescription), (Bug::sample(7, "?"), QuestionableCategory::EmptyMinimalDescription)];
+        let mut analysis = AnalysisResult { actionable, questionable };
+        let waiting_on_me = HashSet::from([5, 7]);
+        let summary = apply(&mut analysis, 2, &TriageWeights::default(), &sla(), &waiting_on_me, now);
+        assert_eq!(analysis.actionable.len(), 3, "two on top plus the urgent one");
+        assert!(analysis.actionable.iter().any(|b| b.id == 5));
+        assert_eq!(analysis.questionable.iter().map(|(b, _)| b.id).collect::<Vec<_>>(), [7]);
+        assert_eq!((summary.total_actionable, summary.total_questionable, summary.shown), (5, 2, 4));
+        assert_eq!(summary.hidden(), 3);
+        assert!(summary.html().contains("3 more bug(s) hidden"));
+    }
+}
@@ -0,0 +1,273 @@
+// GitHub Issues backend: open issues with the bug label assigned to me, mapped onto `Bug`
+use crate::audit::WriteOutcome;
+use crate::azure_devops::{Bug, Identity};
+use crate::environment::Environment;
+use crate::error::BuggerError;
+use crate::provider::IssueProvider;
+use reqwest::blocking::{Client, RequestBuilder};
+use reqwest::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE, USER_AGENT};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::sync::Mutex;
+
+const PAGE_SIZE: usize = 100;
+
+#[derive(Debug, Clone)]
+pub struct GitHubConfig {
+    /// REST API root (GITHUB_API_URL), e.g. `https://github.contoso.com/api/v3` for GitHub Enterprise Server.
+    pub api_url: String,
+    /// `owner/name` (GITHUB_REPO).
+    pub repo: String,
+    pub token: String,
+    /// My login (GITHUB_USER); issues assigned to it are fetched.
+    pub user: String,
+    /// Only issues with this label are bugs (GITHUB_BUG_LABEL, default "bug"; empty for every issue).
+    pub bug_label: Option<String>,
+}
+
+impl GitHubConfig {
+    /// Load from GITHUB_REPO, GITHUB_TOKEN and GITHUB_USER (all required), GITHUB_API_URL and GITHUB_BUG_LABEL.
+    pub fn from_env() -> anyhow::Result<Self> {
+        let required = |name: &str| env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty()).ok_or_else(|| anyhow::anyhow!("Missing {}", name));
+        let repo = required("GITHUB_REPO")?;
+        if !repo.contains('/') {
+            anyhow::bail!("GITHUB_REPO should be owner/name, not '{}'", repo);
+        }
+        Ok(GitHubConfig {
+            api_url: env::var("GITHUB_API_URL").map(|u| u.trim().trim_end_matches('/').to_string()).ok().filter(|u| !u.is_empty()).unwrap_or_else(|| "https://api.github.com".to_string()),
+            repo,
+            token: required("GITHUB_TOKEN")?,
+            user: required("GITHUB_USER")?,
+            bug_label: match env::var("GITHUB_BUG_LABEL") {
+                Ok(label) => Some(label.trim().to_string()).filter(|l| !l.is_empty()),
+                Err(_) => Some("bug".to_string()),
+            },
+        })
+    }
+
+    pub fn issue_url(&self, number: u64) -> String {
+        let web = match self.api_url.as_str() {
+            "https://api.github.com" => "https://github.com",
+            api => api.trim_end_matches("/api/v3"),
+        };
+        format!("{}/{}/issues/{}", web, self.repo, number)
+    }
+}
+
+pub struct GitHubClient {
+    pub config: GitHubConfig,
+    client: Client,
+    /// Issues as listed by `fetch_ids`, which already have every detail, by number.
+    listed: Mutex<HashMap<u64, Value>>,
+}
+
+impl GitHubClient {
+    pub fn new(config: GitHubConfig) -> Self {
+        GitHubClient { config, client: Client::new(), listed: Mutex::new(HashMap::new()) }
+    }
+
+    fn repo_url(&self, path: &str) -> String {
+        format!("{}/repos/{}{}", self.config.api_url, self.config.repo, path)
+    }
+
+    /// GET a path under the repo and parse the JSON response.
+    fn get_json(&self, path: &str, query: &[(&str, String)]) -> Result<Value, BuggerError> {
+        self.send(self.client.get(self.repo_url(path)).query(query))
+    }
+
+    /// Send an authenticated request and parse the JSON response.
+    fn send(&self, request: RequestBuilder) -> Result<Value, BuggerError> {
+        let resp = request
+            .header(AUTHORIZATION, format!("Bearer {}", self.config.token))
+            .header(ACCEPT, "application/vnd.github+json")
+            .header(USER_AGENT, "Bugger")
+            .send()
+            .map_err(|e| BuggerError::Network(format!("Request error: {}", e)))?;
+        let status = resp.status();
+        let text = resp.text().map_err(|e| BuggerError::Network(format!("Response body error: {}", e)))?;
+        if !status.is_success() {
+            return Err(BuggerError::from_status("GitHub", status, &text));
+        }
+        serde_json::from_str(&text).map_err(|e| BuggerError::Parse(format!("JSON error: {}\nRaw response: {}", e, text)))
+    }
+}
+
+fn identity(value: &Value) -> Option<Identity> {
+    let login = value["login"].as_str()?;
+    Some(Identity { display_name: login.to_string(), unique_name: login.to_string(), avatar_url: value["avatar_url"].as_str().map(str::to_string) })
+}
+
+/// Map an issue onto a bug: open issues are Active, the Markdown body is shown as escaped text,
+/// labels become tags and a `severity: <value>` label sets the severity.
+fn issue_to_bug(issue: &Value) -> Option<Bug> {
+    let id = issue["number"].as_u64()?;
+    let title = issue["title"].as_str().unwrap_or("").to_string();
+    let text = |key: &str| issue[key].as_str().map(str::to_string);
+    let tags: Vec<String> = issue["labels"].as_array().map(|labels| labels.iter().filter_map(|l| l["name"].as_str().map(str::to_string)).collect()).unwrap_or_default();
+    let severity = tags.iter().find_map(|label| {
+        let (key, value) = label.split_once(':')?;
+        key.trim().eq_ignore_ascii_case("severity").then(|| value.trim().to_string())
+    });
+    let description = issue["body"].as_str().filter(|b| !b.trim().is_empty()).map(|b| html_escape::encode_text(b).replace('\n', "<br>"));
+    let environment = Environment::extract(&format!("{}\n{}", title, issue["body"].as_str().unwrap_or("")));
+    Some(Bug {
+        id,
+        title,
+        state: if issue["state"] == "closed" { "Closed" } else { "Active" }.to_string(),
+        created_date: text("created_at"),
+        changed_date: text("updated_at"),
+        state_changed_date: text("closed_at"),
+        description,
+        severity,
+        board_column: None,
+        parent_id: None,
+        comment_count: issue["comments"].as_u64().unwrap_or(0),
+        tags,
+        assigned_to: identity(&issue["assignee"]),
+        created_by: identity(&issue["user"]),
+        area_path: None,
+        custom_fields: BTreeMap::new(),
+        environment,
+    })
+}
+
+impl IssueProvider for GitHubClient {
+    /// Open issues (not pull requests) assigned to me with the bug label, newest first.
+    fn fetch_ids(&self) -> Result<Vec<u64>, BuggerError> {
+        let mut query = vec![("state", "open".to_string()), ("assignee", self.config.user.clone()), ("per_page", PAGE_SIZE.to_string())];
+        if let Some(label) = &self.config.bug_label {
+            query.push(("labels", label.clone()));
+        }
+        let mut ids = Vec::new();
+        let mut listed = HashMap::new();
+        for page in 1.. {
+            let mut paged = query.clone();
+            paged.push(("page", page.to_string()));
+            let json = self.get_json("/issues", &paged)?;
+            let issues = json.as_array().cloned().unwrap_or_default();
+            let count = issues.len();
+            for issue in issues.into_iter().filter(|i| i.get("pull_request").is_none()) {
+                if let Some(number) = issue["number"].as_u64() {
+                    ids.push(number);
+                    listed.insert(number, issue);
+                }
+            }
+            if count < PAGE_SIZE {
+                break;
+            }
+        }
+        *self.listed.lock().map_err(|e| e.to_string())? = listed;
+        Ok(ids)
+    }
+
+    /// Listed issues come from `fetch_ids`; only the others are requested one by one.
+    fn fetch_details(&self, ids: &[u64]) -> Result<Vec<Bug>, BuggerError> {
+        let mut bugs = Vec::new();
+        for id in ids {
+            let listed = self.listed.lock().map_err(|e| e.to_string())?.get(id).cloned();
+            let issue = match listed {
+                Some(issue) => issue,
+                None => self.get_json(&format!("/issues/{}", id), &[])?,
+            };
+            // Pull requests share the issue numbering.
+            if issue.get("pull_request").is_none() {
+                bugs.extend(issue_to_bug(&issue));
+            }
+        }
+        Ok(bugs)
+    }
+
+    fn add_tag(&self, id: u64, tag: &str) -> Result<WriteOutcome, BuggerError> {
+        let body = json!({ "labels": [tag] }).to_string();
+        let response = self.send(self.client.post(self.repo_url(&format!("/issues/{}/labels", id))).header(CONTENT_TYPE, "application/json").body(body))?;
+        Ok(WriteOutcome { response, undo: None })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+    use serde_json::json;
+
+    fn client(server: &MockServer) -> GitHubClient {
+        GitHubClient::new(GitHubConfig { api_url: server.base_url(), repo: "octo/app".to_string(), token: "token".to_string(), user: "me".to_string(), bug_label: Some("bug".to_string()) })
+    }
+
+    #[test]
+    fn assigned_bug_issues_are_fetched_without_pull_requests() {
+        let server = MockServer::start();
+        let list = server.mock(|when, then| {
+            when.method(GET)
+                .path("/repos/octo/app/issues")
+                .query_param("assignee", "me")
+                .query_param("labels", "bug")
+                .query_param("page", "1")
+                .header("authorization", "Bearer token");
+            then.status(200).json_body(json!([{ "number": 5 }, { "number": 6, "pull_request": {} }]));
+        });
+        let ids = client(&server).fetch_ids().unwrap();
+        list.assert();
+        assert_eq!(ids, vec![5]);
+    }
+
+    #[test]
+    fn issues_are_mapped_onto_bugs() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/repos/octo/app/issues/5");
+            then.status(200).json_body(json!({
+                "number": 5,
+                "title": "Crash on start with Windows 11",
+                "state": "open",
+                "body": "Steps <b>1</b>\nStart it",
+                "created_at": "2026-09-01T10:00:00Z",
+                "updated_at": "2026-10-01T10:00:00Z",
+                "comments": 2,
+                "labels": [{ "name": "bug" }, { "name": "Severity: 2 - High" }],
+                "assignee": { "login": "me", "avatar_url": "https://avatar/me" },
+                "user": { "login": "reporter" }
+            }));
+        });
+        let bug = client(&server).fetch_details(&[5]).unwrap().remove(0);
+        assert_eq!(bug.id, 5);
+        assert_eq!(bug.state, "Active");
+        assert_eq!(bug.description.as_deref(), Some("Steps &lt;b&gt;1&lt;/b&gt;<br>Start it"));
+        assert_eq!(bug.severity.as_deref(), Some("2 - High"));
+        assert_eq!(bug.tags, vec!["bug", "Severity: 2 - High"]);
+        assert_eq!(bug.comment_count, 2);
+        assert_eq!(bug.assigned_to.unwrap().unique_name, "me");
+        assert_eq!(bug.created_by.unwrap().display_name, "reporter");
+    }
+
+    #[test]
+    fn listed_issues_are_not_fetched_again() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/repos/octo/app/issues").query_param("page", "1");
+            then.status(200).json_body(json!([{ "number": 5, "title": "Listed", "state": "open" }]));
+        });
+        let single = server.mock(|when, then| {
+            when.method(GET).path_matches(httpmock::Regex::new("^/repos/octo/app/issues/[0-9]+$").unwrap());
+            then.status(200).json_body(json!({ "number": 7, "title": "Fetched", "state": "open" }));
+        });
+        let client = client(&server);
+        let ids = client.fetch_ids().unwrap();
+        let bugs = client.fetch_details(&[ids[0], 7]).unwrap();
+        single.assert_hits(1);
+        assert_eq!(bugs.iter().map(|b| b.title.as_str()).collect::<Vec<_>>(), vec!["Listed", "Fetched"]);
+    }
+
+    #[test]
+    fn tags_are_added_as_labels() {
+        let server = MockServer::start();
+        let labels = server.mock(|when, then| {
+            when.method(POST).path("/repos/octo/app/issues/5/labels").json_body(json!({ "labels": ["needs-info"] }));
+            then.status(200).json_body(json!([{ "name": "bug" }, { "name": "needs-info" }]));
+        });
+        let outcome = client(&server).add_tag(5, "needs-info").unwrap();
+        labels.assert();
+        assert!(outcome.undo.is_none());
+    }
+}
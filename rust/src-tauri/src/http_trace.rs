@@ -0,0 +1,78 @@
+// Debug recording of Azure DevOps HTTP exchanges into a session file users can attach to bug reports
+use crate::storage::store_path;
+use chrono::Utc;
+use log::{info, warn};
+use serde::Serialize;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use tauri::AppHandle;
+
+/// Longest request/response body kept per exchange.
+const MAX_BODY_CHARS: usize = 2000;
+
+/// Session trace file, set at startup when BUGGER_HTTP_TRACE is enabled.
+static TRACE_FILE: OnceLock<PathBuf> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HttpExchange {
+    pub timestamp: String,
+    pub method: String,
+    pub url: String,
+    pub status: u16,
+    pub duration_ms: u128,
+    pub request_body: Option<String>,
+    pub response_body: String,
+}
+
+/// Start a new trace file for this session if BUGGER_HTTP_TRACE is set.
+pub fn start(app: &AppHandle) {
+    if !crate::env_flag("BUGGER_HTTP_TRACE", false) {
+        return;
+    }
+    let name = format!("http-trace-{}", Utc::now().format("%Y%m%d-%H%M%S"));
+    match store_path(app, &name) {
+        Ok(path) => {
+            let path = path.with_extension("jsonl");
+            info!("Recording HTTP exchanges to {}", path.display());
+            let _ = TRACE_FILE.set(path);
+        }
+        Err(e) => warn!("HTTP trace disabled: {}", e),
+    }
+}
+
+pub fn is_enabled() -> bool {
+    TRACE_FILE.get().is_some()
+}
+
+/// Replace every secret value with a marker and cut the text to MAX_BODY_CHARS.
+pub fn sanitize(text: &str, secrets: &[&str]) -> String {
+    let mut clean = text.to_string();
+    for secret in secrets.iter().filter(|s| s.len() >= 4) {
+        clean = clean.replace(secret, "[REDACTED]");
+    }
+    if clean.chars().count() > MAX_BODY_CHARS {
+        clean = clean.chars().take(MAX_BODY_CHARS).collect::<String>() + "…[truncated]";
+    }
+    clean
+}
+
+/// Append one exchange to the session file. The caller is responsible for sanitizing it.
+pub fn record(exchange: &HttpExchange) {
+    let Some(path) = TRACE_FILE.get() else {
+        return;
+    };
+    let written = serde_json::to_string(exchange).map_err(|e| e.to_string()).and_then(|line| {
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path).map_err(|e| e.to_string())?;
+        writeln!(file, "{}", line).map_err(|e| e.to_string())
+    });
+    if let Err(e) = written {
+        warn!("Failed to record HTTP exchange: {}", e);
+    }
+}
+
+#[tauri::command]
+/// Path of this session's HTTP trace file, if recording is enabled.
+pub fn get_http_trace_path() -> Option<String> {
+    TRACE_FILE.get().map(|p| p.display().to_string())
+}
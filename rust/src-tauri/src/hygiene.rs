@@ -0,0 +1,160 @@
+// Backlog hygiene score: one 0–100 health number combining questionable, stale and overdue bugs
+// with description quality, tracked daily so its trend can be shown
+use crate::azure_devops::Bug;
+use crate::bug_analysis::is_questionable;
+use crate::calendar::SlaPolicy;
+use crate::checklist::ChecklistScore;
+use crate::storage;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::AppHandle;
+
+/// Daily scores, one per day (the last analysis of the day wins).
+const HYGIENE_STORE: &str = "hygiene_history";
+const HISTORY_DAYS: i64 = 180;
+/// Points each component contributes to a perfect score; they add up to 100.
+const QUESTIONABLE_WEIGHT: f64 = 30.0;
+const STALE_WEIGHT: f64 = 25.0;
+const SLA_WEIGHT: f64 = 25.0;
+const DESCRIPTION_WEIGHT: f64 = 20.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HygieneScore {
+    /// 0 (unhealthy) to 100 (healthy).
+    pub score: u32,
+    /// Shares of bugs, 0.0 to 1.0.
+    pub questionable_ratio: f64,
+    pub stale_ratio: f64,
+    pub sla_breach_ratio: f64,
+    /// Average checklist score, 0.0 to 1.0.
+    pub description_quality: f64,
+}
+
+fn ratio(part: usize, total: usize) -> f64 {
+    if total == 0 { 0.0 } else { part as f64 / total as f64 }
+}
+
+/// A bug is stale when it hasn't changed for `stale_days` (bugs without a change date count from creation).
+pub fn is_stale(bug: &Bug, stale_days: i64, now: DateTime<Utc>) -> bool {
+    bug.changed_date
+        .as_deref()
+        .or(bug.created_date.as_deref())
+        .and_then(|d| DateTime::parse_from_rfc3339(d).ok())
+        .is_some_and(|changed| now - changed.with_timezone(&Utc) > Duration::days(stale_days))
+}
+
+pub fn score(bugs: &[Bug], checklists: &HashMap<u64, ChecklistScore>, sla: &SlaPolicy, stale_days: i64, now: DateTime<Utc>) -> HygieneScore {
+    let today = now.date_naive();
+    let questionable_ratio = ratio(bugs.iter().filter(|b| is_questionable(b).is_some()).count(), bugs.len());
+    let stale_ratio = ratio(bugs.iter().filter(|b| is_stale(b, stale_days, now)).count(), bugs.len());
+    let sla_breach_ratio = ratio(bugs.iter().filter(|b| sla.due_date(b).is_some_and(|due| due < today)).count(), bugs.len());
+    let description_quality = if checklists.is_empty() {
+        1.0
+    } else {
+        checklists.values().map(|c| c.percent() as f64).sum::<f64>() / checklists.len() as f64 / 100.0
+    };
+    let score = QUESTIONABLE_WEIGHT * (1.0 - questionable_ratio)
+        + STALE_WEIGHT * (1.0 - stale_ratio)
+        + SLA_WEIGHT * (1.0 - sla_breach_ratio)
+        + DESCRIPTION_WEIGHT * description_quality;
+    HygieneScore { score: score.round().clamp(0.0, 100.0) as u32, questionable_ratio, stale_ratio, sla_breach_ratio, description_quality }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HygieneSnapshot {
+    pub date: NaiveDate,
+    pub score: u32,
+}
+
+/// Store today's score and return the previous day's, if any.
+pub fn record(app: &AppHandle, today: NaiveDate, score: u32) -> Result<Option<u32>, String> {
+    let mut history: Vec<HygieneSnapshot> = storage::load_json(app, HYGIENE_STORE)?;
+    history.retain(|s| s.date != today && s.date > today - Duration::days(HISTORY_DAYS));
+    let previous = history.iter().max_by_key(|s| s.date).map(|s| s.score);
+    history.push(HygieneSnapshot { date: today, score });
+    history.sort_by_key(|s| s.date);
+    storage::save_json(app, HYGIENE_STORE, &history)?;
+    Ok(previous)
+}
+
+/// The latest score recorded before `today`, without recording anything.
+pub fn previous(app: &AppHandle, today: NaiveDate) -> Result<Option<u32>, String> {
+    let history: Vec<HygieneSnapshot> = storage::load_json(app, HYGIENE_STORE)?;
+    Ok(history.iter().filter(|s| s.date < today).max_by_key(|s| s.date).map(|s| s.score))
+}
+
+/// Headline block with the score, its change since the previous day and the components.
+pub fn html(hygiene: &HygieneScore, previous: Option<u32>) -> String {
+    let trend = match previous.map(|p| hygiene.score as i64 - p as i64) {
+        Some(delta) if delta > 0 => format!(" <span class='trend up' title='Since the previous day'>▲ {}</span>", delta),
+        Some(delta) if delta < 0 => format!(" <span class='trend down' title='Since the previous day'>▼ {}</span>", -delta),
+        Some(_) => " <span class='trend' title='Since the previous day'>▶</span>".to_string(),
+        None => String::new(),
+    };
+    let percent = |r: f64| (r * 100.0).round() as u32;
+    format!(
+        "<div class='hygiene'><span class='hygiene-score'>{}</span>/100{} <b>Backlog hygiene</b><br><small>{}% questionable · {}% stale · {}% past SLA · {}% avg. description quality</small></div>",
+        hygiene.score,
+        trend,
+        percent(hygiene.questionable_ratio),
+        percent(hygiene.stale_ratio),
+        percent(hygiene.sla_breach_ratio),
+        percent(hygiene.description_quality)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn days_ago(now: DateTime<Utc>, days: i64) -> Option<String> {
+        Some((now - Duration::days(days)).to_rfc3339())
+    }
+
+    #[test]
+    fn bugs_are_stale_after_the_last_change_or_creation() {
+        let now = Utc::now();
+        let mut bug = Bug::sample(1, "Crash on start");
+        assert!(!is_stale(&bug, 30, now));
+        bug.created_date = days_ago(now, 40);
+        assert!(is_stale(&bug, 30, now));
+        bug.changed_date = days_ago(now, 2);
+        assert!(!is_stale(&bug, 30, now));
+    }
+
+    #[test]
+    fn an_empty_backlog_is_perfectly_healthy() {
+        let sla = SlaPolicy { days: BTreeMap::new() };
+        assert_eq!(score(&[], &HashMap::new(), &sla, 30, Utc::now()).score, 100);
+    }
+
+    #[test]
+    fn components_are_weighted_into_the_score() {
+        let now = Utc::now();
+        // Questionable (no description), stale and past its one-day SLA.
+        let mut neglected = Bug::sample(1, "Crash on start");
+        neglected.severity = Some("1 - Critical".to_string());
+        neglected.created_date = days_ago(now, 40);
+        let mut fine = Bug::sample(2, "Save button does nothing");
+        fine.description = Some("Open a document, edit it and click Save: nothing happens and the changes are lost.".to_string());
+        fine.created_date = days_ago(now, 1);
+        let checklists = HashMap::from([(2, ChecklistScore { passed: Vec::new(), missing: Vec::new(), score: 1, max: 2 })]);
+        let sla = SlaPolicy { days: BTreeMap::from([(1, 1)]) };
+        let hygiene = score(&[neglected, fine], &checklists, &sla, 30, now);
+        assert_eq!((hygiene.questionable_ratio, hygiene.stale_ratio, hygiene.sla_breach_ratio, hygiene.description_quality), (0.5, 0.5, 0.5, 0.5));
+        assert_eq!(hygiene.score, 50);
+    }
+
+    #[test]
+    fn the_trend_arrow_follows_the_previous_score() {
+        let hygiene = HygieneScore { score: 70, questionable_ratio: 0.1, stale_ratio: 0.2, sla_breach_ratio: 0.0, description_quality: 0.75 };
+        assert!(html(&hygiene, Some(65)).contains("▲ 5"));
+        assert!(html(&hygiene, Some(72)).contains("▼ 2"));
+        assert!(html(&hygiene, Some(70)).contains("▶"));
+        let first = html(&hygiene, None);
+        assert!(!first.contains("trend"));
+        assert!(first.contains("10% questionable · 20% stale · 0% past SLA · 75% avg. description quality"));
+    }
+}
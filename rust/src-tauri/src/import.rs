@@ -0,0 +1,67 @@
+// Ad-hoc analysis of a pasted or imported list of work item ids, e.g. a query exported to CSV
+use crate::provider;
+use crate::{build_report, AppConfig, AppState};
+use log::info;
+use tauri::{AppHandle, State};
+
+/// Split one CSV line into cells, honoring quotes.
+fn csv_cells(line: &str) -> Vec<String> {
+    let mut cells = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                cells.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => cells.push(String::new()),
+            c => cells.last_mut().unwrap().push(c),
+        }
+    }
+    cells
+}
+
+/// Work item ids from a CSV with an `ID` column (as Azure DevOps exports queries), or from any list of
+/// numbers separated by commas, spaces or newlines (`#123` works too). Duplicates are dropped.
+pub fn parse_id_list(text: &str) -> Vec<u64> {
+    let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+    let header = lines.next().map(csv_cells).unwrap_or_default();
+    let ids: Vec<u64> = match header.iter().position(|h| h.trim().eq_ignore_ascii_case("id")) {
+        Some(column) => lines.filter_map(|line| csv_cells(line).get(column)?.trim().parse().ok()).collect(),
+        None => text
+            .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+            .filter_map(|token| token.trim().trim_start_matches('#').parse().ok())
+            .collect(),
+    };
+    let mut unique = Vec::with_capacity(ids.len());
+    for id in ids {
+        if !unique.contains(&id) {
+            unique.push(id);
+        }
+    }
+    unique
+}
+
+#[tauri::command]
+/// Fetches the work items listed in `ids` (pasted text) or the CSV file at `path`, whoever they're
+/// assigned to, and returns the full HTML report for them. My bug list and history are untouched.
+pub fn analyze_id_list(app: AppHandle, state: State<AppState>, ids: Option<String>, path: Option<String>) -> Result<String, String> {
+    let text = match (ids, path) {
+        (Some(ids), _) => ids,
+        (None, Some(path)) => std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?,
+        (None, None) => return Err("Paste a list of ids or choose a CSV file".to_string()),
+    };
+    let ids = parse_id_list(&text);
+    if ids.is_empty() {
+        return Err("No work item ids found".to_string());
+    }
+    info!("Ad-hoc analysis of {} work item(s)", ids.len());
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    let bugs = provider::from_config(config).fetch_details(&ids)?;
+    if bugs.is_empty() {
+        return Ok("<b>None of the listed work items could be found.</b>".to_string());
+    }
+    Ok(build_report(&app, &state, bugs, false)?)
+}
@@ -0,0 +1,170 @@
+// Inflow anomaly detection: unusual spikes in new bugs (overall and per category) vs. the trailing baseline
+use crate::azure_devops::Bug;
+use crate::bug_analysis::{categorize_bug, BugCategory};
+use crate::category_style::CategoryStyles;
+use crate::notifications::{deliver, QueuedNotification};
+use crate::{storage, AppConfig};
+use chrono::{DateTime, Duration, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::AppHandle;
+
+/// Creation date and category of every bug seen, so bugs fixed since still count towards the baseline.
+const SEEN_STORE: &str = "inflow_history";
+/// Anomalies already notified, keyed by "<category>|<date>", so each spike notifies once a day.
+const NOTIFIED_STORE: &str = "inflow_anomalies_notified";
+const RECENT_HOURS: i64 = 48;
+const BASELINE_DAYS: i64 = 28;
+/// Fewer new bugs than this in the recent window is never an anomaly.
+const MIN_COUNT: usize = 3;
+/// Recent inflow must be at least this many times the baseline...
+const MIN_RATIO: f64 = 2.0;
+/// ...and this many standard deviations above it (Poisson).
+const SIGMAS: f64 = 3.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeenBug {
+    pub created: DateTime<Utc>,
+    pub category: BugCategory,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InflowAnomaly {
+    /// None for all bugs together.
+    pub category: Option<BugCategory>,
+    /// New bugs in the last RECENT_HOURS hours.
+    pub recent: usize,
+    /// New bugs expected in that window from the trailing baseline.
+    pub expected: f64,
+}
+
+impl InflowAnomaly {
+    /// e.g. "3x normal Crash reports in the last 48h (7 new, ~2 expected)".
+    pub fn summary(&self, styles: &CategoryStyles) -> String {
+        let what = match &self.category {
+            Some(category) => format!("{} reports", styles.get(category).label),
+            None => "new bugs".to_string(),
+        };
+        if self.expected < 0.5 {
+            return format!("{} {} in the last {}h, where there are usually none", self.recent, what, RECENT_HOURS);
+        }
+        format!(
+            "{:.0}x normal {} in the last {}h ({} new, ~{:.0} expected)",
+            self.recent as f64 / self.expected,
+            what,
+            RECENT_HOURS,
+            self.recent,
+            self.expected
+        )
+    }
+
+    fn key(&self, now: DateTime<Utc>) -> String {
+        let category = self.category.as_ref().map(|c| format!("{:?}", c)).unwrap_or_else(|| "All".to_string());
+        format!("{}|{}", category, now.format("%Y-%m-%d"))
+    }
+}
+
+/// Add the current bugs to the seen history (dropping entries older than the baseline) and return it.
+pub fn record_seen(app: &AppHandle, bugs: &[Bug], now: DateTime<Utc>) -> Result<BTreeMap<u64, SeenBug>, String> {
+    let mut seen: BTreeMap<u64, SeenBug> = storage::load_json(app, SEEN_STORE)?;
+    for bug in bugs {
+        let Some(created) = bug.created_date.as_deref().and_then(|d| DateTime::parse_from_rfc3339(d).ok()) else {
+            continue;
+        };
+        seen.insert(bug.id, SeenBug { created: created.with_timezone(&Utc), category: categorize_bug(bug) });
+    }
+    let horizon = now - Duration::days(BASELINE_DAYS) - Duration::hours(RECENT_HOURS);
+    seen.retain(|_, s| s.created >= horizon);
+    storage::save_json(app, SEEN_STORE, &seen)?;
+    Ok(seen)
+}
+
+fn is_anomaly(recent: usize, expected: f64) -> bool {
+    recent >= MIN_COUNT && recent as f64 >= expected * MIN_RATIO && recent as f64 > expected + SIGMAS * expected.sqrt()
+}
+
+/// Spikes in the last RECENT_HOURS hours compared to the BASELINE_DAYS days before, overall first.
+pub fn detect(seen: &BTreeMap<u64, SeenBug>, now: DateTime<Utc>) -> Vec<InflowAnomaly> {
+    let recent_start = now - Duration::hours(RECENT_HOURS);
+    let baseline_start = recent_start - Duration::days(BASELINE_DAYS);
+    let window_share = RECENT_HOURS as f64 / (BASELINE_DAYS * 24) as f64;
+    let check = |category: Option<&BugCategory>| {
+        let matching = seen.values().filter(|s| category.is_none_or(|c| s.category == *c));
+        let (mut recent, mut baseline) = (0, 0);
+        for s in matching {
+            if s.created >= recent_start {
+                recent += 1;
+            } else if s.created >= baseline_start {
+                baseline += 1;
+            }
+        }
+        let expected = baseline as f64 * window_share;
+        is_anomaly(recent, expected).then(|| InflowAnomaly { category: category.cloned(), recent, expected })
+    };
+    std::iter::once(check(None)).chain(BugCategory::ALL.iter().map(|c| check(Some(c)))).flatten().collect()
+}
+
+/// Record `bugs`, detect spikes and notify about the ones not notified yet today.
+pub fn check(app: &AppHandle, config: &AppConfig, bugs: &[Bug]) -> Vec<InflowAnomaly> {
+    let now = Utc::now();
+    let anomalies = match record_seen(app, bugs, now) {
+        Ok(seen) => detect(&seen, now),
+        Err(e) => {
+            warn!("Inflow history unavailable: {}", e);
+            return Vec::new();
+        }
+    };
+    let mut notified: BTreeMap<String, DateTime<Utc>> = storage::load_json(app, NOTIFIED_STORE).unwrap_or_default();
+    notified.retain(|_, at| *at > now - Duration::days(2));
+    let pending: Vec<QueuedNotification> = anomalies
+        .iter()
+        .filter(|a| notified.insert(a.key(now), now).is_none())
+        .map(|a| QueuedNotification {
+            title: "📈 Unusual bug inflow".to_string(),
+            body: a.summary(&config.category_styles),
+            queued_at: now,
+            bug_id: None,
+        })
+        .collect();
+    if !pending.is_empty() {
+        deliver(app, config.quiet_hours.as_ref(), pending);
+    }
+    if let Err(e) = storage::save_json(app, NOTIFIED_STORE, &notified) {
+        warn!("Failed to save notified anomalies: {}", e);
+    }
+    anomalies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seen(entries: &[(i64, BugCategory)], now: DateTime<Utc>) -> BTreeMap<u64, SeenBug> {
+        entries.iter().enumerate().map(|(id, (hours_ago, category))| (id as u64, SeenBug { created: now - Duration::hours(*hours_ago), category: category.clone() })).collect()
+    }
+
+    #[test]
+    fn spikes_are_detected_overall_and_per_category() {
+        let now = Utc::now();
+        // 14 crashes over the 28-day baseline (one expected per 48h), then 6 crashes and a network bug.
+        let mut entries: Vec<(i64, BugCategory)> = (0..14).map(|day| (RECENT_HOURS + 24 + day * 48, BugCategory::Crash)).collect();
+        entries.extend((0..6).map(|hour| (hour, BugCategory::Crash)));
+        entries.push((5, BugCategory::Network));
+        let anomalies = detect(&seen(&entries, now), now);
+        let found: Vec<_> = anomalies.iter().map(|a| (a.category.clone(), a.recent, a.expected)).collect();
+        assert_eq!(found, [(None, 7, 1.0), (Some(BugCategory::Crash), 6, 1.0)]);
+        let styles = CategoryStyles::from_env();
+        assert_eq!(anomalies[0].summary(&styles), "7x normal new bugs in the last 48h (7 new, ~1 expected)");
+        assert_eq!(anomalies[1].key(now), format!("Crash|{}", now.format("%Y-%m-%d")));
+    }
+
+    #[test]
+    fn small_or_ordinary_inflow_is_not_an_anomaly() {
+        assert!(!is_anomaly(2, 0.0));
+        assert!(is_anomaly(3, 0.0));
+        assert!(!is_anomaly(6, 3.0));
+        let anomaly = InflowAnomaly { category: Some(BugCategory::Crash), recent: 3, expected: 0.0 };
+        assert_eq!(anomaly.summary(&CategoryStyles::from_env()), "3 Crash reports in the last 48h, where there are usually none");
+    }
+}
@@ -0,0 +1,155 @@
+// Opt-in scan of attached log files for known error signatures, shown in each bug's summary
+use crate::azure_devops::{AzureDevOpsClient, Bug};
+use crate::{stacktrace, storage};
+use log::warn;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use tauri::AppHandle;
+
+/// Scanned attachments, keyed by URL; attachments never change, so each is downloaded once.
+const SCAN_CACHE: &str = "attachment_scans";
+const SCANNED_EXTENSIONS: [&str; 3] = ["log", "txt", "evtx"];
+/// Finding name used for the crash signature of a stack trace found in an attachment.
+pub const CRASH_SIGNATURE: &str = "Crash signature";
+/// Longest matching line kept in a finding.
+const MAX_LINE_CHARS: usize = 200;
+
+/// Built-in signatures, used when BUGGER_ERROR_SIGNATURES is not set.
+const DEFAULT_SIGNATURES: [(&str, &str); 6] = [
+    ("Unhandled exception", r"(?i)unhandled exception|exception code"),
+    ("Access violation", r"(?i)access violation|0xc0000005"),
+    ("Bugcheck", r"(?i)bugcheck|stop code|0x0000[0-9a-f]{4}"),
+    ("Out of memory", r"(?i)out of memory|insufficient memory"),
+    ("Stack overflow", r"(?i)stack overflow|0xc00000fd"),
+    ("Fatal error", r"(?i)\bfatal\b"),
+];
+
+#[derive(Debug, Clone)]
+pub struct LogScanConfig {
+    pub enabled: bool,
+    /// How much of each attachment is downloaded and scanned.
+    pub max_kb: usize,
+    pub signatures: Vec<(String, Regex)>,
+}
+
+impl LogScanConfig {
+    /// BUGGER_SCAN_ATTACHMENTS enables the scan, BUGGER_ATTACHMENT_SCAN_KB limits the download (default 256)
+    /// and BUGGER_ERROR_SIGNATURES overrides the signatures as `Name=regex;;Name=regex`.
+    pub fn from_env() -> Self {
+        let pairs: Vec<(String, String)> = match env::var("BUGGER_ERROR_SIGNATURES") {
+            Ok(value) => value
+                .split(";;")
+                .filter_map(|pair| pair.split_once('='))
+                .map(|(name, pattern)| (name.trim().to_string(), pattern.trim().to_string()))
+                .collect(),
+            Err(_) => DEFAULT_SIGNATURES.iter().map(|(n, p)| (n.to_string(), p.to_string())).collect(),
+        };
+        let signatures = pairs
+            .into_iter()
+            .filter_map(|(name, pattern)| match Regex::new(&pattern) {
+                Ok(re) => Some((name, re)),
+                Err(e) => {
+                    warn!("Ignoring invalid error signature '{}': {}", name, e);
+                    None
+                }
+            })
+            .collect();
+        LogScanConfig {
+            enabled: crate::env_flag("BUGGER_SCAN_ATTACHMENTS", false),
+            max_kb: env::var("BUGGER_ATTACHMENT_SCAN_KB").ok().and_then(|v| v.parse().ok()).unwrap_or(256),
+            signatures,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogFinding {
+    pub attachment: String,
+    pub signature: String,
+    /// First matching line.
+    pub line: String,
+}
+
+fn is_scanned(name: &str) -> bool {
+    name.rsplit_once('.').is_some_and(|(_, ext)| SCANNED_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Text of a downloaded prefix. Event logs (.evtx) store strings as UTF-16LE.
+fn decode(name: &str, bytes: &[u8]) -> String {
+    if name.to_lowercase().ends_with(".evtx") {
+        let units: Vec<u16> = bytes.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+        String::from_utf16_lossy(&units)
+    } else {
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+}
+
+/// One finding per signature (the first line it matches), plus the crash signature of the first stack trace.
+pub fn scan_text(attachment: &str, text: &str, signatures: &[(String, Regex)]) -> Vec<LogFinding> {
+    let crash = stacktrace::parse(text).and_then(|t| t.signature().map(str::to_string)).map(|frame| LogFinding {
+        attachment: attachment.to_string(),
+        signature: CRASH_SIGNATURE.to_string(),
+        line: frame,
+    });
+    signatures
+        .iter()
+        .filter_map(|(name, re)| {
+            let line = text.lines().find(|l| re.is_match(l))?;
+            Some(LogFinding {
+                attachment: attachment.to_string(),
+                signature: name.clone(),
+                line: line.trim().chars().filter(|c| !c.is_control()).take(MAX_LINE_CHARS).collect(),
+            })
+        })
+        .chain(crash)
+        .collect()
+}
+
+/// Scan the log attachments of `bugs`, downloading at most `max_downloads` new attachments per run.
+/// Returns findings by bug id.
+pub fn scan_attachments(app: &AppHandle, client: &AzureDevOpsClient, bugs: &[Bug], config: &LogScanConfig, max_downloads: usize) -> HashMap<u64, Vec<LogFinding>> {
+    let mut findings = HashMap::new();
+    if !config.enabled || config.signatures.is_empty() {
+        return findings;
+    }
+    let mut cache: HashMap<String, Vec<LogFinding>> = storage::load_json(app, SCAN_CACHE).unwrap_or_default();
+    let mut downloads = 0;
+    let mut changed = false;
+    for bug in bugs {
+        let attachments = match client.fetch_attachments(bug.id) {
+            Ok(a) => a,
+            Err(e) => {
+                warn!("Failed to list attachments of #{}: {}", bug.id, e);
+                continue;
+            }
+        };
+        let mut bug_findings = Vec::new();
+        for (name, url) in attachments.into_iter().filter(|(name, _)| is_scanned(name)) {
+            if !cache.contains_key(&url) {
+                if downloads >= max_downloads {
+                    continue;
+                }
+                downloads += 1;
+                match client.fetch_prefix(&url, config.max_kb * 1024) {
+                    Ok(bytes) => {
+                        cache.insert(url.clone(), scan_text(&name, &decode(&name, &bytes), &config.signatures));
+                        changed = true;
+                    }
+                    Err(e) => warn!("Failed to download {} on #{}: {}", name, bug.id, e),
+                }
+            }
+            bug_findings.extend(cache.get(&url).cloned().unwrap_or_default());
+        }
+        if !bug_findings.is_empty() {
+            findings.insert(bug.id, bug_findings);
+        }
+    }
+    if changed {
+        if let Err(e) = storage::save_json(app, SCAN_CACHE, &cache) {
+            warn!("Failed to save attachment scan cache: {}", e);
+        }
+    }
+    findings
+}
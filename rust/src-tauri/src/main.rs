@@ -2,6 +2,8 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use std::env;
+use std::sync::atomic::{AtomicBool, AtomicU64};
+use std::sync::Mutex;
 use log::info;
 
 fn load_env() {
@@ -9,94 +11,822 @@ fn load_env() {
     let _ = dotenvy::dotenv();
 }
 
+/// Comma-separated environment variable as a list of trimmed, non-empty values.
+pub fn env_list(name: &str) -> Vec<String> {
+    settings::var(name)
+        .map(|v| v.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+/// A yes/no setting: "1", "true", "on" or "yes" is yes, "0", "false", "off" or "no" is no.
+pub fn parse_flag(text: &str) -> Option<bool> {
+    match text.trim().to_lowercase().as_str() {
+        "1" | "true" | "on" | "yes" => Some(true),
+        "0" | "false" | "off" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+/// A yes/no environment variable; `default` when it's unset or neither.
+pub fn env_flag(name: &str, default: bool) -> bool {
+    settings::var(name).and_then(|v| parse_flag(&v)).unwrap_or(default)
+}
+
+/// Azure DevOps Services or Server root (AZURE_DEVOPS_BASE_URL), without a trailing slash.
+pub fn azure_devops_base_url() -> String {
+    env::var("AZURE_DEVOPS_BASE_URL").map(|u| u.trim().trim_end_matches('/').to_string()).ok().filter(|u| !u.is_empty()).unwrap_or_else(|| "https://dev.azure.com".to_string())
+}
+
+/// A recurrence rule from the environment; invalid rules are logged and ignored.
+fn env_recurrence(name: &str) -> Option<Recurrence> {
+    let rule = env::var(name).ok().filter(|r| !r.trim().is_empty())?;
+    rule.parse().map_err(|e| log::warn!("Ignoring {}: {}", name, e)).ok()
+}
+
 /// Application configuration loaded from environment variables.
 pub struct AppConfig {
+    /// Root of the REST API (AZURE_DEVOPS_BASE_URL), e.g. an Azure DevOps Server collection or a mock server.
+    pub base_url: String,
     pub org: String,
     pub project: String,
     pub user_email: String,
+    /// Other identities that are also me (old accounts, second email), from BUGGER_USER_ALIASES.
+    pub user_aliases: Vec<String>,
     pub azure_devops_pat: String,
+    /// Fallback PAT used when the primary is rejected, e.g. during rotation (AZURE_DEVOPS_PAT_SECONDARY).
+    pub secondary_pat: Option<String>,
+    /// Times a throttled (429) or transiently failing (5xx) Azure DevOps request is retried (BUGGER_HTTP_RETRIES).
+    pub http_retries: u32,
+    /// Work item batches fetched at the same time (BUGGER_FETCH_CONCURRENCY).
+    pub fetch_concurrency: usize,
     pub openai_api_key: Option<String>,
+    pub openai_model: String,
     pub ai_enabled: bool,
+    /// Also categorize bugs with AI and report where it disagrees with the keywords (BUGGER_AI_CATEGORIZATION).
+    pub ai_categorization: bool,
+    /// AI summary, category and actionability per bug; on with an OpenAI key unless BUGGER_AI_TRIAGE=false.
+    pub ai_triage: bool,
+    /// Minutes between background refreshes; `None` disables the scheduler.
+    pub refresh_interval_minutes: Option<u64>,
+    /// Refresh on a recurrence rule instead of an interval (BUGGER_REFRESH_SCHEDULE).
+    pub refresh_schedule: Option<Recurrence>,
+    /// Run the write policies on their own recurrence rule rather than with every refresh (BUGGER_POLICY_SCHEDULE).
+    pub policy_schedule: Option<Recurrence>,
+    pub notification_rules: NotificationRules,
+    /// Working hours; outside them notifications are queued. `None` means always deliver.
+    pub quiet_hours: Option<QuietHours>,
+    /// Extra work item field reference names to fetch onto every bug.
+    pub custom_fields: Vec<String>,
+    /// Field holding the found-in build/version, used for build correlation (always fetched).
+    pub build_field: String,
+    /// Bugs with at least this many comments get their discussion analyzed.
+    pub hot_comment_threshold: u64,
+    /// Bugs with at least this many comments get a discussion summary.
+    pub summary_comment_threshold: u64,
+    /// Days my unanswered question must sit before the bug shows as waiting on the reporter.
+    pub waiting_on_others_days: i64,
+    /// Days without any change before a bug counts as stale in the hygiene score.
+    pub stale_days: i64,
+    /// Focus mode: report only this many top actionable bugs plus urgent ones (BUGGER_FOCUS_TOP).
+    pub focus_top: Option<usize>,
+    /// Streaks and cleanup badges in the report footer (BUGGER_ACHIEVEMENTS).
+    pub achievements: bool,
+    pub auto_close: AutoClosePolicy,
+    /// Round-robin assignment of new unassigned bugs in team mode.
+    pub auto_assign: AutoAssignPolicy,
+    /// Who is away when (BUGGER_OUT_OF_OFFICE), besides absences marked in the app.
+    pub out_of_office: OutOfOffice,
+    pub escalation: EscalationPolicy,
+    /// Category and triage score written back to the work items.
+    pub write_back: WriteBackPolicy,
+    /// Who may triage and who may run the write policies (BUGGER_ROLES).
+    pub roles: Roles,
+    /// How long after a write it can still be undone.
+    pub undo_window_minutes: i64,
+    /// Keep fetched bugs in a local cache and refetch only changed ones (on unless BUGGER_BUG_CACHE=false).
+    pub bug_cache: bool,
+    /// Maximum new avatars/icons downloaded per report run.
+    pub asset_fetch_limit: usize,
+    /// When background refreshes and prefetching are skipped (battery, metered connection) and big syncs wait for idle.
+    pub power: PowerPolicy,
+    /// Descriptions longer than this (HTML characters) are shown truncated with a "show more" button.
+    pub description_max_chars: usize,
+    /// Opt-in error signature scan of attached log files.
+    pub log_scan: LogScanConfig,
+    /// Explanations for bugcheck, exception and HRESULT codes mentioned in bugs.
+    pub error_codes: ErrorCodes,
+    /// Weighted actionability checklist every bug is scored against.
+    pub checklist: Checklist,
+    /// Sections every bug description should fill in, from config or the team's Bug template.
+    pub bug_template: TemplateConfig,
+    /// Local clones that file paths in bugs are resolved against.
+    pub repo_roots: RepoRoots,
+    /// Days to fix per severity, for calendar deadlines.
+    pub sla: SlaPolicy,
+    /// Calendar feed rewritten on every analysis and scheduled refresh (BUGGER_CALENDAR_PATH).
+    pub calendar_path: Option<String>,
+    /// CODEOWNERS-style owners file used for owner suggestions (BUGGER_OWNERS_PATH).
+    pub owners_path: Option<String>,
+    /// Per-person load metrics in team mode (BUGGER_TEAM_METRICS), and whether exports include them.
+    pub team_metrics: bool,
+    pub export_team_metrics: bool,
+    pub export_stamp: ExportStamp,
+    /// Emoji, color and display name per category.
+    pub category_styles: CategoryStyles,
+    /// Filters narrowing which bugs are fetched.
+    pub query_filters: QueryFilters,
+    /// Fetch bugs from GitHub Issues or Jira instead of Azure DevOps (BUGGER_PROVIDER).
+    pub provider: ProviderConfig,
 }
 
 impl AppConfig {
     /// Load configuration from environment variables. Returns an error if any required variable is missing.
     pub fn from_env() -> anyhow::Result<Self> {
         load_env();
-        let org = env::var("AZURE_DEVOPS_ORG").map_err(|_| anyhow::anyhow!("Missing AZURE_DEVOPS_ORG"))?;
-        let project = env::var("AZURE_DEVOPS_PROJECT").map_err(|_| anyhow::anyhow!("Missing AZURE_DEVOPS_PROJECT"))?;
-        let user_email = env::var("AZURE_DEVOPS_USER_EMAIL").map_err(|_| anyhow::anyhow!("Missing AZURE_DEVOPS_USER_EMAIL"))?;
-        let azure_devops_pat = env::var("AZURE_DEVOPS_PAT").map_err(|_| anyhow::anyhow!("Missing AZURE_DEVOPS_PAT"))?;
-        let openai_api_key = env::var("OPENAI_API_KEY").ok();
+        let provider = ProviderConfig::from_env()?;
+        // Other providers stand in for org, project and my email, e.g. a GitHub repo and my login.
+        let (org, project, user_email) = match provider.stand_ins() {
+            Some(stand_ins) => stand_ins,
+            None => (
+                settings::var("AZURE_DEVOPS_ORG").ok_or_else(|| anyhow::anyhow!("Missing AZURE_DEVOPS_ORG"))?,
+                settings::var("AZURE_DEVOPS_PROJECT").ok_or_else(|| anyhow::anyhow!("Missing AZURE_DEVOPS_PROJECT"))?,
+                settings::var("AZURE_DEVOPS_USER_EMAIL").ok_or_else(|| anyhow::anyhow!("Missing AZURE_DEVOPS_USER_EMAIL"))?,
+            ),
+        };
+        // Secrets come from the OS keychain first.
+        let azure_devops_pat = match credentials::var("AZURE_DEVOPS_PAT") {
+            Some(pat) => pat,
+            None if provider.stand_ins().is_some() => String::new(),
+            None => anyhow::bail!("Missing AZURE_DEVOPS_PAT"),
+        };
+        let openai_api_key = credentials::var("OPENAI_API_KEY");
         let ai_enabled = openai_api_key.is_some();
+        let build_field = env::var("BUGGER_BUILD_FIELD").unwrap_or_else(|_| "Microsoft.VSTS.Build.FoundIn".to_string());
+        let mut custom_fields = env_list("BUGGER_CUSTOM_FIELDS");
+        if !custom_fields.contains(&build_field) {
+            custom_fields.push(build_field.clone());
+        }
+        let refresh_interval_minutes = env::var("BUGGER_REFRESH_MINUTES").ok().and_then(|v| v.parse().ok()).filter(|m| *m > 0);
         Ok(AppConfig {
+            base_url: azure_devops_base_url(),
             org,
             project,
             user_email,
+            user_aliases: env_list("BUGGER_USER_ALIASES"),
             azure_devops_pat,
+            secondary_pat: credentials::var("AZURE_DEVOPS_PAT_SECONDARY"),
+            http_retries: env::var("BUGGER_HTTP_RETRIES").ok().and_then(|v| v.parse().ok()).unwrap_or(4),
+            fetch_concurrency: env::var("BUGGER_FETCH_CONCURRENCY").ok().and_then(|v| v.parse().ok()).filter(|n| *n > 0).unwrap_or(6),
             openai_api_key,
+            openai_model: settings::var("OPENAI_MODEL").unwrap_or_else(|| "gpt-4o".to_string()),
             ai_enabled,
+            ai_triage: ai_enabled && env_flag("BUGGER_AI_TRIAGE", false),
+            ai_categorization: env_flag("BUGGER_AI_CATEGORIZATION", false),
+            refresh_interval_minutes,
+            refresh_schedule: env_recurrence("BUGGER_REFRESH_SCHEDULE"),
+            policy_schedule: env_recurrence("BUGGER_POLICY_SCHEDULE"),
+            notification_rules: NotificationRules::from_env(),
+            quiet_hours: QuietHours::from_env(),
+            custom_fields,
+            build_field,
+            hot_comment_threshold: env::var("BUGGER_HOT_COMMENT_THRESHOLD").ok().and_then(|v| v.parse().ok()).unwrap_or(8),
+            summary_comment_threshold: env::var("BUGGER_SUMMARY_COMMENT_THRESHOLD").ok().and_then(|v| v.parse().ok()).unwrap_or(10),
+            waiting_on_others_days: env::var("BUGGER_WAITING_DAYS").ok().and_then(|v| v.parse().ok()).unwrap_or(3),
+            stale_days: env::var("BUGGER_STALE_DAYS").ok().and_then(|v| v.parse().ok()).unwrap_or(30),
+            focus_top: env::var("BUGGER_FOCUS_TOP").ok().and_then(|v| v.parse().ok()).filter(|n| *n > 0),
+            achievements: env_flag("BUGGER_ACHIEVEMENTS", false),
+            auto_close: AutoClosePolicy::from_env(),
+            auto_assign: AutoAssignPolicy::from_env(),
+            out_of_office: OutOfOffice::from_env(),
+            escalation: EscalationPolicy::from_env(),
+            write_back: WriteBackPolicy::from_env(),
+            roles: Roles::from_env(),
+            undo_window_minutes: env::var("BUGGER_UNDO_WINDOW_MINUTES").ok().and_then(|v| v.parse().ok()).unwrap_or(60),
+            bug_cache: env_flag("BUGGER_BUG_CACHE", true),
+            asset_fetch_limit: env::var("BUGGER_ASSET_FETCH_LIMIT").ok().and_then(|v| v.parse().ok()).unwrap_or(50),
+            power: PowerPolicy::from_env(),
+            description_max_chars: env::var("BUGGER_DESCRIPTION_MAX_CHARS").ok().and_then(|v| v.parse().ok()).unwrap_or(20_000),
+            log_scan: LogScanConfig::from_env(),
+            error_codes: ErrorCodes::from_env(),
+            checklist: Checklist::from_env(),
+            bug_template: TemplateConfig::from_env(),
+            repo_roots: RepoRoots::from_env(),
+            sla: SlaPolicy::from_env(),
+            calendar_path: env::var("BUGGER_CALENDAR_PATH").ok().filter(|p| !p.trim().is_empty()),
+            owners_path: env::var("BUGGER_OWNERS_PATH").ok().filter(|p| !p.trim().is_empty()),
+            team_metrics: env_flag("BUGGER_TEAM_METRICS", false),
+            export_team_metrics: env_flag("BUGGER_EXPORT_TEAM_METRICS", false),
+            export_stamp: ExportStamp::from_env(),
+            category_styles: CategoryStyles::from_env(),
+            query_filters: QueryFilters::from_env(),
+            provider,
         })
     }
+
+    /// A bug's web page, in whichever tracker it comes from.
+    pub fn bug_url(&self, id: u64) -> String {
+        match &self.provider {
+            ProviderConfig::AzureDevOps => format!("{}/{}/{}/_workitems/edit/{}", self.base_url, self.org, self.project, id),
+            ProviderConfig::GitHub(github) => github.issue_url(id),
+            ProviderConfig::Jira(jira) => jira.issue_url(id),
+        }
+    }
+
+    /// My primary email followed by any aliases.
+    pub fn my_identities(&self) -> Vec<String> {
+        std::iter::once(self.user_email.clone()).chain(self.user_aliases.iter().cloned()).collect()
+    }
 }
 
+mod accomplishments;
+mod achievements;
+mod ai_categorize;
+use ai_categorize::Disagreement;
+mod ai_triage;
+use ai_triage::AiTriage;
+mod anonymize;
+mod assets;
+use assets::{prefetch as prefetch_assets, AssetCache};
+mod audit;
+mod auto_assign;
+use auto_assign::AutoAssignPolicy;
 mod azure_devops;
+mod baseline;
+mod blame;
+use blame::OwnerSuggestion;
 use azure_devops::AzureDevOpsClient;
 mod bug_analysis;
-use bug_analysis::{analyze_bugs, categorize_bugs, QuestionableCategory, BugCategory};
-use crate::azure_devops::Bug;
+use bug_analysis::{analyze_bugs, categorize_bugs, group_by_board_column, group_by_os_build, needs_environment, strip_html, BugCategory, BugReport, QuestionableCategory};
+use crate::azure_devops::{Bug, Identity};
+mod cache;
+mod calendar;
+use calendar::SlaPolicy;
+mod category_style;
+use category_style::CategoryStyles;
+mod checklist;
+use checklist::{Checklist, ChecklistScore};
+mod code_refs;
+mod compare;
+mod credentials;
+mod dashboard;
+use code_refs::{CodeRef, RepoRoots};
+mod description_diff;
+use description_diff::DescriptionChange;
+mod discussion;
+use discussion::{fetch_threads, find_hot_discussions, sparkline, DiscussionInsight};
+mod environment;
+mod escalation;
+use escalation::{Escalation, EscalationPolicy};
+mod error;
+use error::BuggerError;
+mod error_codes;
+use error_codes::{ErrorCodes, KnownCode};
+mod events;
+mod export;
+mod feed;
+mod fnv;
+mod focus;
+mod github;
+use focus::FocusSummary;
+use export::ExportStamp;
+mod http_trace;
+mod hygiene;
+use hygiene::HygieneScore;
+mod import;
+mod inflow;
+mod jira;
+use inflow::InflowAnomaly;
+mod log_scan;
+mod mcp;
+use log_scan::{scan_attachments, LogFinding, LogScanConfig};
+mod needs_info;
+mod notifications;
+mod odata;
+mod openai;
+mod out_of_office;
+use out_of_office::{AwayAssignee, OutOfOffice};
+mod outputs;
+mod ownership;
+use ownership::OwnershipSuggestion;
+use notifications::NotificationRules;
+mod pat;
+mod policy;
+mod power;
+mod provider;
+use provider::{IssueProvider, ProviderConfig};
+use power::PowerPolicy;
+mod process;
+mod quick_actions;
+mod quick_notes;
+use policy::AutoClosePolicy;
+mod quiet_hours;
+mod recurrence;
+use recurrence::Recurrence;
+mod roles;
+use roles::Roles;
+mod rules;
+use quiet_hours::QuietHours;
+mod refresh;
+mod rollup;
+use rollup::{build_feature_rollup, FeatureRollup};
+mod schema;
+mod screenshot;
+mod server;
+mod settings;
+mod shared_store;
+mod setup;
+mod singleflight;
+use singleflight::SingleFlight;
+mod stacktrace;
+mod team;
+mod template;
+mod thread_summary;
+use thread_summary::ThreadSummary;
+mod time_tracking;
+mod toast;
+use template::{TemplateCompliance, TemplateConfig};
+use team::PersonLoad;
+mod storage;
+mod trends;
+use trends::{group_by_build, BuildStats, Forecast};
+mod triage;
+mod verdict;
+mod waiting;
+mod work_session;
+mod write_back;
+use write_back::WriteBackPolicy;
+use waiting::{find_waiting_on_me, find_waiting_on_others, WaitingItem};
+mod wiql;
+use wiql::QueryFilters;
+
+/// Shared backend state: the bugs from the most recent fetch, reused by follow-up commands.
+#[derive(Default)]
+pub struct AppState {
+    pub bugs: Mutex<Vec<Bug>>,
+    /// Global dry-run switch: when set, every write is logged to the audit log instead of executed.
+    pub simulate: AtomicBool,
+    /// HTML of the most recent report, kept for export.
+    pub last_report: Mutex<Option<String>>,
+    /// Everyone who appears in the most recent report (assignees and commenters), for anonymized exports.
+    pub report_people: Mutex<Vec<Identity>>,
+    /// Frontend channels receiving bug update events.
+    pub subscribers: Mutex<Vec<tauri::ipc::Channel<events::UpdateBatch>>>,
+    /// Sequence number of the last published update batch.
+    pub update_sequence: AtomicU64,
+    /// Coalesces overlapping bug fetches (manual and scheduled) into one run.
+    pub refresh: SingleFlight<BugRefresh>,
+    /// Coalesces overlapping full analyses; every caller gets the same report.
+    pub analysis: SingleFlight<String>,
+    /// The running "work this bug" session, if any.
+    pub work_session: Mutex<Option<work_session::WorkSession>>,
+    /// Bugs from earlier fetches, so a refresh only downloads what changed.
+    pub bug_cache: cache::BugCache,
+}
+
+/// Result of one bug fetch: the cached list it replaced and the new list.
+#[derive(Debug, Clone)]
+pub struct BugRefresh {
+    pub previous: Vec<Bug>,
+    pub current: Vec<Bug>,
+    /// Requests the fetch retried after throttling or a transient server error.
+    pub retried: u32,
+}
+
+/// Longest Retry-After slept through during a refresh, as other callers wait on it; after a longer
+/// one the refresh ends and is run again once the wait is over.
+const REFRESH_RETRY_WAIT: std::time::Duration = std::time::Duration::from_secs(5);
+
+impl AppState {
+    /// Bugs from the last fetch, fetching them first if nothing has been loaded yet.
+    pub fn bugs_or_fetch(&self) -> Result<Vec<Bug>, BuggerError> {
+        let bugs = self.bugs.lock().map_err(|e| e.to_string())?.clone();
+        if bugs.is_empty() {
+            return Ok(self.refresh_bugs()?.current);
+        }
+        Ok(bugs)
+    }
+
+    /// Fetch bugs, replace the cache and publish update events. A call made while another refresh
+    /// is in flight waits for that one instead of hitting the API again.
+    pub fn refresh_bugs(&self) -> Result<BugRefresh, BuggerError> {
+        let mut throttled = None;
+        match self.refresh.run(|| self.refresh_once(&mut throttled)) {
+            Err(BuggerError::RateLimit(_)) if throttled.is_some() => {
+                let wait = throttled.unwrap_or_default();
+                log::warn!("Azure DevOps asked to wait {:.0}s; refreshing again after that", wait.as_secs_f64());
+                std::thread::sleep(wait);
+                self.refresh.run(|| self.refresh_once(&mut None))
+            }
+            result => result,
+        }
+    }
+
+    fn refresh_once(&self, throttled: &mut Option<std::time::Duration>) -> Result<BugRefresh, BuggerError> {
+        let (current, retried) = fetch_bugs(&self.bug_cache, throttled)?;
+        let previous = std::mem::replace(&mut *self.bugs.lock().map_err(|e| e.to_string())?, current.clone());
+        events::publish(self, &previous, &current);
+        Ok(BugRefresh { previous, current, retried })
+    }
+}
+
+/// Fetches all active bugs assigned to the configured user, and how many requests that retried;
+/// from Azure DevOps only the ones changed since the last fetch, unless the bug cache is off. A
+/// Retry-After longer than a refresh should wait is left in `throttled`.
+fn fetch_bugs(cache: &cache::BugCache, throttled: &mut Option<std::time::Duration>) -> Result<(Vec<Bug>, u32), BuggerError> {
+    let config = AppConfig::from_env()?;
+    let identities = config.my_identities();
+    let (mut bugs, retried) = if matches!(config.provider, ProviderConfig::AzureDevOps) {
+        let use_cache = config.bug_cache && cache.is_available();
+        let client = AzureDevOpsClient::new(config).with_max_retry_wait(REFRESH_RETRY_WAIT);
+        let bugs = if use_cache { cache.fetch(&client) } else { client.fetch_ids().and_then(|ids| client.fetch_details(&ids)) };
+        *throttled = client.throttled_for();
+        (bugs?, client.retried())
+    } else {
+        let provider = provider::from_config(config);
+        let ids = provider.fetch_ids()?;
+        (provider.fetch_details(&ids)?, 0)
+    };
+    merge_my_identities(&mut bugs, &identities);
+    Ok((bugs, retried))
+}
+
+/// Show bugs assigned to any of my aliases as assigned to my primary identity, so reports count me once.
+fn merge_my_identities(bugs: &mut [Bug], identities: &[String]) {
+    let is_mine = |who: &Identity| identities.iter().any(|i| i.eq_ignore_ascii_case(&who.unique_name));
+    let primary = bugs
+        .iter()
+        .filter_map(|b| b.assigned_to.clone())
+        .find(|a| a.unique_name.eq_ignore_ascii_case(&identities[0]));
+    for bug in bugs.iter_mut() {
+        if let Some(assignee) = bug.assigned_to.as_mut().filter(|a| is_mine(a)) {
+            match &primary {
+                Some(primary) => *assignee = primary.clone(),
+                None => assignee.unique_name = identities[0].clone(),
+            }
+        }
+    }
+}
+
+/// Last segment of a field reference name for display ("Custom.CustomerName" -> "CustomerName").
+fn short_field_name(reference_name: &str) -> &str {
+    reference_name.rsplit('.').next().unwrap_or(reference_name)
+}
+
+/// Optional report sections computed alongside the core actionable/questionable analysis.
+struct ReportSections {
+    rollup: Vec<FeatureRollup>,
+    builds: Vec<BuildStats>,
+    /// Backlog health score and the previous run's score, if there is one.
+    hygiene: (HygieneScore, Option<u32>),
+    /// Backlog burn-down forecast, once there are a few weeks of daily snapshots.
+    forecast: Option<Forecast>,
+    discussions: Vec<DiscussionInsight>,
+    /// Gist, open question and who owes a response for long threads.
+    thread_summaries: std::collections::HashMap<u64, ThreadSummary>,
+    waiting_on_me: Vec<WaitingItem>,
+    waiting_on_others: Vec<WaitingItem>,
+    assets: AssetCache,
+    styles: CategoryStyles,
+    description_max_chars: usize,
+    log_findings: std::collections::HashMap<u64, Vec<LogFinding>>,
+    /// Crash signature (top meaningful frame) and symbol hint per bug, from the description or attached logs.
+    crash_signatures: std::collections::HashMap<u64, (String, Option<String>)>,
+    /// Known error codes mentioned in each bug's title, description or attached logs.
+    error_codes: std::collections::HashMap<u64, Vec<KnownCode>>,
+    checklists: std::collections::HashMap<u64, ChecklistScore>,
+    /// Word diffs of descriptions edited since an earlier run, for the last week; empty unless it's my report.
+    description_changes: std::collections::HashMap<u64, DescriptionChange>,
+    /// Template sections each bug fills in; empty when no template is configured or found.
+    template: std::collections::HashMap<u64, TemplateCompliance>,
+    /// Files mentioned in each bug that exist in a configured local clone.
+    code_refs: std::collections::HashMap<u64, Vec<CodeRef>>,
+    /// Recent authors and commits of the files each bug mentions.
+    owners: std::collections::HashMap<u64, OwnerSuggestion>,
+    /// Suggested assignees for unassigned and likely misassigned bugs.
+    ownership: std::collections::HashMap<u64, OwnershipSuggestion>,
+    /// Unusual spikes in new bugs compared to the trailing baseline.
+    inflow_anomalies: Vec<InflowAnomaly>,
+    /// Empty unless team mode and team metrics are both enabled.
+    team_load: Vec<PersonLoad>,
+    /// Urgent bugs whose assignee is out of office; team mode only.
+    away_assignees: Vec<AwayAssignee>,
+    /// Bugs past an escalation rule; empty while escalation is off.
+    escalations: Vec<Escalation>,
+    /// Bugs the keyword lists and AI categorize differently; empty unless AI categorization or triage is on.
+    category_disagreements: Vec<Disagreement>,
+    /// AI summary and verdict per bug; empty unless AI triage is on.
+    ai_triage: std::collections::HashMap<u64, AiTriage>,
+    /// Set in focus mode; the bugs passed to the report are then only the focused ones.
+    focus: Option<FocusSummary>,
+    /// Earned streak and cleanup badges; empty unless achievements are on.
+    achievements: Vec<String>,
+}
+
+fn explain_error_codes(bugs: &[Bug], log_findings: &std::collections::HashMap<u64, Vec<LogFinding>>, codes: &ErrorCodes) -> std::collections::HashMap<u64, Vec<KnownCode>> {
+    bugs.iter()
+        .filter_map(|bug| {
+            let mut text = format!("{}\n{}", bug.title, bug.description.as_deref().unwrap_or(""));
+            for finding in log_findings.get(&bug.id).into_iter().flatten() {
+                text.push('\n');
+                text.push_str(&finding.line);
+            }
+            let known = codes.explain(&text);
+            (!known.is_empty()).then_some((bug.id, known))
+        })
+        .collect()
+}
+
+/// Crash signatures of Crash-category bugs: from a stack trace in the description, else from attached
+/// logs, else the first exception or bugcheck code in the title or description.
+fn find_crash_signatures(bugs: &[Bug], log_findings: &std::collections::HashMap<u64, Vec<LogFinding>>) -> std::collections::HashMap<u64, (String, Option<String>)> {
+    bugs.iter()
+        .filter(|b| bug_analysis::categorize_bug(b) == BugCategory::Crash)
+        .filter_map(|bug| {
+            let from_description = bug.description.as_deref().and_then(stacktrace::parse).and_then(|trace| {
+                let signature = trace.signature()?.to_string();
+                Some((signature, trace.symbol_hint()))
+            });
+            let from_logs = || {
+                log_findings.get(&bug.id)?.iter().find(|f| f.signature == log_scan::CRASH_SIGNATURE).map(|f| (f.line.clone(), None))
+            };
+            let from_code = || {
+                let text = format!("{} {}", bug.title, bug.description.as_deref().unwrap_or(""));
+                stacktrace::exception_code(&text).map(|code| (code, None))
+            };
+            Some((bug.id, from_description.or_else(from_logs).or_else(from_code)?))
+        })
+        .collect()
+}
+
+/// Description HTML for the report. Oversized descriptions (pasted logs) are cut to a plain-text preview
+/// with a button that loads the full content on demand.
+fn render_description(bug: &Bug, desc: &str, sections: &ReportSections) -> String {
+    if desc.len() <= sections.description_max_chars {
+        return sections.assets.inline_images(desc);
+    }
+    let preview: String = strip_html(desc).chars().take(sections.description_max_chars).collect();
+    format!(
+        "{}<br><button class='show-more-btn' data-bug-id='{}'>Show full description ({} KB)</button>",
+        html_escape::encode_text(&preview),
+        bug.id,
+        desc.len() / 1024
+    )
+}
 
 /// Generate an HTML report from bug analysis results.
-fn generate_bug_report_html(actionable: &[Bug], questionable: &[(Bug, QuestionableCategory)], categorized: &std::collections::HashMap<BugCategory, Vec<&Bug>>) -> String {
+fn generate_bug_report_html(actionable: &[Bug], questionable: &[(Bug, QuestionableCategory)], categorized: &std::collections::HashMap<BugCategory, Vec<&Bug>>, sections: &ReportSections) -> String {
     let mut html = String::new();
+    for anomaly in &sections.inflow_anomalies {
+        html.push_str(&format!("<div class='warning'>📈 <b>Unusual inflow:</b> {}</div>", html_escape::encode_text(&anomaly.summary(&sections.styles))));
+    }
+    html.push_str(&hygiene::html(&sections.hygiene.0, sections.hygiene.1));
+    if let Some(focus) = &sections.focus {
+        html.push_str(&focus.html());
+    }
+    // Stats cover the whole backlog, also in focus mode.
+    let (total_actionable, total_questionable) = sections.focus.as_ref().map_or((actionable.len(), questionable.len()), |f| (f.total_actionable, f.total_questionable));
     html.push_str("<h2>📈 Bug Stats</h2><ul>");
-    html.push_str(&format!("<li><b>Total active bugs:</b> {}</li>", actionable.len() + questionable.len()));
-    html.push_str(&format!("<li><b>Actionable bugs:</b> {}</li>", actionable.len()));
-    html.push_str(&format!("<li><b>Questionable bugs:</b> {}</li>", questionable.len()));
+    html.push_str(&format!("<li><b>Total active bugs:</b> {}</li>", total_actionable + total_questionable));
+    html.push_str(&format!("<li><b>Actionable bugs:</b> {}</li>", total_actionable));
+    html.push_str(&format!("<li><b>Questionable bugs:</b> {}</li>", total_questionable));
+    if !sections.checklists.is_empty() {
+        let total: u32 = sections.checklists.values().map(|c| c.percent()).sum();
+        html.push_str(&format!("<li><b>Average checklist score:</b> {}%</li>", total / sections.checklists.len() as u32));
+    }
+    if !sections.template.is_empty() {
+        let total: u32 = sections.template.values().map(|t| t.percent()).sum();
+        let incomplete = sections.template.values().filter(|t| !t.missing.is_empty()).count();
+        html.push_str(&format!(
+            "<li><b>Template compliance:</b> {}% ({} bug(s) missing sections)</li>",
+            total / sections.template.len() as u32,
+            incomplete
+        ));
+    }
     html.push_str("</ul>");
-    // Get org and project for links
-    let (org, project) = if let Some(first) = actionable.first().or_else(|| questionable.first().map(|(b, _)| b)) {
-        // Try to get from env (since Bug struct doesn't have org/project)
-        let org = std::env::var("AZURE_DEVOPS_ORG").unwrap_or_else(|_| "ORG".to_string());
-        let project = std::env::var("AZURE_DEVOPS_PROJECT").unwrap_or_else(|_| "PROJECT".to_string());
-        (org, project)
-    } else {
-        ("ORG".to_string(), "PROJECT".to_string())
-    };
-    let bug_url = |id: u64| -> String {
-        format!("https://dev.azure.com/{}/{}/_workitems/edit/{}", org, project, id)
-    };
+    if !sections.team_load.is_empty() {
+        html.push_str(&team::section_html(&sections.team_load));
+    }
+    let config = AppConfig::from_env().ok();
+    let bug_url = |id: u64| -> String { config.as_ref().map_or_else(|| format!("{}/ORG/PROJECT/_workitems/edit/{}", azure_devops_base_url(), id), |c| c.bug_url(id)) };
+    if !sections.away_assignees.is_empty() {
+        html.push_str(&out_of_office::section_html(&sections.away_assignees, bug_url));
+    }
+    if !sections.escalations.is_empty() {
+        html.push_str(&escalation::section_html(&sections.escalations, bug_url));
+    }
+    if !sections.waiting_on_me.is_empty() {
+        html.push_str("<h2>⏳ Waiting on You</h2><div class='warning'>Someone asked you something on these bugs and is blocked on your reply.</div><ul>");
+        for item in &sections.waiting_on_me {
+            html.push_str(&format!(
+                "<li><b><a href=\"{}\" target=\"_blank\">#{}</a>:</b> {} asked {} day(s) ago<br><small>{}</small></li>",
+                bug_url(item.bug_id),
+                item.bug_id,
+                html_escape::encode_text(&item.counterpart),
+                item.days_waiting,
+                html_escape::encode_text(&item.excerpt)
+            ));
+        }
+        html.push_str("</ul>");
+    }
+    if !sections.waiting_on_others.is_empty() {
+        html.push_str("<h2>📨 Waiting on Reporter</h2><ul>");
+        for item in &sections.waiting_on_others {
+            html.push_str(&format!(
+                "<li><b><a href=\"{}\" target=\"_blank\">#{}</a>:</b> no answer from {} for {} day(s) <button class='nudge-btn' data-bug-id='{}'>✉️ Nudge</button><br><small>{}</small></li>",
+                bug_url(item.bug_id),
+                item.bug_id,
+                html_escape::encode_text(&item.counterpart),
+                item.days_waiting,
+                item.bug_id,
+                html_escape::encode_text(&item.excerpt)
+            ));
+        }
+        html.push_str("</ul>");
+    }
+    if !sections.discussions.is_empty() {
+        html.push_str("<h2>🔥 Needs a Conversation</h2><div class='warning'>These threads are going in circles — schedule a call instead of adding comments.</div><ul>");
+        for insight in &sections.discussions {
+            let mut reasons = Vec::new();
+            if insight.escalating {
+                reasons.push("escalating frustration");
+            }
+            if insight.circular_repro {
+                reasons.push("circular cannot-repro exchange");
+            }
+            html.push_str(&format!(
+                "<li><b><a href=\"{}\" target=\"_blank\">#{}</a>:</b> {} <small>({} comments)</small><br><small>Tone over time: <code>{}</code></small></li>",
+                bug_url(insight.bug_id),
+                insight.bug_id,
+                reasons.join(", "),
+                insight.timeline.len(),
+                sparkline(&insight.timeline)
+            ));
+        }
+        html.push_str("</ul>");
+    }
     if !questionable.is_empty() {
         html.push_str("<details open><summary>❓ Questionable Non-Actionable Bugs</summary><div class='warning'>Review these first to clean up your backlog before focusing on actionable bugs.</div><ul>");
         for (bug, cat) in questionable {
             html.push_str(&format!(
-                "<li><b><a href=\"{}\" target=\"_blank\">#{}</a>:</b> {}<br><span class='category-Other'><small>Reason: {:?}</small></span></li>",
+                "<li><b><a href=\"{}\" target=\"_blank\">#{}</a>:</b> {} {} <button class='needs-info-btn' data-bug-id='{}'>📝 Ask for info</button><br><span class='category' style='color:{} !important'><small>Reason: {:?}</small></span>{}</li>",
                 bug_url(bug.id),
                 bug.id,
                 html_escape::encode_text(&bug.title),
-                cat
+                sections.checklists.get(&bug.id).map(|c| c.badge()).unwrap_or_default(),
+                bug.id,
+                sections.styles.get(&BugCategory::Other).color,
+                cat,
+                sections.ai_triage.get(&bug.id).map(|t| t.html(false)).unwrap_or_default()
             ));
         }
         html.push_str("</ul></details>");
     }
+    let bug_icon = sections
+        .assets
+        .bug_icon
+        .as_deref()
+        .map(|uri| format!("<img class='wit-icon' src='{}' width='16' height='16' alt=''> ", uri))
+        .unwrap_or_default();
+    let mut crash_ids: Vec<&u64> = sections.crash_signatures.keys().collect();
+    crash_ids.sort();
+    let crash_groups = stacktrace::group_signatures(crash_ids.into_iter().map(|id| (*id, sections.crash_signatures[id].0.as_str())));
+    if !crash_groups.is_empty() {
+        let mut groups: Vec<(&String, &Vec<u64>)> = crash_groups.iter().collect();
+        groups.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.0.cmp(b.0)));
+        html.push_str("<h2>💥 Crash Signatures</h2><ul>");
+        for (signature, ids) in groups {
+            let links: Vec<String> = ids.iter().map(|id| format!("<a href=\"{}\" target=\"_blank\">#{}</a>", bug_url(*id), id)).collect();
+            html.push_str(&format!("<li><code>{}</code> — <b>{}</b> bug(s): {}</li>", html_escape::encode_text(signature), ids.len(), links.join(", ")));
+        }
+        html.push_str("</ul>");
+    }
     html.push_str("<h2>🗂️ Actionable Bug Categories</h2>");
     for (cat, bugs) in categorized {
-        let cat_class = format!("category-{:?}", cat);
-        html.push_str(&format!("<details><summary><span class='{}'>{:?} ({})</span></summary><ul>", cat_class, cat, bugs.len()));
+        html.push_str(&format!("<details><summary>{}</summary><ul>", sections.styles.html(cat, Some(bugs.len()))));
         for bug in bugs.iter() {
+            let avatar = sections
+                .assets
+                .avatar_for(bug)
+                .map(|uri| format!(" <img class='avatar' src='{}' width='16' height='16' alt=''>", uri))
+                .unwrap_or_default();
+            let assignee = bug.assigned_to.as_ref().map(|a| a.display_name.as_str()).unwrap_or("-");
+            let checklist = sections.checklists.get(&bug.id);
+            let ask_for_info = checklist
+                .filter(|c| c.missing.iter().any(|m| matches!(m, checklist::Check::Repro | checklist::Check::Environment)))
+                .map(|_| format!(" <button class='needs-info-btn' data-bug-id='{}'>📝 Ask for info</button>", bug.id))
+                .unwrap_or_default();
             html.push_str(&format!(
-                "<li><b><a href=\"{}\" target=\"_blank\">#{}</a>:</b> {}<br><small>State: {} | Created: {}</small>",
+                "<li>{}<b><a href=\"{}\" target=\"_blank\">#{}</a>:</b> {} {}{} <button class='work-session-btn' data-bug-id='{}'>🍅 Work on this</button><br><small>State: {} | Created: {} |{} {}</small>",
+                bug_icon,
                 bug_url(bug.id),
                 bug.id,
                 html_escape::encode_text(&bug.title),
-                html_escape::encode_text(&bug.state),
-                bug.created_date.as_deref().unwrap_or("-")
+                checklist.map(|c| c.badge()).unwrap_or_default(),
+                ask_for_info,
+                bug.id,
+                sections.assets.state_badge(&bug.state),
+                bug.created_date.as_deref().unwrap_or("-"),
+                avatar,
+                html_escape::encode_text(assignee)
             ));
+            if let Some(triage) = sections.ai_triage.get(&bug.id) {
+                html.push_str(&triage.html(true));
+            }
+            for (name, value) in &bug.custom_fields {
+                html.push_str(&format!(
+                    "<br><small>{}: {}</small>",
+                    html_escape::encode_text(short_field_name(name)),
+                    html_escape::encode_text(value)
+                ));
+            }
+            if let Some((signature, hint)) = sections.crash_signatures.get(&bug.id) {
+                let same: Vec<String> = crash_groups
+                    .get(signature)
+                    .into_iter()
+                    .flatten()
+                    .filter(|id| **id != bug.id)
+                    .map(|id| format!("<a href=\"{}\" target=\"_blank\">#{}</a>", bug_url(*id), id))
+                    .collect();
+                html.push_str(&format!("<br><small>💥 Crash signature: <code>{}</code>", html_escape::encode_text(signature)));
+                if !same.is_empty() {
+                    html.push_str(&format!(" · same crash as {}", same.join(", ")));
+                }
+                if let Some(hint) = hint {
+                    html.push_str(&format!(" · <i>{}</i>", html_escape::encode_text(hint)));
+                }
+                html.push_str("</small>");
+            }
+            for finding in sections.log_findings.get(&bug.id).into_iter().flatten().filter(|f| f.signature != log_scan::CRASH_SIGNATURE) {
+                html.push_str(&format!(
+                    "<br><small>🔎 {} in {}: <code>{}</code></small>",
+                    html_escape::encode_text(&finding.signature),
+                    html_escape::encode_text(&finding.attachment),
+                    html_escape::encode_text(&finding.line)
+                ));
+            }
+            for rule in rules::matching(bug, rules::RuleSeverity::Warning) {
+                html.push_str(&format!("<br><small>⚠️ {}</small>", rule.name()));
+            }
+            let environment = bug.environment.summary();
+            if !environment.is_empty() {
+                html.push_str(&format!("<br><small>🖥️ {}</small>", html_escape::encode_text(&environment)));
+            } else if needs_environment(bug, cat) {
+                html.push_str("<br><small>⚠️ No environment info (OS, build or device) — ask the reporter</small>");
+            }
+            for known in sections.error_codes.get(&bug.id).into_iter().flatten() {
+                html.push_str(&format!(
+                    "<br><small>📖 <code>{}</code> {} — {}</small>",
+                    known.display_code(),
+                    html_escape::encode_text(&known.name),
+                    html_escape::encode_text(&known.explanation)
+                ));
+            }
+            if let Some(compliance) = sections.template.get(&bug.id).filter(|t| !t.missing.is_empty()) {
+                html.push_str(&format!(
+                    "<br><small>🧾 Missing template sections: {}</small>",
+                    html_escape::encode_text(&compliance.missing.join(", "))
+                ));
+            }
+            for code_ref in sections.code_refs.get(&bug.id).into_iter().flatten() {
+                html.push_str(&format!(
+                    "<br><small>🧭 <code>{}</code> ({}) <button class='open-editor-btn' data-path='{}' data-line='{}'>Open in VS Code</button></small>",
+                    html_escape::encode_text(&code_ref.label()),
+                    html_escape::encode_text(&code_ref.repo),
+                    html_escape::encode_single_quoted_attribute(&code_ref.local.to_string_lossy()),
+                    code_ref.line.unwrap_or(1)
+                ));
+            }
+            if let Some(suggestion) = sections.owners.get(&bug.id).filter(|s| !s.owners.is_empty()) {
+                let owners: Vec<String> = suggestion
+                    .owners
+                    .iter()
+                    .map(|o| format!("{} ({})", html_escape::encode_text(&o.name), o.score))
+                    .collect();
+                html.push_str(&format!("<br><small>👤 Likely owners: {}</small>", owners.join(", ")));
+                for commit in &suggestion.commits {
+                    html.push_str(&format!(
+                        "<br><small>📝 <code>{}</code> {} — {}, {}</small>",
+                        commit.short_hash(),
+                        html_escape::encode_text(&commit.subject),
+                        html_escape::encode_text(&commit.author),
+                        commit.date
+                    ));
+                }
+            }
+            if let Some(suggestion) = sections.ownership.get(&bug.id) {
+                html.push_str(&suggestion.html());
+            }
+            if let Some(summary) = sections.thread_summaries.get(&bug.id) {
+                html.push_str(&summary.html());
+            }
+            if let Some(change) = sections.description_changes.get(&bug.id) {
+                html.push_str(&description_diff::html(change));
+            }
             if let Some(desc) = &bug.description {
                 if !desc.trim().is_empty() {
                     html.push_str(&format!(
-                        "<br><details><summary>Description</summary><div style='white-space:pre-wrap'>{}</div></details>",
-                        desc
+                        "<br><details><summary>Description</summary><div class='description' style='white-space:pre-wrap'>{}</div></details>",
+                        render_description(bug, desc, sections)
                     ));
                 }
             }
@@ -104,31 +834,440 @@ fn generate_bug_report_html(actionable: &[Bug], questionable: &[(Bug, Questionab
         }
         html.push_str("</ul></details>");
     }
+    if !sections.category_disagreements.is_empty() {
+        html.push_str(&format!(
+            "<details><summary>🔀 Keyword vs. AI Categories ({})</summary><div class='warning'>The keyword lists and AI disagree on these bugs. A pair that keeps coming up usually means a keyword list needs adjusting.</div><ul>",
+            sections.category_disagreements.len()
+        ));
+        for d in &sections.category_disagreements {
+            html.push_str(&format!(
+                "<li><b><a href=\"{}\" target=\"_blank\">#{}</a>:</b> {}<br><small>Keywords: {} · AI: {}</small></li>",
+                bug_url(d.bug_id),
+                d.bug_id,
+                html_escape::encode_text(&d.title),
+                sections.styles.html(&d.keyword, None),
+                sections.styles.html(&d.ai, None)
+            ));
+        }
+        html.push_str("</ul></details>");
+    }
+    html.push_str("<h2>📋 Board Columns</h2>");
+    let all_bugs = actionable.iter().chain(questionable.iter().map(|(b, _)| b));
+    for (column, bugs) in group_by_board_column(all_bugs) {
+        html.push_str(&format!("<details><summary>{} ({})</summary><ul>", html_escape::encode_text(&column), bugs.len()));
+        for bug in bugs {
+            html.push_str(&format!(
+                "<li><b><a href=\"{}\" target=\"_blank\">#{}</a>:</b> {}<br><small>State: {}</small></li>",
+                bug_url(bug.id),
+                bug.id,
+                html_escape::encode_text(&bug.title),
+                sections.assets.state_badge(&bug.state)
+            ));
+        }
+        html.push_str("</ul></details>");
+    }
+    let all_bugs = actionable.iter().chain(questionable.iter().map(|(b, _)| b));
+    let builds = group_by_os_build(all_bugs);
+    if builds.iter().any(|(build, _)| build != "(Unknown)") {
+        html.push_str("<h2>🪟 Bugs by OS Build</h2>");
+        for (build, bugs) in builds {
+            html.push_str(&format!("<details><summary>{} ({})</summary><ul>", html_escape::encode_text(&build), bugs.len()));
+            for bug in bugs {
+                html.push_str(&format!(
+                    "<li><b><a href=\"{}\" target=\"_blank\">#{}</a>:</b> {}<br><small>{}</small></li>",
+                    bug_url(bug.id),
+                    bug.id,
+                    html_escape::encode_text(&bug.title),
+                    html_escape::encode_text(&bug.environment.summary())
+                ));
+            }
+            html.push_str("</ul></details>");
+        }
+    }
+    if !sections.rollup.is_empty() {
+        html.push_str("<h2>🎯 Features at Risk</h2><ul>");
+        for entry in &sections.rollup {
+            let ids: Vec<String> = entry
+                .bug_ids
+                .iter()
+                .map(|id| format!("<a href=\"{}\" target=\"_blank\">#{}</a>", bug_url(*id), id))
+                .collect();
+            html.push_str(&format!(
+                "<li><b><a href=\"{}\" target=\"_blank\">{} #{}</a>:</b> {} — <b>{}</b> open bug(s)<br><small>{}</small></li>",
+                bug_url(entry.feature.id),
+                html_escape::encode_text(&entry.feature.work_item_type),
+                entry.feature.id,
+                html_escape::encode_text(&entry.feature.title),
+                entry.bug_ids.len(),
+                ids.join(", ")
+            ));
+        }
+        html.push_str("</ul>");
+    }
+    if !sections.builds.is_empty() || sections.forecast.is_some() {
+        html.push_str("<h2>📈 Trends</h2>");
+    }
+    if let Some(forecast) = &sections.forecast {
+        html.push_str(&format!(
+            "<h3>Backlog forecast</h3><p>{}.<br><small>{} actionable bug(s) · {:.1} new and {:.1} fixed per week over the last {} weeks</small></p>",
+            forecast.summary(),
+            forecast.backlog,
+            forecast.inflow_per_week,
+            forecast.outflow_per_week,
+            forecast.history_weeks
+        ));
+    }
+    if !sections.builds.is_empty() {
+        html.push_str("<h3>Bugs by build</h3><ul>");
+        for stats in &sections.builds {
+            html.push_str(&format!(
+                "<li>{}<b>{}</b>: {} bug(s){}</li>",
+                if stats.spike { "⚠️ " } else { "" },
+                html_escape::encode_text(&stats.build),
+                stats.bug_ids.len(),
+                if stats.spike { " <span class='warning'>abnormal spike</span>" } else { "" }
+            ));
+        }
+        html.push_str("</ul>");
+    }
+    if !sections.achievements.is_empty() {
+        html.push_str(&achievements::footer_html(&sections.achievements));
+    }
     html
 }
 
 #[tauri::command]
-/// Fetches and analyzes bugs, returning an HTML report. Errors are returned as strings.
-fn fetch_and_analyze_bugs() -> Result<String, String> {
+/// Fetches and analyzes bugs, returning an HTML report. Errors say what kind of failure it was,
+/// with a hint on fixing it.
+fn fetch_and_analyze_bugs(app: tauri::AppHandle, state: tauri::State<AppState>) -> Result<String, BuggerError> {
     info!("[Tauri backend] fetch_and_analyze_bugs called");
-    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
-    let client = AzureDevOpsClient::new(config);
-    let ids = client.fetch_active_bugs().map_err(|e| e.to_string())?;
-    if ids.is_empty() {
+    state.analysis.run(|| analyze(&app, &state))
+}
+
+#[tauri::command]
+/// Fetches and analyzes bugs, returning the actionable and questionable bugs, categories and stats as
+/// data. `fetch_and_analyze_bugs` renders the full HTML report instead.
+fn get_bug_report(state: tauri::State<AppState>) -> Result<BugReport, BuggerError> {
+    let refresh = state.refresh_bugs()?;
+    let mut report = BugReport::from(analyze_bugs(refresh.current));
+    report.stats.retried = refresh.retried;
+    Ok(report)
+}
+
+fn analyze(app: &tauri::AppHandle, state: &AppState) -> Result<String, BuggerError> {
+    let BugRefresh { current: all_bugs, retried, .. } = state.refresh_bugs()?;
+    if all_bugs.is_empty() {
         return Ok("<b>No active bugs assigned to you.</b>".to_string());
     }
-    let all_bugs = client.fetch_bug_details(&ids).map_err(|e| e.to_string())?;
     info!("[Tauri backend] Found {} bugs", all_bugs.len());
-    let analysis = analyze_bugs(all_bugs);
+    let mut html = build_report(app, state, all_bugs, true)?;
+    if retried > 0 {
+        html.insert_str(0, &format!("<div class='warning'>⏳ <b>Throttled:</b> Azure DevOps was slowing requests down or briefly failing; retried {} time{}.</div>", retried, if retried == 1 { "" } else { "s" }));
+    }
+    *state.last_report.lock().map_err(|e| e.to_string())? = Some(html.clone());
+    Ok(html)
+}
+
+/// Run the full analysis over `all_bugs` and render the report. With `mine` unset (an ad-hoc set of
+/// someone else's bugs) nothing is recorded: no backlog history, calendar feed or inflow baseline.
+fn build_report(app: &tauri::AppHandle, state: &AppState, all_bugs: Vec<Bug>, mine: bool) -> Result<String, BuggerError> {
+    let config = AppConfig::from_env()?;
+    bug_analysis::reload_taxonomy();
+    let builds = group_by_build(&all_bugs, &config.build_field);
+    let actionable_ids = all_bugs.iter().filter(|b| bug_analysis::is_questionable(b).is_none()).map(|b| b.id).collect();
+    let forecast = if !mine {
+        None
+    } else {
+        match trends::record_backlog(app, chrono::Local::now().date_naive(), actionable_ids) {
+            Ok(history) => trends::forecast(&history),
+            Err(e) => {
+                log::warn!("Backlog history unavailable: {}", e);
+                None
+            }
+        }
+    };
+    let hot_comment_threshold = config.hot_comment_threshold;
+    let identities = config.my_identities();
+    let waiting_on_others_days = config.waiting_on_others_days;
+    // Discussions, attachments, parents, fix history and the bug template are read from Azure DevOps;
+    // other providers' reports go without them.
+    let ado = matches!(config.provider, ProviderConfig::AzureDevOps);
+    // Cached avatars and log scans are still shown; only new downloads wait.
+    let asset_fetch_limit = if ado && config.power.check_heavy_work("attachment and avatar prefetching") { config.asset_fetch_limit } else { 0 };
+    let client = AzureDevOpsClient::new(config);
+    let threads = if ado { fetch_threads(&client, &all_bugs) } else { std::collections::HashMap::new() };
+    let now = chrono::Utc::now();
+    if mine {
+        *state.report_people.lock().map_err(|e| e.to_string())? = threads
+            .values()
+            .flatten()
+            .map(|c| Identity { display_name: c.author.clone(), unique_name: c.author_email.clone(), avatar_url: None })
+            .collect();
+    }
+    let team_load = if ado && client.config.team_metrics && client.config.query_filters.is_team_mode() {
+        let resolvers = team::fetch_recent_resolvers(&client).unwrap_or_else(|e| {
+            log::warn!("Team throughput unavailable: {}", e);
+            Vec::new()
+        });
+        team::team_load(&all_bugs, &resolvers, now)
+    } else {
+        Vec::new()
+    };
+    if mine && ado {
+        calendar::update_feed(app, &client.config, &all_bugs, &threads);
+    }
+    let log_findings = if ado { scan_attachments(app, &client, &all_bugs, &client.config.log_scan, asset_fetch_limit) } else { std::collections::HashMap::new() };
+    let code_refs: std::collections::HashMap<u64, Vec<CodeRef>> = all_bugs
+        .iter()
+        .map(|bug| (bug.id, code_refs::find(&format!("{}\n{}", bug.title, bug.description.as_deref().unwrap_or("")), &client.config.repo_roots)))
+        .filter(|(_, refs)| !refs.is_empty())
+        .collect();
+    let template_sections = if ado { client.config.bug_template.load(&client) } else { Vec::new() };
+    let ai_triage = ai_triage::triage(app, &client.config, &all_bugs, mine);
+    let mut ai_categories = ai_categorize::categorize(app, &client.config, &all_bugs, mine);
+    // Without AI categorization, the triage's suggested categories are compared instead.
+    if ai_categories.is_empty() {
+        ai_categories = ai_triage.iter().map(|(id, t)| (*id, t.category.clone())).collect();
+    }
+    let checklists: std::collections::HashMap<u64, ChecklistScore> = all_bugs
+        .iter()
+        .map(|bug| (bug.id, client.config.checklist.score(bug, log_findings.get(&bug.id).is_some_and(|f| !f.is_empty()))))
+        .collect();
+    let hygiene = hygiene::score(&all_bugs, &checklists, &client.config.sla, client.config.stale_days, now);
+    let previous_hygiene = if !mine {
+        None
+    } else {
+        hygiene::record(app, chrono::Local::now().date_naive(), hygiene.score).unwrap_or_else(|e| {
+            log::warn!("Hygiene history unavailable: {}", e);
+            None
+        })
+    };
+    let description_changes = if mine {
+        description_diff::update(app, &all_bugs, now).unwrap_or_else(|e| {
+            log::warn!("Description history unavailable: {}", e);
+            std::collections::HashMap::new()
+        })
+    } else {
+        std::collections::HashMap::new()
+    };
+    let badges = if mine && client.config.achievements {
+        achievements::update(app, &all_bugs, &hygiene, client.config.stale_days, now).unwrap_or_else(|e| {
+            log::warn!("Achievements unavailable: {}", e);
+            Vec::new()
+        })
+    } else {
+        Vec::new()
+    };
+    let mut sections = ReportSections {
+        rollup: if ado {
+            build_feature_rollup(&client, &all_bugs).unwrap_or_else(|e| {
+                log::warn!("Feature rollup unavailable: {}", e);
+                Vec::new()
+            })
+        } else {
+            Vec::new()
+        },
+        builds,
+        hygiene: (hygiene, previous_hygiene),
+        forecast,
+        discussions: find_hot_discussions(&threads, hot_comment_threshold),
+        thread_summaries: thread_summary::summarize(app, &client.config, &all_bugs, &threads, client.config.summary_comment_threshold, mine),
+        waiting_on_me: find_waiting_on_me(&threads, &identities, now),
+        waiting_on_others: find_waiting_on_others(&threads, &identities, waiting_on_others_days, now),
+        assets: prefetch_assets(app, &client, &all_bugs, asset_fetch_limit),
+        styles: client.config.category_styles.clone(),
+        description_max_chars: client.config.description_max_chars,
+        crash_signatures: find_crash_signatures(&all_bugs, &log_findings),
+        error_codes: explain_error_codes(&all_bugs, &log_findings, &client.config.error_codes),
+        checklists,
+        description_changes,
+        template: if template_sections.is_empty() {
+            std::collections::HashMap::new()
+        } else {
+            all_bugs.iter().map(|bug| (bug.id, template::check(bug, &template_sections))).collect()
+        },
+        owners: code_refs.iter().map(|(id, refs)| (*id, blame::suggest_owners(refs))).collect(),
+        ownership: if ado { ownership::suggestions(app, &client, &all_bugs) } else { std::collections::HashMap::new() },
+        code_refs,
+        log_findings,
+        inflow_anomalies: if mine { inflow::check(app, &client.config, &all_bugs) } else { Vec::new() },
+        team_load,
+        escalations: escalation::escalated(app, &all_bugs, &client.config.escalation, now),
+        away_assignees: if client.config.query_filters.is_team_mode() {
+            out_of_office::urgent_while_away(&all_bugs, &out_of_office::current(app, &client.config), &client.config.sla, now)
+        } else {
+            Vec::new()
+        },
+        category_disagreements: ai_categorize::disagreements(&all_bugs, &ai_categories),
+        ai_triage,
+        focus: None,
+        achievements: badges,
+    };
+    let waiting_on_me = sections.waiting_on_me.iter().map(|w| w.bug_id).collect();
+    let weights = triage::get_triage_weights(app.clone()).unwrap_or_else(|e| {
+        log::warn!("Using default triage weights for the report: {}", e);
+        Default::default()
+    });
+    if mine {
+        quick_actions::update(app, &quick_actions::top_urgent(&all_bugs, &weights, &client.config.sla, &waiting_on_me, now));
+    }
+    let mut analysis = analyze_bugs(all_bugs);
+    if let Some(top) = client.config.focus_top {
+        sections.focus = Some(focus::apply(&mut analysis, top, &weights, &client.config.sla, &waiting_on_me, now));
+    }
     let actionable = &analysis.actionable;
     let questionable = &analysis.questionable;
     let categorized = categorize_bugs(actionable);
-    Ok(generate_bug_report_html(actionable, questionable, &categorized))
+    Ok(generate_bug_report_html(actionable, questionable, &categorized, &sections))
+}
+
+#[tauri::command]
+/// Full description HTML of a bug in the report, for descriptions truncated in it.
+fn get_bug_description(state: tauri::State<AppState>, id: u64) -> Result<String, BuggerError> {
+    let cached = state.bugs.lock().map_err(|e| e.to_string())?.iter().find(|b| b.id == id).cloned();
+    let bug = match cached {
+        Some(bug) => bug,
+        // Ad-hoc reports show bugs that aren't in my list.
+        None => {
+            let config = AppConfig::from_env()?;
+            provider::from_config(config).fetch_details(&[id])?.into_iter().next().ok_or_else(|| format!("Bug #{} not found", id))?
+        }
+    };
+    Ok(bug.description.unwrap_or_default())
+}
+
+#[tauri::command]
+/// HTML of the most recent report, if an analysis has run.
+fn get_last_report(state: tauri::State<AppState>) -> Result<Option<String>, String> {
+    Ok(state.last_report.lock().map_err(|e| e.to_string())?.clone())
 }
 
 fn main() {
-    tauri::Builder::default()
-        .invoke_handler(tauri::generate_handler![fetch_and_analyze_bugs])
+    let builder = tauri::Builder::default();
+    // Jump list entries start the app again; the running instance takes their action.
+    #[cfg(windows)]
+    let builder = builder.plugin(tauri_plugin_single_instance::init(|app, args, _cwd| quick_actions::handle_second_instance(app, args)));
+    builder
+        .plugin(tauri_plugin_notification::init())
+        .plugin(quick_notes::shortcut_plugin())
+        .manage(AppState {
+            simulate: AtomicBool::new(env_flag("BUGGER_SIMULATE", false)),
+            ..Default::default()
+        })
+        .setup(|app| {
+            settings::apply_saved(app.handle());
+            rules::load_saved(app.handle());
+            cache::BugCache::init(app.handle());
+            http_trace::start(app.handle());
+            if let Ok(config) = AppConfig::from_env() {
+                pat::warn_if_expiring(app.handle(), &config);
+            }
+            refresh::start_scheduler(app.handle().clone());
+            outputs::start_scheduler(app.handle().clone());
+            server::start(app.handle().clone());
+            if server::is_headless() {
+                server::hide_windows(app.handle());
+            }
+            quick_actions::init(app.handle());
+            quick_notes::register_shortcut(app.handle());
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            fetch_and_analyze_bugs,
+            get_bug_report,
+            cache::force_full_refresh,
+            get_bug_description,
+            get_last_report,
+            quick_actions::take_quick_action,
+            import::analyze_id_list,
+            compare::compare_filters,
+            triage::get_next_triage_bug,
+            triage::record_triage_decision,
+            triage::get_triage_weights,
+            triage::save_triage_weights,
+            triage::evaluate_triage_weights,
+            waiting::draft_nudge_comment,
+            needs_info::draft_needs_info_comments,
+            needs_info::mark_needs_info,
+            waiting::post_comment,
+            ownership::reassign_bug,
+            time_tracking::start_timer,
+            time_tracking::stop_timer,
+            time_tracking::retry_time_write_back,
+            time_tracking::get_time_report,
+            work_session::open_bug,
+            work_session::start_work_session,
+            work_session::finish_work_session,
+            policy::run_auto_close_policy,
+            auto_assign::run_auto_assign_policy,
+            escalation::run_escalation_policy,
+            write_back::run_write_back_policy,
+            out_of_office::get_out_of_office,
+            out_of_office::set_out_of_office,
+            out_of_office::remove_out_of_office,
+            audit::get_audit_log,
+            audit::undo_action,
+            audit::set_simulation_mode,
+            audit::get_simulation_preview,
+            export::export_report_html,
+            export::export_bugs,
+            export::export_accomplishment_report,
+            odata::export_analysis,
+            outputs::get_output_schedules,
+            outputs::save_output_schedules,
+            outputs::run_output_now,
+            dashboard::get_dashboard_widget,
+            recurrence::preview_recurrence,
+            recurrence::get_next_runs,
+            power::get_power_status,
+            calendar::export_calendar,
+            process::get_process_model,
+            roles::get_role,
+            rules::list_rules,
+            rules::set_rule_enabled,
+            rules::set_rule_param,
+            code_refs::open_in_editor,
+            accomplishments::get_accomplishment_report,
+            wiql::preview_bug_query,
+            events::subscribe_updates,
+            baseline::mark_baseline,
+            baseline::list_baselines,
+            baseline::delete_baseline,
+            baseline::compare_to_baseline,
+            setup::get_config_status,
+            settings::get_settings,
+            credentials::set_credential,
+            credentials::clear_credential,
+            credentials::get_credential_status,
+            settings::save_settings,
+            pat::get_pat_info,
+            http_trace::get_http_trace_path,
+            schema::get_schema,
+            verdict::analyze_work_item,
+            verdict::analyze_text,
+            verdict::review_new_bug,
+            verdict::file_bug,
+            quick_notes::save_quick_note,
+            quick_notes::list_quick_notes,
+            quick_notes::delete_quick_note,
+            quick_notes::file_quick_note,
+            screenshot::capture_screenshot,
+            screenshot::attach_image
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_are_read_like_the_environment() {
+        assert_eq!(parse_flag(" Off "), Some(false));
+        assert_eq!(parse_flag("yes"), Some(true));
+        assert_eq!(parse_flag("TRUE"), Some(true));
+        assert_eq!(parse_flag("1"), Some(true));
+        assert_eq!(parse_flag("sometimes"), None);
+    }
+}
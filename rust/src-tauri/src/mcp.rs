@@ -0,0 +1,200 @@
+// MCP (Model Context Protocol) server over the local server's `/mcp` endpoint, so AI coding
+// assistants can list, search and look up bugs from the running app
+use crate::azure_devops::Bug;
+use crate::bug_analysis::{categorize_bug, is_questionable, strip_html, BugCategory};
+use crate::{verdict, AppConfig, AppState};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager};
+
+const PROTOCOL_VERSION: &str = "2025-03-26";
+/// Bugs returned per list or search call.
+const MAX_RESULTS: usize = 50;
+
+fn tools() -> Value {
+    json!([
+        {
+            "name": "list_bugs",
+            "description": "List my active bugs with their category and whether they're actionable.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "category": { "type": "string", "description": "Only this category, e.g. crash, security, performance, ui." },
+                    "actionable": { "type": "boolean", "description": "true for actionable bugs only, false for questionable ones only." }
+                }
+            }
+        },
+        {
+            "name": "search_bugs",
+            "description": "Find my active bugs whose title or description contains every word of the query.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "query": { "type": "string" } },
+                "required": ["query"]
+            }
+        },
+        {
+            "name": "get_bug",
+            "description": "Full analysis of one work item (any, not just mine): category, actionability, checklist, missing template sections and known error codes, plus its description.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "id": { "type": "string", "description": "Work item id or URL." } },
+                "required": ["id"]
+            }
+        }
+    ])
+}
+
+fn summary(bug: &Bug, config: &AppConfig) -> Value {
+    json!({
+        "id": bug.id,
+        "title": bug.title,
+        "state": bug.state,
+        "severity": bug.severity,
+        "category": categorize_bug(bug),
+        "questionable": is_questionable(bug),
+        "url": config.bug_url(bug.id),
+    })
+}
+
+fn list_bugs(app: &AppHandle, args: &Value) -> Result<Value, String> {
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    let category: Option<BugCategory> = args["category"].as_str().map(str::parse).transpose()?;
+    let actionable = args["actionable"].as_bool();
+    let bugs = app.state::<AppState>().bugs_or_fetch()?;
+    let matching: Vec<Value> = bugs
+        .iter()
+        .filter(|b| category.as_ref().is_none_or(|c| categorize_bug(b) == *c))
+        .filter(|b| actionable.is_none_or(|a| is_questionable(b).is_none() == a))
+        .take(MAX_RESULTS)
+        .map(|b| summary(b, &config))
+        .collect();
+    Ok(json!(matching))
+}
+
+/// Whether the bug's title or description contains every (lowercase) word.
+fn contains_all(bug: &Bug, words: &[String]) -> bool {
+    let text = format!("{}\n{}", bug.title, strip_html(bug.description.as_deref().unwrap_or(""))).to_lowercase();
+    words.iter().all(|w| text.contains(w))
+}
+
+fn search_bugs(app: &AppHandle, args: &Value) -> Result<Value, String> {
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    let words: Vec<String> = args["query"].as_str().unwrap_or("").split_whitespace().map(|w| w.to_lowercase()).collect();
+    if words.is_empty() {
+        return Err("query is required".to_string());
+    }
+    let bugs = app.state::<AppState>().bugs_or_fetch()?;
+    let matching: Vec<Value> = bugs
+        .iter()
+        .filter(|b| contains_all(b, &words))
+        .take(MAX_RESULTS)
+        .map(|b| summary(b, &config))
+        .collect();
+    Ok(json!(matching))
+}
+
+fn get_bug(app: &AppHandle, args: &Value) -> Result<Value, String> {
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    let input = args["id"].as_str().map(str::to_string).or_else(|| args["id"].as_u64().map(|id| id.to_string())).unwrap_or_default();
+    let id = verdict::parse_work_item_ref(&input, &config.org)?;
+    let verdict = verdict::work_item_verdict(id)?;
+    let description = app
+        .state::<AppState>()
+        .bugs
+        .lock()
+        .ok()
+        .and_then(|bugs| bugs.iter().find(|b| b.id == id).and_then(|b| b.description.clone()))
+        .map(|d| strip_html(&d));
+    Ok(json!({ "problems": verdict.problems(), "verdict": verdict, "description": description }))
+}
+
+fn call_tool(app: &AppHandle, params: &Value) -> Value {
+    let args = &params["arguments"];
+    let result = match params["name"].as_str().unwrap_or("") {
+        "list_bugs" => list_bugs(app, args),
+        "search_bugs" => search_bugs(app, args),
+        "get_bug" => get_bug(app, args),
+        name => Err(format!("Unknown tool '{}'", name)),
+    };
+    tool_result(result)
+}
+
+/// Tool failures are reported to the model as results, not protocol errors.
+fn tool_result(result: Result<Value, String>) -> Value {
+    match result {
+        Ok(value) => json!({ "content": [{ "type": "text", "text": value.to_string() }], "isError": false }),
+        Err(e) => json!({ "content": [{ "type": "text", "text": e }], "isError": true }),
+    }
+}
+
+/// Handle one JSON-RPC message; `None` for notifications, which get no reply.
+pub fn handle(app: &AppHandle, body: &str) -> Option<Value> {
+    respond(body, |params| call_tool(app, params))
+}
+
+/// The reply to one JSON-RPC message, with tool calls made by `call_tool`.
+fn respond(body: &str, call_tool: impl FnOnce(&Value) -> Value) -> Option<Value> {
+    let request: Value = match serde_json::from_str(body) {
+        Ok(request) => request,
+        Err(e) => return Some(json!({ "jsonrpc": "2.0", "id": null, "error": { "code": -32700, "message": format!("Parse error: {}", e) } })),
+    };
+    let id = request.get("id")?.clone();
+    let params = &request["params"];
+    let result = match request["method"].as_str().unwrap_or("") {
+        "initialize" => json!({
+            "protocolVersion": params["protocolVersion"].as_str().unwrap_or(PROTOCOL_VERSION),
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "bugger", "version": env!("CARGO_PKG_VERSION") }
+        }),
+        "ping" => json!({}),
+        "tools/list" => json!({ "tools": tools() }),
+        "tools/call" => call_tool(params),
+        method => return Some(json!({ "jsonrpc": "2.0", "id": id, "error": { "code": -32601, "message": format!("Method not found: {}", method) } })),
+    };
+    Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reply(body: &str) -> Option<Value> {
+        respond(body, |params| tool_result(Ok(params["name"].clone())))
+    }
+
+    #[test]
+    fn initialize_echoes_the_client_protocol_version() {
+        let echoed = reply(r#"{ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": { "protocolVersion": "2024-11-05" } }"#).unwrap();
+        assert_eq!(echoed["id"], 1);
+        assert_eq!(echoed["result"]["protocolVersion"], "2024-11-05");
+        assert_eq!(echoed["result"]["serverInfo"]["name"], "bugger");
+        let default = reply(r#"{ "jsonrpc": "2.0", "id": 2, "method": "initialize" }"#).unwrap();
+        assert_eq!(default["result"]["protocolVersion"], PROTOCOL_VERSION);
+    }
+
+    #[test]
+    fn notifications_get_no_reply_and_errors_get_json_rpc_codes() {
+        assert_eq!(reply(r#"{ "jsonrpc": "2.0", "method": "notifications/initialized" }"#), None);
+        assert_eq!(reply("{ not json").unwrap()["error"]["code"], -32700);
+        assert_eq!(reply(r#"{ "jsonrpc": "2.0", "id": "a", "method": "resources/list" }"#).unwrap()["error"]["code"], -32601);
+    }
+
+    #[test]
+    fn tools_are_listed_and_called_by_name() {
+        let listed = reply(r#"{ "jsonrpc": "2.0", "id": 1, "method": "tools/list" }"#).unwrap();
+        let names: Vec<&str> = listed["result"]["tools"].as_array().unwrap().iter().filter_map(|t| t["name"].as_str()).collect();
+        assert_eq!(names, ["list_bugs", "search_bugs", "get_bug"]);
+        let called = reply(r#"{ "jsonrpc": "2.0", "id": 2, "method": "tools/call", "params": { "name": "search_bugs" } }"#).unwrap();
+        assert_eq!(called["result"]["content"][0]["text"], "\"search_bugs\"");
+        assert_eq!(tool_result(Err("query is required".to_string()))["isError"], true);
+    }
+
+    #[test]
+    fn search_needs_every_word_in_the_title_or_description() {
+        let mut bug = Bug::sample(1, "Explorer crashes");
+        bug.description = Some("<p>Opening a <b>zip</b> file</p>".to_string());
+        let words = |q: &str| q.split_whitespace().map(str::to_lowercase).collect::<Vec<_>>();
+        assert!(contains_all(&bug, &words("explorer ZIP")));
+        assert!(!contains_all(&bug, &words("explorer tar")));
+    }
+}
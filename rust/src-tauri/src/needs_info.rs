@@ -0,0 +1,198 @@
+// Needs-info comment templates per kind of missing information, filled in with bug specifics (no AI)
+use crate::audit::{self, WriteAction};
+use crate::azure_devops::Bug;
+use crate::bug_analysis::{is_questionable, QuestionableCategory};
+use crate::checklist::{Check, Checklist};
+use crate::error::BuggerError;
+use crate::triage::{self, TriageDecision};
+use crate::{provider, stacktrace, AppConfig, AppState};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::env;
+use std::sync::OnceLock;
+use tauri::{AppHandle, State};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NeedsInfo {
+    NoRepro,
+    NoEnvironment,
+    DeadLink,
+    Duplicate,
+}
+
+impl NeedsInfo {
+    /// Suffix of the BUGGER_TEMPLATE_<KIND> variable overriding the built-in text.
+    fn env_name(&self) -> &'static str {
+        match self {
+            NeedsInfo::NoRepro => "NO_REPRO",
+            NeedsInfo::NoEnvironment => "NO_ENVIRONMENT",
+            NeedsInfo::DeadLink => "DEAD_LINK",
+            NeedsInfo::Duplicate => "DUPLICATE",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            NeedsInfo::NoRepro => "Ask for repro steps",
+            NeedsInfo::NoEnvironment => "Ask for environment details",
+            NeedsInfo::DeadLink => "Report a dead link",
+            NeedsInfo::Duplicate => "Suggest a duplicate",
+        }
+    }
+
+    fn default_template(&self) -> &'static str {
+        match self {
+            NeedsInfo::NoRepro => "Hi {reporter}, thanks for filing #{id} ({title}). I can't reproduce this yet — could you add step-by-step repro steps, what you expected to happen and what happened instead? Still missing: {missing}.",
+            NeedsInfo::NoEnvironment => "Hi {reporter}, to investigate #{id} I need to know where this happens: OS version and build (winver), device model, and browser/app version if relevant. Could you add those details?",
+            NeedsInfo::DeadLink => "Hi {reporter}, the link in #{id} ({link}) no longer works. Could you re-attach the file or share a working link?",
+            NeedsInfo::Duplicate => "Hi {reporter}, #{id} looks like the same issue as #{duplicate_id} ({duplicate_title}): {duplicate_url}. If you agree, I'll resolve this one as a duplicate — let me know if yours is different.",
+        }
+    }
+
+    /// Template text: BUGGER_TEMPLATE_<KIND> if set (`\n` for line breaks), else the built-in one.
+    pub fn template(&self) -> String {
+        env::var(format!("BUGGER_TEMPLATE_{}", self.env_name()))
+            .ok()
+            .filter(|t| !t.trim().is_empty())
+            .map(|t| t.replace("\\n", "\n"))
+            .unwrap_or_else(|| self.default_template().to_string())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct NeedsInfoDraft {
+    pub kind: NeedsInfo,
+    pub label: String,
+    pub text: String,
+}
+
+/// Replace `{name}` placeholders; unknown placeholders are left as written.
+fn fill(template: &str, vars: &[(&str, String)]) -> String {
+    vars.iter().fold(template.to_string(), |text, (name, value)| text.replace(&format!("{{{}}}", name), value))
+}
+
+fn link_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r#"https?://[^\s"'<>]+"#).unwrap())
+}
+
+/// Titles compared for duplicates: lowercase words only.
+fn normalized_title(title: &str) -> String {
+    title.to_lowercase().split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()).collect::<Vec<_>>().join(" ")
+}
+
+fn crash_signature(bug: &Bug) -> Option<String> {
+    let description = bug.description.as_deref().unwrap_or("");
+    stacktrace::parse(description)
+        .and_then(|trace| trace.signature().map(str::to_string))
+        .or_else(|| stacktrace::exception_code(description))
+}
+
+/// Another open bug with the same crash signature or the same title.
+fn find_duplicate<'a>(bug: &Bug, others: &'a [Bug]) -> Option<&'a Bug> {
+    let title = normalized_title(&bug.title);
+    let signature = crash_signature(bug);
+    others.iter().filter(|o| o.id != bug.id).find(|o| {
+        (!title.is_empty() && normalized_title(&o.title) == title) || (signature.is_some() && crash_signature(o) == signature)
+    })
+}
+
+/// Drafts for every kind of missing information that applies to `bug`, most useful first.
+pub fn drafts(bug: &Bug, others: &[Bug], checklist: &Checklist, bug_url: impl Fn(u64) -> String) -> Vec<NeedsInfoDraft> {
+    let score = checklist.score(bug, false);
+    let questionable = is_questionable(bug);
+    let reporter = bug.created_by.as_ref().map(|p| p.display_name.clone()).unwrap_or_else(|| "there".to_string());
+    let mut vars: Vec<(&str, String)> = vec![
+        ("reporter", reporter),
+        ("id", bug.id.to_string()),
+        ("title", bug.title.clone()),
+        ("missing", score.missing.iter().map(|c| c.label()).collect::<Vec<_>>().join(", ")),
+    ];
+    let mut kinds = Vec::new();
+    if let Some(duplicate) = find_duplicate(bug, others) {
+        vars.push(("duplicate_id", duplicate.id.to_string()));
+        vars.push(("duplicate_title", duplicate.title.clone()));
+        vars.push(("duplicate_url", bug_url(duplicate.id)));
+        kinds.push(NeedsInfo::Duplicate);
+    }
+    if questionable == Some(QuestionableCategory::DeadLinks) {
+        let link = link_re().find(bug.description.as_deref().unwrap_or("")).map(|m| m.as_str().to_string()).unwrap_or_default();
+        vars.push(("link", link));
+        kinds.push(NeedsInfo::DeadLink);
+    }
+    if score.missing.contains(&Check::Repro) || matches!(questionable, Some(q) if q != QuestionableCategory::DeadLinks) {
+        kinds.push(NeedsInfo::NoRepro);
+    }
+    if score.missing.contains(&Check::Environment) {
+        kinds.push(NeedsInfo::NoEnvironment);
+    }
+    kinds
+        .into_iter()
+        .map(|kind| NeedsInfoDraft { kind, label: kind.label().to_string(), text: fill(&kind.template(), &vars) })
+        .collect()
+}
+
+#[tauri::command]
+/// Needs-info comment drafts for a bug, filled in from templates; posted with `post_comment` after the user edits them.
+pub fn draft_needs_info_comments(state: State<AppState>, id: u64) -> Result<Vec<NeedsInfoDraft>, BuggerError> {
+    let config = AppConfig::from_env()?;
+    let bugs = state.bugs_or_fetch()?;
+    let bug = bugs.iter().find(|b| b.id == id).ok_or_else(|| format!("Bug #{} is not in the current bug list", id))?;
+    let bug_url = |id: u64| config.bug_url(id);
+    Ok(drafts(bug, &bugs, &config.checklist, bug_url))
+}
+
+#[tauri::command]
+/// Adds the needs-info tag (BUGGER_AUTOCLOSE_TAG, a label on GitHub and Jira) to a bug and records it
+/// as needs-info in the triage queue. Undoable from the audit log on Azure DevOps.
+pub fn mark_needs_info(app: AppHandle, state: State<AppState>, id: u64) -> Result<(), BuggerError> {
+    let config = AppConfig::from_env()?;
+    let tag = config.auto_close.tag.clone();
+    let actor = config.user_email.clone();
+    let bug = state.bugs_or_fetch()?.into_iter().find(|b| b.id == id).ok_or_else(|| format!("Bug #{} is not in the current bug list", id))?;
+    if !bug.tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+        let client = provider::from_config(config);
+        audit::perform(&app, &actor, id, WriteAction::Tag, format!("Tagged {}", tag), false, || client.add_tag(id, &tag))?;
+    }
+    triage::record_triage_decision(app.clone(), state, id, TriageDecision::NeedsInfo).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_placeholders_are_filled_and_others_kept() {
+        let text = fill("Hi {reporter}, see #{id} {unknown}", &[("reporter", "Ana".to_string()), ("id", "7".to_string())]);
+        assert_eq!(text, "Hi Ana, see #7 {unknown}");
+    }
+
+    #[test]
+    fn duplicates_are_found_by_normalized_title() {
+        let bug = Bug::sample(1, "Crash: on start!");
+        let others = [Bug::sample(1, "Crash on start"), Bug::sample(2, "Slow scrolling"), Bug::sample(3, "crash ON start")];
+        assert_eq!(find_duplicate(&bug, &others).map(|d| d.id), Some(3));
+        assert!(find_duplicate(&Bug::sample(4, "Blank window"), &others).is_none());
+    }
+
+    #[test]
+    fn a_duplicate_is_suggested_first_with_its_link() {
+        let bug = Bug::sample(1, "Crash on start");
+        let others = [Bug::sample(3, "Crash on start")];
+        let drafts = drafts(&bug, &others, &Checklist::from_env(), |id| format!("https://bugs/{}", id));
+        assert_eq!(drafts[0].kind, NeedsInfo::Duplicate);
+        assert!(drafts[0].text.contains("#3 (Crash on start): https://bugs/3"));
+        assert!(drafts.iter().any(|d| d.kind == NeedsInfo::NoRepro));
+    }
+
+    #[test]
+    fn dead_links_and_missing_environment_get_their_own_drafts() {
+        let mut bug = Bug::sample(1, "Installer download broken");
+        bug.description = Some("Steps to reproduce: open https://contoso.com/setup.exe from the release notes, it returns 404 Not Found.".to_string());
+        let kinds: Vec<_> = drafts(&bug, &[], &Checklist::from_env(), |id| id.to_string()).into_iter().map(|d| (d.kind, d.text)).collect();
+        assert_eq!(kinds.iter().map(|(kind, _)| *kind).collect::<Vec<_>>(), [NeedsInfo::DeadLink, NeedsInfo::NoEnvironment]);
+        assert!(kinds[0].1.contains("the link in #1 (https://contoso.com/setup.exe) no longer works"));
+    }
+}
@@ -0,0 +1,146 @@
+// Desktop notification rules: which categories/severities are loud and which stay silent
+use crate::azure_devops::Bug;
+use crate::bug_analysis::{categorize_bug, BugCategory};
+use crate::category_style::CategoryStyles;
+use crate::quiet_hours::QuietHours;
+use crate::{out_of_office, toast, work_session};
+use crate::{env_list, storage};
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+const NOTIFICATION_QUEUE: &str = "notification_queue";
+
+/// Filters deciding which newly detected bugs raise a desktop notification.
+#[derive(Debug, Clone, Default)]
+pub struct NotificationRules {
+    /// Categories that notify (BUGGER_NOTIFY_CATEGORIES, e.g. "Crash,Security").
+    pub categories: Vec<BugCategory>,
+    /// Severity values that notify regardless of category (BUGGER_NOTIFY_SEVERITIES, e.g. "1 - Critical").
+    pub severities: Vec<String>,
+}
+
+impl NotificationRules {
+    /// Load rules from environment variables; unknown category names are logged and ignored.
+    pub fn from_env() -> Self {
+        let categories = env_list("BUGGER_NOTIFY_CATEGORIES")
+            .iter()
+            .filter_map(|name| name.parse().map_err(|e| warn!("{}", e)).ok())
+            .collect();
+        let severities = env_list("BUGGER_NOTIFY_SEVERITIES");
+        NotificationRules { categories, severities }
+    }
+
+    /// With no rules configured every bug notifies; otherwise a bug notifies if its category or severity matches.
+    pub fn should_notify(&self, bug: &Bug, category: &BugCategory) -> bool {
+        if self.categories.is_empty() && self.severities.is_empty() {
+            return true;
+        }
+        self.categories.contains(category)
+            || bug.severity.as_ref().is_some_and(|sev| self.severities.iter().any(|s| s.eq_ignore_ascii_case(sev)))
+    }
+}
+
+/// A notification waiting for working hours.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedNotification {
+    pub title: String,
+    pub body: String,
+    pub queued_at: DateTime<Utc>,
+    /// The bug it's about, for the toast's triage buttons.
+    #[serde(default)]
+    pub bug_id: Option<u64>,
+}
+
+/// Notify about newly detected bugs that pass the rules; the rest are added silently.
+pub fn notify_new_bugs(app: &AppHandle, rules: &NotificationRules, styles: &CategoryStyles, quiet_hours: Option<&QuietHours>, added: &[Bug]) {
+    let pending = added
+        .iter()
+        .filter_map(|bug| {
+            let category = categorize_bug(bug);
+            rules.should_notify(bug, &category).then(|| QueuedNotification {
+                title: format!("New {} bug #{}", styles.display(&category), bug.id),
+                body: bug.title.clone(),
+                queued_at: Utc::now(),
+                bug_id: Some(bug.id),
+            })
+        })
+        .collect();
+    deliver(app, quiet_hours, pending);
+}
+
+/// Whether notifications wait: during a work session or outside working hours.
+fn is_held(in_session: bool, quiet_hours: Option<&QuietHours>, now: DateTime<Utc>) -> bool {
+    in_session || quiet_hours.is_some_and(|q| !q.is_working_time(now))
+}
+
+/// Show notifications now, or hold them in the queue while outside working hours or during a work
+/// session. Anything queued earlier goes out with the first delivery after that. While I'm out of
+/// office new notifications are dropped.
+pub fn deliver(app: &AppHandle, quiet_hours: Option<&QuietHours>, notifications: Vec<QueuedNotification>) {
+    if out_of_office::am_out(app) {
+        return;
+    }
+    let mut queue: Vec<QueuedNotification> = storage::load_json(app, NOTIFICATION_QUEUE).unwrap_or_else(|e| {
+        warn!("Dropping unreadable notification queue: {}", e);
+        Vec::new()
+    });
+    let had_queued = !queue.is_empty();
+    queue.extend(notifications);
+    if is_held(work_session::is_active(app), quiet_hours, Utc::now()) {
+        if !queue.is_empty() {
+            if let Err(e) = storage::save_json(app, NOTIFICATION_QUEUE, &queue) {
+                warn!("Failed to queue notifications: {}", e);
+            }
+        }
+        return;
+    }
+    for n in &queue {
+        toast::show(app, n);
+    }
+    if had_queued {
+        if let Err(e) = storage::save_json(app, NOTIFICATION_QUEUE, &Vec::<QueuedNotification>::new()) {
+            warn!("Failed to clear notification queue: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, NaiveTime, Weekday};
+
+    #[test]
+    fn rules_match_on_category_or_severity() {
+        let mut bug = Bug::sample(1, "Explorer crashes");
+        assert!(NotificationRules::default().should_notify(&bug, &BugCategory::Crash));
+        let rules = NotificationRules { categories: vec![BugCategory::Security], severities: vec!["1 - Critical".to_string()] };
+        assert!(!rules.should_notify(&bug, &BugCategory::Crash));
+        assert!(rules.should_notify(&bug, &BugCategory::Security));
+        bug.severity = Some("1 - critical".to_string());
+        assert!(rules.should_notify(&bug, &BugCategory::Crash));
+    }
+
+    #[test]
+    fn notifications_wait_for_working_hours_and_the_end_of_a_session() {
+        let hours = QuietHours {
+            start: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+            days: vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri],
+            offset: FixedOffset::east_opt(0),
+        };
+        // Thursday 2026-10-15.
+        let at = |time: &str| DateTime::parse_from_rfc3339(&format!("2026-10-15T{}Z", time)).unwrap().with_timezone(&Utc);
+        assert!(!is_held(false, Some(&hours), at("10:00:00")));
+        assert!(is_held(false, Some(&hours), at("20:00:00")));
+        assert!(is_held(true, Some(&hours), at("10:00:00")));
+        assert!(!is_held(false, None, at("20:00:00")));
+    }
+
+    #[test]
+    fn notifications_queued_before_triage_buttons_still_load() {
+        let queued: QueuedNotification = serde_json::from_str(r#"{ "title": "New Crash bug #1", "body": "Explorer crashes", "queued_at": "2026-10-15T08:00:00Z" }"#).unwrap();
+        assert_eq!(queued.bug_id, None);
+    }
+}
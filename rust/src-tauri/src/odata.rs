@@ -0,0 +1,164 @@
+// Flat analysis data for Power BI and other dashboards: one row per bug with stable column names,
+// as CSV/JSON files or an OData feed served by local server mode
+use crate::azure_devops::{AzureDevOpsClient, Bug};
+use crate::bug_analysis::{categorize_bug, is_questionable};
+use crate::export::csv_cell;
+use crate::triage::bug_age_days;
+use crate::{process, template, AppConfig, AppState};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Map, Value};
+use tauri::State;
+
+/// Columns and their OData types. Names are a contract with dashboards: add columns at the end,
+/// never rename or drop them.
+pub const COLUMNS: [(&str, &str); 17] = [
+    ("id", "Edm.Int64"),
+    ("title", "Edm.String"),
+    ("state", "Edm.String"),
+    ("state_category", "Edm.String"),
+    ("category", "Edm.String"),
+    ("severity", "Edm.String"),
+    ("assigned_to", "Edm.String"),
+    ("area_path", "Edm.String"),
+    ("tags", "Edm.String"),
+    ("created_date", "Edm.DateTimeOffset"),
+    ("age_days", "Edm.Int64"),
+    ("comment_count", "Edm.Int64"),
+    ("actionable", "Edm.Boolean"),
+    ("questionable_reason", "Edm.String"),
+    ("checklist_percent", "Edm.Int32"),
+    ("template_percent", "Edm.Int32"),
+    ("snapshot_date", "Edm.Date"),
+];
+
+/// One row per bug, keyed by column name. `template_percent` is null when no template is configured.
+pub fn rows(client: &AzureDevOpsClient, bugs: &[Bug], now: DateTime<Utc>) -> Vec<Map<String, Value>> {
+    let config = &client.config;
+    let process = process::model(client);
+    let sections = config.bug_template.load(client);
+    bugs.iter()
+        .map(|bug| {
+            let questionable = is_questionable(bug);
+            let values = [
+                json!(bug.id),
+                json!(bug.title),
+                json!(bug.state),
+                json!(process.category(&bug.state)),
+                json!(categorize_bug(bug)),
+                json!(bug.severity),
+                json!(bug.assigned_to.as_ref().map(|a| &a.display_name)),
+                json!(bug.area_path),
+                json!(bug.tags.join("; ")),
+                json!(bug.created_date),
+                json!(bug_age_days(bug, now)),
+                json!(bug.comment_count),
+                json!(questionable.is_none()),
+                json!(questionable),
+                json!(config.checklist.score(bug, false).percent()),
+                json!((!sections.is_empty()).then(|| template::check(bug, &sections).percent())),
+                json!(now.format("%Y-%m-%d").to_string()),
+            ];
+            COLUMNS.iter().map(|(name, _)| name.to_string()).zip(values).collect()
+        })
+        .collect()
+}
+
+pub fn csv(rows: &[Map<String, Value>]) -> String {
+    let mut out = COLUMNS.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(",");
+    out.push('\n');
+    for row in rows {
+        let cells: Vec<String> = COLUMNS
+            .iter()
+            .map(|(name, _)| match &row[*name] {
+                Value::Null => String::new(),
+                Value::String(s) => csv_cell(s),
+                other => other.to_string(),
+            })
+            .collect();
+        out.push_str(&cells.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// OData service document listing the single `Bugs` entity set.
+pub fn service_document(service_url: &str) -> Value {
+    json!({
+        "@odata.context": format!("{}/$metadata", service_url),
+        "value": [{ "name": "Bugs", "kind": "EntitySet", "url": "Bugs" }]
+    })
+}
+
+/// OData CSDL describing the `Bugs` entity set, which Power BI reads before the data.
+pub fn metadata() -> String {
+    let properties: String = COLUMNS
+        .iter()
+        .map(|(name, edm)| format!("<Property Name=\"{}\" Type=\"{}\"{}/>", name, edm, if *name == "id" { " Nullable=\"false\"" } else { "" }))
+        .collect();
+    format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?><edmx:Edmx Version=\"4.0\" xmlns:edmx=\"http://docs.oasis-open.org/odata/ns/edmx\"><edmx:DataServices><Schema Namespace=\"Bugger\" xmlns=\"http://docs.oasis-open.org/odata/ns/edm\"><EntityType Name=\"Bug\"><Key><PropertyRef Name=\"id\"/></Key>{}</EntityType><EntityContainer Name=\"Container\"><EntitySet Name=\"Bugs\" EntityType=\"Bugger.Bug\"/></EntityContainer></Schema></edmx:DataServices></edmx:Edmx>",
+        properties
+    )
+}
+
+/// The `Bugs` entity set as an OData JSON response.
+pub fn entity_set(rows: Vec<Map<String, Value>>, service_url: &str) -> Value {
+    json!({ "@odata.context": format!("{}/$metadata#Bugs", service_url), "value": rows })
+}
+
+#[tauri::command]
+/// Writes the per-bug analysis rows to `path` as `csv` or OData `json`, for Power BI refresh.
+pub fn export_analysis(state: State<AppState>, path: String, format: String) -> Result<(), String> {
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    let bugs = state.bugs_or_fetch()?;
+    let rows = rows(&AzureDevOpsClient::new(config), &bugs, Utc::now());
+    let contents = match format.to_lowercase().as_str() {
+        "csv" => csv(&rows),
+        "json" => serde_json::to_string_pretty(&json!({ "value": rows })).map_err(|e| format!("JSON serialize error: {}", e))?,
+        other => return Err(format!("Unsupported export format '{}' (use csv or json)", other)),
+    };
+    std::fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(title: &str) -> Map<String, Value> {
+        let mut row: Map<String, Value> = COLUMNS.iter().map(|(name, _)| (name.to_string(), Value::Null)).collect();
+        row.insert("id".to_string(), json!(42));
+        row.insert("title".to_string(), json!(title));
+        row.insert("actionable".to_string(), json!(true));
+        row
+    }
+
+    #[test]
+    fn csv_has_every_column_in_order_and_quotes_text() {
+        let csv = csv(&[row("Crash, then \"hang\"")]);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(COLUMNS.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(",").as_str()));
+        let cells = lines.next().unwrap();
+        assert!(cells.starts_with("42,\"Crash, then \"\"hang\"\"\",,"), "{}", cells);
+        assert!(cells.contains(",true,"));
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn metadata_declares_every_column_with_id_as_the_key() {
+        let metadata = metadata();
+        for (name, edm) in COLUMNS {
+            assert!(metadata.contains(&format!("<Property Name=\"{}\" Type=\"{}\"", name, edm)), "{} missing", name);
+        }
+        assert!(metadata.contains("<Property Name=\"id\" Type=\"Edm.Int64\" Nullable=\"false\"/>"));
+        assert!(metadata.contains("<PropertyRef Name=\"id\"/>"));
+    }
+
+    #[test]
+    fn feed_documents_point_at_the_metadata() {
+        let url = "http://localhost:8787/odata";
+        assert_eq!(service_document(url)["@odata.context"], "http://localhost:8787/odata/$metadata");
+        let set = entity_set(vec![row("Crash")], url);
+        assert_eq!(set["@odata.context"], "http://localhost:8787/odata/$metadata#Bugs");
+        assert_eq!(set["value"][0]["id"], 42);
+    }
+}
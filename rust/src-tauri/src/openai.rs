@@ -0,0 +1,38 @@
+// Minimal OpenAI chat completions client (GPT-4o by default, like the Python version)
+use reqwest::blocking::Client;
+use reqwest::header::CONTENT_TYPE;
+use serde_json::Value;
+
+const CHAT_COMPLETIONS_URL: &str = "https://api.openai.com/v1/chat/completions";
+
+/// Send a single system + user prompt and return the trimmed reply text.
+pub fn chat_completion(api_key: &str, model: &str, system: &str, prompt: &str, max_tokens: u32) -> Result<String, String> {
+    let body = serde_json::json!({
+        "model": model,
+        "messages": [
+            { "role": "system", "content": system },
+            { "role": "user", "content": prompt }
+        ],
+        "max_tokens": max_tokens,
+        // Low temperature for consistent results
+        "temperature": 0.1
+    });
+    let body = serde_json::to_vec(&body).map_err(|e| format!("JSON serialize error: {}", e))?;
+    let resp = Client::new()
+        .post(CHAT_COMPLETIONS_URL)
+        .bearer_auth(api_key)
+        .header(CONTENT_TYPE, "application/json")
+        .body(body)
+        .send()
+        .map_err(|e| format!("OpenAI request error: {}", e))?;
+    let status = resp.status();
+    let resp_text = resp.text().map_err(|e| format!("OpenAI response text error: {}", e))?;
+    if !status.is_success() {
+        return Err(format!("OpenAI API error ({}): {}", status, resp_text));
+    }
+    let json: Value = serde_json::from_str(&resp_text).map_err(|e| format!("OpenAI JSON error: {}", e))?;
+    json["choices"][0]["message"]["content"]
+        .as_str()
+        .map(|s| s.trim().to_string())
+        .ok_or_else(|| format!("OpenAI response had no content: {}", resp_text))
+}
@@ -0,0 +1,218 @@
+// Out-of-office periods per person, from config or marked in the app: people who are away are
+// skipped by automation, my own absences silence notifications, and the team report flags urgent
+// bugs assigned to someone who is out
+use crate::azure_devops::Bug;
+use crate::calendar::SlaPolicy;
+use crate::focus::is_urgent;
+use crate::{storage, AppConfig};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use log::warn;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tauri::AppHandle;
+
+/// Absences marked in the app; BUGGER_OUT_OF_OFFICE ones aren't stored.
+const ABSENCE_STORE: &str = "out_of_office";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Absence {
+    /// Email or unique name.
+    pub who: String,
+    pub from: NaiveDate,
+    /// Inclusive.
+    pub until: NaiveDate,
+    /// From BUGGER_OUT_OF_OFFICE rather than marked in the app (and so not removable there).
+    #[serde(default)]
+    pub configured: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OutOfOffice {
+    pub absences: Vec<Absence>,
+}
+
+/// An urgent bug whose assignee is away today.
+#[derive(Debug, Clone, Serialize)]
+pub struct AwayAssignee {
+    pub bug_id: u64,
+    pub title: String,
+    pub assignee: String,
+    pub until: NaiveDate,
+}
+
+fn parse_absence(entry: &str) -> Option<Absence> {
+    let (who, range) = entry.split_once('=')?;
+    let (from, until) = range.split_once("..").unwrap_or((range, range));
+    let date = |d: &str| NaiveDate::parse_from_str(d.trim(), "%Y-%m-%d").ok();
+    Some(Absence { who: who.trim().to_string(), from: date(from)?, until: date(until)?, configured: true })
+}
+
+impl OutOfOffice {
+    /// Load from BUGGER_OUT_OF_OFFICE, e.g. "jane@contoso.com=2026-10-12..2026-10-16,bob@contoso.com=2026-11-02".
+    pub fn from_env() -> Self {
+        let absences = crate::env_list("BUGGER_OUT_OF_OFFICE")
+            .iter()
+            .filter_map(|entry| {
+                let absence = parse_absence(entry);
+                if absence.is_none() {
+                    warn!("Ignoring out-of-office entry '{}', expected email=YYYY-MM-DD..YYYY-MM-DD", entry);
+                }
+                absence
+            })
+            .collect();
+        OutOfOffice { absences }
+    }
+
+    /// The absence covering `date` for `who`, if any.
+    pub fn absence(&self, who: &str, date: NaiveDate) -> Option<&Absence> {
+        self.absences.iter().find(|a| a.who.eq_ignore_ascii_case(who) && a.from <= date && date <= a.until)
+    }
+
+    pub fn is_out(&self, who: &str, date: NaiveDate) -> bool {
+        self.absence(who, date).is_some()
+    }
+}
+
+fn saved(app: &AppHandle) -> Vec<Absence> {
+    storage::load_json(app, ABSENCE_STORE).unwrap_or_else(|e| {
+        warn!("Ignoring saved absences: {}", e);
+        Vec::new()
+    })
+}
+
+/// Configured and app-marked absences together.
+pub fn current(app: &AppHandle, config: &AppConfig) -> OutOfOffice {
+    let mut absences = config.out_of_office.absences.clone();
+    absences.extend(saved(app));
+    OutOfOffice { absences }
+}
+
+/// Whether I'm out today under any of my identities.
+pub fn am_out(app: &AppHandle) -> bool {
+    let Ok(config) = AppConfig::from_env() else {
+        return false;
+    };
+    let today = Local::now().date_naive();
+    let away = current(app, &config);
+    config.my_identities().iter().any(|me| away.is_out(me, today))
+}
+
+/// Urgent bugs assigned to someone who is out today, by assignee.
+pub fn urgent_while_away(bugs: &[Bug], away: &OutOfOffice, sla: &SlaPolicy, now: DateTime<Utc>) -> Vec<AwayAssignee> {
+    let today = now.with_timezone(&Local).date_naive();
+    let mut found: Vec<AwayAssignee> = bugs
+        .iter()
+        .filter_map(|bug| {
+            let assignee = bug.assigned_to.as_ref()?;
+            let absence = away.absence(&assignee.unique_name, today)?;
+            is_urgent(bug, sla, &HashSet::new(), now).then(|| AwayAssignee {
+                bug_id: bug.id,
+                title: bug.title.clone(),
+                assignee: assignee.display_name.clone(),
+                until: absence.until,
+            })
+        })
+        .collect();
+    found.sort_by(|a, b| a.assignee.cmp(&b.assignee).then(a.bug_id.cmp(&b.bug_id)));
+    found
+}
+
+pub fn section_html(items: &[AwayAssignee], bug_url: impl Fn(u64) -> String) -> String {
+    let mut html = String::from("<h2>🌴 Urgent Bugs With Assignees Out of Office</h2><div class='warning'>These need someone else while their owner is away.</div><ul>");
+    for item in items {
+        html.push_str(&format!(
+            "<li><a href=\"{}\" target=\"_blank\">#{}</a> {} — {} (out until {})</li>",
+            bug_url(item.bug_id),
+            item.bug_id,
+            html_escape::encode_text(&item.title),
+            html_escape::encode_text(&item.assignee),
+            item.until
+        ));
+    }
+    html.push_str("</ul>");
+    html
+}
+
+#[tauri::command]
+/// All known absences that haven't ended yet, soonest first.
+pub fn get_out_of_office(app: AppHandle) -> Result<Vec<Absence>, String> {
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    let today = Local::now().date_naive();
+    let mut absences = current(&app, &config).absences;
+    absences.retain(|a| a.until >= today);
+    absences.sort_by(|a, b| a.from.cmp(&b.from).then(a.who.cmp(&b.who)));
+    Ok(absences)
+}
+
+#[tauri::command]
+/// Marks `who` (default: me) out of office from `from` to `until`, inclusive.
+pub fn set_out_of_office(app: AppHandle, who: Option<String>, from: NaiveDate, until: NaiveDate) -> Result<Vec<Absence>, String> {
+    if until < from {
+        return Err("The absence ends before it starts".to_string());
+    }
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    let who = who.map(|w| w.trim().to_string()).filter(|w| !w.is_empty()).unwrap_or_else(|| config.user_email.clone());
+    let today = Local::now().date_naive();
+    storage::update_json(&app, ABSENCE_STORE, |absences: &mut Vec<Absence>| {
+        absences.retain(|a| a.until >= today);
+        absences.push(Absence { who, from, until, configured: false });
+    })?;
+    get_out_of_office(app)
+}
+
+#[tauri::command]
+/// Removes an absence marked in the app.
+pub fn remove_out_of_office(app: AppHandle, who: String, from: NaiveDate) -> Result<Vec<Absence>, String> {
+    storage::update_json(&app, ABSENCE_STORE, |absences: &mut Vec<Absence>| {
+        absences.retain(|a| !(a.who.eq_ignore_ascii_case(&who) && a.from == from));
+    })?;
+    get_out_of_office(app)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::azure_devops::Identity;
+    use std::collections::BTreeMap;
+
+    fn date(d: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn absences_parse_as_ranges_or_single_days() {
+        let range = parse_absence(" jane@contoso.com = 2026-10-12..2026-10-16").unwrap();
+        assert_eq!((range.who.as_str(), range.from, range.until, range.configured), ("jane@contoso.com", date("2026-10-12"), date("2026-10-16"), true));
+        let day = parse_absence("bob@contoso.com=2026-11-02").unwrap();
+        assert_eq!((day.from, day.until), (date("2026-11-02"), date("2026-11-02")));
+        assert!(parse_absence("bob@contoso.com").is_none());
+        assert!(parse_absence("bob@contoso.com=next week").is_none());
+    }
+
+    #[test]
+    fn absences_include_both_ends_and_ignore_case() {
+        let away = OutOfOffice { absences: vec![parse_absence("jane@contoso.com=2026-10-12..2026-10-16").unwrap()] };
+        assert!(away.is_out("Jane@Contoso.com", date("2026-10-12")) && away.is_out("jane@contoso.com", date("2026-10-16")));
+        assert!(!away.is_out("jane@contoso.com", date("2026-10-17")));
+        assert!(!away.is_out("bob@contoso.com", date("2026-10-13")));
+    }
+
+    #[test]
+    fn only_urgent_bugs_of_people_who_are_out_are_flagged() {
+        let now = Utc::now();
+        let today = now.with_timezone(&Local).date_naive();
+        let away = OutOfOffice { absences: vec![Absence { who: "jane@contoso.com".to_string(), from: today, until: today, configured: false }] };
+        let bug = |id, severity: &str, who: &str| {
+            let mut bug = Bug::sample(id, "<Crash> on start");
+            bug.severity = Some(severity.to_string());
+            bug.assigned_to = Some(Identity { display_name: who.split('@').next().unwrap().to_string(), unique_name: who.to_string(), avatar_url: None });
+            bug
+        };
+        let bugs = [bug(1, "1 - Critical", "jane@contoso.com"), bug(2, "3 - Medium", "jane@contoso.com"), bug(3, "1 - Critical", "bob@contoso.com")];
+        let found = urgent_while_away(&bugs, &away, &SlaPolicy { days: BTreeMap::new() }, now);
+        assert_eq!(found.iter().map(|f| (f.bug_id, f.assignee.as_str(), f.until)).collect::<Vec<_>>(), [(1, "jane", today)]);
+        let html = section_html(&found, |id| format!("https://bugs/{}", id));
+        assert!(html.contains(&format!("<a href=\"https://bugs/1\" target=\"_blank\">#1</a> &lt;Crash&gt; on start — jane (out until {})", today)));
+    }
+}
@@ -0,0 +1,365 @@
+// Scheduled report outputs: each channel (HTML file, email, Slack, Teams, webhook, dashboard widget) with its own cadence and filter
+use crate::azure_devops::Bug;
+use crate::bug_analysis::{categorize_bug, BugCategory};
+use crate::dashboard;
+use crate::error::BuggerError;
+use crate::export::standalone_report;
+use crate::recurrence::{weekday_code, Recurrence};
+use crate::{storage, AppConfig, AppState};
+use chrono::{DateTime, Duration, NaiveTime, Timelike, Utc, Weekday};
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::env;
+use tauri::{AppHandle, Manager};
+
+pub const SCHEDULE_STORE: &str = "output_schedules";
+/// How often the scheduler checks for due outputs.
+const TICK_SECONDS: u64 = 60;
+/// Longest bug list in chat messages; the rest is summarized as "and N more".
+const MAX_LISTED_BUGS: usize = 25;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Channel {
+    HtmlFile { path: String },
+    /// Sent through the SMTP server in BUGGER_SMTP_*.
+    Email { to: Vec<String> },
+    Slack { webhook_url: String },
+    Teams { webhook_url: String },
+    /// Generic JSON POST with the filtered bug list.
+    Webhook { url: String },
+    /// Dashboard widget payload for the filtered bugs: JSON when the path ends in `.json`, otherwise
+    /// the HTML fragment, e.g. in a repo file shown by the markdown widget.
+    Dashboard { path: String },
+}
+
+/// When an output runs, in BUGGER_TIMEZONE or the system timezone. Times are "HH:MM", days "Mon".."Sun".
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "every", rename_all = "snake_case")]
+pub enum Cadence {
+    Hourly,
+    Daily { at: String },
+    Weekly { day: String, at: String },
+    /// An iCalendar recurrence rule, e.g. "FREQ=MONTHLY;BYDAY=1MO;BYHOUR=9".
+    Rule { rule: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum OutputFilter {
+    /// The full analysis report (chat channels get the bug list).
+    FullReport,
+    /// Severity 1/2, crashes and security bugs only.
+    Urgent,
+    Categories { categories: Vec<BugCategory> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct OutputSchedule {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub channel: Channel,
+    pub cadence: Cadence,
+    pub filter: OutputFilter,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub last_run: Option<DateTime<Utc>>,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+fn parse_time(at: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(at.trim(), "%H:%M").map_err(|_| format!("'{}' is not a time (use HH:MM)", at))
+}
+
+impl Channel {
+    /// Whether the output messages people (email, Slack, Teams), so it waits for working hours.
+    pub fn is_message(&self) -> bool {
+        matches!(self, Channel::Email { .. } | Channel::Slack { .. } | Channel::Teams { .. })
+    }
+}
+
+impl Cadence {
+    /// The recurrence rule behind a cadence; daily and weekly ones are shorthands. `None` for hourly,
+    /// which counts from the last run instead.
+    pub fn recurrence(&self) -> Result<Option<Recurrence>, String> {
+        let rule = match self {
+            Cadence::Hourly => return Ok(None),
+            Cadence::Daily { at } => {
+                let at = parse_time(at)?;
+                format!("FREQ=DAILY;BYHOUR={};BYMINUTE={}", at.hour(), at.minute())
+            }
+            Cadence::Weekly { day, at } => {
+                let day = day.parse::<Weekday>().map_err(|_| format!("'{}' is not a weekday (use Mon..Sun)", day))?;
+                let at = parse_time(at)?;
+                format!("FREQ=WEEKLY;BYDAY={};BYHOUR={};BYMINUTE={}", weekday_code(day), at.hour(), at.minute())
+            }
+            Cadence::Rule { rule } => rule.clone(),
+        };
+        rule.parse().map(Some)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        self.recurrence().map(|_| ())
+    }
+
+    /// The most recent scheduled run at or before `now`.
+    fn latest_slot(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self.recurrence().ok()? {
+            None => Some(now - Duration::hours(1)),
+            Some(rule) => rule.previous_local(now),
+        }
+    }
+
+    pub fn is_due(&self, last_run: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+        match (self.latest_slot(now), last_run) {
+            (Some(slot), Some(last)) => last <= slot,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+}
+
+impl OutputFilter {
+    pub fn matches(&self, bug: &Bug) -> bool {
+        match self {
+            OutputFilter::FullReport => true,
+            OutputFilter::Urgent => {
+                let severe = bug.severity.as_deref().is_some_and(|s| s.starts_with('1') || s.starts_with('2'));
+                severe || matches!(categorize_bug(bug), BugCategory::Crash | BugCategory::Security)
+            }
+            OutputFilter::Categories { categories } => categories.contains(&categorize_bug(bug)),
+        }
+    }
+}
+
+fn load(app: &AppHandle) -> Result<Vec<OutputSchedule>, String> {
+    storage::load_json(app, SCHEDULE_STORE)
+}
+
+/// Plain bug list in the given line format, capped at MAX_LISTED_BUGS.
+fn bug_lines(bugs: &[&Bug], line: impl Fn(&Bug) -> String) -> String {
+    let mut lines: Vec<String> = bugs.iter().take(MAX_LISTED_BUGS).map(|b| line(b)).collect();
+    if bugs.len() > MAX_LISTED_BUGS {
+        lines.push(format!("…and {} more", bugs.len() - MAX_LISTED_BUGS));
+    }
+    lines.join("\n")
+}
+
+/// Escape the characters Slack treats as control sequences in message text.
+fn slack_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn post_json(url: &str, body: &serde_json::Value) -> Result<(), BuggerError> {
+    let resp = reqwest::blocking::Client::new()
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body.to_string())
+        .send()
+        .map_err(|e| BuggerError::Network(format!("POST failed: {}", e)))?;
+    let status = resp.status();
+    if !status.is_success() {
+        return Err(BuggerError::from_status("Webhook", status, &resp.text().unwrap_or_default()));
+    }
+    Ok(())
+}
+
+/// Send through BUGGER_SMTP_HOST/PORT (default 587, STARTTLS) as BUGGER_SMTP_USER/PASSWORD,
+/// from BUGGER_SMTP_FROM (default: the configured user email).
+pub fn send_email(config: &AppConfig, to: &[String], subject: &str, html: String) -> Result<(), String> {
+    let host = env::var("BUGGER_SMTP_HOST").map_err(|_| "BUGGER_SMTP_HOST is not set".to_string())?;
+    let port = env::var("BUGGER_SMTP_PORT").ok().and_then(|p| p.trim().parse().ok()).unwrap_or(587);
+    let from = env::var("BUGGER_SMTP_FROM").unwrap_or_else(|_| config.user_email.clone());
+    let mut message = Message::builder().from(from.parse().map_err(|e| format!("Invalid sender '{}': {}", from, e))?).subject(subject);
+    for recipient in to {
+        message = message.to(recipient.parse().map_err(|e| format!("Invalid recipient '{}': {}", recipient, e))?);
+    }
+    let message = message.header(ContentType::TEXT_HTML).body(html).map_err(|e| e.to_string())?;
+    let mut transport = SmtpTransport::starttls_relay(&host).map_err(|e| e.to_string())?.port(port);
+    if let (Ok(user), Ok(password)) = (env::var("BUGGER_SMTP_USER"), env::var("BUGGER_SMTP_PASSWORD")) {
+        transport = transport.credentials(Credentials::new(user, password));
+    }
+    transport.build().send(&message).map(|_| ()).map_err(|e| format!("SMTP error: {}", e))
+}
+
+/// Report HTML for a schedule: the full analysis, or a list of the bugs passing its filter.
+fn report_html(app: &AppHandle, schedule: &OutputSchedule, config: &AppConfig, bugs: &[&Bug]) -> Result<String, String> {
+    if matches!(schedule.filter, OutputFilter::FullReport) {
+        let state = app.state::<AppState>();
+        return Ok(state.analysis.run(|| crate::analyze(app, &state))?);
+    }
+    let items = bug_lines(bugs, |b| {
+        format!(
+            "<li><a href=\"{}\">#{}</a> [{:?}] {}</li>",
+            config.bug_url(b.id),
+            b.id,
+            categorize_bug(b),
+            html_escape::encode_text(&b.title)
+        )
+    });
+    Ok(format!("<h2>{}</h2><p>{} bug(s)</p><ul>{}</ul>", html_escape::encode_text(&schedule.name), bugs.len(), items))
+}
+
+/// Produce and deliver one output.
+pub fn run_output(app: &AppHandle, schedule: &OutputSchedule) -> Result<(), BuggerError> {
+    let config = AppConfig::from_env()?;
+    let all_bugs = app.state::<AppState>().bugs_or_fetch()?;
+    let bugs: Vec<&Bug> = all_bugs.iter().filter(|b| schedule.filter.matches(b)).collect();
+    let title = format!("Bugger: {} — {} bug(s)", schedule.name, bugs.len());
+    match &schedule.channel {
+        Channel::HtmlFile { path } => {
+            let html = standalone_report(&report_html(app, schedule, &config, &bugs)?, &config.export_stamp, &config.user_email);
+            Ok(std::fs::write(path, html).map_err(|e| format!("Failed to write {}: {}", path, e))?)
+        }
+        Channel::Email { to } => {
+            let html = standalone_report(&report_html(app, schedule, &config, &bugs)?, &config.export_stamp, &config.user_email);
+            Ok(send_email(&config, to, &title, html)?)
+        }
+        Channel::Slack { webhook_url } => {
+            let list = bug_lines(&bugs, |b| format!("• <{}|#{}> {}", config.bug_url(b.id), b.id, slack_escape(&b.title)));
+            post_json(webhook_url, &serde_json::json!({ "text": format!("*{}*\n{}", slack_escape(&title), list) }))
+        }
+        Channel::Teams { webhook_url } => {
+            let list = bug_lines(&bugs, |b| format!("- [#{}]({}) {}", b.id, config.bug_url(b.id), b.title));
+            post_json(webhook_url, &serde_json::json!({ "text": format!("**{}**\n\n{}", title, list) }))
+        }
+        Channel::Webhook { url } => {
+            let items: Vec<serde_json::Value> = bugs
+                .iter()
+                .map(|b| {
+                    serde_json::json!({
+                        "id": b.id,
+                        "title": b.title,
+                        "state": b.state,
+                        "severity": b.severity,
+                        "category": categorize_bug(b),
+                        "url": config.bug_url(b.id),
+                    })
+                })
+                .collect();
+            post_json(url, &serde_json::json!({ "schedule": schedule.name, "generated_at": Utc::now(), "bugs": items }))
+        }
+        Channel::Dashboard { path } => {
+            let bugs: Vec<Bug> = bugs.into_iter().cloned().collect();
+            let widget = dashboard::widget(app, &config, &bugs, Utc::now());
+            let body = if path.to_lowercase().ends_with(".json") {
+                serde_json::to_string_pretty(&widget).map_err(|e| e.to_string())?
+            } else {
+                dashboard::html(&widget)
+            };
+            Ok(std::fs::write(path, body).map_err(|e| format!("Failed to write {}: {}", path, e))?)
+        }
+    }
+}
+
+/// Run every enabled output that is due and record when it ran (also after a failure, so a broken
+/// channel is retried at its next slot rather than every tick). Only the run times are written
+/// back, so schedules edited while an output was running keep the edits. Messages due outside
+/// working hours stay due until they start.
+fn run_due(app: &AppHandle) -> Result<(), String> {
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    let now = Utc::now();
+    let quiet = config.quiet_hours.as_ref().is_some_and(|q| !q.is_working_time(now));
+    let mut ran = Vec::new();
+    for schedule in load(app)?.iter().filter(|s| s.enabled && s.cadence.is_due(s.last_run, now) && !(quiet && s.channel.is_message())) {
+        match run_output(app, schedule) {
+            Ok(()) => info!("Output '{}' delivered", schedule.name),
+            Err(e) => warn!("Output '{}' failed: {}", schedule.name, e),
+        }
+        ran.push(schedule.id.clone());
+    }
+    if !ran.is_empty() {
+        storage::update_json(app, SCHEDULE_STORE, |schedules: &mut Vec<OutputSchedule>| {
+            for schedule in schedules.iter_mut().filter(|s| ran.contains(&s.id)) {
+                schedule.last_run = Some(now);
+            }
+        })?;
+    }
+    Ok(())
+}
+
+/// Check for due outputs once a minute in the background.
+pub fn start_scheduler(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(std::time::Duration::from_secs(TICK_SECONDS));
+        if let Err(e) = run_due(&app) {
+            warn!("Output scheduler failed: {}", e);
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_output_schedules(app: AppHandle) -> Result<Vec<OutputSchedule>, String> {
+    load(&app)
+}
+
+#[tauri::command]
+/// Replaces the output schedules. New schedules get an id and start at their next slot, not immediately.
+pub fn save_output_schedules(app: AppHandle, schedules: Vec<OutputSchedule>) -> Result<Vec<OutputSchedule>, String> {
+    let now = Utc::now();
+    let mut saved = Vec::with_capacity(schedules.len());
+    for (i, mut schedule) in schedules.into_iter().enumerate() {
+        if schedule.name.trim().is_empty() {
+            return Err(format!("Output #{} needs a name", i + 1));
+        }
+        schedule.cadence.validate().map_err(|e| format!("{}: {}", schedule.name, e))?;
+        if schedule.id.is_empty() {
+            schedule.id = format!("{}-{}", now.timestamp_millis(), i);
+        }
+        schedule.last_run = schedule.last_run.or(Some(now));
+        saved.push(schedule);
+    }
+    storage::save_json(&app, SCHEDULE_STORE, &saved)?;
+    Ok(saved)
+}
+
+#[tauri::command]
+/// Delivers one output immediately, regardless of its cadence.
+pub fn run_output_now(app: AppHandle, id: String) -> Result<(), BuggerError> {
+    let schedule = load(&app)?.into_iter().find(|s| s.id == id).ok_or_else(|| format!("No output with id '{}'", id))?;
+    run_output(&app, &schedule)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_messages_wait_for_working_hours() {
+        assert!(Channel::Email { to: vec!["me@contoso.com".to_string()] }.is_message());
+        assert!(Channel::Teams { webhook_url: "https://contoso.webhook.office.com/x".to_string() }.is_message());
+        assert!(!Channel::Dashboard { path: "widget.json".to_string() }.is_message());
+        assert!(!Channel::Webhook { url: "https://contoso.com/hook".to_string() }.is_message());
+    }
+
+    #[test]
+    fn slack_text_is_escaped() {
+        assert_eq!(slack_escape("a <b> & <!channel>"), "a &lt;b&gt; &amp; &lt;!channel&gt;");
+        assert_eq!(slack_escape("plain"), "plain");
+    }
+
+    #[test]
+    fn hourly_outputs_are_due_an_hour_after_the_last_run() {
+        let now = Utc::now();
+        assert!(Cadence::Hourly.is_due(None, now));
+        assert!(Cadence::Hourly.is_due(Some(now - Duration::minutes(61)), now));
+        assert!(!Cadence::Hourly.is_due(Some(now - Duration::minutes(30)), now));
+    }
+
+    #[test]
+    fn cadences_validate_times_and_days() {
+        assert!(Cadence::Daily { at: "09:30".to_string() }.validate().is_ok());
+        assert!(Cadence::Daily { at: "9h".to_string() }.validate().is_err());
+        assert!(Cadence::Weekly { day: "Mon".to_string(), at: "09:00".to_string() }.validate().is_ok());
+        assert!(Cadence::Weekly { day: "Someday".to_string(), at: "09:00".to_string() }.validate().is_err());
+    }
+}
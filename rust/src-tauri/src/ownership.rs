@@ -0,0 +1,281 @@
+// Owner suggestions for unassigned or misassigned bugs: an owners file (CODEOWNERS-style, by area
+// path or category) plus who fixed similar bugs before
+use crate::audit::{self, update_fields_reversible, WriteAction};
+use crate::azure_devops::{AzureDevOpsClient, Bug, Identity};
+use crate::bug_analysis::{categorize_bug, BugCategory};
+use crate::wiql::WiqlQuery;
+use crate::error::BuggerError;
+use crate::{power, storage, AppConfig};
+use chrono::{Local, NaiveDate};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tauri::AppHandle;
+
+/// Recently fixed bugs and who fixed them, refreshed once a day.
+const FIXES_STORE: &str = "fix_history";
+const HISTORY_DAYS: u32 = 180;
+/// Most recent fixes fetched per refresh.
+const FETCH_LIMIT: usize = 200;
+const MAX_FIXES: usize = 2000;
+const OWNERS_FILE_SCORE: u32 = 10;
+/// Lowest score worth suggesting, and the score that outweighs an assignee with no similar fixes.
+const MIN_SCORE: u32 = 4;
+const MISASSIGNED_SCORE: u32 = 8;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PastFix {
+    bug_id: u64,
+    title: String,
+    area_path: Option<String>,
+    category: BugCategory,
+    fixer: Identity,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct FixHistory {
+    refreshed: Option<NaiveDate>,
+    fixes: Vec<PastFix>,
+}
+
+enum OwnerPattern {
+    Area(String),
+    Category(BugCategory),
+}
+
+struct OwnerRule {
+    pattern: OwnerPattern,
+    line: String,
+    owners: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OwnershipSuggestion {
+    pub bug_id: u64,
+    /// Unique name to assign to.
+    pub suggested: String,
+    pub display_name: String,
+    pub reasons: Vec<String>,
+    /// Whether the bug has no assignee (otherwise it looks misassigned).
+    pub unassigned: bool,
+}
+
+impl OwnershipSuggestion {
+    pub fn html(&self) -> String {
+        format!(
+            "<br><small>🧑‍🔧 {}: suggested owner <b>{}</b> ({}) <button class='reassign-btn' data-bug-id='{}' data-to='{}'>Reassign</button></small>",
+            if self.unassigned { "Unassigned" } else { "Possibly misassigned" },
+            html_escape::encode_text(&self.display_name),
+            html_escape::encode_text(&self.reasons.join("; ")),
+            self.bug_id,
+            html_escape::encode_single_quoted_attribute(&self.suggested)
+        )
+    }
+}
+
+/// Owners file lines: `<area path or category:<name>> <owner email>...`; `#` starts a comment.
+fn parse_owners(text: &str) -> Vec<OwnerRule> {
+    text.lines()
+        .map(|l| l.split('#').next().unwrap_or("").trim())
+        .filter(|l| !l.is_empty())
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let target = parts.next()?;
+            let owners: Vec<String> = parts.map(str::to_string).collect();
+            if owners.is_empty() {
+                warn!("Ignoring owners line without owners: {}", line);
+                return None;
+            }
+            let pattern = match target.strip_prefix("category:") {
+                Some(name) => match name.parse() {
+                    Ok(category) => OwnerPattern::Category(category),
+                    Err(e) => {
+                        warn!("Ignoring owners line: {}", e);
+                        return None;
+                    }
+                },
+                None => OwnerPattern::Area(target.trim_end_matches('\\').to_lowercase()),
+            };
+            Some(OwnerRule { pattern, line: target.to_string(), owners })
+        })
+        .collect()
+}
+
+fn load_owners(config: &AppConfig) -> Vec<OwnerRule> {
+    let Some(path) = &config.owners_path else {
+        return Vec::new();
+    };
+    match std::fs::read_to_string(path) {
+        Ok(text) => parse_owners(&text),
+        Err(e) => {
+            warn!("Failed to read owners file {}: {}", path, e);
+            Vec::new()
+        }
+    }
+}
+
+fn is_under(area: &str, path: &str) -> bool {
+    let area = area.to_lowercase();
+    area == path || area.starts_with(&format!("{}\\", path))
+}
+
+/// Like CODEOWNERS, the last matching line wins.
+fn owners_rule<'a>(rules: &'a [OwnerRule], bug: &Bug, category: &BugCategory) -> Option<&'a OwnerRule> {
+    rules.iter().rev().find(|rule| match &rule.pattern {
+        OwnerPattern::Area(path) => bug.area_path.as_deref().is_some_and(|a| is_under(a, path)),
+        OwnerPattern::Category(c) => c == category,
+    })
+}
+
+fn title_words(title: &str) -> HashSet<String> {
+    title.split(|c: char| !c.is_alphanumeric()).filter(|w| w.chars().count() > 3).map(str::to_lowercase).collect()
+}
+
+/// The parent of an area below the project's own subareas, e.g. "Fabrikam\Web" for
+/// "Fabrikam\Web\Login". Areas directly under the project root share nothing but the project.
+fn parent_area(path: &str) -> Option<&str> {
+    let parent = path.rsplit_once('\\')?.0;
+    parent.contains('\\').then_some(parent)
+}
+
+/// How alike a past fix is to `bug`: same or sibling area, same category and shared title words.
+fn similarity(bug: &Bug, category: &BugCategory, words: &HashSet<String>, fix: &PastFix) -> u32 {
+    let area = match (bug.area_path.as_deref(), fix.area_path.as_deref()) {
+        (Some(a), Some(b)) if a.eq_ignore_ascii_case(b) => 3,
+        (Some(a), Some(b)) if parent_area(a).zip(parent_area(b)).is_some_and(|(a, b)| a.eq_ignore_ascii_case(b)) => 1,
+        _ => 0,
+    };
+    let category = if *category == fix.category { 2 } else { 0 };
+    let shared = words.intersection(&title_words(&fix.title)).count().min(3) as u32;
+    area + category + shared
+}
+
+/// Add the fixes of the last HISTORY_DAYS days (in the team's areas, in team mode) to the stored history, once a day.
+/// It's a big sync, so once there is a history it waits until the user is idle.
+fn refresh_history(app: &AppHandle, client: &AzureDevOpsClient) -> Result<FixHistory, String> {
+    let mut history: FixHistory = storage::load_json(app, FIXES_STORE)?;
+    let today = Local::now().date_naive();
+    if history.refreshed == Some(today) || (history.refreshed.is_some() && !client.config.power.big_sync_allowed(&power::detect())) {
+        return Ok(history);
+    }
+    let wiql = WiqlQuery::new()
+        .work_item_type("Bug")
+        .area_under_any(&client.config.query_filters.team_area_paths)
+        .resolved_within_days(HISTORY_DAYS)
+        .order_by("Microsoft.VSTS.Common.ResolvedDate", true)
+        .build();
+    let mut ids = client.run_wiql(&wiql)?;
+    ids.truncate(FETCH_LIMIT);
+    let fixers: HashMap<u64, Identity> = client.fetch_resolvers(&ids)?.into_iter().collect();
+    let bugs = client.fetch_bug_details(&ids)?;
+    history.fixes.retain(|f| !fixers.contains_key(&f.bug_id));
+    history.fixes.extend(bugs.iter().filter_map(|bug| {
+        Some(PastFix { bug_id: bug.id, title: bug.title.clone(), area_path: bug.area_path.clone(), category: categorize_bug(bug), fixer: fixers.get(&bug.id)?.clone() })
+    }));
+    let excess = history.fixes.len().saturating_sub(MAX_FIXES);
+    history.fixes.drain(..excess);
+    history.refreshed = Some(today);
+    storage::save_json(app, FIXES_STORE, &history)?;
+    Ok(history)
+}
+
+fn suggest(bug: &Bug, rules: &[OwnerRule], fixes: &[PastFix]) -> Option<OwnershipSuggestion> {
+    let category = categorize_bug(bug);
+    let words = title_words(&bug.title);
+    // Keyed by lowercase unique name: (display name, score, similar fixes).
+    let mut candidates: HashMap<String, (String, u32, usize)> = HashMap::new();
+    for fix in fixes.iter().filter(|f| f.bug_id != bug.id) {
+        let score = similarity(bug, &category, &words, fix);
+        if score >= 2 {
+            let entry = candidates.entry(fix.fixer.unique_name.to_lowercase()).or_insert_with(|| (fix.fixer.display_name.clone(), 0, 0));
+            entry.1 += score;
+            entry.2 += 1;
+        }
+    }
+    let rule = owners_rule(rules, bug, &category);
+    let rule_owners: Vec<String> = rule.map(|r| r.owners.iter().map(|o| o.to_lowercase()).collect()).unwrap_or_default();
+    for owner in &rule_owners {
+        candidates.entry(owner.clone()).or_insert_with(|| (owner.clone(), 0, 0)).1 += OWNERS_FILE_SCORE;
+    }
+    let (key, (name, score, similar)) = candidates.iter().max_by(|a, b| a.1 .1.cmp(&b.1 .1).then(b.0.cmp(a.0)))?;
+    if *score < MIN_SCORE {
+        return None;
+    }
+    let unassigned = bug.assigned_to.is_none();
+    if let Some(assignee) = &bug.assigned_to {
+        let assignee = assignee.unique_name.to_lowercase();
+        let assignee_score = candidates.get(&assignee).map_or(0, |c| c.1);
+        let against_owners_file = !rule_owners.is_empty() && !rule_owners.contains(&assignee);
+        if assignee == *key || !(against_owners_file || (assignee_score == 0 && *score >= MISASSIGNED_SCORE)) {
+            return None;
+        }
+    }
+    let mut reasons = Vec::new();
+    if let Some(rule) = rule.filter(|_| rule_owners.contains(key)) {
+        reasons.push(format!("owners file: {}", rule.line));
+    }
+    if *similar > 0 {
+        reasons.push(format!("fixed {} similar bug(s)", similar));
+    }
+    Some(OwnershipSuggestion { bug_id: bug.id, suggested: key.clone(), display_name: name.clone(), reasons, unassigned })
+}
+
+/// Owner suggestions for the unassigned and likely misassigned bugs in `bugs`.
+pub fn suggestions(app: &AppHandle, client: &AzureDevOpsClient, bugs: &[Bug]) -> HashMap<u64, OwnershipSuggestion> {
+    let rules = load_owners(&client.config);
+    let fixes = refresh_history(app, client).map(|h| h.fixes).unwrap_or_else(|e| {
+        warn!("Fix history unavailable: {}", e);
+        Vec::new()
+    });
+    bugs.iter().filter_map(|bug| suggest(bug, &rules, &fixes)).map(|s| (s.bug_id, s)).collect()
+}
+
+#[tauri::command]
+/// Assigns a bug to `to` (unique name or email). Undoable from the audit log.
+pub fn reassign_bug(app: AppHandle, id: u64, to: String) -> Result<(), BuggerError> {
+    let config = AppConfig::from_env()?;
+    let actor = config.user_email.clone();
+    let client = AzureDevOpsClient::new(config);
+    audit::perform(&app, &actor, id, WriteAction::Reassign, format!("Assigned to {}", to), false, || update_fields_reversible(&client, id, &[("System.AssignedTo", to.as_str())]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fix(area_path: &str) -> PastFix {
+        PastFix {
+            bug_id: 1,
+            title: "Unrelated".to_string(),
+            area_path: Some(area_path.to_string()),
+            category: BugCategory::Crash,
+            fixer: Identity { display_name: "Ann".to_string(), unique_name: "ann@contoso.com".to_string(), avatar_url: None },
+        }
+    }
+
+    fn area_score(bug_area: &str, fix_area: &str) -> u32 {
+        let mut bug = Bug::sample(2, "Something");
+        bug.area_path = Some(bug_area.to_string());
+        similarity(&bug, &BugCategory::Performance, &HashSet::new(), &fix(fix_area))
+    }
+
+    #[test]
+    fn sibling_areas_need_a_shared_parent_below_the_root() {
+        assert_eq!(area_score("Fabrikam\\Web\\Login", "fabrikam\\web\\login"), 3);
+        assert_eq!(area_score("Fabrikam\\Web\\Login", "Fabrikam\\Web\\Search"), 1);
+        assert_eq!(area_score("Fabrikam\\Web\\Login", "Fabrikam\\WEB\\Search"), 1);
+        assert_eq!(area_score("Fabrikam\\Web", "Fabrikam\\Mobile"), 0);
+        assert_eq!(area_score("Fabrikam", "Contoso"), 0);
+        assert_eq!(area_score("Fabrikam\\Web\\Login", "Fabrikam\\Mobile\\Login"), 0);
+    }
+
+    #[test]
+    fn category_and_title_words_add_up() {
+        let mut bug = Bug::sample(2, "Crash when saving large files");
+        bug.area_path = None;
+        let words = title_words(&bug.title);
+        let past = PastFix { title: "Crash saving large projects".to_string(), ..fix("Fabrikam\\Web") };
+        // Same category (2) and "crash", "saving", "large" shared (3, capped).
+        assert_eq!(similarity(&bug, &BugCategory::Crash, &words, &past), 5);
+    }
+}
@@ -0,0 +1,127 @@
+// PAT introspection: which scopes the token can use, when it expires, and an early expiry warning
+use crate::azure_devops::{is_auth_failure, probe};
+use crate::notifications::{deliver, QueuedNotification};
+use crate::{storage, AppConfig};
+use chrono::{NaiveDate, Utc};
+use log::warn;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::env;
+use tauri::AppHandle;
+
+/// Warn this many days before the PAT expires.
+const EXPIRY_WARNING_DAYS: i64 = 7;
+
+/// Date of the last expiry warning, so it is shown at most once a day.
+const LAST_WARNING_STORE: &str = "pat_expiry_warning";
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ScopeCheck {
+    /// Scope name as shown on the Azure DevOps token page.
+    pub scope: String,
+    pub granted: bool,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PatInfo {
+    pub scopes: Vec<ScopeCheck>,
+    /// Expiry date from BUGGER_PAT_EXPIRES (YYYY-MM-DD), if configured.
+    pub expires: Option<NaiveDate>,
+    pub days_left: Option<i64>,
+    pub expiring_soon: bool,
+}
+
+/// Expiry date of the PAT. The tokens API only accepts Microsoft Entra tokens, not the PAT itself,
+/// so the date is taken from BUGGER_PAT_EXPIRES as shown on the token page.
+pub fn pat_expiry() -> Option<NaiveDate> {
+    let value = env::var("BUGGER_PAT_EXPIRES").ok()?;
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .map_err(|_| warn!("Invalid BUGGER_PAT_EXPIRES '{}', expected YYYY-MM-DD", value))
+        .ok()
+}
+
+fn days_left(expires: NaiveDate) -> i64 {
+    (expires - Utc::now().date_naive()).num_days()
+}
+
+/// Probe one read-only endpoint per scope Bugger can use; anything but success counts as not granted,
+/// including the 203 sign-in page Azure DevOps answers a rejected token with.
+pub fn probe_scopes(config: &AppConfig) -> Vec<ScopeCheck> {
+    let base = format!("{}/{}", config.base_url, config.org);
+    let wiql = serde_json::json!({ "query": "SELECT [System.Id] FROM WorkItems WHERE [System.Id] = 0" });
+    let probes = [
+        ("Project and Team (Read)", format!("{}/_apis/projects?$top=1&api-version=7.0", base), None),
+        ("Work Items (Read)", format!("{}/{}/_apis/wit/wiql?api-version=7.0", base, config.project), Some(&wiql)),
+        ("Code (Read)", format!("{}/{}/_apis/git/repositories?api-version=7.0", base, config.project), None),
+        ("Build (Read)", format!("{}/{}/_apis/build/definitions?$top=1&api-version=7.0", base, config.project), None),
+    ];
+    probes
+        .into_iter()
+        .map(|(scope, url, body)| ScopeCheck {
+            scope: scope.to_string(),
+            granted: probe(&url, &config.azure_devops_pat, body).is_ok_and(|s| s.is_success() && !is_auth_failure(s)),
+        })
+        .collect()
+}
+
+/// Notify when the PAT expires within a week, at most once a day.
+pub fn warn_if_expiring(app: &AppHandle, config: &AppConfig) {
+    let Some(expires) = pat_expiry() else {
+        return;
+    };
+    let days = days_left(expires);
+    if days > EXPIRY_WARNING_DAYS {
+        return;
+    }
+    let today = Utc::now().date_naive();
+    let last: Option<NaiveDate> = storage::load_json(app, LAST_WARNING_STORE).unwrap_or_default();
+    if last == Some(today) {
+        return;
+    }
+    let body = if days < 0 {
+        format!("Your Azure DevOps PAT expired on {}. Create a new one to keep Bugger working.", expires)
+    } else {
+        format!("Your Azure DevOps PAT expires in {} day(s) ({}). Renew it before it stops working.", days, expires)
+    };
+    deliver(app, config.quiet_hours.as_ref(), vec![QueuedNotification { title: "Azure DevOps token expiring".to_string(), body, queued_at: Utc::now(), bug_id: None }]);
+    if let Err(e) = storage::save_json(app, LAST_WARNING_STORE, &Some(today)) {
+        warn!("Failed to record PAT expiry warning: {}", e);
+    }
+}
+
+#[tauri::command]
+/// Reports the PAT's usable scopes (by probing) and its expiry for the settings screen.
+pub fn get_pat_info() -> Result<PatInfo, String> {
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    let expires = pat_expiry();
+    let days_left = expires.map(days_left);
+    Ok(PatInfo {
+        scopes: probe_scopes(&config),
+        expires,
+        days_left,
+        expiring_soon: days_left.is_some_and(|d| d <= EXPIRY_WARNING_DAYS),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::azure_devops::tests::test_config;
+    use httpmock::prelude::*;
+
+    #[test]
+    fn scopes_are_probed_on_the_configured_server() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/contoso/_apis/projects");
+            then.status(200).body("{}");
+        });
+        server.mock(|when, then| {
+            when.method(POST).path("/contoso/Fabrikam/_apis/wit/wiql");
+            then.status(200).body("{}");
+        });
+        let checks = probe_scopes(&test_config(&server.base_url()));
+        let granted: Vec<_> = checks.iter().filter(|c| c.granted).map(|c| c.scope.as_str()).collect();
+        assert_eq!(granted, ["Project and Team (Read)", "Work Items (Read)"]);
+    }
+}
@@ -0,0 +1,240 @@
+// Opt-in auto-close policy for abandoned questionable bugs: warn, then resolve as Cannot Reproduce
+use crate::audit::{self, update_fields_reversible, WriteAction, WriteOutcome};
+use crate::azure_devops::{AzureDevOpsClient, Bug, Comment};
+use crate::bug_analysis::is_questionable;
+use crate::process::{self, StateCategory};
+use crate::roles::Role;
+use crate::error::BuggerError;
+use crate::{AppConfig, AppState};
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::env;
+use tauri::{AppHandle, State};
+
+/// Marker identifying Bugger's final-warning comment so the second stage can find it.
+const WARNING_MARKER: &str = "[Bugger auto-close]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyMode {
+    Off,
+    DryRun,
+    On,
+}
+
+#[derive(Debug, Clone)]
+pub struct AutoClosePolicy {
+    pub mode: PolicyMode,
+    /// Tag marking bugs waiting for information from the reporter.
+    pub tag: String,
+    /// Days without a response before the final warning is posted.
+    pub warn_after_days: i64,
+    /// Days after the warning before the bug is resolved.
+    pub close_after_days: i64,
+}
+
+impl AutoClosePolicy {
+    /// Load from BUGGER_AUTOCLOSE (off | dry-run | on), BUGGER_AUTOCLOSE_TAG,
+    /// BUGGER_AUTOCLOSE_WARN_DAYS and BUGGER_AUTOCLOSE_CLOSE_DAYS. Off unless explicitly enabled.
+    pub fn from_env() -> Self {
+        let mode = match env::var("BUGGER_AUTOCLOSE").unwrap_or_default().trim().to_lowercase().as_str() {
+            "on" | "true" => PolicyMode::On,
+            "dry-run" | "dryrun" => PolicyMode::DryRun,
+            _ => PolicyMode::Off,
+        };
+        let days = |name: &str, default: i64| env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default);
+        AutoClosePolicy {
+            mode,
+            tag: env::var("BUGGER_AUTOCLOSE_TAG").unwrap_or_else(|_| "needs-info".to_string()),
+            warn_after_days: days("BUGGER_AUTOCLOSE_WARN_DAYS", 14),
+            close_after_days: days("BUGGER_AUTOCLOSE_CLOSE_DAYS", 7),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+pub enum PolicyStep {
+    Warn,
+    Resolve,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PolicyAction {
+    pub bug_id: u64,
+    pub step: PolicyStep,
+    pub dry_run: bool,
+    pub error: Option<String>,
+}
+
+fn days_since(date: Option<&str>, now: DateTime<Utc>) -> i64 {
+    date.and_then(|d| DateTime::parse_from_rfc3339(d).ok())
+        .map(|d| (now - d.with_timezone(&Utc)).num_days())
+        .unwrap_or(0)
+}
+
+/// Bugs the policy applies to: questionable and carrying the needs-info tag.
+pub fn is_candidate(bug: &Bug, policy: &AutoClosePolicy) -> bool {
+    is_questionable(bug).is_some() && bug.tags.iter().any(|t| t.eq_ignore_ascii_case(&policy.tag))
+}
+
+/// Decide the next step for a candidate bug given its comment thread, if any is due.
+pub fn next_step(bug: &Bug, comments: &[Comment], policy: &AutoClosePolicy, me: &[String], now: DateTime<Utc>) -> Option<PolicyStep> {
+    let from_others = |c: &&Comment| !me.iter().any(|m| c.author_email.eq_ignore_ascii_case(m));
+    match comments.iter().rposition(|c| c.text.contains(WARNING_MARKER)) {
+        Some(warning_idx) => {
+            // Anyone answering after the warning cancels the close; a human should re-triage.
+            if comments[warning_idx + 1..].iter().any(|c| from_others(&c)) {
+                return None;
+            }
+            let warned_days = days_since(comments[warning_idx].created_date.as_deref(), now);
+            (warned_days >= policy.close_after_days).then_some(PolicyStep::Resolve)
+        }
+        None => {
+            let last_response = comments.iter().rev().find(from_others).and_then(|c| c.created_date.as_deref());
+            let quiet_days = days_since(last_response.or(bug.created_date.as_deref()), now);
+            (quiet_days >= policy.warn_after_days).then_some(PolicyStep::Warn)
+        }
+    }
+}
+
+fn apply_step(client: &AzureDevOpsClient, bug_id: u64, step: PolicyStep, policy: &AutoClosePolicy) -> Result<WriteOutcome, BuggerError> {
+    match step {
+        PolicyStep::Warn => client
+            .add_comment(
+                bug_id,
+                &format!(
+                    "{} This bug has been waiting for more information for {} days. If there is no response within {} days it will be resolved as Cannot Reproduce.",
+                    WARNING_MARKER, policy.warn_after_days, policy.close_after_days
+                ),
+            )
+            .map(WriteOutcome::comment),
+        PolicyStep::Resolve => {
+            // Scrum and Basic bugs go straight to Done, which has no resolved reason.
+            let process = process::model(client);
+            let state = process.resolved_state();
+            let mut updates = vec![("System.State", state.as_str())];
+            if process.category(&state) == StateCategory::Resolved {
+                updates.push(("Microsoft.VSTS.Common.ResolvedReason", "Cannot Reproduce"));
+            }
+            update_fields_reversible(client, bug_id, &updates)
+        }
+    }
+}
+
+/// Evaluate the policy over `bugs` and perform (or, in dry-run, only log) the due steps.
+/// Every action is written to the audit log.
+pub fn run_auto_close(app: &AppHandle, client: &AzureDevOpsClient, bugs: &[Bug], policy: &AutoClosePolicy, dry_run: bool) -> Vec<PolicyAction> {
+    let my_email = client.config.user_email.clone();
+    let me = client.config.my_identities();
+    let now = Utc::now();
+    let mut actions = Vec::new();
+    for bug in bugs.iter().filter(|b| is_candidate(b, policy)) {
+        let comments = match client.fetch_comments(bug.id) {
+            Ok(c) => c,
+            Err(e) => {
+                warn!("Auto-close: failed to fetch comments for #{}: {}", bug.id, e);
+                continue;
+            }
+        };
+        let Some(step) = next_step(bug, &comments, policy, &me, now) else {
+            continue;
+        };
+        let action = match step {
+            PolicyStep::Warn => WriteAction::AutoCloseWarning,
+            PolicyStep::Resolve => WriteAction::AutoCloseResolve,
+        };
+        let result = audit::perform(app, &my_email, bug.id, action, bug.title.clone(), dry_run, || apply_step(client, bug.id, step, policy));
+        info!("Auto-close {:?} #{} (dry run: {})", step, bug.id, dry_run);
+        actions.push(PolicyAction { bug_id: bug.id, step, dry_run, error: result.err().map(String::from) });
+    }
+    actions
+}
+
+#[tauri::command]
+/// Runs the auto-close policy now. `dry_run` defaults to the configured mode; running while the
+/// policy is off is always a dry run.
+pub fn run_auto_close_policy(app: AppHandle, state: State<AppState>, dry_run: Option<bool>) -> Result<Vec<PolicyAction>, BuggerError> {
+    let config = AppConfig::from_env()?;
+    config.provider.require_azure_devops("The auto-close policy")?;
+    let policy = config.auto_close.clone();
+    let dry_run = policy.mode != PolicyMode::On || dry_run.unwrap_or(false);
+    if !dry_run {
+        config.roles.check(&config.user_email, Role::Admin, "running the auto-close policy")?;
+    }
+    let bugs = state.bugs_or_fetch()?;
+    Ok(run_auto_close(&app, &AzureDevOpsClient::new(config), &bugs, &policy, dry_run))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn policy() -> AutoClosePolicy {
+        AutoClosePolicy { mode: PolicyMode::On, tag: "needs-info".to_string(), warn_after_days: 14, close_after_days: 7 }
+    }
+
+    fn me() -> Vec<String> {
+        vec!["me@contoso.com".to_string()]
+    }
+
+    fn created(days_ago: i64, now: DateTime<Utc>) -> Bug {
+        let mut bug = Bug::sample(1, "Crash");
+        bug.created_date = Some((now - Duration::days(days_ago)).to_rfc3339());
+        bug
+    }
+
+    fn comment(by: &str, text: &str, days_ago: i64, now: DateTime<Utc>) -> Comment {
+        Comment { text: text.to_string(), author: by.to_string(), author_email: by.to_string(), created_date: Some((now - Duration::days(days_ago)).to_rfc3339()) }
+    }
+
+    fn warning(days_ago: i64, now: DateTime<Utc>) -> Comment {
+        comment("me@contoso.com", &format!("{} Waiting for more information.", WARNING_MARKER), days_ago, now)
+    }
+
+    #[test]
+    fn only_tagged_questionable_bugs_are_candidates() {
+        let mut bug = Bug::sample(1, "Crash");
+        assert!(!is_candidate(&bug, &policy()));
+        bug.tags.push("Needs-Info".to_string());
+        assert!(is_candidate(&bug, &policy()));
+        bug.description = Some("Explorer crashes when opening a zip file from the desktop, every time since build 26100.".to_string());
+        assert!(!is_candidate(&bug, &policy()));
+    }
+
+    #[test]
+    fn warns_after_the_quiet_days() {
+        let now = Utc::now();
+        assert_eq!(next_step(&created(14, now), &[], &policy(), &me(), now), Some(PolicyStep::Warn));
+        assert_eq!(next_step(&created(13, now), &[], &policy(), &me(), now), None);
+        // The reporter's last answer restarts the count.
+        let answered = [comment("reporter@contoso.com", "It happens on build 26100.", 3, now)];
+        assert_eq!(next_step(&created(30, now), &answered, &policy(), &me(), now), None);
+    }
+
+    #[test]
+    fn resolves_the_close_days_after_the_warning() {
+        let now = Utc::now();
+        let bug = created(30, now);
+        assert_eq!(next_step(&bug, &[warning(7, now)], &policy(), &me(), now), Some(PolicyStep::Resolve));
+        assert_eq!(next_step(&bug, &[warning(6, now)], &policy(), &me(), now), None);
+    }
+
+    #[test]
+    fn a_reply_after_the_warning_cancels_the_close() {
+        let now = Utc::now();
+        let thread = [warning(10, now), comment("reporter@contoso.com", "Logs attached.", 9, now)];
+        assert_eq!(next_step(&created(30, now), &thread, &policy(), &me(), now), None);
+    }
+
+    #[test]
+    fn my_own_comments_are_not_a_response() {
+        let now = Utc::now();
+        let bug = created(30, now);
+        let pinged = [comment("ME@contoso.com", "Any news?", 1, now)];
+        assert_eq!(next_step(&bug, &pinged, &policy(), &me(), now), Some(PolicyStep::Warn));
+        let thread = [warning(8, now), comment("me@contoso.com", "Closing soon.", 2, now)];
+        assert_eq!(next_step(&bug, &thread, &policy(), &me(), now), Some(PolicyStep::Resolve));
+    }
+}
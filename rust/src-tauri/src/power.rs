@@ -0,0 +1,262 @@
+// Power and network awareness: scheduled refreshes and prefetching are skipped on battery or a
+// metered connection, and big syncs wait until the user is idle
+use crate::env_flag;
+use log::info;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::env;
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a detected state is reused; detection runs external commands on some platforms and
+/// is checked before every refresh, prefetch and sync.
+const DETECT_TTL: Duration = Duration::from_secs(30);
+
+static DETECTED: Mutex<Option<(Instant, SystemState)>> = Mutex::new(None);
+
+/// What the system reports right now; `None` where it can't be detected on this platform.
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct SystemState {
+    pub on_battery: Option<bool>,
+    pub metered: Option<bool>,
+    /// Seconds since the last keyboard or mouse input.
+    pub idle_seconds: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PowerPolicy {
+    pub skip_on_battery: bool,
+    pub skip_on_metered: bool,
+    /// Minutes without input before big syncs run; 0 runs them any time.
+    pub idle_minutes: u64,
+}
+
+/// The current state and what it means for background work, for the settings page.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PowerStatus {
+    pub state: SystemState,
+    /// Why refreshes and prefetching are being skipped, if they are.
+    pub heavy_work_blocked: Option<String>,
+    pub big_sync_allowed: bool,
+}
+
+impl PowerPolicy {
+    /// Load from BUGGER_SKIP_ON_BATTERY and BUGGER_SKIP_ON_METERED (both on by default) and
+    /// BUGGER_BIG_SYNC_IDLE_MINUTES (default 5).
+    pub fn from_env() -> Self {
+        PowerPolicy {
+            skip_on_battery: env_flag("BUGGER_SKIP_ON_BATTERY", true),
+            skip_on_metered: env_flag("BUGGER_SKIP_ON_METERED", true),
+            idle_minutes: env::var("BUGGER_BIG_SYNC_IDLE_MINUTES").ok().and_then(|v| v.parse().ok()).unwrap_or(5),
+        }
+    }
+
+    /// Why heavy background work should be skipped in `state`, if it should.
+    pub fn heavy_work_blocked(&self, state: &SystemState) -> Option<&'static str> {
+        if self.skip_on_battery && state.on_battery == Some(true) {
+            Some("running on battery")
+        } else if self.skip_on_metered && state.metered == Some(true) {
+            Some("on a metered connection")
+        } else {
+            None
+        }
+    }
+
+    /// Big syncs run when heavy work is allowed and the user has been idle long enough. Where idle
+    /// time can't be detected they aren't held back.
+    pub fn big_sync_allowed(&self, state: &SystemState) -> bool {
+        self.heavy_work_blocked(state).is_none() && state.idle_seconds.is_none_or(|s| s >= self.idle_minutes * 60)
+    }
+
+    /// Detect the system state and log why heavy work is skipped, if it is.
+    pub fn check_heavy_work(&self, what: &str) -> bool {
+        match self.heavy_work_blocked(&detect()) {
+            Some(reason) => {
+                info!("Skipping {}: {}", what, reason);
+                false
+            }
+            None => true,
+        }
+    }
+}
+
+/// The current system state, reused for DETECT_TTL.
+pub fn detect() -> SystemState {
+    let Ok(mut detected) = DETECTED.lock() else {
+        return SystemState { on_battery: on_battery(), metered: metered(), idle_seconds: idle_seconds() };
+    };
+    if let Some((_, state)) = detected.as_ref().filter(|(at, _)| at.elapsed() < DETECT_TTL) {
+        return state.clone();
+    }
+    let state = SystemState { on_battery: on_battery(), metered: metered(), idle_seconds: idle_seconds() };
+    *detected = Some((Instant::now(), state.clone()));
+    state
+}
+
+/// Trimmed stdout of a command that succeeded.
+#[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+fn output(program: &str, args: &[&str]) -> Option<String> {
+    let mut command = Command::new(program);
+    command.args(args);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        // CREATE_NO_WINDOW: no console flashing up on every check.
+        command.creation_flags(0x0800_0000);
+    }
+    let out = command.output().ok().filter(|o| o.status.success())?;
+    Some(String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+#[cfg(windows)]
+fn on_battery() -> Option<bool> {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+    let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return None;
+    }
+    match status.ACLineStatus {
+        0 => Some(true),
+        1 => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(windows)]
+fn idle_seconds() -> Option<u64> {
+    use windows_sys::Win32::System::SystemInformation::GetTickCount;
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+    let mut info = LASTINPUTINFO { cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32, dwTime: 0 };
+    if unsafe { GetLastInputInfo(&mut info) } == 0 {
+        return None;
+    }
+    // Both are milliseconds since boot and wrap after 49.7 days.
+    Some(u64::from(unsafe { GetTickCount() }.wrapping_sub(info.dwTime)) / 1000)
+}
+
+#[cfg(windows)]
+fn metered() -> Option<bool> {
+    const SCRIPT: &str = "[void][Windows.Networking.Connectivity.NetworkInformation,Windows.Networking.Connectivity,ContentType=WindowsRuntime]; \
+        $p = [Windows.Networking.Connectivity.NetworkInformation]::GetInternetConnectionProfile(); \
+        if ($p) { $p.GetConnectionCost().NetworkCostType }";
+    match output("powershell", &["-NoProfile", "-NonInteractive", "-Command", SCRIPT])?.as_str() {
+        "Fixed" | "Variable" => Some(true),
+        "Unrestricted" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn on_battery() -> Option<bool> {
+    let read = |path: &std::path::Path, file: &str| std::fs::read_to_string(path.join(file)).map(|s| s.trim().to_string()).unwrap_or_default();
+    let mut discharging = false;
+    for entry in std::fs::read_dir("/sys/class/power_supply").ok()?.flatten() {
+        let path = entry.path();
+        match read(&path, "type").as_str() {
+            "Mains" | "USB" if read(&path, "online") == "1" => return Some(false),
+            "Battery" if read(&path, "scope") != "Device" => discharging |= read(&path, "status") == "Discharging",
+            _ => {}
+        }
+    }
+    Some(discharging)
+}
+
+#[cfg(target_os = "linux")]
+fn metered() -> Option<bool> {
+    // NMMetered: 1 yes, 2 no, 3 guessed yes, 4 guessed no.
+    let reply = output("busctl", &["get-property", "org.freedesktop.NetworkManager", "/org/freedesktop/NetworkManager", "org.freedesktop.NetworkManager", "Metered"])?;
+    match reply.strip_prefix("u ")? {
+        "1" | "3" => Some(true),
+        "2" | "4" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn idle_seconds() -> Option<u64> {
+    // Only under X11 with xprintidle installed (milliseconds).
+    output("xprintidle", &[])?.parse::<u64>().ok().map(|ms| ms / 1000)
+}
+
+#[cfg(target_os = "macos")]
+fn on_battery() -> Option<bool> {
+    let batt = output("pmset", &["-g", "batt"])?;
+    Some(batt.lines().next()?.contains("'Battery Power'"))
+}
+
+#[cfg(target_os = "macos")]
+fn metered() -> Option<bool> {
+    None
+}
+
+#[cfg(target_os = "macos")]
+fn idle_seconds() -> Option<u64> {
+    // "HIDIdleTime" = <nanoseconds>
+    let ioreg = output("ioreg", &["-c", "IOHIDSystem", "-d", "4"])?;
+    let line = ioreg.lines().find(|l| l.contains("\"HIDIdleTime\""))?;
+    line.rsplit('=').next()?.trim().parse::<u64>().ok().map(|ns| ns / 1_000_000_000)
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+fn on_battery() -> Option<bool> {
+    None
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+fn metered() -> Option<bool> {
+    None
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+fn idle_seconds() -> Option<u64> {
+    None
+}
+
+#[tauri::command]
+/// The detected battery, connection and idle state, and whether background work runs in it.
+pub fn get_power_status() -> Result<PowerStatus, String> {
+    let policy = crate::AppConfig::from_env().map_err(|e| e.to_string())?.power;
+    let state = detect();
+    Ok(PowerStatus { heavy_work_blocked: policy.heavy_work_blocked(&state).map(str::to_string), big_sync_allowed: policy.big_sync_allowed(&state), state })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> PowerPolicy {
+        PowerPolicy { skip_on_battery: true, skip_on_metered: true, idle_minutes: 5 }
+    }
+
+    #[test]
+    fn heavy_work_is_blocked_on_battery_or_metered_when_configured() {
+        let state = |on_battery, metered| SystemState { on_battery, metered, idle_seconds: None };
+        assert_eq!(policy().heavy_work_blocked(&state(Some(true), Some(true))), Some("running on battery"));
+        assert_eq!(policy().heavy_work_blocked(&state(Some(false), Some(true))), Some("on a metered connection"));
+        assert_eq!(policy().heavy_work_blocked(&state(Some(false), Some(false))), None);
+        // Unknown isn't treated as blocked.
+        assert_eq!(policy().heavy_work_blocked(&state(None, None)), None);
+        let relaxed = PowerPolicy { skip_on_battery: false, skip_on_metered: false, ..policy() };
+        assert_eq!(relaxed.heavy_work_blocked(&state(Some(true), Some(true))), None);
+    }
+
+    #[test]
+    fn big_syncs_wait_for_idle_time_where_it_is_known() {
+        let state = |idle_seconds| SystemState { on_battery: Some(false), metered: Some(false), idle_seconds };
+        assert!(policy().big_sync_allowed(&state(None)));
+        assert!(policy().big_sync_allowed(&state(Some(300))));
+        assert!(!policy().big_sync_allowed(&state(Some(299))));
+        assert!(PowerPolicy { idle_minutes: 0, ..policy() }.big_sync_allowed(&state(Some(0))));
+        assert!(!policy().big_sync_allowed(&SystemState { on_battery: Some(true), ..state(None) }));
+    }
+
+    #[test]
+    fn detection_is_reused_briefly() {
+        let first = detect();
+        let cached = DETECTED.lock().unwrap().as_ref().map(|(at, _)| *at);
+        let second = detect();
+        assert_eq!(first.on_battery, second.on_battery);
+        assert_eq!(DETECTED.lock().unwrap().as_ref().map(|(at, _)| *at), cached);
+    }
+}
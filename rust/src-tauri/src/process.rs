@@ -0,0 +1,134 @@
+// Process model awareness: map Agile/Scrum/CMMI/Basic (and inherited) state names onto normalized states
+use crate::azure_devops::AzureDevOpsClient;
+use crate::AppConfig;
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+/// Normalized state, after Azure DevOps' state categories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub enum StateCategory {
+    /// New, Proposed, To Do, Approved.
+    Proposed,
+    /// Active, Committed, In Progress, Doing.
+    InProgress,
+    /// Resolved: fixed but not yet verified.
+    Resolved,
+    /// Closed, Done.
+    Completed,
+    Removed,
+}
+
+impl StateCategory {
+    fn parse(category: &str) -> Option<Self> {
+        match category {
+            "Proposed" => Some(StateCategory::Proposed),
+            "InProgress" => Some(StateCategory::InProgress),
+            "Resolved" => Some(StateCategory::Resolved),
+            "Completed" => Some(StateCategory::Completed),
+            "Removed" => Some(StateCategory::Removed),
+            _ => None,
+        }
+    }
+}
+
+/// State names of the built-in processes, used for states the project didn't report.
+const KNOWN_STATES: [(&str, StateCategory); 12] = [
+    ("New", StateCategory::Proposed),
+    ("Proposed", StateCategory::Proposed),
+    ("To Do", StateCategory::Proposed),
+    ("Approved", StateCategory::Proposed),
+    ("Active", StateCategory::InProgress),
+    ("Committed", StateCategory::InProgress),
+    ("In Progress", StateCategory::InProgress),
+    ("Doing", StateCategory::InProgress),
+    ("Resolved", StateCategory::Resolved),
+    ("Closed", StateCategory::Completed),
+    ("Done", StateCategory::Completed),
+    ("Removed", StateCategory::Removed),
+];
+
+/// The project's process and its Bug states in workflow order.
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+pub struct ProcessModel {
+    pub process: Option<String>,
+    pub states: Vec<(String, StateCategory)>,
+}
+
+impl ProcessModel {
+    /// Normalized state for a state name; unknown custom states count as in progress.
+    pub fn category(&self, state: &str) -> StateCategory {
+        self.states
+            .iter()
+            .map(|(name, category)| (name.as_str(), *category))
+            .chain(KNOWN_STATES)
+            .find(|(name, _)| name.eq_ignore_ascii_case(state))
+            .map_or(StateCategory::InProgress, |(_, category)| category)
+    }
+
+    /// Resolved or completed: the work is done.
+    pub fn is_done(&self, state: &str) -> bool {
+        matches!(self.category(state), StateCategory::Resolved | StateCategory::Completed)
+    }
+
+    /// The project's Bug states in any of `categories`, in workflow order.
+    pub fn states(&self, categories: &[StateCategory]) -> Vec<String> {
+        self.states.iter().filter(|(_, c)| categories.contains(c)).map(|(name, _)| name.clone()).collect()
+    }
+
+    /// State to move a bug to when resolving it: the first Resolved state, else the first Completed
+    /// one (Scrum and Basic have no Resolved), else "Resolved".
+    pub fn resolved_state(&self) -> String {
+        self.states(&[StateCategory::Resolved])
+            .into_iter()
+            .chain(self.states(&[StateCategory::Completed]))
+            .next()
+            .unwrap_or_else(|| "Resolved".to_string())
+    }
+}
+
+fn fetch(client: &AzureDevOpsClient) -> Result<ProcessModel, String> {
+    let states = client
+        .fetch_work_item_type_states("Bug")?
+        .into_iter()
+        .filter_map(|state| Some((state.name.clone(), StateCategory::parse(&state.category)?)))
+        .collect();
+    let process = client.fetch_process_name().unwrap_or_else(|e| {
+        warn!("Process name unavailable: {}", e);
+        None
+    });
+    Ok(ProcessModel { process, states })
+}
+
+/// The process model of the client's project, fetched once per session. Falls back to the
+/// built-in state names when it can't be fetched.
+pub fn model(client: &AzureDevOpsClient) -> ProcessModel {
+    static MODELS: OnceLock<Mutex<HashMap<String, ProcessModel>>> = OnceLock::new();
+    let key = format!("{}/{}", client.config.org, client.config.project);
+    let models = MODELS.get_or_init(Default::default);
+    if let Some(model) = models.lock().ok().and_then(|m| m.get(&key).cloned()) {
+        return model;
+    }
+    match fetch(client) {
+        Ok(model) => {
+            info!("Process: {} ({} Bug states)", model.process.as_deref().unwrap_or("unknown"), model.states.len());
+            if let Ok(mut models) = models.lock() {
+                models.insert(key, model.clone());
+            }
+            model
+        }
+        Err(e) => {
+            warn!("Process model unavailable, using built-in state names: {}", e);
+            ProcessModel::default()
+        }
+    }
+}
+
+#[tauri::command]
+/// The project's process and how its Bug states are normalized.
+pub fn get_process_model() -> Result<ProcessModel, String> {
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    Ok(model(&AzureDevOpsClient::new(config)))
+}
@@ -0,0 +1,111 @@
+// Issue tracker backends: the analysis and report work on `Bug`s from whichever one is configured
+use crate::audit::{add_tag_reversible, WriteOutcome};
+use crate::azure_devops::{AzureDevOpsClient, Bug};
+use crate::error::BuggerError;
+use crate::github::{GitHubClient, GitHubConfig};
+use crate::jira::{JiraClient, JiraConfig};
+use crate::AppConfig;
+use std::env;
+
+pub trait IssueProvider {
+    /// Ids of the bugs the report covers.
+    fn fetch_ids(&self) -> Result<Vec<u64>, BuggerError>;
+    /// Details of `ids`; ids that aren't bugs are left out.
+    fn fetch_details(&self, ids: &[u64]) -> Result<Vec<Bug>, BuggerError>;
+    /// Add `tag` to a bug (a label on GitHub and Jira). Only Azure DevOps tags can be undone.
+    fn add_tag(&self, id: u64, tag: &str) -> Result<WriteOutcome, BuggerError>;
+}
+
+impl IssueProvider for AzureDevOpsClient {
+    fn fetch_ids(&self) -> Result<Vec<u64>, BuggerError> {
+        self.fetch_active_bugs()
+    }
+
+    fn fetch_details(&self, ids: &[u64]) -> Result<Vec<Bug>, BuggerError> {
+        self.fetch_bug_details(ids)
+    }
+
+    fn add_tag(&self, id: u64, tag: &str) -> Result<WriteOutcome, BuggerError> {
+        add_tag_reversible(self, id, tag)
+    }
+}
+
+/// Where bugs come from (BUGGER_PROVIDER: azure-devops, github or jira).
+#[derive(Debug, Clone)]
+pub enum ProviderConfig {
+    AzureDevOps,
+    GitHub(GitHubConfig),
+    Jira(JiraConfig),
+}
+
+impl ProviderConfig {
+    pub fn from_env() -> anyhow::Result<Self> {
+        match env::var("BUGGER_PROVIDER").unwrap_or_default().trim().to_lowercase().as_str() {
+            "github" => Ok(ProviderConfig::GitHub(GitHubConfig::from_env()?)),
+            "jira" => Ok(ProviderConfig::Jira(JiraConfig::from_env()?)),
+            _ => Ok(ProviderConfig::AzureDevOps),
+        }
+    }
+
+    /// Whether BUGGER_PROVIDER selects Azure DevOps, the default.
+    pub fn is_azure_devops_selected() -> bool {
+        !matches!(env::var("BUGGER_PROVIDER").unwrap_or_default().trim().to_lowercase().as_str(), "github" | "jira")
+    }
+
+    /// Fails with a Config error unless this is Azure DevOps, for `feature`s no other tracker supports.
+    pub fn require_azure_devops(&self, feature: &str) -> Result<(), BuggerError> {
+        match self {
+            ProviderConfig::AzureDevOps => Ok(()),
+            _ => Err(BuggerError::Config(format!("{} needs Azure DevOps; BUGGER_PROVIDER selects another tracker", feature))),
+        }
+    }
+
+    /// Settings required by the provider BUGGER_PROVIDER selects, for the setup check.
+    pub fn required_settings() -> &'static [&'static str] {
+        match env::var("BUGGER_PROVIDER").unwrap_or_default().trim().to_lowercase().as_str() {
+            "github" => &["GITHUB_REPO", "GITHUB_TOKEN", "GITHUB_USER"],
+            "jira" => &["JIRA_BASE_URL", "JIRA_EMAIL", "JIRA_TOKEN", "JIRA_PROJECT"],
+            _ => &["AZURE_DEVOPS_ORG", "AZURE_DEVOPS_PROJECT", "AZURE_DEVOPS_USER_EMAIL", "AZURE_DEVOPS_PAT"],
+        }
+    }
+
+    /// Org, project and my identity standing in for the Azure DevOps settings; `None` for Azure DevOps.
+    pub fn stand_ins(&self) -> Option<(String, String, String)> {
+        match self {
+            ProviderConfig::AzureDevOps => None,
+            ProviderConfig::GitHub(github) => {
+                let (owner, name) = github.repo.split_once('/').unwrap_or_default();
+                Some((owner.to_string(), name.to_string(), github.user.clone()))
+            }
+            ProviderConfig::Jira(jira) => {
+                // The site, e.g. contoso.atlassian.net, like an Azure DevOps organization.
+                let site = jira.base_url.split("://").last().unwrap_or_default().split('/').next().unwrap_or_default();
+                Some((site.to_string(), jira.project.clone(), jira.email.clone()))
+            }
+        }
+    }
+}
+
+/// The client for the configured provider.
+pub fn from_config(config: AppConfig) -> Box<dyn IssueProvider> {
+    match config.provider.clone() {
+        ProviderConfig::GitHub(github) => Box::new(GitHubClient::new(github)),
+        ProviderConfig::Jira(jira) => Box::new(JiraClient::new(jira)),
+        ProviderConfig::AzureDevOps => Box::new(AzureDevOpsClient::new(config)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn azure_devops_only_features_refuse_other_trackers() {
+        assert!(ProviderConfig::AzureDevOps.require_azure_devops("Filing a bug").is_ok());
+        let github = ProviderConfig::GitHub(GitHubConfig { api_url: "https://api.github.com".to_string(), repo: "octo/app".to_string(), token: "token".to_string(), user: "me".to_string(), bug_label: None });
+        match github.require_azure_devops("Filing a bug") {
+            Err(BuggerError::Config(message)) => assert!(message.starts_with("Filing a bug needs Azure DevOps")),
+            other => panic!("expected a Config error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,295 @@
+// OS-level quick actions: a Windows jump list and a macOS dock menu with "Refresh now", "Open latest
+// report" and the top urgent bugs, rebuilt after each analysis
+use crate::azure_devops::Bug;
+use crate::calendar::SlaPolicy;
+use crate::focus;
+use crate::triage::{ranked, TriageWeights};
+use crate::work_session;
+use chrono::{DateTime, Utc};
+use log::warn;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Urgent bugs listed after "Refresh now" and "Open latest report".
+const URGENT_BUGS: usize = 3;
+/// Launch argument of a jump list entry, e.g. `--quick-action=bug:123`.
+const ARG: &str = "--quick-action=";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, JsonSchema)]
+#[serde(tag = "action", content = "id", rename_all = "snake_case")]
+pub enum QuickAction {
+    Refresh,
+    OpenReport,
+    OpenBug(u64),
+}
+
+impl QuickAction {
+    fn id(&self) -> String {
+        match self {
+            QuickAction::Refresh => "refresh".to_string(),
+            QuickAction::OpenReport => "report".to_string(),
+            QuickAction::OpenBug(id) => format!("bug:{}", id),
+        }
+    }
+
+    fn parse(id: &str) -> Option<Self> {
+        match id {
+            "refresh" => Some(QuickAction::Refresh),
+            "report" => Some(QuickAction::OpenReport),
+            _ => id.strip_prefix("bug:")?.parse().ok().map(QuickAction::OpenBug),
+        }
+    }
+
+    fn from_args(args: &[String]) -> Option<Self> {
+        args.iter().find_map(|a| Self::parse(a.strip_prefix(ARG)?))
+    }
+}
+
+/// Action the app was launched with, waiting for the frontend to load.
+static PENDING: Mutex<Option<QuickAction>> = Mutex::new(None);
+
+/// The fixed actions followed by the urgent bugs.
+fn entries(urgent: &[(u64, String)]) -> Vec<(String, QuickAction)> {
+    let mut entries = vec![("Refresh now".to_string(), QuickAction::Refresh), ("Open latest report".to_string(), QuickAction::OpenReport)];
+    entries.extend(urgent.iter().map(|(id, title)| (format!("#{} {}", id, title), QuickAction::OpenBug(*id))));
+    entries
+}
+
+/// The most urgent bugs by triage score, with their titles.
+pub fn top_urgent(bugs: &[Bug], weights: &TriageWeights, sla: &SlaPolicy, waiting_on_me: &HashSet<u64>, now: DateTime<Utc>) -> Vec<(u64, String)> {
+    ranked(bugs, now, weights).into_iter().filter(|(bug, _)| focus::is_urgent(bug, sla, waiting_on_me, now)).take(URGENT_BUGS).map(|(bug, _)| (bug.id, bug.title.clone())).collect()
+}
+
+/// Rebuild the jump list or dock menu with `urgent` bugs.
+pub fn update(app: &AppHandle, urgent: &[(u64, String)]) {
+    if let Err(e) = platform::set(app, entries(urgent)) {
+        warn!("Failed to update quick actions: {}", e);
+    }
+}
+
+/// Run an action: bugs open in their own window, refreshes and the report go to the main window.
+pub fn run(app: &AppHandle, action: QuickAction) {
+    if let QuickAction::OpenBug(id) = action {
+        if let Err(e) = work_session::open_bug(app.clone(), id) {
+            warn!("Quick action on #{} failed: {}", id, e);
+        }
+        return;
+    }
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    if let Err(e) = app.emit("quick-action", action) {
+        warn!("Failed to send quick action {}: {}", action.id(), e);
+    }
+}
+
+/// Set up the quick actions without urgent bugs and handle the one the app was launched with.
+pub fn init(app: &AppHandle) {
+    #[cfg(target_os = "macos")]
+    app.on_menu_event(|app, event| {
+        if let Some(action) = event.id().as_ref().strip_prefix(platform::MENU_ID_PREFIX).and_then(QuickAction::parse) {
+            run(app, action);
+        }
+    });
+    update(app, &[]);
+    match QuickAction::from_args(&std::env::args().collect::<Vec<_>>()) {
+        Some(action @ QuickAction::OpenBug(_)) => run(app, action),
+        Some(action) => {
+            if let Ok(mut pending) = PENDING.lock() {
+                *pending = Some(action);
+            }
+        }
+        None => {}
+    }
+}
+
+/// A jump list entry started a second instance: run its action here, or just bring up the window.
+#[cfg(windows)]
+pub fn handle_second_instance(app: &AppHandle, args: Vec<String>) {
+    match QuickAction::from_args(&args) {
+        Some(action) => run(app, action),
+        None => {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::{QuickAction, ARG};
+    use tauri::AppHandle;
+    use windows::core::{Interface, HSTRING};
+    use windows::Win32::Storage::EnhancedStorage::PKEY_Title;
+    use windows::Win32::System::Com::StructuredStorage::PROPVARIANT;
+    use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED};
+    use windows::Win32::UI::Shell::Common::{IObjectArray, IObjectCollection};
+    use windows::Win32::UI::Shell::PropertiesSystem::IPropertyStore;
+    use windows::Win32::UI::Shell::{DestinationList, EnumerableObjectCollection, ICustomDestinationList, IShellLinkW, ShellLink};
+
+    /// Replace the jump list's tasks with one link per entry, each starting the app with its action.
+    fn build(exe: &HSTRING, entries: &[(String, QuickAction)]) -> windows::core::Result<()> {
+        unsafe {
+            let list: ICustomDestinationList = CoCreateInstance(&DestinationList, None, CLSCTX_INPROC_SERVER)?;
+            let mut slots = 0;
+            let _removed: IObjectArray = list.BeginList(&mut slots)?;
+            let tasks: IObjectCollection = CoCreateInstance(&EnumerableObjectCollection, None, CLSCTX_INPROC_SERVER)?;
+            for (label, action) in entries {
+                let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)?;
+                link.SetPath(exe)?;
+                link.SetArguments(&HSTRING::from(format!("{}{}", ARG, action.id())))?;
+                link.SetIconLocation(exe, 0)?;
+                let store: IPropertyStore = link.cast()?;
+                store.SetValue(&PKEY_Title, &PROPVARIANT::from(label.as_str()))?;
+                store.Commit()?;
+                tasks.AddObject(&link)?;
+            }
+            list.AddUserTasks(&tasks.cast::<IObjectArray>()?)?;
+            list.CommitList()
+        }
+    }
+
+    pub fn set(_app: &AppHandle, entries: Vec<(String, QuickAction)>) -> Result<(), String> {
+        let exe = HSTRING::from(std::env::current_exe().map_err(|e| e.to_string())?.as_path());
+        // On a thread of its own: the jump list needs COM in a single-threaded apartment.
+        std::thread::spawn(move || {
+            let initialized = unsafe { CoInitializeEx(None, COINIT_APARTMENTTHREADED) }.is_ok();
+            let result = build(&exe, &entries).map_err(|e| e.to_string());
+            if initialized {
+                unsafe { CoUninitialize() };
+            }
+            result
+        })
+        .join()
+        .map_err(|_| "Jump list thread panicked".to_string())?
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::QuickAction;
+    use muda::{ContextMenu, IsMenuItem, Menu, MenuItem};
+    use objc2::runtime::{AnyClass, AnyObject, Imp, Sel};
+    use objc2::{msg_send, sel};
+    use std::cell::RefCell;
+    use std::sync::atomic::{AtomicPtr, Ordering};
+    use tauri::AppHandle;
+
+    /// Menu item ids are this plus the action's id.
+    pub const MENU_ID_PREFIX: &str = "quick-action:";
+
+    thread_local! {
+        /// The current dock menu; only touched on the main thread.
+        static MENU: RefCell<Option<Menu>> = const { RefCell::new(None) };
+    }
+    static NS_MENU: AtomicPtr<AnyObject> = AtomicPtr::new(std::ptr::null_mut());
+
+    /// `applicationDockMenu:` of the app delegate.
+    unsafe extern "C-unwind" fn dock_menu(_this: &AnyObject, _cmd: Sel, _sender: *mut AnyObject) -> *mut AnyObject {
+        NS_MENU.load(Ordering::Acquire)
+    }
+
+    /// Teach the app delegate (the windowing library's) to answer `applicationDockMenu:`.
+    fn install() {
+        let Some(ns_application) = AnyClass::get(c"NSApplication") else {
+            return;
+        };
+        let ns_app: *mut AnyObject = unsafe { msg_send![ns_application, sharedApplication] };
+        let delegate: *mut AnyObject = unsafe { msg_send![ns_app, delegate] };
+        if delegate.is_null() {
+            return;
+        }
+        let class = unsafe { (*delegate).class() } as *const AnyClass as *mut AnyClass;
+        let imp = unsafe { std::mem::transmute::<unsafe extern "C-unwind" fn(&AnyObject, Sel, *mut AnyObject) -> *mut AnyObject, Imp>(dock_menu) };
+        unsafe { objc2::ffi::class_addMethod(class, sel!(applicationDockMenu:), imp, c"@@:@".as_ptr()) };
+    }
+
+    pub fn set(app: &AppHandle, entries: Vec<(String, QuickAction)>) -> Result<(), String> {
+        app.run_on_main_thread(move || {
+            let items: Vec<MenuItem> = entries.iter().map(|(label, action)| MenuItem::with_id(format!("{}{}", MENU_ID_PREFIX, action.id()), label, true, None)).collect();
+            let refs: Vec<&dyn IsMenuItem> = items.iter().map(|item| item as &dyn IsMenuItem).collect();
+            let menu = match Menu::with_items(&refs) {
+                Ok(menu) => menu,
+                Err(e) => {
+                    log::warn!("Failed to build the dock menu: {}", e);
+                    return;
+                }
+            };
+            NS_MENU.store(menu.ns_menu() as *mut AnyObject, Ordering::Release);
+            MENU.with(|current| {
+                if current.replace(Some(menu)).is_none() {
+                    install();
+                }
+            });
+        })
+        .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+mod platform {
+    use super::QuickAction;
+    use tauri::AppHandle;
+
+    /// No jump list or dock menu here.
+    pub fn set(_app: &AppHandle, _entries: Vec<(String, QuickAction)>) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[tauri::command]
+/// The quick action the app was launched with, once; the frontend asks when it loads.
+pub fn take_quick_action() -> Result<Option<QuickAction>, String> {
+    Ok(PENDING.lock().map_err(|e| e.to_string())?.take())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn action_ids_round_trip() {
+        for action in [QuickAction::Refresh, QuickAction::OpenReport, QuickAction::OpenBug(42)] {
+            assert_eq!(QuickAction::parse(&action.id()), Some(action));
+        }
+        assert_eq!(QuickAction::parse("bug:"), None);
+        assert_eq!(QuickAction::parse("bug:x"), None);
+        assert_eq!(QuickAction::parse("quit"), None);
+    }
+
+    #[test]
+    fn the_action_is_read_from_the_launch_arguments() {
+        let args = |list: &[&str]| list.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        assert_eq!(QuickAction::from_args(&args(&["bugger.exe", "--quick-action=bug:7"])), Some(QuickAction::OpenBug(7)));
+        assert_eq!(QuickAction::from_args(&args(&["bugger.exe", "--quick-action=nope", "--quick-action=refresh"])), Some(QuickAction::Refresh));
+        assert_eq!(QuickAction::from_args(&args(&["bugger.exe", "refresh"])), None);
+    }
+
+    #[test]
+    fn only_urgent_bugs_are_listed_highest_score_first() {
+        let now = Utc::now();
+        let critical = |id: u64, title: &str| Bug { severity: Some("1 - Critical".to_string()), ..Bug::sample(id, title) };
+        let bugs = [
+            Bug::sample(1, "Typo in the footer"),
+            critical(2, "Button misaligned"),
+            critical(3, "App crashes on start"),
+            Bug::sample(4, "Tooltip flickers"),
+            critical(5, "Icon blurry"),
+            critical(6, "Label cut off"),
+        ];
+        let sla = SlaPolicy { days: BTreeMap::new() };
+        let waiting_on_me = HashSet::from([4]);
+        let urgent = top_urgent(&bugs, &TriageWeights::default(), &sla, &waiting_on_me, now);
+        assert_eq!(urgent.len(), URGENT_BUGS);
+        assert_eq!(urgent[0], (3, "App crashes on start".to_string()));
+        assert!(urgent.iter().all(|(id, _)| *id != 1));
+        assert_eq!(entries(&urgent).len(), URGENT_BUGS + 2);
+    }
+}
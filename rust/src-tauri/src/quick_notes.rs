@@ -0,0 +1,146 @@
+// Quick bug notes: a global shortcut pops up a small capture window; notes are kept locally until
+// they're filed as bugs through the quality gate or dropped
+use crate::error::BuggerError;
+use crate::storage;
+use crate::verdict::{self, FiledBug};
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::env;
+use tauri::plugin::TauriPlugin;
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder, Wry};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+const NOTES_STORE: &str = "quick_notes";
+const CAPTURE_WINDOW: &str = "capture";
+/// Longest bug title taken from a note's first line.
+const MAX_TITLE_CHARS: usize = 120;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct QuickNote {
+    pub id: u64,
+    pub text: String,
+    pub created: DateTime<Utc>,
+}
+
+/// Capture shortcut (BUGGER_CAPTURE_SHORTCUT, e.g. `Alt+Shift+N`). Off unless set, as any default
+/// chord would take a key from some other app, such as Ctrl+Shift+B (Build) from IDEs.
+fn shortcut() -> Option<String> {
+    env::var("BUGGER_CAPTURE_SHORTCUT").ok().map(|s| s.trim().to_string()).filter(|s| !s.is_empty() && !s.eq_ignore_ascii_case("off"))
+}
+
+/// The global shortcut plugin, popping up the capture window when a shortcut is pressed.
+pub fn shortcut_plugin() -> TauriPlugin<Wry> {
+    tauri_plugin_global_shortcut::Builder::new()
+        .with_handler(|app, _shortcut, event| {
+            if event.state() == ShortcutState::Pressed {
+                show_capture_window(app);
+            }
+        })
+        .build()
+}
+
+/// Register the capture shortcut; another app holding it only costs a warning.
+pub fn register_shortcut(app: &AppHandle) {
+    let Some(shortcut) = shortcut() else {
+        return;
+    };
+    match app.global_shortcut().register(shortcut.as_str()) {
+        Ok(()) => info!("Quick note shortcut: {}", shortcut),
+        Err(e) => warn!("Failed to register the quick note shortcut {}: {}", shortcut, e),
+    }
+}
+
+/// Bring up the capture window, creating it the first time.
+fn show_capture_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(CAPTURE_WINDOW) {
+        let _ = window.show();
+        let _ = window.set_focus();
+        return;
+    }
+    let built = WebviewWindowBuilder::new(app, CAPTURE_WINDOW, WebviewUrl::App("capture.html".into()))
+        .title("Quick bug note")
+        .inner_size(420.0, 220.0)
+        .resizable(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .center()
+        .build();
+    if let Err(e) = built {
+        warn!("Failed to open the capture window: {}", e);
+    }
+}
+
+/// A bug title from the note's first line, shortened if needed.
+fn title(text: &str) -> String {
+    let first = text.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or_default();
+    match first.char_indices().nth(MAX_TITLE_CHARS) {
+        Some((cut, _)) => format!("{}…", first[..cut].trim_end()),
+        None => first.to_string(),
+    }
+}
+
+#[tauri::command]
+/// Keeps a note from the capture window.
+pub fn save_quick_note(app: AppHandle, text: String) -> Result<QuickNote, String> {
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err("The note is empty".to_string());
+    }
+    let mut notes: Vec<QuickNote> = storage::load_json(&app, NOTES_STORE)?;
+    let note = QuickNote { id: notes.iter().map(|n| n.id).max().unwrap_or(0) + 1, text, created: Utc::now() };
+    notes.push(note.clone());
+    storage::save_json(&app, NOTES_STORE, &notes)?;
+    Ok(note)
+}
+
+#[tauri::command]
+/// Saved notes, oldest first.
+pub fn list_quick_notes(app: AppHandle) -> Result<Vec<QuickNote>, String> {
+    storage::load_json(&app, NOTES_STORE)
+}
+
+#[tauri::command]
+/// Drops a note.
+pub fn delete_quick_note(app: AppHandle, id: u64) -> Result<(), String> {
+    let mut notes: Vec<QuickNote> = storage::load_json(&app, NOTES_STORE)?;
+    notes.retain(|n| n.id != id);
+    storage::save_json(&app, NOTES_STORE, &notes)
+}
+
+#[tauri::command]
+/// Files a note as a bug: its first line is the title and the whole note the description. Like
+/// `file_bug`, a note that wouldn't be actionable is refused unless `file_anyway` is set. Once the bug
+/// exists the note is dropped.
+pub fn file_quick_note(app: AppHandle, id: u64, file_anyway: Option<bool>) -> Result<FiledBug, BuggerError> {
+    let notes: Vec<QuickNote> = storage::load_json(&app, NOTES_STORE)?;
+    let note = notes.iter().find(|n| n.id == id).ok_or_else(|| format!("Note {} not found", id))?;
+    let filed = verdict::file_bug(app.clone(), title(&note.text), note.text.clone(), file_anyway)?;
+    // Simulation mode creates nothing, so the note stays.
+    if filed.id.is_some() {
+        delete_quick_note(app, id)?;
+    }
+    Ok(filed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_title_is_the_first_non_empty_line() {
+        assert_eq!(title("\n  Login button does nothing  \nSteps: click it"), "Login button does nothing");
+        assert_eq!(title("   \n"), "");
+    }
+
+    #[test]
+    fn long_titles_are_cut_at_a_character_boundary() {
+        let long = format!("{} tail", "é".repeat(MAX_TITLE_CHARS));
+        assert_eq!(title(&long), format!("{}…", "é".repeat(MAX_TITLE_CHARS)));
+        let spaced = format!("{} {}", "a".repeat(MAX_TITLE_CHARS - 1), "b".repeat(10));
+        assert_eq!(title(&spaced), format!("{}…", "a".repeat(MAX_TITLE_CHARS - 1)));
+        let exact = "x".repeat(MAX_TITLE_CHARS);
+        assert_eq!(title(&exact), exact);
+    }
+}
@@ -0,0 +1,113 @@
+// Working-hours / do-not-disturb window: outbound messages are held outside it
+use chrono::{DateTime, Datelike, FixedOffset, Local, NaiveTime, Utc, Weekday};
+use log::warn;
+use std::env;
+
+/// Working hours in the user's timezone. Outside this window refreshes still run but
+/// outbound messages are queued.
+#[derive(Debug, Clone)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub days: Vec<Weekday>,
+    /// Fixed UTC offset from BUGGER_TIMEZONE (e.g. "+02:00"); `None` follows the system timezone,
+    /// daylight saving time included.
+    pub offset: Option<FixedOffset>,
+}
+
+impl QuietHours {
+    /// Load from BUGGER_WORK_HOURS ("09:00-17:30"), BUGGER_WORK_DAYS ("Mon,Tue,...", default Mon-Fri)
+    /// and BUGGER_TIMEZONE. Returns `None` when no working hours are configured.
+    pub fn from_env() -> Option<Self> {
+        let hours = env::var("BUGGER_WORK_HOURS").ok()?;
+        let Some((start, end)) = parse_hours(&hours) else {
+            warn!("Invalid BUGGER_WORK_HOURS '{}', expected HH:MM-HH:MM", hours);
+            return None;
+        };
+        let mut days: Vec<Weekday> = crate::env_list("BUGGER_WORK_DAYS").iter().filter_map(|d| d.parse().ok()).collect();
+        if days.is_empty() {
+            days = vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri];
+        }
+        Some(QuietHours { start, end, days, offset: timezone_override() })
+    }
+
+    /// Whether `now` falls inside working hours on a working day.
+    pub fn is_working_time(&self, now: DateTime<Utc>) -> bool {
+        let local = match self.offset {
+            Some(offset) => now.with_timezone(&offset).naive_local(),
+            None => now.with_timezone(&Local).naive_local(),
+        };
+        if !self.days.contains(&local.weekday()) {
+            return false;
+        }
+        let time = local.time();
+        if self.start <= self.end {
+            time >= self.start && time < self.end
+        } else {
+            // Overnight shift, e.g. 22:00-06:00
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+/// BUGGER_TIMEZONE as a fixed UTC offset, if it's set and valid; `None` follows the system timezone.
+pub fn timezone_override() -> Option<FixedOffset> {
+    let tz = env::var("BUGGER_TIMEZONE").ok().filter(|tz| !tz.trim().is_empty())?;
+    tz.trim().parse().map_err(|_| warn!("Invalid BUGGER_TIMEZONE '{}', ignoring it", tz)).ok()
+}
+
+pub fn parse_hours(s: &str) -> Option<(NaiveTime, NaiveTime)> {
+    let (start, end) = s.split_once('-')?;
+    let start = NaiveTime::parse_from_str(start.trim(), "%H:%M").ok()?;
+    let end = NaiveTime::parse_from_str(end.trim(), "%H:%M").ok()?;
+    Some((start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn hours(start: &str, end: &str, offset: Option<FixedOffset>) -> QuietHours {
+        let (start, end) = parse_hours(&format!("{}-{}", start, end)).unwrap();
+        QuietHours { start, end, days: vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri], offset }
+    }
+
+    #[test]
+    fn working_time_is_checked_at_the_fixed_offset() {
+        let plus_two = FixedOffset::east_opt(2 * 3600);
+        // Thursday 2026-10-15.
+        let at = |h, m| Utc.with_ymd_and_hms(2026, 10, 15, h, m, 0).unwrap();
+        let day = hours("09:00", "17:30", plus_two);
+        assert!(!day.is_working_time(at(6, 59)));
+        assert!(day.is_working_time(at(7, 0)));
+        assert!(day.is_working_time(at(15, 29)));
+        assert!(!day.is_working_time(at(15, 30)));
+        // Saturday local time.
+        assert!(!day.is_working_time(Utc.with_ymd_and_hms(2026, 10, 17, 10, 0, 0).unwrap()));
+        let night = hours("22:00", "06:00", plus_two);
+        assert!(night.is_working_time(at(21, 0)));
+        assert!(night.is_working_time(at(3, 59)));
+        assert!(!night.is_working_time(at(4, 0)));
+    }
+
+    #[test]
+    fn without_an_override_the_system_timezone_is_used() {
+        let now = Utc::now();
+        let local = now.with_timezone(&Local);
+        let around = |from: i64, to: i64| QuietHours {
+            start: local.time() + chrono::Duration::minutes(from),
+            end: local.time() + chrono::Duration::minutes(to),
+            days: vec![local.weekday()],
+            offset: None,
+        };
+        assert!(around(-1, 2).is_working_time(now));
+        assert!(!around(12 * 60, 12 * 60 + 2).is_working_time(now));
+    }
+
+    #[test]
+    fn hours_parse_as_a_range() {
+        assert_eq!(parse_hours("09:00 - 17:30"), Some((NaiveTime::from_hms_opt(9, 0, 0).unwrap(), NaiveTime::from_hms_opt(17, 30, 0).unwrap())));
+        assert_eq!(parse_hours("9-5"), None);
+    }
+}
@@ -0,0 +1,420 @@
+// iCalendar-style recurrence rules (a subset of RFC 5545 RRULE) for scheduled jobs, e.g.
+// "FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR;BYHOUR=8;BYMINUTE=30" or "FREQ=MONTHLY;BYDAY=1MO;BYHOUR=9"
+use crate::outputs::Cadence;
+use crate::quiet_hours::timezone_override;
+use crate::{storage, AppConfig};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Local, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+use tauri::AppHandle;
+
+/// Days searched for the next or previous occurrence; enough for any monthly rule.
+const SEARCH_DAYS: i64 = 400;
+/// How often a waiting job checks the clock, so it still runs on time after the machine sleeps.
+const TICK_SECONDS: i64 = 60;
+const PREVIEW_RUNS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Frequency {
+    Minutely,
+    Hourly,
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recurrence {
+    frequency: Frequency,
+    interval: u32,
+    /// Weekdays, with an optional position in the month for monthly rules (1 = first, -1 = last).
+    by_day: Vec<(Option<i32>, Weekday)>,
+    /// Days of the month; negative counts from the end.
+    by_month_day: Vec<i32>,
+    by_hour: Vec<u32>,
+    by_minute: Vec<u32>,
+    rule: String,
+}
+
+fn parse_weekday(code: &str) -> Result<Weekday, String> {
+    match code {
+        "MO" => Ok(Weekday::Mon),
+        "TU" => Ok(Weekday::Tue),
+        "WE" => Ok(Weekday::Wed),
+        "TH" => Ok(Weekday::Thu),
+        "FR" => Ok(Weekday::Fri),
+        "SA" => Ok(Weekday::Sat),
+        "SU" => Ok(Weekday::Sun),
+        _ => Err(format!("'{}' is not a weekday (use MO..SU)", code)),
+    }
+}
+
+/// The two-letter RRULE code of a weekday, e.g. "MO".
+pub fn weekday_code(day: Weekday) -> String {
+    format!("{:?}", day)[..2].to_uppercase()
+}
+
+fn parse_list<T: FromStr>(key: &str, value: &str, valid: impl Fn(&T) -> bool) -> Result<Vec<T>, String> {
+    value
+        .split(',')
+        .map(|v| v.trim().parse::<T>().ok().filter(|n| valid(n)).ok_or_else(|| format!("Invalid {} value '{}'", key, v)))
+        .collect()
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    let (year, month) = if date.month() == 12 { (date.year() + 1, 1) } else { (date.year(), date.month() + 1) };
+    NaiveDate::from_ymd_opt(year, month, 1).and_then(|d| d.pred_opt()).map_or(31, |d| d.day())
+}
+
+impl FromStr for Recurrence {
+    type Err = String;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        let rule = rule.trim();
+        let body = rule.strip_prefix("RRULE:").unwrap_or(rule);
+        let mut frequency = None;
+        let mut recurrence = Recurrence {
+            frequency: Frequency::Daily,
+            interval: 1,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_hour: Vec::new(),
+            by_minute: Vec::new(),
+            rule: rule.to_string(),
+        };
+        for part in body.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+            let (key, value) = part.split_once('=').ok_or_else(|| format!("'{}' is not KEY=VALUE", part))?;
+            let value = value.trim().to_uppercase();
+            match key.trim().to_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.as_str() {
+                        "MINUTELY" => Frequency::Minutely,
+                        "HOURLY" => Frequency::Hourly,
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        _ => return Err(format!("Unsupported FREQ '{}' (use MINUTELY, HOURLY, DAILY, WEEKLY or MONTHLY)", value)),
+                    })
+                }
+                "INTERVAL" => recurrence.interval = value.parse().ok().filter(|n| *n >= 1).ok_or_else(|| format!("Invalid INTERVAL '{}'", value))?,
+                "BYDAY" => {
+                    for item in value.split(',').map(str::trim) {
+                        // The weekday is the last two characters; counted in chars, as the text may not be ASCII.
+                        let split = item.char_indices().rev().nth(1).map_or(0, |(i, _)| i);
+                        let (position, code) = item.split_at(split);
+                        let position = match position {
+                            "" => None,
+                            p => Some(p.trim_start_matches('+').parse::<i32>().ok().filter(|n| *n != 0 && n.abs() <= 5).ok_or_else(|| format!("Invalid BYDAY value '{}'", item))?),
+                        };
+                        recurrence.by_day.push((position, parse_weekday(code)?));
+                    }
+                }
+                "BYMONTHDAY" => recurrence.by_month_day = parse_list("BYMONTHDAY", &value, |d: &i32| *d != 0 && d.abs() <= 31)?,
+                "BYHOUR" => recurrence.by_hour = parse_list("BYHOUR", &value, |h: &u32| *h < 24)?,
+                "BYMINUTE" => recurrence.by_minute = parse_list("BYMINUTE", &value, |m: &u32| *m < 60)?,
+                other => return Err(format!("Unsupported rule part '{}'", other)),
+            }
+        }
+        recurrence.frequency = frequency.ok_or("The rule needs a FREQ")?;
+        let fine = matches!(recurrence.frequency, Frequency::Minutely | Frequency::Hourly);
+        // Without a start date, longer intervals have nothing to count from.
+        if recurrence.interval > 1 && !fine {
+            return Err("INTERVAL is only supported with FREQ=MINUTELY or HOURLY".to_string());
+        }
+        if recurrence.frequency != Frequency::Monthly && recurrence.by_day.iter().any(|(p, _)| p.is_some()) {
+            return Err("Numbered weekdays (e.g. 1MO) need FREQ=MONTHLY".to_string());
+        }
+        if recurrence.frequency == Frequency::Weekly && recurrence.by_day.is_empty() {
+            return Err("FREQ=WEEKLY needs BYDAY".to_string());
+        }
+        if recurrence.frequency == Frequency::Monthly && recurrence.by_day.is_empty() && recurrence.by_month_day.is_empty() {
+            return Err("FREQ=MONTHLY needs BYDAY or BYMONTHDAY".to_string());
+        }
+        if !fine && recurrence.by_hour.is_empty() {
+            return Err("Daily, weekly and monthly rules need BYHOUR".to_string());
+        }
+        if recurrence.by_minute.is_empty() {
+            recurrence.by_minute.push(0);
+        }
+        Ok(recurrence)
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.rule)
+    }
+}
+
+impl Recurrence {
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let weekday_matches = |(position, day): &(Option<i32>, Weekday)| {
+            date.weekday() == *day
+                && match position {
+                    None => true,
+                    Some(n) if *n > 0 => (date.day() as i32 - 1) / 7 + 1 == *n,
+                    Some(n) => (days_in_month(date) as i32 - date.day() as i32) / 7 + 1 == -n,
+                }
+        };
+        let month_day_matches = |d: &i32| if *d > 0 { date.day() as i32 == *d } else { days_in_month(date) as i32 + 1 + d == date.day() as i32 };
+        (self.by_day.is_empty() || self.by_day.iter().any(weekday_matches)) && (self.by_month_day.is_empty() || self.by_month_day.iter().any(month_day_matches))
+    }
+
+    /// Times of day the rule fires on a matching day, earliest first.
+    fn times(&self) -> Vec<NaiveTime> {
+        let hour_allowed = |h: u32| self.by_hour.is_empty() || self.by_hour.contains(&h);
+        let mut minutes_of_day: Vec<u32> = match self.frequency {
+            Frequency::Minutely => (0..24 * 60).step_by(self.interval as usize).filter(|m| hour_allowed(m / 60)).collect(),
+            Frequency::Hourly => (0..24)
+                .step_by(self.interval as usize)
+                .filter(|h| hour_allowed(*h))
+                .flat_map(|h| self.by_minute.iter().map(move |m| h * 60 + m))
+                .collect(),
+            _ => self.by_hour.iter().flat_map(|h| self.by_minute.iter().map(move |m| h * 60 + m)).collect(),
+        };
+        minutes_of_day.sort_unstable();
+        minutes_of_day.dedup();
+        minutes_of_day.into_iter().filter_map(|m| NaiveTime::from_hms_opt(m / 60, m % 60, 0)).collect()
+    }
+
+    /// Occurrences on `date` in `tz`, each at that day's offset. A time skipped by a daylight saving
+    /// change doesn't fire; one repeated by it fires once, the first time.
+    fn occurrences_on<Tz: TimeZone>(&self, date: NaiveDate, tz: &Tz) -> Vec<DateTime<Utc>> {
+        if !self.day_matches(date) {
+            return Vec::new();
+        }
+        self.times().into_iter().filter_map(|t| tz.from_local_datetime(&date.and_time(t)).earliest()).map(|t| t.with_timezone(&Utc)).collect()
+    }
+
+    /// The first occurrence strictly after `after`, in the timezone `tz`.
+    pub fn next_after<Tz: TimeZone>(&self, after: DateTime<Utc>, tz: &Tz) -> Option<DateTime<Utc>> {
+        let start = after.with_timezone(tz).date_naive();
+        (0..SEARCH_DAYS).map(|d| start + Duration::days(d)).flat_map(|date| self.occurrences_on(date, tz)).find(|t| *t > after)
+    }
+
+    /// The latest occurrence at or before `now`.
+    pub fn previous_at_or_before<Tz: TimeZone>(&self, now: DateTime<Utc>, tz: &Tz) -> Option<DateTime<Utc>> {
+        let start = now.with_timezone(tz).date_naive();
+        (0..SEARCH_DAYS)
+            .map(|d| start - Duration::days(d))
+            .flat_map(|date| self.occurrences_on(date, tz).into_iter().rev())
+            .find(|t| *t <= now)
+    }
+
+    /// The next `count` occurrences after `after`.
+    pub fn upcoming<Tz: TimeZone>(&self, after: DateTime<Utc>, tz: &Tz, count: usize) -> Vec<DateTime<Utc>> {
+        std::iter::successors(self.next_after(after, tz), |t| self.next_after(*t, tz)).take(count).collect()
+    }
+
+    /// `next_after` in BUGGER_TIMEZONE, or the system timezone without one.
+    pub fn next_local(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match timezone_override() {
+            Some(offset) => self.next_after(after, &offset),
+            None => self.next_after(after, &Local),
+        }
+    }
+
+    /// `previous_at_or_before` in BUGGER_TIMEZONE, or the system timezone without one.
+    pub fn previous_local(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match timezone_override() {
+            Some(offset) => self.previous_at_or_before(now, &offset),
+            None => self.previous_at_or_before(now, &Local),
+        }
+    }
+
+    /// `upcoming` in BUGGER_TIMEZONE, or the system timezone without one.
+    pub fn upcoming_local(&self, after: DateTime<Utc>, count: usize) -> Vec<DateTime<Utc>> {
+        match timezone_override() {
+            Some(offset) => self.upcoming(after, &offset, count),
+            None => self.upcoming(after, &Local, count),
+        }
+    }
+}
+
+/// Run `job` at every occurrence of `rule` (in BUGGER_TIMEZONE, or the system timezone) on a
+/// background thread.
+pub fn spawn(name: &'static str, rule: Recurrence, job: impl Fn() + Send + 'static) {
+    info!("Scheduled {} on '{}'", name, rule);
+    std::thread::spawn(move || {
+        let Some(mut next) = rule.next_local(Utc::now()) else {
+            warn!("Schedule '{}' for {} never fires", rule, name);
+            return;
+        };
+        loop {
+            let wait = (next - Utc::now()).min(Duration::seconds(TICK_SECONDS));
+            std::thread::sleep(wait.to_std().unwrap_or_default());
+            let now = Utc::now();
+            if now < next {
+                continue;
+            }
+            job();
+            match rule.next_local(now) {
+                Some(t) => next = t,
+                None => return,
+            }
+        }
+    });
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ScheduledJob {
+    pub job: String,
+    /// The recurrence rule, or a description for interval-based jobs.
+    pub schedule: String,
+    pub next_runs: Vec<DateTime<FixedOffset>>,
+}
+
+/// `times` in BUGGER_TIMEZONE, or at the system timezone's offset on each date.
+fn local(times: Vec<DateTime<Utc>>) -> Vec<DateTime<FixedOffset>> {
+    let offset = timezone_override();
+    times.into_iter().map(|t| offset.map_or_else(|| t.with_timezone(&Local).fixed_offset(), |o| t.with_timezone(&o))).collect()
+}
+
+#[tauri::command]
+/// The next `count` (default 5) times a recurrence rule fires, in BUGGER_TIMEZONE or the system
+/// timezone; errors if the rule is invalid.
+pub fn preview_recurrence(rule: String, count: Option<usize>) -> Result<Vec<DateTime<FixedOffset>>, String> {
+    let rule: Recurrence = rule.parse()?;
+    Ok(local(rule.upcoming_local(Utc::now(), count.unwrap_or(PREVIEW_RUNS))))
+}
+
+#[tauri::command]
+/// Upcoming runs of every scheduled job: refreshes, policy runs and enabled outputs.
+pub fn get_next_runs(app: AppHandle) -> Result<Vec<ScheduledJob>, String> {
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    let now = Utc::now();
+    let mut jobs = Vec::new();
+    match (&config.refresh_schedule, config.refresh_interval_minutes) {
+        (Some(rule), _) => jobs.push(ScheduledJob { job: "Refresh".to_string(), schedule: rule.to_string(), next_runs: local(rule.upcoming_local(now, PREVIEW_RUNS)) }),
+        (None, Some(minutes)) => jobs.push(ScheduledJob { job: "Refresh".to_string(), schedule: format!("every {} minutes after start", minutes), next_runs: Vec::new() }),
+        (None, None) => {}
+    }
+    if let Some(rule) = &config.policy_schedule {
+        jobs.push(ScheduledJob { job: "Policies".to_string(), schedule: rule.to_string(), next_runs: local(rule.upcoming_local(now, PREVIEW_RUNS)) });
+    }
+    let schedules: Vec<crate::outputs::OutputSchedule> = storage::load_json(&app, crate::outputs::SCHEDULE_STORE)?;
+    for schedule in schedules.iter().filter(|s| s.enabled) {
+        let (description, next_runs) = match schedule.cadence.recurrence()? {
+            Some(rule) => (rule.to_string(), rule.upcoming_local(now, PREVIEW_RUNS)),
+            None if matches!(schedule.cadence, Cadence::Hourly) => ("hourly".to_string(), schedule.last_run.map(|t| vec![(t + Duration::hours(1)).max(now)]).unwrap_or_default()),
+            None => (String::new(), Vec::new()),
+        };
+        jobs.push(ScheduledJob { job: format!("Output: {}", schedule.name), schedule: description, next_runs: local(next_runs) });
+    }
+    Ok(jobs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{MappedLocalTime, NaiveDateTime};
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn offset() -> FixedOffset {
+        FixedOffset::east_opt(0).unwrap()
+    }
+
+    #[test]
+    fn weekdays_at_half_past_eight() {
+        let rule: Recurrence = "FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR;BYHOUR=8;BYMINUTE=30".parse().unwrap();
+        // Friday 2026-10-16, after 08:30: next is Monday.
+        assert_eq!(rule.next_after(utc("2026-10-16T09:00:00Z"), &offset()), Some(utc("2026-10-19T08:30:00Z")));
+        assert_eq!(rule.next_after(utc("2026-10-16T08:00:00Z"), &offset()), Some(utc("2026-10-16T08:30:00Z")));
+        assert_eq!(rule.previous_at_or_before(utc("2026-10-18T12:00:00Z"), &offset()), Some(utc("2026-10-16T08:30:00Z")));
+    }
+
+    #[test]
+    fn first_and_last_weekday_of_month() {
+        let first: Recurrence = "RRULE:FREQ=MONTHLY;BYDAY=1MO;BYHOUR=9".parse().unwrap();
+        assert_eq!(first.upcoming(utc("2026-10-15T00:00:00Z"), &offset(), 2), vec![utc("2026-11-02T09:00:00Z"), utc("2026-12-07T09:00:00Z")]);
+        let last: Recurrence = "FREQ=MONTHLY;BYDAY=-1FR;BYHOUR=16".parse().unwrap();
+        assert_eq!(last.next_after(utc("2026-10-15T00:00:00Z"), &offset()), Some(utc("2026-10-30T16:00:00Z")));
+        let month_end: Recurrence = "FREQ=MONTHLY;BYMONTHDAY=-1;BYHOUR=18".parse().unwrap();
+        assert_eq!(month_end.next_after(utc("2027-02-01T00:00:00Z"), &offset()), Some(utc("2027-02-28T18:00:00Z")));
+    }
+
+    #[test]
+    fn intervals_align_to_midnight_in_the_timezone() {
+        let rule: Recurrence = "FREQ=MINUTELY;INTERVAL=15;BYHOUR=9,10".parse().unwrap();
+        assert_eq!(rule.next_after(utc("2026-10-15T09:07:00Z"), &offset()), Some(utc("2026-10-15T09:15:00Z")));
+        assert_eq!(rule.next_after(utc("2026-10-15T10:50:00Z"), &offset()), Some(utc("2026-10-16T09:00:00Z")));
+        let plus_two = FixedOffset::east_opt(2 * 3600).unwrap();
+        let hourly: Recurrence = "FREQ=HOURLY;INTERVAL=6;BYMINUTE=5".parse().unwrap();
+        assert_eq!(hourly.next_after(utc("2026-10-15T03:00:00Z"), &plus_two), Some(utc("2026-10-15T04:05:00Z")));
+    }
+
+    /// Central European time in 2026: +01:00, and +02:00 from 29 March to 25 October (01:00 UTC).
+    #[derive(Clone)]
+    struct CentralEurope;
+
+    impl CentralEurope {
+        fn offset_at(at: &NaiveDateTime) -> FixedOffset {
+            let summer = utc("2026-03-29T01:00:00Z").naive_utc() <= *at && *at < utc("2026-10-25T01:00:00Z").naive_utc();
+            FixedOffset::east_opt(if summer { 2 * 3600 } else { 3600 }).unwrap()
+        }
+    }
+
+    impl TimeZone for CentralEurope {
+        type Offset = FixedOffset;
+
+        fn from_offset(_: &FixedOffset) -> Self {
+            CentralEurope
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> MappedLocalTime<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_time(NaiveTime::MIN))
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> MappedLocalTime<FixedOffset> {
+            let valid: Vec<FixedOffset> = [3600, 2 * 3600]
+                .into_iter()
+                .filter_map(FixedOffset::east_opt)
+                .filter(|offset| Self::offset_at(&(*local - Duration::seconds(offset.local_minus_utc() as i64))) == *offset)
+                .collect();
+            match valid[..] {
+                [offset] => MappedLocalTime::Single(offset),
+                [winter, summer] => MappedLocalTime::Ambiguous(summer, winter),
+                _ => MappedLocalTime::None,
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            Self::offset_at(&utc.and_time(NaiveTime::MIN))
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            Self::offset_at(utc)
+        }
+    }
+
+    #[test]
+    fn occurrences_follow_daylight_saving_changes() {
+        let nine: Recurrence = "FREQ=DAILY;BYHOUR=9".parse().unwrap();
+        assert_eq!(nine.upcoming(utc("2026-10-24T00:00:00Z"), &CentralEurope, 2), vec![utc("2026-10-24T07:00:00Z"), utc("2026-10-25T08:00:00Z")]);
+        assert_eq!(nine.previous_at_or_before(utc("2026-03-30T12:00:00Z"), &CentralEurope), Some(utc("2026-03-30T07:00:00Z")));
+        let half_past_two: Recurrence = "FREQ=DAILY;BYHOUR=2;BYMINUTE=30".parse().unwrap();
+        // 02:30 happens twice on 25 October and fires the first time; it doesn't happen on 29 March.
+        assert_eq!(half_past_two.upcoming(utc("2026-10-24T12:00:00Z"), &CentralEurope, 2), vec![utc("2026-10-25T00:30:00Z"), utc("2026-10-26T01:30:00Z")]);
+        assert_eq!(half_past_two.next_after(utc("2026-03-28T12:00:00Z"), &CentralEurope), Some(utc("2026-03-30T00:30:00Z")));
+    }
+
+    #[test]
+    fn non_ascii_weekdays_are_rejected_not_a_panic() {
+        for rule in ["FREQ=WEEKLY;BYDAY=ÉA;BYHOUR=8", "FREQ=MONTHLY;BYDAY=1É;BYHOUR=8", "FREQ=WEEKLY;BYDAY=É;BYHOUR=8"] {
+            assert!(rule.parse::<Recurrence>().is_err(), "{} should be rejected", rule);
+        }
+    }
+
+    #[test]
+    fn rejects_unsupported_rules() {
+        for rule in ["BYHOUR=8", "FREQ=YEARLY;BYHOUR=8", "FREQ=DAILY", "FREQ=WEEKLY;BYHOUR=8", "FREQ=DAILY;INTERVAL=2;BYHOUR=8", "FREQ=WEEKLY;BYDAY=1MO;BYHOUR=8", "FREQ=DAILY;BYHOUR=24", "FREQ=DAILY;BYHOUR=8;COUNT=3"] {
+            assert!(rule.parse::<Recurrence>().is_err(), "{} should be rejected", rule);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+// Scheduled background refresh: re-fetch bugs, diff against the last run and notify
+use crate::auto_assign::run_auto_assign;
+use crate::azure_devops::Bug;
+use crate::azure_devops::AzureDevOpsClient;
+use crate::discussion::fetch_threads;
+use crate::escalation::run_escalations;
+use crate::{calendar, feed, inflow, recurrence};
+use crate::notifications::notify_new_bugs;
+use crate::pat::warn_if_expiring;
+use crate::policy::{run_auto_close, PolicyMode};
+use crate::provider::ProviderConfig;
+use crate::roles::Role;
+use crate::write_back::run_write_back;
+use crate::{AppConfig, AppState, BugRefresh};
+use log::{info, warn};
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// Changes between two fetches of the same query.
+#[derive(Debug, Clone, Default)]
+pub struct RefreshDiff {
+    pub added: Vec<Bug>,
+    pub updated: Vec<Bug>,
+    pub removed: Vec<u64>,
+}
+
+/// Compare the previous and current bug lists by id; a bug counts as updated when title, state or description changed.
+pub fn diff_bugs(previous: &[Bug], current: &[Bug]) -> RefreshDiff {
+    let old: HashMap<u64, &Bug> = previous.iter().map(|b| (b.id, b)).collect();
+    let mut diff = RefreshDiff::default();
+    for bug in current {
+        match old.get(&bug.id) {
+            None => diff.added.push(bug.clone()),
+            Some(prev) if prev.title != bug.title || prev.state != bug.state || prev.description != bug.description => {
+                diff.updated.push(bug.clone())
+            }
+            Some(_) => {}
+        }
+    }
+    let new_ids: Vec<u64> = current.iter().map(|b| b.id).collect();
+    diff.removed = previous.iter().map(|b| b.id).filter(|id| !new_ids.contains(id)).collect();
+    diff
+}
+
+/// Start the background refresh on BUGGER_REFRESH_SCHEDULE, or every BUGGER_REFRESH_MINUTES, and
+/// the policy runs on BUGGER_POLICY_SCHEDULE if configured.
+pub fn start_scheduler(app: AppHandle) {
+    let Ok(config) = AppConfig::from_env() else {
+        return;
+    };
+    if let Some(rule) = config.policy_schedule {
+        let app = app.clone();
+        recurrence::spawn("policies", rule, move || {
+            if let Err(e) = run_scheduled_policies(&app) {
+                warn!("Scheduled policy run failed: {}", e);
+            }
+        });
+    }
+    if let Some(rule) = config.refresh_schedule {
+        recurrence::spawn("refresh", rule, move || {
+            if let Err(e) = run_scheduled_refresh(&app) {
+                warn!("Scheduled refresh failed: {}", e);
+            }
+        });
+        return;
+    }
+    let Some(minutes) = config.refresh_interval_minutes else {
+        return;
+    };
+    info!("Scheduled refresh every {} minutes", minutes);
+    std::thread::spawn(move || loop {
+        std::thread::sleep(Duration::from_secs(minutes * 60));
+        if let Err(e) = run_scheduled_refresh(&app) {
+            warn!("Scheduled refresh failed: {}", e);
+        }
+    });
+}
+
+/// One scheduled refresh: fetch, diff against the cached bugs and notify about new ones.
+pub fn run_scheduled_refresh(app: &AppHandle) -> Result<RefreshDiff, String> {
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    if !config.power.check_heavy_work("scheduled refresh") {
+        return Ok(RefreshDiff::default());
+    }
+    let BugRefresh { previous, current, .. } = app.state::<AppState>().refresh_bugs()?;
+    let first_run = previous.is_empty();
+    let diff = diff_bugs(&previous, &current);
+    // Everything is "new" on the first load, which isn't worth a notification storm.
+    let added: &[Bug] = if first_run { &[] } else { &diff.added };
+    notify_new_bugs(app, &config.notification_rules, &config.category_styles, config.quiet_hours.as_ref(), added);
+    inflow::check(app, &config, &current);
+    if let Err(e) = feed::record(app, &config, &current) {
+        warn!("Feed history unavailable: {}", e);
+    }
+    // The PAT, comment threads and write policies are all Azure DevOps-only.
+    if matches!(config.provider, ProviderConfig::AzureDevOps) {
+        warn_if_expiring(app, &config);
+        let client = AzureDevOpsClient::new(config);
+        if client.config.calendar_path.is_some() {
+            calendar::update_feed(app, &client.config, &current, &fetch_threads(&client, &current));
+        }
+        // With their own schedule, policies don't run on every refresh.
+        if client.config.policy_schedule.is_none() {
+            run_policies(app, &client, &current);
+        }
+    }
+    info!("Scheduled refresh: {} added, {} updated, {} removed", diff.added.len(), diff.updated.len(), diff.removed.len());
+    Ok(diff)
+}
+
+/// Run the enabled write policies (auto-close, auto-assign, escalation, write-back) over `bugs`.
+fn run_policies(app: &AppHandle, client: &AzureDevOpsClient, bugs: &[Bug]) {
+    // With a shared team configuration only admins' apps run the policies.
+    if client.config.roles.of(&client.config.user_email) < Role::Admin {
+        return;
+    }
+    if client.config.auto_close.mode != PolicyMode::Off {
+        let policy = client.config.auto_close.clone();
+        let dry_run = policy.mode == PolicyMode::DryRun;
+        run_auto_close(app, client, bugs, &policy, dry_run);
+    }
+    let assign = client.config.auto_assign.clone();
+    if assign.mode != PolicyMode::Off && !assign.rotation.is_empty() && client.config.query_filters.is_team_mode() {
+        run_auto_assign(app, client, bugs, &assign, assign.mode == PolicyMode::DryRun);
+    }
+    let escalation = client.config.escalation.clone();
+    if escalation.mode != PolicyMode::Off && !escalation.rules.is_empty() {
+        run_escalations(app, client, bugs, &escalation, escalation.mode == PolicyMode::DryRun);
+    }
+    let write_back = client.config.write_back.clone();
+    if write_back.mode != PolicyMode::Off && write_back.has_target() {
+        run_write_back(app, client, bugs, &write_back, write_back.mode == PolicyMode::DryRun);
+    }
+}
+
+/// One scheduled policy run on the last fetched bugs (fetching them if there are none yet).
+pub fn run_scheduled_policies(app: &AppHandle) -> Result<(), String> {
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    if !matches!(config.provider, ProviderConfig::AzureDevOps) {
+        return Ok(());
+    }
+    let bugs = app.state::<AppState>().bugs_or_fetch()?;
+    run_policies(app, &AzureDevOpsClient::new(config), &bugs);
+    Ok(())
+}
+
@@ -0,0 +1,157 @@
+// Roles for team deployments: a shared configuration says who may triage (comment, tag, reassign)
+// and who may run the write policies, so it can be handed to everyone without making them all admins.
+// Roles come from each user's own settings, so they keep people from doing what they didn't mean to,
+// not from doing what they're set on: the PAT's permissions in Azure DevOps are what's enforced.
+use crate::audit::WriteAction;
+use crate::AppConfig;
+use log::warn;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::env;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Reads and analyzes, but changes nothing.
+    Viewer,
+    /// Also comments, tags, reassigns, records triage decisions and undoes.
+    Triager,
+    /// Also runs the auto-close, auto-assign, escalation and write-back policies and changes rules.
+    Admin,
+}
+
+impl Role {
+    fn parse(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "viewer" => Some(Role::Viewer),
+            "triager" => Some(Role::Triager),
+            "admin" => Some(Role::Admin),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Triager => "triager",
+            Role::Admin => "admin",
+        }
+    }
+
+    /// The least role that may perform audited write `action`.
+    pub fn required_for(action: WriteAction) -> Role {
+        match action {
+            WriteAction::AutoCloseWarning | WriteAction::AutoCloseResolve | WriteAction::AutoAssign | WriteAction::Escalate | WriteAction::WriteBack => Role::Admin,
+            WriteAction::Comment | WriteAction::Tag | WriteAction::Reassign | WriteAction::Attach | WriteAction::LogTime | WriteAction::FileBug | WriteAction::Undo => {
+                Role::Triager
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Roles {
+    assigned: Vec<(String, Role)>,
+    /// Role of everyone not listed.
+    default: Role,
+}
+
+impl Roles {
+    /// Load from BUGGER_ROLES, e.g. "lead@contoso.com=admin,jane@contoso.com=triager", and
+    /// BUGGER_DEFAULT_ROLE for everyone else: viewer when roles are listed, otherwise admin, so a
+    /// personal setup without roles keeps every feature.
+    pub fn from_env() -> Self {
+        Roles::parse(&crate::env_list("BUGGER_ROLES"), env::var("BUGGER_DEFAULT_ROLE").ok().as_deref())
+    }
+
+    fn parse(entries: &[String], default: Option<&str>) -> Self {
+        let assigned: Vec<(String, Role)> = entries
+            .iter()
+            .filter_map(|entry| {
+                let role = entry.split_once('=').and_then(|(who, role)| Some((who.trim().to_string(), Role::parse(role)?)));
+                if role.is_none() {
+                    warn!("Ignoring role entry '{}', expected email=viewer|triager|admin", entry);
+                }
+                role
+            })
+            .collect();
+        let fallback = if assigned.is_empty() { Role::Admin } else { Role::Viewer };
+        let default = default.and_then(Role::parse).unwrap_or(fallback);
+        Roles { assigned, default }
+    }
+
+    pub fn of(&self, who: &str) -> Role {
+        self.assigned.iter().find(|(email, _)| email.eq_ignore_ascii_case(who)).map_or(self.default, |(_, role)| *role)
+    }
+
+    /// An error unless `who` has at least the `needed` role for `what`.
+    pub fn check(&self, who: &str, needed: Role, what: &str) -> Result<(), String> {
+        let role = self.of(who);
+        if role >= needed {
+            return Ok(());
+        }
+        Err(format!("Your role ({}) doesn't allow {}; it needs {} (BUGGER_ROLES)", role.name(), what, needed.name()))
+    }
+}
+
+/// An error unless the configured user has at least the `needed` role for `what`.
+pub fn require(needed: Role, what: &str) -> Result<(), String> {
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    config.roles.check(&config.user_email, needed, what)
+}
+
+#[tauri::command]
+/// The configured user's role, so the frontend can hide what it doesn't allow.
+pub fn get_role() -> Result<Role, String> {
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    Ok(config.roles.of(&config.user_email))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roles(entries: &[&str], default: Option<&str>) -> Roles {
+        Roles::parse(&entries.iter().map(|e| e.to_string()).collect::<Vec<_>>(), default)
+    }
+
+    #[test]
+    fn role_names_are_parsed_loosely() {
+        assert_eq!(Role::parse(" Admin "), Some(Role::Admin));
+        assert_eq!(Role::parse("triager"), Some(Role::Triager));
+        assert_eq!(Role::parse("VIEWER"), Some(Role::Viewer));
+        assert_eq!(Role::parse("owner"), None);
+    }
+
+    #[test]
+    fn policies_need_an_admin_and_other_writes_a_triager() {
+        assert_eq!(Role::required_for(WriteAction::WriteBack), Role::Admin);
+        assert_eq!(Role::required_for(WriteAction::AutoCloseResolve), Role::Admin);
+        assert_eq!(Role::required_for(WriteAction::Comment), Role::Triager);
+        assert_eq!(Role::required_for(WriteAction::Undo), Role::Triager);
+    }
+
+    #[test]
+    fn everyone_is_an_admin_without_roles() {
+        assert_eq!(roles(&[], None).of("anyone@contoso.com"), Role::Admin);
+        assert_eq!(roles(&[], Some("triager")).of("anyone@contoso.com"), Role::Triager);
+    }
+
+    #[test]
+    fn people_not_listed_get_the_default_role() {
+        let roles = roles(&["lead@contoso.com=admin", "jane@contoso.com = triager", "bob@contoso.com=owner", "nobody"], None);
+        assert_eq!(roles.of("Lead@Contoso.com"), Role::Admin);
+        assert_eq!(roles.of("jane@contoso.com"), Role::Triager);
+        assert_eq!(roles.of("bob@contoso.com"), Role::Viewer);
+        assert_eq!(roles.of("someone@contoso.com"), Role::Viewer);
+    }
+
+    #[test]
+    fn check_allows_the_needed_role_or_more() {
+        let roles = roles(&["lead@contoso.com=admin", "jane@contoso.com=triager"], None);
+        assert!(roles.check("lead@contoso.com", Role::Triager, "commenting").is_ok());
+        assert!(roles.check("jane@contoso.com", Role::Triager, "commenting").is_ok());
+        let error = roles.check("jane@contoso.com", Role::Admin, "running write-back").unwrap_err();
+        assert!(error.contains("triager") && error.contains("running write-back") && error.contains("admin"), "{}", error);
+    }
+}
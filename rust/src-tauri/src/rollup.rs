@@ -0,0 +1,61 @@
+// Parent feature/epic rollup: which deliverables have the most open bugs underneath them
+use crate::azure_devops::{AzureDevOpsClient, Bug, WorkItemSummary};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// How many parent levels to walk (bug -> story -> feature -> epic) before giving up.
+const MAX_PARENT_DEPTH: usize = 4;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureRollup {
+    pub feature: WorkItemSummary,
+    pub bug_ids: Vec<u64>,
+}
+
+/// Resolve each bug's nearest Feature (or Epic, if there is no feature in between) and
+/// return the deliverables sorted by number of open bugs, most at risk first.
+pub fn build_feature_rollup(client: &AzureDevOpsClient, bugs: &[Bug]) -> Result<Vec<FeatureRollup>, String> {
+    let mut known: HashMap<u64, WorkItemSummary> = HashMap::new();
+    let mut frontier: Vec<u64> = bugs.iter().filter_map(|b| b.parent_id).collect();
+    for _ in 0..MAX_PARENT_DEPTH {
+        frontier.sort_unstable();
+        frontier.dedup();
+        frontier.retain(|id| !known.contains_key(id));
+        if frontier.is_empty() {
+            break;
+        }
+        let fetched = client.fetch_work_item_summaries(&frontier)?;
+        frontier = fetched.iter().filter_map(|w| w.parent_id).collect();
+        known.extend(fetched.into_iter().map(|w| (w.id, w)));
+    }
+
+    let mut rollup: HashMap<u64, FeatureRollup> = HashMap::new();
+    for bug in bugs {
+        if let Some(feature) = nearest_deliverable(bug.parent_id, &known) {
+            rollup
+                .entry(feature.id)
+                .or_insert_with(|| FeatureRollup { feature: feature.clone(), bug_ids: Vec::new() })
+                .bug_ids
+                .push(bug.id);
+        }
+    }
+    let mut rollup: Vec<FeatureRollup> = rollup.into_values().collect();
+    rollup.sort_by(|a, b| b.bug_ids.len().cmp(&a.bug_ids.len()).then(a.feature.id.cmp(&b.feature.id)));
+    Ok(rollup)
+}
+
+fn nearest_deliverable(mut parent: Option<u64>, known: &HashMap<u64, WorkItemSummary>) -> Option<&WorkItemSummary> {
+    let mut epic = None;
+    for _ in 0..MAX_PARENT_DEPTH {
+        let Some(item) = parent.and_then(|id| known.get(&id)) else {
+            break;
+        };
+        match item.work_item_type.as_str() {
+            "Feature" => return Some(item),
+            "Epic" if epic.is_none() => epic = Some(item),
+            _ => {}
+        }
+        parent = item.parent_id;
+    }
+    epic
+}
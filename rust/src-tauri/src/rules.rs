@@ -0,0 +1,283 @@
+// Analysis rule registry: the questionable-bug checks, each with an on/off switch, severity and parameters
+use crate::azure_devops::Bug;
+use crate::bug_analysis::{strip_html, QuestionableCategory, TextContent};
+use crate::roles::{self, Role};
+use crate::storage;
+use log::warn;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{OnceLock, RwLock};
+use tauri::AppHandle;
+
+/// Overrides saved from the settings UI, keyed by rule id.
+const RULES_STORE: &str = "rules";
+
+/// What a matching rule does to a bug.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleSeverity {
+    /// Moves the bug into the Questionable list.
+    Questionable,
+    /// Keeps the bug actionable but flags it in the report.
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum RuleId {
+    EmptyDescription,
+    ShortDescription,
+    SpecialCharacters,
+    DuplicateTitle,
+    DeadLinks,
+}
+
+impl RuleId {
+    /// Evaluation order: the first matching Questionable rule decides the reason shown.
+    pub const ALL: [RuleId; 5] = [RuleId::EmptyDescription, RuleId::ShortDescription, RuleId::SpecialCharacters, RuleId::DuplicateTitle, RuleId::DeadLinks];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            RuleId::EmptyDescription => "empty-description",
+            RuleId::ShortDescription => "short-description",
+            RuleId::SpecialCharacters => "special-characters",
+            RuleId::DuplicateTitle => "duplicate-title",
+            RuleId::DeadLinks => "dead-links",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RuleId::EmptyDescription => "Empty description",
+            RuleId::ShortDescription => "Minimal description",
+            RuleId::SpecialCharacters => "Special characters only",
+            RuleId::DuplicateTitle => "Description repeats the title",
+            RuleId::DeadLinks => "Dead links",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            RuleId::EmptyDescription => "The description is empty.",
+            RuleId::ShortDescription => "The description text has fewer than min_chars letters or digits (min_chars_cjk in Chinese, Japanese or Korean) or fewer than min_words words, and no images.",
+            RuleId::SpecialCharacters => "The description text has no letters or digits, e.g. only emoji or symbols.",
+            RuleId::DuplicateTitle => "The description is just the title again.",
+            RuleId::DeadLinks => "The description links somewhere and mentions a 404.",
+        }
+    }
+
+    pub fn category(&self) -> QuestionableCategory {
+        match self {
+            RuleId::EmptyDescription => QuestionableCategory::EmptyMinimalDescription,
+            RuleId::ShortDescription => QuestionableCategory::SingleWordDescription,
+            RuleId::SpecialCharacters => QuestionableCategory::SpecialCharactersSoup,
+            RuleId::DuplicateTitle => QuestionableCategory::DuplicateTitleDescription,
+            RuleId::DeadLinks => QuestionableCategory::DeadLinks,
+        }
+    }
+
+    /// Parameters and their defaults.
+    fn default_params(&self) -> BTreeMap<String, f64> {
+        match self {
+            RuleId::ShortDescription => BTreeMap::from([("min_chars".to_string(), 8.0), ("min_chars_cjk".to_string(), 4.0), ("min_words".to_string(), 1.0)]),
+            _ => BTreeMap::new(),
+        }
+    }
+
+    fn parse(key: &str) -> Result<Self, String> {
+        RuleId::ALL.into_iter().find(|r| r.key() == key).ok_or_else(|| format!("Unknown rule '{}'", key))
+    }
+
+    fn matches(&self, bug: &Bug, params: &BTreeMap<String, f64>) -> bool {
+        let desc = bug.description.as_deref().unwrap_or("").trim();
+        let param = |name: &str| params.get(name).copied().unwrap_or(0.0);
+        match self {
+            RuleId::EmptyDescription => desc.is_empty(),
+            RuleId::ShortDescription => {
+                // A screenshot says more than the text around it.
+                if desc.contains("<img") {
+                    return false;
+                }
+                let text = strip_html(desc);
+                let content = TextContent::of(&text);
+                // Text without any letters or digits is the special-characters rule's call.
+                if !text.is_empty() && content.graphemes == 0 {
+                    return false;
+                }
+                let min_chars = if content.is_mostly_cjk() { param("min_chars_cjk") } else { param("min_chars") };
+                // Word counts mean little in scripts written without spaces.
+                (content.graphemes as f64) < min_chars || (!content.is_mostly_cjk() && (content.words as f64) < param("min_words"))
+            }
+            RuleId::SpecialCharacters => {
+                let text = strip_html(desc);
+                !text.is_empty() && TextContent::of(&text).graphemes == 0
+            }
+            RuleId::DuplicateTitle => desc == bug.title,
+            RuleId::DeadLinks => desc.contains("http") && desc.contains("404"),
+        }
+    }
+}
+
+/// A rule's saved settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RuleOverride {
+    enabled: bool,
+    severity: RuleSeverity,
+    #[serde(default)]
+    params: BTreeMap<String, f64>,
+}
+
+impl RuleOverride {
+    fn default_for(rule: RuleId) -> Self {
+        RuleOverride { enabled: true, severity: RuleSeverity::Questionable, params: rule.default_params() }
+    }
+}
+
+/// A rule as listed for the settings UI.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct RuleInfo {
+    pub id: RuleId,
+    pub name: String,
+    pub description: String,
+    pub enabled: bool,
+    pub severity: RuleSeverity,
+    pub params: BTreeMap<String, f64>,
+}
+
+fn registry() -> &'static RwLock<BTreeMap<String, RuleOverride>> {
+    static RULES: OnceLock<RwLock<BTreeMap<String, RuleOverride>>> = OnceLock::new();
+    RULES.get_or_init(Default::default)
+}
+
+fn settings(rule: RuleId) -> RuleOverride {
+    let mut settings = registry().read().ok().and_then(|r| r.get(rule.key()).cloned()).unwrap_or_else(|| RuleOverride::default_for(rule));
+    // Parameters added since the override was saved keep their defaults.
+    for (name, value) in rule.default_params() {
+        settings.params.entry(name).or_insert(value);
+    }
+    settings
+}
+
+/// Load saved rule settings. Called once at startup.
+pub fn load_saved(app: &AppHandle) {
+    match storage::load_json::<BTreeMap<String, RuleOverride>>(app, RULES_STORE) {
+        Ok(saved) => {
+            if let Ok(mut rules) = registry().write() {
+                *rules = saved;
+            }
+        }
+        Err(e) => warn!("Ignoring saved rule settings: {}", e),
+    }
+}
+
+/// Enabled rules matching `bug` with the given severity, in evaluation order.
+pub fn matching(bug: &Bug, severity: RuleSeverity) -> Vec<RuleId> {
+    RuleId::ALL
+        .into_iter()
+        .filter(|rule| {
+            let settings = settings(*rule);
+            settings.enabled && settings.severity == severity && rule.matches(bug, &settings.params)
+        })
+        .collect()
+}
+
+fn update(app: &AppHandle, id: &str, change: impl FnOnce(&mut RuleOverride) -> Result<(), String>) -> Result<RuleInfo, String> {
+    roles::require(Role::Admin, "changing rules")?;
+    let rule = RuleId::parse(id)?;
+    let mut settings = settings(rule);
+    change(&mut settings)?;
+    // Other rules keep what teammates saved meanwhile.
+    let saved = storage::update_json(app, RULES_STORE, |rules: &mut BTreeMap<String, RuleOverride>| {
+        rules.insert(rule.key().to_string(), settings);
+    })?;
+    *registry().write().map_err(|e| e.to_string())? = saved;
+    Ok(info(rule))
+}
+
+fn info(rule: RuleId) -> RuleInfo {
+    let settings = settings(rule);
+    RuleInfo {
+        id: rule,
+        name: rule.name().to_string(),
+        description: rule.description().to_string(),
+        enabled: settings.enabled,
+        severity: settings.severity,
+        params: settings.params,
+    }
+}
+
+#[tauri::command]
+/// Every analysis rule with its current settings, in evaluation order.
+pub fn list_rules() -> Vec<RuleInfo> {
+    RuleId::ALL.into_iter().map(info).collect()
+}
+
+#[tauri::command]
+/// Turns a rule on or off; takes effect on the next analysis.
+pub fn set_rule_enabled(app: AppHandle, id: String, enabled: bool) -> Result<RuleInfo, String> {
+    update(&app, &id, |settings| {
+        settings.enabled = enabled;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+/// Sets a rule parameter (e.g. `min_chars`), or its `severity` (`"questionable"` or `"warning"`).
+pub fn set_rule_param(app: AppHandle, id: String, name: String, value: serde_json::Value) -> Result<RuleInfo, String> {
+    update(&app, &id, |settings| {
+        if name == "severity" {
+            settings.severity = serde_json::from_value(value).map_err(|_| "Severity must be \"questionable\" or \"warning\"".to_string())?;
+            return Ok(());
+        }
+        if !settings.params.contains_key(&name) {
+            return Err(format!("Rule '{}' has no parameter '{}'", id, name));
+        }
+        let value = value.as_f64().ok_or_else(|| format!("Parameter '{}' must be a number", name))?;
+        settings.params.insert(name, value);
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(rule: RuleId, description: &str) -> bool {
+        let mut bug = Bug::sample(1, "Login fails");
+        bug.description = Some(description.to_string());
+        rule.matches(&bug, &rule.default_params())
+    }
+
+    #[test]
+    fn short_descriptions_are_counted_in_graphemes_with_cjk_thresholds() {
+        assert!(matches(RuleId::ShortDescription, "Crash"));
+        assert!(!matches(RuleId::ShortDescription, "Crashes when I open the login page"));
+        // Four CJK characters carry enough content, and there are no spaces to count words by.
+        assert!(!matches(RuleId::ShortDescription, "登录页面崩溃"));
+        assert!(matches(RuleId::ShortDescription, "崩溃"));
+        assert!(!matches(RuleId::ShortDescription, "로그인 화면에서 충돌"));
+        // Combining marks count with their letter: "e\u{301}" is one character, not two.
+        assert!(matches(RuleId::ShortDescription, "cafe\u{301}s"));
+        assert!(!matches(RuleId::ShortDescription, "<p>Crash</p><img src='screenshot.png'>"));
+    }
+
+    #[test]
+    fn symbol_only_descriptions_are_special_characters_not_short() {
+        for soup in ["🔥🔥🔥", "!!! ??? ...", "<p>👍🏽👍🏽</p>"] {
+            assert!(matches(RuleId::SpecialCharacters, soup), "{}", soup);
+            assert!(!matches(RuleId::ShortDescription, soup), "{}", soup);
+        }
+        assert!(!matches(RuleId::SpecialCharacters, "🔥 Crash on login 🔥"));
+        assert!(!matches(RuleId::SpecialCharacters, ""));
+    }
+
+    #[test]
+    fn the_other_rules_match_what_they_describe() {
+        assert!(matches(RuleId::EmptyDescription, "  "));
+        assert!(!matches(RuleId::EmptyDescription, "Crash"));
+        assert!(matches(RuleId::DuplicateTitle, "Login fails"));
+        assert!(matches(RuleId::DeadLinks, "See https://contoso.com/logs, it returns 404"));
+        assert!(!matches(RuleId::DeadLinks, "See https://contoso.com/logs"));
+    }
+}
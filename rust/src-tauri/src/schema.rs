@@ -0,0 +1,198 @@
+// Versioned JSON Schema of everything the commands return, for the frontend and external consumers
+use crate::audit::AuditEntry;
+use crate::auto_assign::AssignAction;
+use crate::baseline::{BaselineComparison, BaselineInfo};
+use crate::bug_analysis::BugReport;
+use crate::compare::Comparison;
+use crate::credentials::CredentialStatus;
+use crate::dashboard::DashboardWidget;
+use crate::error::BuggerError;
+use crate::escalation::EscalationAction;
+use crate::events::{UpdateBatch, EVENT_SCHEMA_VERSION};
+use crate::needs_info::NeedsInfoDraft;
+use crate::out_of_office::Absence;
+use crate::outputs::OutputSchedule;
+use crate::pat::PatInfo;
+use crate::policy::PolicyAction;
+use crate::power::PowerStatus;
+use crate::process::ProcessModel;
+use crate::quick_actions::QuickAction;
+use crate::quick_notes::QuickNote;
+use crate::recurrence::ScheduledJob;
+use crate::roles::Role;
+use crate::rules::RuleInfo;
+use crate::screenshot::AttachedImage;
+use crate::settings::{FieldError, Settings};
+use crate::setup::ConfigStatus;
+use crate::time_tracking::{RunningTimer, TimeEntry, TimeReport};
+use crate::triage::{TriageItem, TriageOutcome, TriageWeights, WhatIf};
+use crate::verdict::{FiledBug, PreFileReview, Verdict};
+use crate::wiql::QueryPreview;
+use crate::work_session::WorkSession;
+use crate::write_back::WriteBackAction;
+use chrono::{DateTime, FixedOffset};
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::JsonSchema;
+use serde_json::{json, Map, Value};
+
+/// Version of the command payloads. Within a version changes are additive only: new output fields
+/// may appear (consumers must ignore unknown fields), new input fields are optional, and nothing is
+/// renamed, retyped or removed. Anything else bumps this.
+pub const SCHEMA_VERSION: u32 = 3;
+
+/// Schemas of one command's success and error payloads.
+fn command<T: JsonSchema, E: JsonSchema>(gen: &mut SchemaGenerator) -> Value {
+    json!({ "returns": gen.subschema_for::<T>(), "error": gen.subschema_for::<E>() })
+}
+
+/// The JSON Schema document: each command's payloads, the update event stream, and shared definitions.
+pub fn document() -> Value {
+    let mut gen = SchemaSettings::draft07().into_generator();
+    let gen = &mut gen;
+    let commands: Vec<(&str, Value)> = vec![
+        ("fetch_and_analyze_bugs", command::<String, BuggerError>(gen)),
+        ("get_bug_report", command::<BugReport, BuggerError>(gen)),
+        ("force_full_refresh", command::<(), BuggerError>(gen)),
+        ("get_bug_description", command::<String, BuggerError>(gen)),
+        ("get_last_report", command::<Option<String>, String>(gen)),
+        ("take_quick_action", command::<Option<QuickAction>, String>(gen)),
+        ("analyze_id_list", command::<String, String>(gen)),
+        ("compare_filters", command::<Comparison, String>(gen)),
+        ("get_next_triage_bug", command::<Option<TriageItem>, BuggerError>(gen)),
+        ("record_triage_decision", command::<TriageOutcome, BuggerError>(gen)),
+        ("get_triage_weights", command::<TriageWeights, String>(gen)),
+        ("save_triage_weights", command::<TriageWeights, String>(gen)),
+        ("evaluate_triage_weights", command::<Vec<WhatIf>, BuggerError>(gen)),
+        ("draft_nudge_comment", command::<String, BuggerError>(gen)),
+        ("draft_needs_info_comments", command::<Vec<NeedsInfoDraft>, BuggerError>(gen)),
+        ("mark_needs_info", command::<(), BuggerError>(gen)),
+        ("post_comment", command::<(), BuggerError>(gen)),
+        ("reassign_bug", command::<(), BuggerError>(gen)),
+        ("start_timer", command::<RunningTimer, String>(gen)),
+        ("stop_timer", command::<Option<TimeEntry>, BuggerError>(gen)),
+        ("retry_time_write_back", command::<TimeEntry, BuggerError>(gen)),
+        ("get_time_report", command::<TimeReport, String>(gen)),
+        ("open_bug", command::<(), String>(gen)),
+        ("start_work_session", command::<WorkSession, String>(gen)),
+        ("finish_work_session", command::<Option<TimeEntry>, BuggerError>(gen)),
+        ("run_auto_close_policy", command::<Vec<PolicyAction>, BuggerError>(gen)),
+        ("run_auto_assign_policy", command::<Vec<AssignAction>, BuggerError>(gen)),
+        ("run_escalation_policy", command::<Vec<EscalationAction>, BuggerError>(gen)),
+        ("run_write_back_policy", command::<Vec<WriteBackAction>, BuggerError>(gen)),
+        ("get_out_of_office", command::<Vec<Absence>, String>(gen)),
+        ("set_out_of_office", command::<Vec<Absence>, String>(gen)),
+        ("remove_out_of_office", command::<Vec<Absence>, String>(gen)),
+        ("get_audit_log", command::<Vec<AuditEntry>, String>(gen)),
+        ("undo_action", command::<(), BuggerError>(gen)),
+        ("set_simulation_mode", command::<bool, ()>(gen)),
+        ("get_simulation_preview", command::<String, String>(gen)),
+        ("export_report_html", command::<(), String>(gen)),
+        ("export_bugs", command::<(), String>(gen)),
+        ("export_accomplishment_report", command::<(), String>(gen)),
+        ("export_analysis", command::<(), String>(gen)),
+        ("get_output_schedules", command::<Vec<OutputSchedule>, String>(gen)),
+        ("save_output_schedules", command::<Vec<OutputSchedule>, String>(gen)),
+        ("run_output_now", command::<(), BuggerError>(gen)),
+        ("get_dashboard_widget", command::<DashboardWidget, String>(gen)),
+        ("preview_recurrence", command::<Vec<DateTime<FixedOffset>>, String>(gen)),
+        ("get_next_runs", command::<Vec<ScheduledJob>, String>(gen)),
+        ("get_power_status", command::<PowerStatus, String>(gen)),
+        ("export_calendar", command::<(), String>(gen)),
+        ("get_process_model", command::<ProcessModel, String>(gen)),
+        ("get_role", command::<Role, String>(gen)),
+        ("list_rules", command::<Vec<RuleInfo>, ()>(gen)),
+        ("set_rule_enabled", command::<RuleInfo, String>(gen)),
+        ("set_rule_param", command::<RuleInfo, String>(gen)),
+        ("open_in_editor", command::<(), String>(gen)),
+        ("get_accomplishment_report", command::<String, String>(gen)),
+        ("preview_bug_query", command::<QueryPreview, BuggerError>(gen)),
+        ("subscribe_updates", command::<(), String>(gen)),
+        ("mark_baseline", command::<BaselineInfo, String>(gen)),
+        ("list_baselines", command::<Vec<BaselineInfo>, String>(gen)),
+        ("delete_baseline", command::<(), String>(gen)),
+        ("compare_to_baseline", command::<BaselineComparison, String>(gen)),
+        ("get_config_status", command::<ConfigStatus, ()>(gen)),
+        ("get_settings", command::<Settings, ()>(gen)),
+        ("save_settings", command::<Settings, Vec<FieldError>>(gen)),
+        ("set_credential", command::<(), String>(gen)),
+        ("clear_credential", command::<(), String>(gen)),
+        ("get_credential_status", command::<Vec<CredentialStatus>, ()>(gen)),
+        ("get_pat_info", command::<PatInfo, String>(gen)),
+        ("get_http_trace_path", command::<Option<String>, ()>(gen)),
+        ("get_schema", command::<Value, ()>(gen)),
+        ("analyze_work_item", command::<Verdict, BuggerError>(gen)),
+        ("analyze_text", command::<Verdict, BuggerError>(gen)),
+        ("review_new_bug", command::<PreFileReview, BuggerError>(gen)),
+        ("file_bug", command::<FiledBug, BuggerError>(gen)),
+        ("save_quick_note", command::<QuickNote, String>(gen)),
+        ("list_quick_notes", command::<Vec<QuickNote>, String>(gen)),
+        ("delete_quick_note", command::<(), String>(gen)),
+        ("file_quick_note", command::<FiledBug, BuggerError>(gen)),
+        ("capture_screenshot", command::<String, String>(gen)),
+        ("attach_image", command::<AttachedImage, BuggerError>(gen)),
+    ];
+    let events = gen.subschema_for::<UpdateBatch>();
+    let commands: Map<String, Value> = commands.into_iter().map(|(name, schema)| (name.to_string(), schema)).collect();
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "Bugger API",
+        "version": SCHEMA_VERSION,
+        "commands": commands,
+        "events": { "version": EVENT_SCHEMA_VERSION, "batch": events },
+        "definitions": gen.take_definitions(),
+    })
+}
+
+#[tauri::command]
+/// JSON Schema of every command's payloads, with the schema version.
+pub fn get_schema() -> Value {
+    document()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Commands registered with `generate_handler!` in main.rs, without their module paths.
+    fn registered_commands() -> Vec<String> {
+        let main = include_str!("main.rs");
+        let start = main.find("generate_handler![").unwrap() + "generate_handler![".len();
+        let list = &main[start..start + main[start..].find(']').unwrap()];
+        list.split(',').map(|c| c.trim().rsplit("::").next().unwrap().to_string()).filter(|c| !c.is_empty()).collect()
+    }
+
+    #[test]
+    fn every_registered_command_is_described() {
+        let document = document();
+        let commands = document["commands"].as_object().unwrap();
+        let registered = registered_commands();
+        assert!(registered.iter().any(|c| c == "fetch_and_analyze_bugs") && registered.iter().any(|c| c == "get_schema"));
+        let missing: Vec<&String> = registered.iter().filter(|c| !commands.contains_key(*c)).collect();
+        assert!(missing.is_empty(), "missing from the schema: {:?}", missing);
+        assert_eq!(document["version"], SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn every_reference_resolves_to_a_definition() {
+        fn refs(value: &Value, found: &mut Vec<String>) {
+            match value {
+                Value::Object(map) => {
+                    if let Some(Value::String(r)) = map.get("$ref") {
+                        found.push(r.clone());
+                    }
+                    map.values().for_each(|v| refs(v, found));
+                }
+                Value::Array(items) => items.iter().for_each(|v| refs(v, found)),
+                _ => {}
+            }
+        }
+        let document = document();
+        let mut found = Vec::new();
+        refs(&document, &mut found);
+        assert!(!found.is_empty());
+        for r in found {
+            let name = r.strip_prefix("#/definitions/").unwrap_or_else(|| panic!("unexpected reference {}", r));
+            assert!(document["definitions"].get(name).is_some(), "unresolved reference {}", r);
+        }
+    }
+}
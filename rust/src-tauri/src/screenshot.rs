@@ -0,0 +1,158 @@
+// Screenshots attached to bugs: capture the screen (or take a dropped image), upload it as a work item
+// attachment and optionally say what it shows in a comment, so a screenshot never stands alone
+use crate::audit::{self, WriteAction, WriteOutcome};
+use crate::azure_devops::AzureDevOpsClient;
+use crate::error::BuggerError;
+use crate::AppConfig;
+use base64::Engine;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// Time for the hidden window to disappear from the screen before capturing.
+const HIDE_DELAY: Duration = Duration::from_millis(400);
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct AttachedImage {
+    pub bug_id: u64,
+    pub file_name: String,
+    /// Attachment URL; null in simulation mode.
+    pub url: Option<String>,
+    /// What went wrong after the image was attached, e.g. posting the comment.
+    pub warning: Option<String>,
+}
+
+/// Image type from the file signature; only images can be attached.
+fn image_extension(bytes: &[u8]) -> Option<&'static str> {
+    match bytes {
+        [0x89, b'P', b'N', b'G', ..] => Some("png"),
+        [0xFF, 0xD8, 0xFF, ..] => Some("jpg"),
+        [b'G', b'I', b'F', b'8', ..] => Some("gif"),
+        [b'B', b'M', ..] => Some("bmp"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some("webp"),
+        _ => None,
+    }
+}
+
+/// Run a screenshot tool; true if it succeeded.
+fn run(program: &str, args: &[&str]) -> bool {
+    let mut command = Command::new(program);
+    command.args(args);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        // CREATE_NO_WINDOW: no console showing up in the screenshot.
+        command.creation_flags(0x0800_0000);
+    }
+    command.status().is_ok_and(|s| s.success())
+}
+
+#[cfg(windows)]
+fn capture_to(path: &Path) -> bool {
+    let script = format!(
+        "Add-Type -AssemblyName System.Windows.Forms,System.Drawing; \
+         $b = [System.Windows.Forms.SystemInformation]::VirtualScreen; \
+         $bmp = New-Object System.Drawing.Bitmap $b.Width, $b.Height; \
+         [System.Drawing.Graphics]::FromImage($bmp).CopyFromScreen($b.Left, $b.Top, 0, 0, $bmp.Size); \
+         $bmp.Save('{}', [System.Drawing.Imaging.ImageFormat]::Png)",
+        path.display().to_string().replace('\'', "''")
+    );
+    run("powershell", &["-NoProfile", "-NonInteractive", "-Command", &script])
+}
+
+#[cfg(target_os = "macos")]
+fn capture_to(path: &Path) -> bool {
+    run("screencapture", &["-x", &path.display().to_string()])
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn capture_to(path: &Path) -> bool {
+    // Whichever screenshot tool the desktop has: GNOME, wlroots (Wayland), KDE, then ImageMagick (X11).
+    let file = path.display().to_string();
+    run("gnome-screenshot", &["-f", &file]) || run("grim", &[&file]) || run("spectacle", &["-b", "-n", "-o", &file]) || run("import", &["-window", "root", &file])
+}
+
+/// A PNG of the whole screen.
+fn capture() -> Result<Vec<u8>, String> {
+    // A fresh private directory, so nobody else can plant or read the file.
+    let dir = tempfile::Builder::new().prefix("bugger-screenshot-").tempdir().map_err(|e| format!("Could not create a temporary directory: {}", e))?;
+    let path = dir.path().join("screenshot.png");
+    let captured = capture_to(&path);
+    let bytes = std::fs::read(&path);
+    match bytes {
+        Ok(bytes) if captured && !bytes.is_empty() => Ok(bytes),
+        _ => Err("Could not capture the screen: no screenshot tool worked".to_string()),
+    }
+}
+
+// Off the main thread, so the windows actually get hidden while it waits.
+#[tauri::command(async)]
+/// Captures the screen with the app's windows hidden, as a base64 PNG for the attach preview.
+pub fn capture_screenshot(app: AppHandle) -> Result<String, String> {
+    let visible: Vec<_> = app.webview_windows().into_values().filter(|w| w.is_visible().unwrap_or(false)).collect();
+    for window in &visible {
+        let _ = window.hide();
+    }
+    std::thread::sleep(HIDE_DELAY);
+    let result = capture();
+    for window in &visible {
+        let _ = window.show();
+    }
+    Ok(base64::engine::general_purpose::STANDARD.encode(result?))
+}
+
+#[tauri::command]
+/// Uploads an image (base64, captured or dropped) and attaches it to bug `id`. A `comment` is kept
+/// on the attachment and posted on the bug, so the discussion says what the image shows. The image
+/// stays attached when posting the comment fails; that comes back as a warning.
+pub fn attach_image(app: AppHandle, id: u64, image: String, file_name: Option<String>, comment: Option<String>) -> Result<AttachedImage, BuggerError> {
+    let bytes = base64::engine::general_purpose::STANDARD.decode(image.trim()).map_err(|e| format!("Not a valid image: {}", e))?;
+    let extension = image_extension(&bytes).ok_or("Only PNG, JPEG, GIF, BMP and WebP images can be attached")?;
+    let file_name = file_name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty()).unwrap_or_else(|| format!("screenshot-{}.{}", chrono::Local::now().format("%Y%m%d-%H%M%S"), extension));
+    let comment = comment.map(|c| c.trim().to_string()).filter(|c| !c.is_empty());
+    let config = AppConfig::from_env()?;
+    let actor = config.user_email.clone();
+    let client = AzureDevOpsClient::new(config);
+    let mut url = None;
+    audit::perform(&app, &actor, id, WriteAction::Attach, file_name.clone(), false, || {
+        let uploaded = client.upload_attachment(&file_name, &bytes)?;
+        let attachment_url = uploaded["url"].as_str().ok_or("The upload returned no attachment URL")?.to_string();
+        let response = client.link_attachment(id, &attachment_url, comment.as_deref())?;
+        url = Some(attachment_url);
+        Ok(WriteOutcome { response, undo: None })
+    })?;
+    let mut warning = None;
+    if let (Some(text), Some(url)) = (&comment, &url) {
+        let html = format!("{}<br><img src=\"{}\" alt=\"{}\">", html_escape::encode_text(text).replace('\n', "<br>"), url, html_escape::encode_double_quoted_attribute(&file_name));
+        if let Err(e) = audit::perform(&app, &actor, id, WriteAction::Comment, text.clone(), false, || Ok(WriteOutcome::comment(client.add_comment(id, &html)?))) {
+            log::warn!("Attached {} to #{} but could not post the comment: {}", file_name, id, e);
+            warning = Some(format!("The image was attached, but the comment could not be posted: {}", e));
+        }
+    }
+    Ok(AttachedImage { bug_id: id, file_name, url, warning })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn images_are_recognized_by_their_signature() {
+        assert_eq!(image_extension(b"\x89PNG\r\n\x1a\n...."), Some("png"));
+        assert_eq!(image_extension(&[0xFF, 0xD8, 0xFF, 0xE0]), Some("jpg"));
+        assert_eq!(image_extension(b"GIF89a"), Some("gif"));
+        assert_eq!(image_extension(b"BM...."), Some("bmp"));
+        assert_eq!(image_extension(b"RIFF\x10\0\0\0WEBPVP8 "), Some("webp"));
+    }
+
+    #[test]
+    fn other_files_are_not_images() {
+        assert_eq!(image_extension(b""), None);
+        assert_eq!(image_extension(b"%PDF-1.7"), None);
+        assert_eq!(image_extension(b"RIFF\x10\0\0\0WAVEfmt "), None);
+        assert_eq!(image_extension(b"\x89PN"), None);
+    }
+}
@@ -0,0 +1,372 @@
+// Local server mode: a small HTTP server on localhost for feed readers, the browser extension, MCP clients,
+// Power BI and other local tools
+use crate::feed::{self, FeedFilter};
+use crate::azure_devops::AzureDevOpsClient;
+use crate::{mcp, odata, verdict};
+use crate::{AppConfig, AppState};
+use log::{info, warn};
+use std::collections::BTreeMap;
+use std::env;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// Port for local server mode (BUGGER_SERVER_PORT); `None` keeps the server off.
+pub fn port() -> Option<u16> {
+    env::var("BUGGER_SERVER_PORT").ok().and_then(|v| v.trim().parse().ok()).filter(|p| *p > 0)
+}
+
+/// Run without showing the window (BUGGER_HEADLESS or `--headless`), e.g. as a background feed server.
+pub fn is_headless() -> bool {
+    env::args().any(|a| a == "--headless") || crate::env_flag("BUGGER_HEADLESS", false)
+}
+
+/// Hide every window; the app keeps running its schedulers and the local server.
+pub fn hide_windows(app: &AppHandle) {
+    for window in app.webview_windows().values() {
+        if let Err(e) = window.hide() {
+            warn!("Failed to hide window: {}", e);
+        }
+    }
+}
+
+/// Shared secret the browser extension sends as `Authorization: Bearer <token>`
+/// (BUGGER_EXTENSION_TOKEN); the extension endpoint is off without it.
+fn extension_token() -> Option<String> {
+    env::var("BUGGER_EXTENSION_TOKEN").ok().map(|t| t.trim().to_string()).filter(|t| !t.is_empty())
+}
+
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+    /// Allow cross-origin calls (from the extension); only set on token-protected endpoints.
+    pub cors: bool,
+}
+
+impl Response {
+    fn text(status: u16, body: impl Into<String>) -> Self {
+        Response { status, content_type: "text/plain; charset=utf-8", body: body.into(), cors: false }
+    }
+
+    fn json(status: u16, body: &serde_json::Value) -> Self {
+        Response { status, content_type: "application/json", body: body.to_string(), cors: true }
+    }
+}
+
+/// Compare without bailing out at the first difference, so response timing doesn't leak the token.
+fn token_matches(given: &str, expected: &str) -> bool {
+    given.len() == expected.len() && given.bytes().zip(expected.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Decode `%XX` escapes and `+` in a query component.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' => match value.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                Some(b) => {
+                    out.push(b);
+                    i += 2;
+                }
+                None => out.push(b'%'),
+            },
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn parse_query(query: &str) -> BTreeMap<String, String> {
+    query
+        .split('&')
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            let (key, value) = p.split_once('=').unwrap_or((p, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+fn feed(app: &AppHandle, query: &BTreeMap<String, String>, self_url: &str) -> Result<Response, String> {
+    let filter = match FeedFilter::from_query(query) {
+        Ok(filter) => filter,
+        Err(e) => return Ok(Response::text(400, e)),
+    };
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    let bugs = app.state::<AppState>().bugs_or_fetch()?;
+    let entries = feed::record(app, &config, &bugs)?;
+    Ok(Response { status: 200, content_type: "application/atom+xml; charset=utf-8", body: feed::atom(&entries, &filter, &config, self_url), cors: false })
+}
+
+/// Verdict on the work item the extension is looking at: `GET /api/verdict?id=123` (or a work item URL).
+fn extension_verdict(query: &BTreeMap<String, String>, headers: &BTreeMap<String, String>) -> Result<Response, String> {
+    let Some(token) = extension_token() else {
+        return Ok(Response::text(404, "Not found"));
+    };
+    let given = headers.get("authorization").and_then(|v| v.strip_prefix("Bearer ")).unwrap_or("");
+    if !token_matches(given.trim(), &token) {
+        return Ok(Response::json(401, &serde_json::json!({ "error": "Missing or wrong token" })));
+    }
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    let id = match verdict::parse_work_item_ref(query.get("id").map_or("", |s| s.as_str()), &config.org) {
+        Ok(id) => id,
+        Err(e) => return Ok(Response::json(400, &serde_json::json!({ "error": e }))),
+    };
+    let verdict = verdict::work_item_verdict(id)?;
+    let problems = verdict.problems();
+    Ok(Response::json(200, &serde_json::json!({ "verdict": verdict, "problems": problems })))
+}
+
+/// Analysis rows for dashboards: the OData service under `/odata` (for Power BI's OData feed
+/// connector) or `/analysis.csv`.
+fn analysis(app: &AppHandle, path: &str, port: u16) -> Result<Response, String> {
+    let service_url = format!("http://localhost:{}/odata", port);
+    match path {
+        "/odata" | "/odata/" => return Ok(Response::json(200, &odata::service_document(&service_url))),
+        "/odata/$metadata" => return Ok(Response { status: 200, content_type: "application/xml", body: odata::metadata(), cors: false }),
+        _ => {}
+    }
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    let bugs = app.state::<AppState>().bugs_or_fetch()?;
+    let rows = odata::rows(&AzureDevOpsClient::new(config), &bugs, chrono::Utc::now());
+    Ok(if path == "/odata/Bugs" {
+        Response::json(200, &odata::entity_set(rows, &service_url))
+    } else {
+        Response { status: 200, content_type: "text/csv; charset=utf-8", body: odata::csv(&rows), cors: false }
+    })
+}
+
+/// Largest request body accepted (MCP messages are small); larger ones are refused with 413.
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+/// Largest request line plus headers read.
+const MAX_HEAD_BYTES: u64 = 16 * 1024;
+/// Most headers accepted in one request.
+const MAX_HEADERS: usize = 100;
+/// Connections handled at once; more are turned away until one finishes.
+const MAX_CONNECTIONS: usize = 16;
+/// How long a client may take to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Whether the Host header names this server. A DNS-rebinding page reaches 127.0.0.1 under its own
+/// host name, so anything else is refused.
+fn is_local_host(headers: &BTreeMap<String, String>, port: u16) -> bool {
+    headers.get("host").is_some_and(|host| {
+        ["127.0.0.1", "localhost", "[::1]"].iter().any(|name| host.eq_ignore_ascii_case(&format!("{}:{}", name, port)))
+    })
+}
+
+/// Whether a request comes from a page on another site; those are refused to block DNS rebinding.
+/// Browser extensions (the verdict badge) have their own scheme and are let through to the token check.
+fn is_foreign_origin(headers: &BTreeMap<String, String>) -> bool {
+    headers.get("origin").is_some_and(|origin| {
+        if origin.starts_with("chrome-extension://") || origin.starts_with("moz-extension://") || origin.starts_with("extension://") {
+            return false;
+        }
+        let host = origin.split("://").nth(1).unwrap_or("").split(':').next().unwrap_or("");
+        !matches!(host, "localhost" | "127.0.0.1" | "[::1]")
+    })
+}
+
+/// One JSON-RPC message from an MCP client (Streamable HTTP transport, JSON responses only).
+fn mcp_message(app: &AppHandle, body: &str) -> Response {
+    match mcp::handle(app, body) {
+        Some(reply) => Response { cors: false, ..Response::json(200, &reply) },
+        None => Response::text(202, ""),
+    }
+}
+
+fn route(app: &AppHandle, method: &str, target: &str, headers: &BTreeMap<String, String>, body: &str, port: u16) -> Response {
+    if !is_local_host(headers, port) || is_foreign_origin(headers) {
+        return Response::text(403, "Forbidden");
+    }
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = parse_query(query);
+    let result = match (method, path) {
+        ("GET", "/feed.atom" | "/feed") => feed(app, &query, &format!("http://localhost:{}{}", port, target)),
+        // CORS preflight for the Authorization header.
+        ("OPTIONS", "/api/verdict") => Ok(Response { cors: true, ..Response::text(204, "") }),
+        ("GET", "/api/verdict") => extension_verdict(&query, headers),
+        ("GET", "/odata" | "/odata/" | "/odata/$metadata" | "/odata/Bugs" | "/analysis.csv") => analysis(app, path, port),
+        ("POST", "/mcp") => Ok(mcp_message(app, body)),
+        ("GET", "/mcp") => Ok(Response::text(405, "Method not allowed")),
+        ("GET", _) => Ok(Response::text(404, "Not found")),
+        _ => Ok(Response::text(405, "Method not allowed")),
+    };
+    result.unwrap_or_else(|e| {
+        warn!("Local server: {} {} failed: {}", method, path, e);
+        Response::text(500, e)
+    })
+}
+
+struct Request {
+    method: String,
+    target: String,
+    headers: BTreeMap<String, String>,
+    body: String,
+}
+
+/// Read one request, or the error response for one that's too large or malformed.
+fn read_request(reader: &mut impl BufRead) -> std::io::Result<Result<Request, Response>> {
+    let mut head = reader.by_ref().take(MAX_HEAD_BYTES);
+    let mut request_line = String::new();
+    head.read_line(&mut request_line)?;
+    let mut headers = BTreeMap::new();
+    let mut header = String::new();
+    let mut count = 0;
+    while head.read_line(&mut header)? > 2 {
+        if head.limit() == 0 && !header.ends_with('\n') {
+            return Ok(Err(Response::text(431, "Request header fields too large")));
+        }
+        count += 1;
+        if count > MAX_HEADERS {
+            return Ok(Err(Response::text(431, "Too many request header fields")));
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+        header.clear();
+    }
+    if head.limit() == 0 {
+        return Ok(Err(Response::text(431, "Request header fields too large")));
+    }
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or("").to_string(), parts.next().unwrap_or("/").to_string());
+    let length = match headers.get("content-length").map(|l| l.parse::<usize>()) {
+        None => 0,
+        Some(Ok(length)) if length <= MAX_BODY_BYTES => length,
+        Some(Ok(_)) => return Ok(Err(Response::text(413, "Request body too large"))),
+        Some(Err(_)) => return Ok(Err(Response::text(400, "Invalid Content-Length"))),
+    };
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    Ok(Ok(Request { method, target, headers, body: String::from_utf8_lossy(&body).into_owned() }))
+}
+
+fn handle(app: &AppHandle, stream: TcpStream, port: u16) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let response = match read_request(&mut reader)? {
+        Ok(request) => route(app, &request.method, &request.target, &request.headers, &request.body, port),
+        Err(response) => response,
+    };
+    let reason = match response.status {
+        200 => "OK",
+        202 => "Accepted",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Content Too Large",
+        431 => "Request Header Fields Too Large",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    };
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+        response.status,
+        reason,
+        response.content_type,
+        response.body.len(),
+        if response.cors {
+            "Access-Control-Allow-Origin: *\r\nAccess-Control-Allow-Methods: GET\r\nAccess-Control-Allow-Headers: Authorization\r\n"
+        } else {
+            ""
+        },
+        response.body
+    )
+}
+
+/// Start local server mode on 127.0.0.1 if BUGGER_SERVER_PORT is configured.
+pub fn start(app: AppHandle) {
+    let Some(port) = port() else {
+        return;
+    };
+    let listener = match TcpListener::bind(("127.0.0.1", port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            warn!("Local server: failed to listen on port {}: {}", port, e);
+            return;
+        }
+    };
+    info!("Local server listening on http://localhost:{}", port);
+    let open = Arc::new(AtomicUsize::new(0));
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            if open.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                open.fetch_sub(1, Ordering::SeqCst);
+                let _ = stream.write_all(b"HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+                continue;
+            }
+            let (app, open) = (app.clone(), open.clone());
+            std::thread::spawn(move || {
+                if let Err(e) = handle(&app, stream, port) {
+                    warn!("Local server: connection error: {}", e);
+                }
+                open.fetch_sub(1, Ordering::SeqCst);
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn only_this_servers_host_names_are_accepted() {
+        assert!(is_local_host(&headers(&[("host", "localhost:8765")]), 8765));
+        assert!(is_local_host(&headers(&[("host", "127.0.0.1:8765")]), 8765));
+        assert!(!is_local_host(&headers(&[("host", "attacker.example:8765")]), 8765));
+        assert!(!is_local_host(&headers(&[("host", "localhost:9999")]), 8765));
+        assert!(!is_local_host(&headers(&[]), 8765));
+    }
+
+    #[test]
+    fn pages_on_other_sites_are_foreign_but_extensions_are_not() {
+        assert!(!is_foreign_origin(&headers(&[])));
+        assert!(!is_foreign_origin(&headers(&[("origin", "http://localhost:1420")])));
+        assert!(!is_foreign_origin(&headers(&[("origin", "chrome-extension://abcdef")])));
+        assert!(is_foreign_origin(&headers(&[("origin", "https://attacker.example")])));
+        assert!(is_foreign_origin(&headers(&[("origin", "http://localhost.attacker.example")])));
+    }
+
+    fn read(request: &[u8]) -> Result<Request, u16> {
+        read_request(&mut std::io::Cursor::new(request)).unwrap().map_err(|response| response.status)
+    }
+
+    #[test]
+    fn requests_are_read_up_to_their_content_length() {
+        let request = read(b"POST /mcp HTTP/1.1\r\nHost: localhost:8765\r\nContent-Length: 2\r\n\r\n{}trailing").unwrap();
+        assert_eq!((request.method.as_str(), request.target.as_str(), request.body.as_str()), ("POST", "/mcp", "{}"));
+        assert_eq!(request.headers.get("host").map(String::as_str), Some("localhost:8765"));
+    }
+
+    #[test]
+    fn oversized_requests_are_refused_instead_of_truncated() {
+        let body = format!("POST /mcp HTTP/1.1\r\nContent-Length: {}\r\n\r\n", MAX_BODY_BYTES + 1);
+        assert_eq!(read(body.as_bytes()).err(), Some(413));
+        assert_eq!(read(b"POST /mcp HTTP/1.1\r\nContent-Length: lots\r\n\r\n").err(), Some(400));
+        let long_header = format!("GET / HTTP/1.1\r\nX-Filler: {}\r\n\r\n", "a".repeat(MAX_HEAD_BYTES as usize));
+        assert_eq!(read(long_header.as_bytes()).err(), Some(431));
+        let many_headers = format!("GET / HTTP/1.1\r\n{}\r\n", "X-Filler: a\r\n".repeat(MAX_HEADERS + 1));
+        assert_eq!(read(many_headers.as_bytes()).err(), Some(431));
+        let enough_headers = format!("GET / HTTP/1.1\r\n{}\r\n", "X-Filler: a\r\n".repeat(MAX_HEADERS));
+        assert!(read(enough_headers.as_bytes()).is_ok());
+    }
+}
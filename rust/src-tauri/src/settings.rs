@@ -0,0 +1,312 @@
+// Connection and AI settings saved from the app: normalized, validated against Azure DevOps, then
+// applied; `.env` and the environment are the fallback for anything not saved. Secrets go to the OS
+// keychain, never into the saved file.
+use crate::azure_devops::{is_auth_failure, probe};
+use crate::credentials::{self, Credential};
+use crate::parse_flag;
+use crate::provider::ProviderConfig;
+use crate::storage::{load_json, save_json};
+use log::warn;
+use reqwest::StatusCode;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::sync::{OnceLock, RwLock};
+use tauri::AppHandle;
+
+/// Settings editable in the app. Saved values take precedence over `.env`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Settings {
+    pub org: String,
+    pub project: String,
+    pub user_email: String,
+    /// Kept in the OS keychain. Empty in `get_settings`; saving it empty keeps the current token.
+    pub azure_devops_pat: String,
+    /// Used when the first PAT is rejected, e.g. while rotating tokens. Kept like the PAT.
+    #[serde(default)]
+    pub azure_devops_pat_secondary: String,
+    /// Like the PAT: never sent back, and empty keeps the current key.
+    #[serde(default)]
+    pub openai_api_key: String,
+    #[serde(default)]
+    pub openai_model: String,
+    /// `None` leaves BUGGER_AI_TRIAGE and BUGGER_AI_CATEGORIZATION as they are.
+    #[serde(default)]
+    pub ai_triage: Option<bool>,
+    #[serde(default)]
+    pub ai_categorization: Option<bool>,
+}
+
+/// Saved settings by variable name, read before the environment, which is never written to while
+/// other threads may be reading it. `None` hides the variable, e.g. a credential cleared this session.
+fn overlay() -> &'static RwLock<HashMap<String, Option<String>>> {
+    static OVERLAY: OnceLock<RwLock<HashMap<String, Option<String>>>> = OnceLock::new();
+    OVERLAY.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// A setting saved in the app, or else from the environment.
+pub fn var(name: &str) -> Option<String> {
+    match overlay().read().ok().and_then(|saved| saved.get(name).cloned()) {
+        Some(saved) => saved,
+        None => env::var(name).ok(),
+    }
+}
+
+/// Use `value` for `name` from now on, whatever the environment says; `None` unsets it.
+pub fn set_var(name: &str, value: Option<String>) {
+    if let Ok(mut saved) = overlay().write() {
+        saved.insert(name.to_string(), value);
+    }
+}
+
+fn env_or_empty(name: &str) -> String {
+    var(name).unwrap_or_default()
+}
+
+/// A validation problem with one settings field.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+fn field_error(field: &str, message: impl Into<String>) -> FieldError {
+    FieldError { field: field.to_string(), message: message.into() }
+}
+
+impl Settings {
+    /// Trim values, accept a full organization URL for `org`, and lower-case the email.
+    pub fn normalize(mut self) -> Self {
+        self.org = self.org.trim().trim_end_matches('/').to_string();
+        for prefix in ["https://dev.azure.com/", "http://dev.azure.com/"] {
+            if let Some(org) = self.org.strip_prefix(prefix) {
+                self.org = org.split('/').next().unwrap_or_default().to_string();
+            }
+        }
+        self.project = self.project.trim().to_string();
+        self.user_email = self.user_email.trim().to_lowercase();
+        self.azure_devops_pat = self.azure_devops_pat.trim().to_string();
+        self.azure_devops_pat_secondary = self.azure_devops_pat_secondary.trim().to_string();
+        self.openai_api_key = self.openai_api_key.trim().to_string();
+        self.openai_model = self.openai_model.trim().to_string();
+        self
+    }
+
+    /// The settings in effect, saved or from the environment, without secrets.
+    fn current() -> Self {
+        let flag = |name: &str| var(name).and_then(|v| parse_flag(&v));
+        Settings {
+            org: env_or_empty("AZURE_DEVOPS_ORG"),
+            project: env_or_empty("AZURE_DEVOPS_PROJECT"),
+            user_email: env_or_empty("AZURE_DEVOPS_USER_EMAIL"),
+            azure_devops_pat: String::new(),
+            azure_devops_pat_secondary: String::new(),
+            openai_api_key: String::new(),
+            openai_model: env_or_empty("OPENAI_MODEL"),
+            // Both send bug text to OpenAI, so both are off until turned on.
+            ai_triage: Some(flag("BUGGER_AI_TRIAGE").unwrap_or(false)),
+            ai_categorization: Some(flag("BUGGER_AI_CATEGORIZATION").unwrap_or(false)),
+        }
+    }
+
+    /// Fill secrets left empty with the ones in effect, to validate with.
+    fn with_current_secrets(mut self) -> Self {
+        if self.azure_devops_pat.is_empty() {
+            self.azure_devops_pat = credentials::var("AZURE_DEVOPS_PAT").unwrap_or_default();
+        }
+        if self.openai_api_key.is_empty() {
+            self.openai_api_key = credentials::var("OPENAI_API_KEY").unwrap_or_default();
+        }
+        self
+    }
+
+    fn secrets(&self) -> [(Credential, &str, &String); 3] {
+        [
+            (Credential::AzureDevopsPat, "azure_devops_pat", &self.azure_devops_pat),
+            (Credential::AzureDevopsPatSecondary, "azure_devops_pat_secondary", &self.azure_devops_pat_secondary),
+            (Credential::OpenaiApiKey, "openai_api_key", &self.openai_api_key),
+        ]
+    }
+
+    fn without_secrets(&self) -> Self {
+        Settings { azure_devops_pat: String::new(), azure_devops_pat_secondary: String::new(), openai_api_key: String::new(), ..self.clone() }
+    }
+
+    /// Checks that need no network: the fields Azure DevOps requires, if it's the provider, and email format.
+    fn local_errors(&self, azure_devops: bool) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        let required = [("org", &self.org), ("project", &self.project), ("user_email", &self.user_email), ("azure_devops_pat", &self.azure_devops_pat)];
+        for (field, value) in required.into_iter().filter(|_| azure_devops) {
+            if value.is_empty() {
+                errors.push(field_error(field, "Required"));
+            }
+        }
+        let valid_email = self.user_email.split_once('@').is_some_and(|(user, domain)| !user.is_empty() && domain.contains('.') && !domain.ends_with('.'));
+        if !self.user_email.is_empty() && !valid_email {
+            errors.push(field_error("user_email", "Not a valid email address"));
+        }
+        errors
+    }
+
+    /// Probe Azure DevOps: the PAT is accepted, org and project exist, and the PAT can read work items.
+    /// Azure DevOps being out of reach isn't an error, so settings can be saved offline.
+    fn remote_errors(&self) -> Vec<FieldError> {
+        let unreachable = |e: String| {
+            warn!("Saving settings without checking them, Azure DevOps is out of reach: {}", e);
+            Vec::new()
+        };
+        let base_url = crate::azure_devops_base_url();
+        let project_url = format!("{}/{}/_apis/projects/{}?api-version=7.0", base_url, self.org, self.project);
+        match probe(&project_url, &self.azure_devops_pat, None) {
+            Ok(status) if is_auth_failure(status) => return vec![field_error("azure_devops_pat", "Rejected by Azure DevOps — check that the token is valid for this organization")],
+            Ok(status) if status.is_success() => {}
+            Ok(StatusCode::NOT_FOUND) => {
+                let org_url = format!("{}/{}/_apis/projects?$top=1&api-version=7.0", base_url, self.org);
+                return match probe(&org_url, &self.azure_devops_pat, None) {
+                    Ok(status) if status.is_success() && !is_auth_failure(status) => vec![field_error("project", format!("Project not found in organization '{}'", self.org))],
+                    _ => vec![field_error("org", format!("Organization '{}' not found", self.org))],
+                };
+            }
+            Ok(status) => return vec![field_error("org", format!("Unexpected response checking the project ({})", status))],
+            Err(e) => return unreachable(e.to_string()),
+        }
+        // A query that matches nothing still needs the Work Items (Read) scope.
+        let wiql_url = format!("{}/{}/{}/_apis/wit/wiql?api-version=7.0", base_url, self.org, self.project);
+        let wiql = serde_json::json!({ "query": "SELECT [System.Id] FROM WorkItems WHERE [System.Id] = 0" });
+        match probe(&wiql_url, &self.azure_devops_pat, Some(&wiql)) {
+            Ok(status) if is_auth_failure(status) || status == StatusCode::FORBIDDEN => vec![field_error("azure_devops_pat", "Token is missing the Work Items (Read & Write) scope")],
+            Ok(status) if status.is_success() => Vec::new(),
+            Ok(status) => vec![field_error("azure_devops_pat", format!("Unexpected response checking work item access ({})", status))],
+            Err(e) => unreachable(e.to_string()),
+        }
+    }
+
+    /// Put in front of the environment, where `AppConfig::from_env` picks the values up.
+    fn apply(&self) {
+        for (name, value) in [
+            ("AZURE_DEVOPS_ORG", &self.org),
+            ("AZURE_DEVOPS_PROJECT", &self.project),
+            ("AZURE_DEVOPS_USER_EMAIL", &self.user_email),
+            ("OPENAI_MODEL", &self.openai_model),
+        ] {
+            if !value.is_empty() {
+                set_var(name, Some(value.clone()));
+            }
+        }
+        for (name, value) in [("BUGGER_AI_TRIAGE", self.ai_triage), ("BUGGER_AI_CATEGORIZATION", self.ai_categorization)] {
+            if let Some(on) = value {
+                set_var(name, Some(on.to_string()));
+            }
+        }
+    }
+}
+
+/// Apply settings saved by a previous session. Called once at startup, before `.env` is read.
+pub fn apply_saved(app: &AppHandle) {
+    match load_json::<Settings>(app, "settings") {
+        Ok(settings) => {
+            settings.apply();
+            move_secrets_to_keychain(app, &settings);
+        }
+        Err(e) => warn!("Ignoring saved settings: {}", e),
+    }
+}
+
+/// Settings saved by older versions hold secrets in plaintext: move them to the keychain. A secret
+/// the keychain won't take is still used for this session.
+fn move_secrets_to_keychain(app: &AppHandle, settings: &Settings) {
+    let mut moved = false;
+    for (credential, _, secret) in settings.secrets() {
+        if secret.is_empty() {
+            continue;
+        }
+        match credentials::store(credential, secret) {
+            Ok(()) => moved = true,
+            Err(e) => {
+                warn!("{}", e);
+                set_var(credential.env_name(), Some(secret.clone()));
+            }
+        }
+    }
+    if moved {
+        if let Err(e) = save_json(app, "settings", &settings.without_secrets()) {
+            warn!("Failed to remove secrets from the saved settings: {}", e);
+        }
+    }
+}
+
+#[tauri::command]
+/// The settings in effect, saved or from `.env`, for the settings form. Secrets are left out.
+pub fn get_settings() -> Settings {
+    Settings::current()
+}
+
+#[tauri::command]
+/// Normalizes and validates settings, then saves and applies them. Empty secrets keep the current
+/// ones; only secrets entered here go to the keychain. The Azure DevOps fields are only required, and
+/// checked, when it's the provider. Returns the saved (normalized) settings without secrets, or every
+/// problem found keyed by field.
+pub fn save_settings(app: AppHandle, settings: Settings) -> Result<Settings, Vec<FieldError>> {
+    let settings = settings.normalize();
+    let azure_devops = ProviderConfig::is_azure_devops_selected();
+    let checked = settings.clone().with_current_secrets();
+    let mut errors = checked.local_errors(azure_devops);
+    if errors.is_empty() && azure_devops {
+        errors = checked.remote_errors();
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    for (credential, field, secret) in settings.secrets() {
+        if !secret.is_empty() {
+            credentials::store(credential, secret).map_err(|e| vec![field_error(field, e)])?;
+        }
+    }
+    save_json(&app, "settings", &settings.without_secrets()).map_err(|e| vec![field_error("settings", e)])?;
+    settings.apply();
+    Ok(Settings::current())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(email: &str) -> Settings {
+        Settings { user_email: email.to_string(), ..Default::default() }
+    }
+
+    fn fields(errors: Vec<FieldError>) -> Vec<String> {
+        errors.into_iter().map(|e| e.field).collect()
+    }
+
+    #[test]
+    fn organization_urls_and_email_case_are_normalized() {
+        let settings = Settings { org: " https://dev.azure.com/contoso/Project/ ".to_string(), user_email: " Me@Contoso.com ".to_string(), ..Default::default() }.normalize();
+        assert_eq!(settings.org, "contoso");
+        assert_eq!(settings.user_email, "me@contoso.com");
+    }
+
+    #[test]
+    fn azure_devops_fields_are_required_only_for_azure_devops() {
+        assert_eq!(fields(settings("me@contoso.com").local_errors(true)), vec!["org", "project", "azure_devops_pat"]);
+        assert!(settings("").local_errors(false).is_empty());
+    }
+
+    #[test]
+    fn saved_settings_come_before_the_environment() {
+        assert_eq!(var("BUGGER_TEST_SAVED_SETTING"), None);
+        set_var("BUGGER_TEST_SAVED_SETTING", Some("saved".to_string()));
+        assert_eq!(var("BUGGER_TEST_SAVED_SETTING").as_deref(), Some("saved"));
+        // Cleared this session, a variable from the environment doesn't apply either.
+        set_var("PATH", None);
+        assert_eq!(var("PATH"), None);
+        assert!(env::var("PATH").is_ok());
+    }
+
+    #[test]
+    fn an_email_is_checked_for_every_provider() {
+        assert_eq!(fields(settings("me@contoso").local_errors(false)), vec!["user_email"]);
+        assert_eq!(fields(settings("@contoso.com").local_errors(false)), vec!["user_email"]);
+    }
+}
@@ -0,0 +1,103 @@
+// First-run setup: report which settings are missing or invalid instead of failing with a raw error
+use crate::azure_devops::primary_pat_rejected;
+use crate::credentials;
+use crate::provider::ProviderConfig;
+use crate::quiet_hours::parse_hours;
+use chrono::FixedOffset;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+
+/// Optional settings that must be whole numbers when set.
+const NUMERIC: [&str; 11] = [
+    "BUGGER_REFRESH_MINUTES",
+    "BUGGER_HOT_COMMENT_THRESHOLD",
+    "BUGGER_WAITING_DAYS",
+    "BUGGER_UNDO_WINDOW_MINUTES",
+    "BUGGER_ASSET_FETCH_LIMIT",
+    "BUGGER_DESCRIPTION_MAX_CHARS",
+    "BUGGER_ATTACHMENT_SCAN_KB",
+    "BUGGER_CHANGED_WITHIN_DAYS",
+    "BUGGER_AUTOCLOSE_WARN_DAYS",
+    "BUGGER_AUTOCLOSE_CLOSE_DAYS",
+    "BUGGER_SMTP_PORT",
+];
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct InvalidSetting {
+    pub name: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ConfigStatus {
+    /// True when every required setting is present and nothing is invalid.
+    pub ready: bool,
+    pub missing: Vec<String>,
+    pub invalid: Vec<InvalidSetting>,
+    /// Problems that don't block use, e.g. a rejected primary PAT covered by the secondary.
+    pub warnings: Vec<InvalidSetting>,
+    pub ai_enabled: bool,
+}
+
+fn invalid(name: &str, reason: impl Into<String>) -> InvalidSetting {
+    InvalidSetting { name: name.to_string(), reason: reason.into() }
+}
+
+/// Check the environment without failing on the first problem.
+pub fn check_config() -> ConfigStatus {
+    crate::load_env();
+    // Secrets may be in the OS keychain instead.
+    let value = credentials::var;
+    let mut missing = Vec::new();
+    let mut problems = Vec::new();
+    // Settings without which nothing works.
+    let required = ProviderConfig::required_settings();
+    for name in required {
+        match value(name) {
+            None => missing.push(name.to_string()),
+            // Values still copied verbatim from .env.example.
+            Some(v) if v.starts_with("your_") => problems.push(invalid(name, "still set to the placeholder from .env.example")),
+            Some(_) => {}
+        }
+    }
+    if required.contains(&"AZURE_DEVOPS_USER_EMAIL") && value("AZURE_DEVOPS_USER_EMAIL").is_some_and(|e| !e.contains('@')) {
+        problems.push(invalid("AZURE_DEVOPS_USER_EMAIL", "expected an email address"));
+    }
+    for name in NUMERIC {
+        if let Some(v) = value(name).filter(|v| v.trim().parse::<u64>().is_err()) {
+            problems.push(invalid(name, format!("'{}' is not a whole number", v)));
+        }
+    }
+    if let Some(v) = value("BUGGER_AUTOCLOSE") {
+        if !["off", "dry-run", "dryrun", "on", "true", "false"].contains(&v.trim().to_lowercase().as_str()) {
+            problems.push(invalid("BUGGER_AUTOCLOSE", format!("'{}' should be off, dry-run or on", v)));
+        }
+    }
+    if let Some(v) = value("BUGGER_WORK_HOURS").filter(|v| parse_hours(v).is_none()) {
+        problems.push(invalid("BUGGER_WORK_HOURS", format!("'{}' should look like 09:00-17:30", v)));
+    }
+    if let Some(v) = value("BUGGER_PAT_EXPIRES").filter(|v| chrono::NaiveDate::parse_from_str(v.trim(), "%Y-%m-%d").is_err()) {
+        problems.push(invalid("BUGGER_PAT_EXPIRES", format!("'{}' should be a date like 2026-12-31", v)));
+    }
+    if let Some(v) = value("BUGGER_TIMEZONE").filter(|v| v.parse::<FixedOffset>().is_err()) {
+        problems.push(invalid("BUGGER_TIMEZONE", format!("'{}' should be a UTC offset like +02:00", v)));
+    }
+    let mut warnings = Vec::new();
+    if value("AZURE_DEVOPS_PAT").is_some_and(|pat| primary_pat_rejected(&pat)) {
+        warnings.push(invalid("AZURE_DEVOPS_PAT", "rejected by Azure DevOps; using AZURE_DEVOPS_PAT_SECONDARY — replace it"));
+    }
+    ConfigStatus {
+        ready: missing.is_empty() && problems.is_empty(),
+        missing,
+        invalid: problems,
+        warnings,
+        ai_enabled: value("OPENAI_API_KEY").is_some_and(|k| !k.starts_with("your_")),
+    }
+}
+
+#[tauri::command]
+/// Reports which settings are missing or invalid so the frontend can show setup instead of an error.
+pub fn get_config_status() -> ConfigStatus {
+    check_config()
+}
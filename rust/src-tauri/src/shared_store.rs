@@ -0,0 +1,241 @@
+// Team-shared state: triage decisions and snoozes, rule overrides, absences and escalations kept in one SQLite
+// file on a network share or in Postgres (BUGGER_SHARED_STORE), so teammates running Bugger individually
+// don't repeat each other's work
+use chrono::Utc;
+use postgres_native_tls::MakeTlsConnector;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// Stores shared unless BUGGER_SHARED_STORES names others.
+const DEFAULT_SHARED: [&str; 5] = ["triage", "rules", "out_of_office", "escalations", "rotation"];
+/// How long a SQLite write waits for a teammate's to finish.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SharedStore {
+    /// A database file, e.g. on a network share.
+    Sqlite(PathBuf),
+    /// A `postgres://` connection URL.
+    Postgres(String),
+}
+
+impl SharedStore {
+    /// From BUGGER_SHARED_STORE: a `postgres://` (or `postgresql://`) URL, otherwise a SQLite file path.
+    pub fn from_env() -> Option<Self> {
+        let location = env::var("BUGGER_SHARED_STORE").ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty())?;
+        if location.starts_with("postgres://") || location.starts_with("postgresql://") {
+            Some(SharedStore::Postgres(location))
+        } else {
+            Some(SharedStore::Sqlite(PathBuf::from(location)))
+        }
+    }
+
+    fn sqlite(path: &Path) -> Result<rusqlite::Connection, String> {
+        let db = rusqlite::Connection::open(path).map_err(|e| format!("Failed to open shared store {}: {}", path.display(), e))?;
+        // The default rollback journal, as WAL doesn't work on network shares.
+        db.busy_timeout(BUSY_TIMEOUT).map_err(|e| e.to_string())?;
+        db.execute("CREATE TABLE IF NOT EXISTS bugger_stores (name TEXT PRIMARY KEY, value TEXT NOT NULL, updated_at TEXT NOT NULL)", [])
+            .map_err(|e| format!("Shared store error: {}", e))?;
+        Ok(db)
+    }
+
+    fn postgres(url: &str) -> Result<postgres::Client, String> {
+        let tls = MakeTlsConnector::new(native_tls::TlsConnector::new().map_err(|e| e.to_string())?);
+        let mut db = postgres::Client::connect(url, tls).map_err(|e| format!("Failed to connect to the shared store: {}", e))?;
+        db.batch_execute("CREATE TABLE IF NOT EXISTS bugger_stores (name TEXT PRIMARY KEY, value TEXT NOT NULL, updated_at TIMESTAMPTZ NOT NULL)")
+            .map_err(|e| format!("Shared store error: {}", e))?;
+        Ok(db)
+    }
+
+    /// Run `work` on the open connection to this store, connecting first if there's none yet (or it
+    /// was to another store). A connection that fails is dropped, so the next call reconnects.
+    fn with<R>(&self, work: impl FnOnce(&mut Connection) -> Result<R, String>) -> Result<R, String> {
+        let mut open = connection().lock().map_err(|e| e.to_string())?;
+        let reusable = match open.as_ref() {
+            Some((store, Connection::Postgres(db))) => store == self && !db.is_closed(),
+            Some((store, Connection::Sqlite(_))) => store == self,
+            None => false,
+        };
+        if !reusable {
+            *open = None;
+            let db = match self {
+                SharedStore::Sqlite(path) => Connection::Sqlite(Self::sqlite(path)?),
+                SharedStore::Postgres(url) => Connection::Postgres(Self::postgres(url)?),
+            };
+            *open = Some((self.clone(), db));
+        }
+        let (_, db) = open.as_mut().ok_or("Shared store connection lost")?;
+        let result = work(db);
+        if result.is_err() {
+            *open = None;
+        }
+        result
+    }
+
+    /// The JSON saved under `name`, if there is any.
+    pub fn load(&self, name: &str) -> Result<Option<String>, String> {
+        self.with(|db| match db {
+            Connection::Sqlite(db) => match db.query_row("SELECT value FROM bugger_stores WHERE name = ?1", [name], |row| row.get(0)) {
+                Ok(value) => Ok(Some(value)),
+                Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                Err(e) => Err(format!("Shared store error: {}", e)),
+            },
+            Connection::Postgres(db) => {
+                let row = db.query_opt("SELECT value FROM bugger_stores WHERE name = $1", &[&name]).map_err(|e| format!("Shared store error: {}", e))?;
+                Ok(row.map(|row| row.get(0)))
+            }
+        })
+    }
+
+    /// Replace what's saved under `name`; the last writer wins.
+    pub fn save(&self, name: &str, value: &str) -> Result<(), String> {
+        self.with(|db| {
+            let result = match db {
+                Connection::Sqlite(db) => db
+                    .execute(
+                        "INSERT INTO bugger_stores (name, value, updated_at) VALUES (?1, ?2, ?3) \
+                         ON CONFLICT(name) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+                        [name, value, &Utc::now().to_rfc3339()],
+                    )
+                    .map(|_| ())
+                    .map_err(|e| e.to_string()),
+                Connection::Postgres(db) => db
+                    .execute(
+                        "INSERT INTO bugger_stores (name, value, updated_at) VALUES ($1, $2, now()) \
+                         ON CONFLICT (name) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+                        &[&name, &value],
+                    )
+                    .map(|_| ())
+                    .map_err(|e| e.to_string()),
+            };
+            result.map_err(|e| format!("Shared store error: {}", e))
+        })
+    }
+
+    /// Read, change and write back what's saved under `name` while holding the store's write lock, so
+    /// a teammate's concurrent update isn't lost. `change` gets the current JSON and returns the new.
+    pub fn update(&self, name: &str, change: impl FnOnce(Option<String>) -> Result<String, String>) -> Result<(), String> {
+        let error = |e: String| format!("Shared store error: {}", e);
+        self.with(|db| match db {
+            Connection::Sqlite(db) => {
+                let tx = db.transaction_with_behavior(rusqlite::TransactionBehavior::Immediate).map_err(|e| error(e.to_string()))?;
+                let current = match tx.query_row("SELECT value FROM bugger_stores WHERE name = ?1", [name], |row| row.get(0)) {
+                    Ok(value) => Some(value),
+                    Err(rusqlite::Error::QueryReturnedNoRows) => None,
+                    Err(e) => return Err(error(e.to_string())),
+                };
+                let value = change(current)?;
+                tx.execute(
+                    "INSERT INTO bugger_stores (name, value, updated_at) VALUES (?1, ?2, ?3) \
+                     ON CONFLICT(name) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+                    [name, &value, &Utc::now().to_rfc3339()],
+                )
+                .map_err(|e| error(e.to_string()))?;
+                tx.commit().map_err(|e| error(e.to_string()))
+            }
+            Connection::Postgres(db) => {
+                let mut tx = db.transaction().map_err(|e| error(e.to_string()))?;
+                // Locks the name even before its first row exists, which `FOR UPDATE` can't.
+                tx.execute("SELECT pg_advisory_xact_lock(hashtext($1))", &[&name]).map_err(|e| error(e.to_string()))?;
+                let current = tx.query_opt("SELECT value FROM bugger_stores WHERE name = $1", &[&name]).map_err(|e| error(e.to_string()))?.map(|row| row.get(0));
+                let value = change(current)?;
+                tx.execute(
+                    "INSERT INTO bugger_stores (name, value, updated_at) VALUES ($1, $2, now()) \
+                     ON CONFLICT (name) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+                    &[&name, &value],
+                )
+                .map_err(|e| error(e.to_string()))?;
+                tx.commit().map_err(|e| error(e.to_string()))
+            }
+        })
+    }
+}
+
+enum Connection {
+    Sqlite(rusqlite::Connection),
+    Postgres(postgres::Client),
+}
+
+/// The connection in use, kept open between loads and saves rather than reconnecting (and, for
+/// Postgres, repeating the TLS handshake) every time.
+fn connection() -> &'static Mutex<Option<(SharedStore, Connection)>> {
+    static CONNECTION: OnceLock<Mutex<Option<(SharedStore, Connection)>>> = OnceLock::new();
+    CONNECTION.get_or_init(|| Mutex::new(None))
+}
+
+/// Whether store `name` is shared: one of BUGGER_SHARED_STORES, or of the defaults if that isn't set.
+fn is_shared(name: &str) -> bool {
+    let configured = crate::env_list("BUGGER_SHARED_STORES");
+    if configured.is_empty() {
+        DEFAULT_SHARED.contains(&name)
+    } else {
+        configured.iter().any(|s| s == name)
+    }
+}
+
+/// Where store `name` is shared, if a shared store is configured and includes it.
+pub fn for_store(name: &str) -> Option<SharedStore> {
+    SharedStore::from_env().filter(|_| is_shared(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store() -> (tempfile::TempDir, SharedStore) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SharedStore::Sqlite(dir.path().join("shared.sqlite"));
+        (dir, store)
+    }
+
+    #[test]
+    fn saved_values_are_loaded_back() {
+        let (_dir, store) = store();
+        assert_eq!(store.load("triage").unwrap(), None);
+        store.save("triage", "{\"a\":1}").unwrap();
+        store.save("triage", "{\"a\":2}").unwrap();
+        assert_eq!(store.load("triage").unwrap().as_deref(), Some("{\"a\":2}"));
+        assert_eq!(store.load("rules").unwrap(), None);
+    }
+
+    #[test]
+    fn update_sees_the_current_value() {
+        let (_dir, store) = store();
+        store.update("rotation", |current| Ok(format!("{}+1", current.unwrap_or_default()))).unwrap();
+        store.update("rotation", |current| Ok(format!("{}+1", current.unwrap_or_default()))).unwrap();
+        assert_eq!(store.load("rotation").unwrap().as_deref(), Some("+1+1"));
+    }
+
+    #[test]
+    fn a_failed_change_leaves_the_value_alone() {
+        let (_dir, store) = store();
+        store.save("rules", "kept").unwrap();
+        assert!(store.update("rules", |_| Err("no".to_string())).is_err());
+        assert_eq!(store.load("rules").unwrap().as_deref(), Some("kept"));
+    }
+
+    #[test]
+    fn concurrent_updates_are_not_lost() {
+        let (_dir, store) = store();
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                let store = &store;
+                scope.spawn(move || {
+                    for _ in 0..5 {
+                        store.update("triage", |current| Ok((current.map_or(0, |c| c.parse::<u32>().unwrap()) + 1).to_string())).unwrap();
+                    }
+                });
+            }
+        });
+        assert_eq!(store.load("triage").unwrap().as_deref(), Some("20"));
+    }
+
+    #[test]
+    fn an_unreachable_store_is_an_error() {
+        let (dir, _) = store();
+        let store = SharedStore::Sqlite(dir.path().join("missing").join("shared.sqlite"));
+        assert!(store.load("triage").is_err());
+    }
+}
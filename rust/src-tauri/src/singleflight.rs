@@ -0,0 +1,130 @@
+// Single-flight execution: concurrent callers of the same job share one run and its result
+use crate::error::BuggerError;
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+
+type Flight<T> = Arc<(Mutex<Option<Result<T, BuggerError>>>, Condvar)>;
+
+fn relock<G>(result: Result<G, PoisonError<G>>) -> G {
+    result.unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Coalesces overlapping calls: the first caller runs the job, callers arriving while it is in flight
+/// wait for and receive a clone of the same result. Calls after it finishes start a new run.
+pub struct SingleFlight<T> {
+    in_flight: Mutex<Option<Flight<T>>>,
+}
+
+impl<T> Default for SingleFlight<T> {
+    fn default() -> Self {
+        SingleFlight { in_flight: Mutex::new(None) }
+    }
+}
+
+impl<T: Clone> SingleFlight<T> {
+    pub fn run(&self, job: impl FnOnce() -> Result<T, BuggerError>) -> Result<T, BuggerError> {
+        let (flight, leader) = {
+            let mut slot = relock(self.in_flight.lock());
+            match slot.as_ref() {
+                Some(flight) => (flight.clone(), false),
+                None => {
+                    let flight: Flight<T> = Arc::new((Mutex::new(None), Condvar::new()));
+                    *slot = Some(flight.clone());
+                    (flight, true)
+                }
+            }
+        };
+        let (result, done) = &*flight;
+        if !leader {
+            let mut result = relock(result.lock());
+            while result.is_none() {
+                result = relock(done.wait(result));
+            }
+            return result.clone().unwrap_or_else(|| Err(BuggerError::Other("Refresh did not complete".to_string())));
+        }
+        let landing = Landing { single: self, flight: &flight };
+        let outcome = job();
+        *relock(result.lock()) = Some(outcome.clone());
+        drop(landing);
+        outcome
+    }
+}
+
+/// Ends a run: clears the in-flight slot and wakes the waiters, also when the job panics (they then
+/// get an error rather than waiting forever).
+struct Landing<'a, T> {
+    single: &'a SingleFlight<T>,
+    flight: &'a Flight<T>,
+}
+
+impl<T> Drop for Landing<'_, T> {
+    fn drop(&mut self) {
+        let (result, done) = &**self.flight;
+        relock(result.lock()).get_or_insert_with(|| Err(BuggerError::Other("Refresh failed unexpectedly".to_string())));
+        *relock(self.single.in_flight.lock()) = None;
+        done.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn waiters_are_released_when_the_job_panics() {
+        let single: Arc<SingleFlight<u32>> = Arc::new(SingleFlight::default());
+        let (started, running) = mpsc::channel();
+        let leader = {
+            let single = single.clone();
+            thread::spawn(move || {
+                single.run(|| {
+                    started.send(()).unwrap();
+                    thread::sleep(Duration::from_millis(200));
+                    panic!("job failed");
+                })
+            })
+        };
+        running.recv().unwrap();
+        let waiter = {
+            let single = single.clone();
+            thread::spawn(move || single.run(|| Ok(2)))
+        };
+        assert!(leader.join().is_err(), "the leader's panic propagates");
+        assert!(matches!(waiter.join().unwrap(), Err(BuggerError::Other(_))));
+        // The next call starts a new run.
+        assert_eq!(single.run(|| Ok(3)).unwrap(), 3);
+    }
+
+    #[test]
+    fn overlapping_callers_share_one_run() {
+        let single: Arc<SingleFlight<u32>> = Arc::new(SingleFlight::default());
+        let runs = Arc::new(Mutex::new(0));
+        let (started, running) = mpsc::channel();
+        let leader = {
+            let (single, runs) = (single.clone(), runs.clone());
+            thread::spawn(move || {
+                single.run(|| {
+                    *runs.lock().unwrap() += 1;
+                    started.send(()).unwrap();
+                    thread::sleep(Duration::from_millis(200));
+                    Ok(1)
+                })
+            })
+        };
+        running.recv().unwrap();
+        let waiter = {
+            let (single, runs) = (single.clone(), runs.clone());
+            thread::spawn(move || {
+                single.run(|| {
+                    *runs.lock().unwrap() += 1;
+                    Ok(2)
+                })
+            })
+        };
+        assert_eq!(leader.join().unwrap().unwrap(), 1);
+        assert_eq!(waiter.join().unwrap().unwrap(), 1);
+        assert_eq!(*runs.lock().unwrap(), 1);
+    }
+}
@@ -0,0 +1,174 @@
+// Stack trace detection: parse frames from descriptions and logs, derive a crash signature
+use crate::bug_analysis::strip_html;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+/// Frames that show how a crash was reported rather than where it happened; skipped for the signature.
+const REPORTING_FRAMES: [&str; 10] = [
+    "nt!KeBugCheckEx",
+    "nt!KeBugCheck2",
+    "nt!KiBugCheckDispatch",
+    "nt!KiPageFault",
+    "nt!KiSystemServiceCopyEnd",
+    "ntdll!KiUserExceptionDispatcher",
+    "ntdll!RtlDispatchException",
+    "KERNELBASE!RaiseException",
+    "KERNELBASE!RaiseFailFastException",
+    "ucrtbase!abort",
+];
+
+/// Fewer frames than this is more likely a stray `module!symbol` mention than a stack.
+const MIN_FRAMES: usize = 2;
+
+#[derive(Debug, Clone, Default)]
+pub struct StackTrace {
+    /// Frames innermost first, without addresses or offsets, e.g. "mydriver!ReadConfig".
+    pub frames: Vec<String>,
+    /// Modules that appear only as `module+0x1234`, i.e. without symbols loaded.
+    pub unsymbolicated: Vec<String>,
+}
+
+fn windbg_frame() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\b([A-Za-z_][\w.]*)!([\w:~<>$@?`]+)(?:\+0x[0-9a-fA-F]+)?").unwrap())
+}
+
+fn unsymbolicated_frame() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\b([A-Za-z_][\w]*(?:\.(?:dll|sys|exe))?)\+0x[0-9a-fA-F]+\b").unwrap())
+}
+
+/// .NET and Java: `at Namespace.Type.Method(...)`.
+fn managed_frame() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^\s*at\s+([\w.$<>`+]+)\s*\(").unwrap())
+}
+
+/// Python: `File "x.py", line 3, in func` (innermost frame last).
+fn python_frame() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r#"^\s*File "([^"]+)", line \d+, in (\S+)"#).unwrap())
+}
+
+/// Split rich text into plain lines, keeping the line structure stack traces depend on.
+pub fn text_lines(text: &str) -> Vec<String> {
+    let mut normalized = text.to_string();
+    for tag in ["<br>", "<br/>", "<br />", "</div>", "</p>", "</li>", "<BR>"] {
+        normalized = normalized.replace(tag, "\n");
+    }
+    normalized.lines().map(strip_html).filter(|l| !l.is_empty()).collect()
+}
+
+/// Find the first stack trace in `text` (HTML or plain).
+pub fn parse(text: &str) -> Option<StackTrace> {
+    let mut trace = StackTrace::default();
+    let mut python = Vec::new();
+    for line in text_lines(text) {
+        if let Some(c) = managed_frame().captures(&line) {
+            trace.frames.push(c[1].to_string());
+        } else if let Some(c) = python_frame().captures(&line) {
+            let file = c[1].rsplit(['/', '\\']).next().unwrap_or(&c[1]).to_string();
+            python.push(format!("{}:{}", file, &c[2]));
+        } else if let Some(c) = windbg_frame().captures(&line) {
+            trace.frames.push(format!("{}!{}", &c[1], &c[2]));
+        } else if let Some(c) = unsymbolicated_frame().captures(&line) {
+            trace.frames.push(c[1].to_string());
+            if !trace.unsymbolicated.contains(&c[1].to_string()) {
+                trace.unsymbolicated.push(c[1].to_string());
+            }
+        }
+    }
+    python.reverse();
+    trace.frames.extend(python);
+    (trace.frames.len() >= MIN_FRAMES).then_some(trace)
+}
+
+impl StackTrace {
+    /// Innermost frame that isn't crash-reporting machinery.
+    pub fn signature(&self) -> Option<&str> {
+        self.frames
+            .iter()
+            .find(|f| !REPORTING_FRAMES.iter().any(|r| r.eq_ignore_ascii_case(f)))
+            .or(self.frames.first())
+            .map(|f| f.as_str())
+    }
+
+    /// Hint shown when the top of the stack lacks symbols.
+    pub fn symbol_hint(&self) -> Option<String> {
+        let top = self.signature()?;
+        self.unsymbolicated
+            .iter()
+            .any(|m| m == top)
+            .then(|| format!("No symbols for {} — load matching PDBs to get a function name", top))
+    }
+}
+
+fn exception_code_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\b0[xX][0-9a-fA-F]{8}\b").unwrap())
+}
+
+/// First 32-bit exception/bugcheck code in `text` (e.g. "0xC0000005"), used as the signature when
+/// there is no stack trace. Zero is skipped since it shows up in unrelated contexts.
+pub fn exception_code(text: &str) -> Option<String> {
+    exception_code_re()
+        .find_iter(text)
+        .map(|m| format!("0x{}", m.as_str()[2..].to_uppercase()))
+        .find(|code| code != "0x00000000")
+}
+
+/// Group bug ids by crash signature, sorted by signature; ids keep input order.
+pub fn group_signatures<'a>(signatures: impl IntoIterator<Item = (u64, &'a str)>) -> BTreeMap<String, Vec<u64>> {
+    let mut groups: BTreeMap<String, Vec<u64>> = BTreeMap::new();
+    for (id, signature) in signatures {
+        groups.entry(signature.to_string()).or_default().push(id);
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windbg_signatures_skip_bugcheck_frames() {
+        let trace = parse("<div>nt!KeBugCheckEx</div><div>mydriver!ReadConfig+0x4a</div><div>nt!IopfCallDriver+0x55</div>").unwrap();
+        assert_eq!(trace.frames, ["nt!KeBugCheckEx", "mydriver!ReadConfig", "nt!IopfCallDriver"]);
+        assert_eq!(trace.signature(), Some("mydriver!ReadConfig"));
+        assert_eq!(trace.symbol_hint(), None);
+    }
+
+    #[test]
+    fn managed_and_python_frames_are_parsed_innermost_first() {
+        let managed = parse("Unhandled exception\n   at Contoso.Io.Reader.Open(String path)\n   at Contoso.App.Main(String[] args)").unwrap();
+        assert_eq!(managed.signature(), Some("Contoso.Io.Reader.Open"));
+        let python = parse("Traceback (most recent call last):\n  File \"/app/main.py\", line 3, in main\n  File \"C:\\app\\io.py\", line 9, in read\nKeyError").unwrap();
+        assert_eq!(python.frames, ["io.py:read", "main.py:main"]);
+    }
+
+    #[test]
+    fn frames_without_symbols_get_a_hint() {
+        let trace = parse("mydriver.sys+0x1a2b\nnt!IofCallDriver+0x55").unwrap();
+        assert_eq!(trace.unsymbolicated, ["mydriver.sys"]);
+        assert_eq!(trace.symbol_hint().as_deref(), Some("No symbols for mydriver.sys — load matching PDBs to get a function name"));
+    }
+
+    #[test]
+    fn a_single_mention_is_not_a_stack() {
+        assert!(parse("Fixed in mydriver!ReadConfig last week").is_none());
+        assert!(parse("").is_none());
+    }
+
+    #[test]
+    fn exception_codes_are_normalized_and_zero_is_skipped() {
+        assert_eq!(exception_code("status 0x00000000 then 0xc0000005").as_deref(), Some("0xC0000005"));
+        assert_eq!(exception_code("no code, just 0x1234"), None);
+    }
+
+    #[test]
+    fn bugs_are_grouped_by_signature_in_input_order() {
+        let groups = group_signatures([(3, "b!Two"), (1, "a!One"), (2, "b!Two")]);
+        assert_eq!(groups.into_iter().collect::<Vec<_>>(), [("a!One".to_string(), vec![1]), ("b!Two".to_string(), vec![3, 2])]);
+    }
+}
@@ -0,0 +1,112 @@
+// Small JSON-file persistence for local app state (triage decisions etc.); stores shared with the team
+// live in the shared store instead (see `shared_store`), with a local copy for when it can't be reached
+use crate::shared_store;
+use log::warn;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::Write;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+/// Path of a named JSON store inside the app data directory, creating the directory if needed.
+pub fn store_path(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| format!("App data dir error: {}", e))?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    Ok(dir.join(format!("{}.json", name)))
+}
+
+/// Load a named store, returning the default value if it doesn't exist yet. Until a shared store has
+/// an entry the local file is read, so the first save moves it there; the local copy is also used
+/// while the shared store can't be reached.
+pub fn load_json<T: DeserializeOwned + Default>(app: &AppHandle, name: &str) -> Result<T, String> {
+    if let Some(shared) = shared_store::for_store(name) {
+        match shared.load(name) {
+            Ok(Some(text)) => {
+                let value = serde_json::from_str(&text).map_err(|e| format!("Corrupt shared store {}: {}", name, e))?;
+                save_local(app, name, &text);
+                return Ok(value);
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Using the local copy of {}: {}", name, e),
+        }
+    }
+    load_local(app, name)
+}
+
+/// Keep the local copy of a shared store current; failing that only costs the offline fallback.
+fn save_local(app: &AppHandle, name: &str, text: &str) {
+    let written = store_path(app, name).and_then(|path| std::fs::write(&path, text).map_err(|e| format!("Failed to write {}: {}", path.display(), e)));
+    if let Err(e) = written {
+        warn!("Failed to keep a local copy of {}: {}", name, e);
+    }
+}
+
+fn load_local<T: DeserializeOwned + Default>(app: &AppHandle, name: &str) -> Result<T, String> {
+    let path = store_path(app, name)?;
+    if !path.exists() {
+        return Ok(T::default());
+    }
+    let text = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&text).map_err(|e| format!("Corrupt store {}: {}", path.display(), e))
+}
+
+/// Persist a named store, replacing its previous contents.
+pub fn save_json<T: Serialize>(app: &AppHandle, name: &str, value: &T) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(value).map_err(|e| format!("JSON serialize error: {}", e))?;
+    if let Some(shared) = shared_store::for_store(name) {
+        shared.save(name, &text)?;
+        save_local(app, name, &text);
+        return Ok(());
+    }
+    let path = store_path(app, name)?;
+    std::fs::write(&path, text).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Load, change and persist a named store in one step, returning the new value. A shared store stays
+/// locked in between, so teammates changing other entries at the same time don't overwrite each other.
+pub fn update_json<T: Serialize + DeserializeOwned + Default>(app: &AppHandle, name: &str, change: impl FnOnce(&mut T)) -> Result<T, String> {
+    let Some(shared) = shared_store::for_store(name) else {
+        let mut value = load_json(app, name)?;
+        change(&mut value);
+        save_json(app, name, &value)?;
+        return Ok(value);
+    };
+    let mut updated = None;
+    let mut saved = String::new();
+    shared.update(name, |text| {
+        let mut value: T = match text {
+            Some(text) => serde_json::from_str(&text).map_err(|e| format!("Corrupt shared store {}: {}", name, e))?,
+            None => load_local(app, name)?,
+        };
+        change(&mut value);
+        let text = serde_json::to_string_pretty(&value).map_err(|e| format!("JSON serialize error: {}", e))?;
+        updated = Some(value);
+        saved = text.clone();
+        Ok(text)
+    })?;
+    save_local(app, name, &saved);
+    updated.ok_or_else(|| format!("Shared store {} wasn't updated", name))
+}
+
+/// Append one record to a named JSON-lines log (`<name>.jsonl`) in the app data directory.
+pub fn append_jsonl<T: Serialize>(app: &AppHandle, name: &str, value: &T) -> Result<(), String> {
+    let path = store_path(app, name)?.with_extension("jsonl");
+    let line = serde_json::to_string(value).map_err(|e| format!("JSON serialize error: {}", e))?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+
+/// Read every record of a named JSON-lines log, skipping lines that fail to parse.
+pub fn load_jsonl<T: DeserializeOwned>(app: &AppHandle, name: &str) -> Result<Vec<T>, String> {
+    let path = store_path(app, name)?.with_extension("jsonl");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let text = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(text.lines().filter_map(|l| serde_json::from_str(l).ok()).collect())
+}
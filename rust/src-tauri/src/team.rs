@@ -0,0 +1,130 @@
+// Team load-balance metrics (opt-in): open bugs, average age and resolution throughput per person
+use crate::azure_devops::{AzureDevOpsClient, Bug, Identity};
+use crate::wiql::WiqlQuery;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Window for resolution throughput.
+pub const THROUGHPUT_DAYS: u32 = 28;
+/// Markers around the team section in the report, so exports can leave it out.
+const SECTION_START: &str = "<!--team-metrics-->";
+const SECTION_END: &str = "<!--/team-metrics-->";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PersonLoad {
+    pub name: String,
+    pub open: usize,
+    pub average_age_days: i64,
+    /// Bugs resolved in the last THROUGHPUT_DAYS days.
+    pub resolved: usize,
+}
+
+/// Who resolved the team's bugs in the last THROUGHPUT_DAYS days.
+pub fn fetch_recent_resolvers(client: &AzureDevOpsClient) -> Result<Vec<Identity>, String> {
+    let wiql = WiqlQuery::new()
+        .work_item_type("Bug")
+        .area_under_any(&client.config.query_filters.team_area_paths)
+        .resolved_within_days(THROUGHPUT_DAYS)
+        .build();
+    let ids = client.run_wiql(&wiql)?;
+    Ok(client.fetch_resolvers(&ids)?.into_iter().map(|(_, who)| who).collect())
+}
+
+/// Per-person load, most open bugs first. Unassigned bugs are counted under "(Unassigned)".
+pub fn team_load(bugs: &[Bug], resolvers: &[Identity], now: DateTime<Utc>) -> Vec<PersonLoad> {
+    // Keyed by unique name so display name changes don't split a person.
+    let mut people: HashMap<String, (String, Vec<i64>, usize)> = HashMap::new();
+    for bug in bugs {
+        let (key, name) = match &bug.assigned_to {
+            Some(a) => (a.unique_name.to_lowercase(), a.display_name.clone()),
+            None => (String::new(), "(Unassigned)".to_string()),
+        };
+        let age = bug
+            .created_date
+            .as_deref()
+            .and_then(|d| DateTime::parse_from_rfc3339(d).ok())
+            .map(|d| (now - d.with_timezone(&Utc)).num_days().max(0))
+            .unwrap_or(0);
+        people.entry(key).or_insert_with(|| (name, Vec::new(), 0)).1.push(age);
+    }
+    for who in resolvers {
+        people.entry(who.unique_name.to_lowercase()).or_insert_with(|| (who.display_name.clone(), Vec::new(), 0)).2 += 1;
+    }
+    let mut load: Vec<PersonLoad> = people
+        .into_values()
+        .map(|(name, ages, resolved)| PersonLoad {
+            name,
+            open: ages.len(),
+            average_age_days: if ages.is_empty() { 0 } else { ages.iter().sum::<i64>() / ages.len() as i64 },
+            resolved,
+        })
+        .collect();
+    load.sort_by(|a, b| b.open.cmp(&a.open).then(a.name.cmp(&b.name)));
+    load
+}
+
+/// Report section for the team's load; wrapped in markers so `strip_section` can remove it.
+pub fn section_html(load: &[PersonLoad]) -> String {
+    let mut html = format!(
+        "{}<h2>👥 Team Load</h2><table class='team-load'><tr><th>Person</th><th>Open</th><th>Avg. age (days)</th><th>Resolved ({}d)</th></tr>",
+        SECTION_START, THROUGHPUT_DAYS
+    );
+    for person in load {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            html_escape::encode_text(&person.name),
+            person.open,
+            person.average_age_days,
+            person.resolved
+        ));
+    }
+    html.push_str("</table>");
+    html.push_str(SECTION_END);
+    html
+}
+
+/// Remove the team section from report HTML (exports leave it out unless BUGGER_EXPORT_TEAM_METRICS is set).
+pub fn strip_section(html: &str) -> String {
+    match (html.find(SECTION_START), html.find(SECTION_END)) {
+        (Some(start), Some(end)) if end > start => format!("{}{}", &html[..start], &html[end + SECTION_END.len()..]),
+        _ => html.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn person(name: &str, unique_name: &str) -> Identity {
+        Identity { display_name: name.to_string(), unique_name: unique_name.to_string(), avatar_url: None }
+    }
+
+    fn assigned(id: u64, who: Option<Identity>, age_days: i64, now: DateTime<Utc>) -> Bug {
+        let mut bug = Bug::sample(id, "Bug");
+        bug.assigned_to = who;
+        bug.created_date = Some((now - Duration::days(age_days)).to_rfc3339());
+        bug
+    }
+
+    #[test]
+    fn load_is_counted_per_person_and_sorted_by_open_bugs() {
+        let now = Utc::now();
+        let ana = || Some(person("Ana", "ana@contoso.com"));
+        let bugs = [assigned(1, ana(), 10, now), assigned(2, Some(person("Ana B.", "ANA@contoso.com")), 20, now), assigned(3, None, 4, now)];
+        let resolvers = [person("Ana", "ana@contoso.com"), person("Bo", "bo@contoso.com"), person("Bo", "bo@contoso.com")];
+        let load = team_load(&bugs, &resolvers, now);
+        let rows: Vec<_> = load.iter().map(|p| (p.name.as_str(), p.open, p.average_age_days, p.resolved)).collect();
+        assert_eq!(rows, [("Ana", 2, 15, 1), ("(Unassigned)", 1, 4, 0), ("Bo", 0, 0, 2)]);
+    }
+
+    #[test]
+    fn the_section_can_be_stripped_from_the_report() {
+        let section = section_html(&[PersonLoad { name: "<Ana>".to_string(), open: 1, average_age_days: 3, resolved: 0 }]);
+        assert!(section.contains("<td>&lt;Ana&gt;</td>"));
+        let report = format!("<h1>Report</h1>{}<p>Rest</p>", section);
+        assert_eq!(strip_section(&report), "<h1>Report</h1><p>Rest</p>");
+        assert_eq!(strip_section("<p>No team</p>"), "<p>No team</p>");
+    }
+}
@@ -0,0 +1,115 @@
+// Bug template compliance: required description sections from the team's work item template (or config)
+use crate::azure_devops::{AzureDevOpsClient, Bug};
+use crate::bug_analysis::strip_html;
+use crate::env_list;
+use crate::stacktrace::text_lines;
+use log::warn;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::env;
+use std::sync::OnceLock;
+
+/// Longest line still taken as a section heading.
+const MAX_HEADING_CHARS: usize = 60;
+
+/// Where the required sections come from: BUGGER_TEMPLATE_SECTIONS (e.g. `Repro Steps,Expected,Actual`)
+/// if set, else the team's Bug template (BUGGER_TEMPLATE_TEAM, default "<project> Team";
+/// BUGGER_TEMPLATE_NAME picks one by name, default the first).
+#[derive(Debug, Clone)]
+pub struct TemplateConfig {
+    pub sections: Vec<String>,
+    pub team: Option<String>,
+    pub name: Option<String>,
+}
+
+impl TemplateConfig {
+    pub fn from_env() -> Self {
+        let non_empty = |name: &str| env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        TemplateConfig {
+            sections: env_list("BUGGER_TEMPLATE_SECTIONS"),
+            team: non_empty("BUGGER_TEMPLATE_TEAM"),
+            name: non_empty("BUGGER_TEMPLATE_NAME"),
+        }
+    }
+
+    /// The required sections: configured, else parsed from the team's template. Empty when neither
+    /// is available, which turns the check off.
+    pub fn load(&self, client: &AzureDevOpsClient) -> Vec<String> {
+        if !self.sections.is_empty() {
+            return self.sections.clone();
+        }
+        let team = self.team.clone().unwrap_or_else(|| format!("{} Team", client.config.project));
+        match client.fetch_bug_template(&team, self.name.as_deref()) {
+            Ok(Some(html)) => template_sections(&html),
+            Ok(None) => Vec::new(),
+            Err(e) => {
+                warn!("Bug template unavailable: {}", e);
+                Vec::new()
+            }
+        }
+    }
+}
+
+fn heading_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?is)<(h[1-6]|b|strong)\b[^>]*>(.*?)</(?:h[1-6]|b|strong)>").unwrap())
+}
+
+/// Section headings in a template: HTML headings, bold text and short lines ending in a colon.
+pub fn template_sections(html: &str) -> Vec<String> {
+    let mut sections: Vec<String> = Vec::new();
+    let mut add = |text: &str| {
+        let heading = text.trim().trim_end_matches(':').trim().to_string();
+        if !heading.is_empty() && heading.chars().count() <= MAX_HEADING_CHARS && !sections.iter().any(|s| s.eq_ignore_ascii_case(&heading)) {
+            sections.push(heading);
+        }
+    };
+    for c in heading_re().captures_iter(html) {
+        add(&strip_html(&c[2]));
+    }
+    for line in text_lines(html).iter().filter(|l| l.trim_end().ends_with(':')) {
+        add(line);
+    }
+    sections
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TemplateCompliance {
+    pub missing: Vec<String>,
+    pub total: usize,
+}
+
+impl TemplateCompliance {
+    pub fn percent(&self) -> u32 {
+        if self.total == 0 {
+            return 100;
+        }
+        ((self.total - self.missing.len()) * 100 / self.total) as u32
+    }
+}
+
+/// Which of `sections` a bug's description fills in: the heading must be there with some text
+/// before the next heading (an untouched template doesn't count).
+pub fn check(bug: &Bug, sections: &[String]) -> TemplateCompliance {
+    let text = text_lines(bug.description.as_deref().unwrap_or("")).join("\n").to_lowercase();
+    let mut found: Vec<(usize, usize, &String)> = sections
+        .iter()
+        .filter_map(|s| {
+            let heading = s.to_lowercase();
+            text.find(&heading).map(|start| (start, start + heading.len(), s))
+        })
+        .collect();
+    found.sort_by_key(|f| f.0);
+    let mut filled: Vec<&String> = Vec::new();
+    for (i, (_, end, section)) in found.iter().enumerate() {
+        let next = found.get(i + 1).map_or(text.len(), |f| f.0).max(*end);
+        if !text[*end..next].trim_matches(|c: char| c.is_whitespace() || c == ':' || c == '-').is_empty() {
+            filled.push(section);
+        }
+    }
+    TemplateCompliance {
+        missing: sections.iter().filter(|s| !filled.contains(s)).cloned().collect(),
+        total: sections.len(),
+    }
+}
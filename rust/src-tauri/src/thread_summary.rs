@@ -0,0 +1,273 @@
+// Summaries of long comment threads: the gist, the open question and who owes a response, so a
+// bug with forty comments doesn't need reading top to bottom
+use crate::azure_devops::{Bug, Comment};
+use crate::bug_analysis::strip_html;
+use crate::waiting::is_me;
+use crate::{openai, storage, AppConfig};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::AppHandle;
+
+/// Summaries per bug, so a thread is only summarized again when it gets new comments.
+const SUMMARIES_STORE: &str = "thread_summaries";
+/// Threads sent to the AI per report run; the rest get an extractive summary until the next run.
+const AI_PER_RUN: usize = 10;
+/// Sentences in an extractive summary.
+const KEY_SENTENCES: usize = 3;
+const SENTENCE_CHARS: usize = 200;
+/// Comment characters sent to the AI, from the end of the thread.
+const PROMPT_CHARS: usize = 12_000;
+const STOPWORDS: &[&str] = &[
+    "this", "that", "with", "have", "from", "they", "will", "would", "there", "their", "what", "when", "which", "been",
+    "were", "also", "just", "could", "should", "about", "into", "then", "than", "them", "some", "only", "still",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadSummary {
+    /// Comments in the thread when it was summarized.
+    pub comments: usize,
+    pub points: Vec<String>,
+    pub open_question: Option<String>,
+    /// Who the open question is waiting on ("You" when it's me).
+    pub owes_response: Option<String>,
+    pub ai: bool,
+}
+
+impl ThreadSummary {
+    pub fn html(&self) -> String {
+        let mut html = format!(
+            "<br><details class='thread-summary'><summary>💬 Discussion summary ({} comments{})</summary><ul>",
+            self.comments,
+            if self.ai { ", AI" } else { "" }
+        );
+        for point in &self.points {
+            html.push_str(&format!("<li>{}</li>", html_escape::encode_text(point)));
+        }
+        html.push_str("</ul>");
+        if let Some(question) = &self.open_question {
+            html.push_str(&format!("<div>❓ Open question: <i>{}</i></div>", html_escape::encode_text(question)));
+        }
+        if let Some(owes) = &self.owes_response {
+            html.push_str(&format!("<div>⏳ Waiting on: <b>{}</b></div>", html_escape::encode_text(owes)));
+        }
+        html.push_str("</details>");
+        html
+    }
+}
+
+fn sentences(text: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    let mut current = String::new();
+    for c in text.chars() {
+        current.push(c);
+        if matches!(c, '.' | '!' | '?' | '\n') {
+            let sentence = current.split_whitespace().collect::<Vec<_>>().join(" ");
+            if !sentence.is_empty() {
+                found.push(sentence);
+            }
+            current.clear();
+        }
+    }
+    let rest = current.split_whitespace().collect::<Vec<_>>().join(" ");
+    if !rest.is_empty() {
+        found.push(rest);
+    }
+    found
+}
+
+fn truncate(text: &str) -> String {
+    let mut short: String = text.chars().take(SENTENCE_CHARS).collect();
+    if text.chars().count() > SENTENCE_CHARS {
+        short.push('…');
+    }
+    short
+}
+
+fn content_words(sentence: &str) -> impl Iterator<Item = String> + '_ {
+    sentence
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() > 3)
+        .map(str::to_lowercase)
+        .filter(|w| !STOPWORDS.contains(&w.as_str()))
+}
+
+/// The sentences whose words come up most in the thread, in thread order.
+fn key_sentences(texts: &[String]) -> Vec<String> {
+    let all: Vec<String> = texts.iter().flat_map(|t| sentences(t)).filter(|s| s.chars().count() >= 20).collect();
+    let mut frequency: HashMap<String, usize> = HashMap::new();
+    for word in all.iter().flat_map(|s| content_words(s)) {
+        *frequency.entry(word).or_default() += 1;
+    }
+    let mut scored: Vec<(usize, f64)> = all
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let words: Vec<String> = content_words(s).collect();
+            let total: usize = words.iter().map(|w| frequency[w]).sum();
+            (i, total as f64 / (words.len().max(1) as f64).sqrt())
+        })
+        .collect();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    let mut picked: Vec<usize> = scored.into_iter().take(KEY_SENTENCES).map(|(i, _)| i).collect();
+    picked.sort_unstable();
+    picked.into_iter().map(|i| truncate(&all[i])).collect()
+}
+
+/// Sentence punctuation and the private-use characters standing in for it inside URLs.
+const URL_MASKS: [(char, char); 3] = [('?', '\u{E000}'), ('.', '\u{E001}'), ('!', '\u{E002}')];
+
+/// `text` with the punctuation inside URLs masked, so a query string isn't taken for a question
+/// or a dot in a host name for the end of a sentence.
+fn mask_urls(text: &str) -> String {
+    text.split(' ')
+        .map(|word| {
+            if !(word.contains("://") || word.starts_with("www.")) {
+                return word.to_string();
+            }
+            let url = word.trim_end_matches(['?', '.', '!', ',', ')']);
+            let masked: String = url.chars().map(|c| URL_MASKS.iter().find(|(p, _)| *p == c).map_or(c, |(_, m)| *m)).collect();
+            format!("{}{}", masked, &word[url.len()..])
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn unmask_urls(text: &str) -> String {
+    text.chars().map(|c| URL_MASKS.iter().find(|(_, m)| *m == c).map_or(c, |(p, _)| *p)).collect()
+}
+
+/// The last question nobody else has replied to since, and who it's waiting on.
+fn open_question(comments: &[Comment], texts: &[String], me: &[String]) -> (Option<String>, Option<String>) {
+    let texts: Vec<String> = texts.iter().map(|t| mask_urls(t)).collect();
+    let Some(asked) = texts.iter().rposition(|t| t.contains('?')) else {
+        return (None, None);
+    };
+    let asker = &comments[asked];
+    let answered = comments[asked + 1..].iter().any(|c| !c.author_email.eq_ignore_ascii_case(&asker.author_email));
+    if answered {
+        return (None, None);
+    }
+    let question = sentences(&texts[asked]).into_iter().rev().find(|s| s.contains('?')).map(|s| truncate(&unmask_urls(&s)));
+    let owes = if is_me(asker, me) {
+        comments[..asked].iter().rev().find(|c| !is_me(c, me)).map_or_else(|| "the reporter".to_string(), |c| c.author.clone())
+    } else {
+        "You".to_string()
+    };
+    (question, Some(owes))
+}
+
+fn extractive(comments: &[Comment], me: &[String]) -> ThreadSummary {
+    let texts: Vec<String> = comments.iter().map(|c| strip_html(&c.text)).collect();
+    let (open_question, owes_response) = open_question(comments, &texts, me);
+    ThreadSummary { comments: comments.len(), points: key_sentences(&texts), open_question, owes_response, ai: false }
+}
+
+/// Ask the AI for the gist; anything it leaves out falls back to the extractive summary.
+fn ask(config: &AppConfig, api_key: &str, bug: &Bug, comments: &[Comment], fallback: ThreadSummary) -> Result<ThreadSummary, String> {
+    let mut thread = String::new();
+    for c in comments.iter().rev() {
+        let entry = format!("[{}] {}: {}\n", c.created_date.as_deref().unwrap_or(""), c.author, strip_html(&c.text).trim());
+        if thread.len() + entry.len() > PROMPT_CHARS {
+            break;
+        }
+        thread.insert_str(0, &entry);
+    }
+    let prompt = format!(
+        "Bug: {}\nI am {}.\n\nComment thread, oldest first:\n{}\nSummarize the discussion. Reply in exactly this form:\n- <key point>\n- <key point>\n- <key point>\nOpen question: <the question still unanswered, or none>\nWaiting on: <who owes a response; \"You\" if it's me, or none>",
+        bug.title, config.user_email, thread
+    );
+    let reply = openai::chat_completion(api_key, &config.openai_model, "You summarize software bug discussions for the engineer who owns the bug.", &prompt, 300)?;
+    let field = |label: &str| {
+        reply
+            .lines()
+            .find_map(|l| l.trim().strip_prefix(label))
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty() && !v.eq_ignore_ascii_case("none"))
+    };
+    let points: Vec<String> = reply.lines().filter_map(|l| l.trim().strip_prefix("- ")).map(|p| truncate(p.trim())).collect();
+    if points.is_empty() {
+        return Err(format!("Unexpected summary format: {}", reply));
+    }
+    Ok(ThreadSummary {
+        comments: comments.len(),
+        points,
+        open_question: field("Open question:").or(fallback.open_question),
+        owes_response: field("Waiting on:").or(fallback.owes_response),
+        ai: true,
+    })
+}
+
+/// Summaries of threads with at least `threshold` comments, by AI when an OpenAI key is set and
+/// extractive otherwise. Summaries are cached until the thread gets new comments. `prune` drops cached
+/// summaries of bugs not in `bugs`, so pass it only when they're every active bug (my own report).
+pub fn summarize(app: &AppHandle, config: &AppConfig, bugs: &[Bug], threads: &HashMap<u64, Vec<Comment>>, threshold: u64, prune: bool) -> HashMap<u64, ThreadSummary> {
+    let mut cache: HashMap<u64, ThreadSummary> = storage::load_json(app, SUMMARIES_STORE).unwrap_or_else(|e| {
+        warn!("Ignoring cached thread summaries: {}", e);
+        HashMap::new()
+    });
+    let me = config.my_identities();
+    let mut ai_budget = if config.ai_enabled { AI_PER_RUN } else { 0 };
+    let mut summaries = HashMap::new();
+    for bug in bugs {
+        let Some(comments) = threads.get(&bug.id).filter(|c| c.len() as u64 >= threshold) else {
+            continue;
+        };
+        if let Some(cached) = cache.get(&bug.id).filter(|s| s.comments == comments.len() && (s.ai || ai_budget == 0)) {
+            summaries.insert(bug.id, cached.clone());
+            continue;
+        }
+        let mut summary = extractive(comments, &me);
+        if let Some(api_key) = config.openai_api_key.as_deref().filter(|_| ai_budget > 0) {
+            ai_budget -= 1;
+            match ask(config, api_key, bug, comments, summary.clone()) {
+                Ok(ai) => summary = ai,
+                Err(e) => warn!("AI summary of #{} failed: {}", bug.id, e),
+            }
+        }
+        summaries.insert(bug.id, summary);
+    }
+    // Only bugs still in the report stay cached, when `bugs` are all of them.
+    if prune {
+        cache.retain(|id, _| bugs.iter().any(|b| b.id == *id));
+    }
+    cache.extend(summaries.iter().map(|(id, s)| (*id, s.clone())));
+    if let Err(e) = storage::save_json(app, SUMMARIES_STORE, &cache) {
+        warn!("Failed to save thread summaries: {}", e);
+    }
+    summaries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(author: &str, text: &str) -> Comment {
+        Comment { text: text.to_string(), author: author.to_string(), author_email: format!("{}@contoso.com", author.to_lowercase()), created_date: None }
+    }
+
+    fn open(comments: &[Comment]) -> (Option<String>, Option<String>) {
+        let texts: Vec<String> = comments.iter().map(|c| c.text.clone()).collect();
+        open_question(comments, &texts, &["me@contoso.com".to_string()])
+    }
+
+    #[test]
+    fn an_unanswered_question_waits_on_me() {
+        let comments = [comment("Me", "Looking into it."), comment("Jane", "It crashes again. Which build fixes it?")];
+        assert_eq!(open(&comments), (Some("Which build fixes it?".to_string()), Some("You".to_string())));
+    }
+
+    #[test]
+    fn an_answered_question_is_not_open() {
+        let comments = [comment("Jane", "Which build fixes it?"), comment("Me", "Build 42.")];
+        assert_eq!(open(&comments), (None, None));
+    }
+
+    #[test]
+    fn question_marks_in_urls_are_not_questions() {
+        let comments = [comment("Jane", "Can you look?"), comment("Me", "Logs are at https://logs.contoso.com/view?id=7 now.")];
+        assert_eq!(open(&comments), (None, None));
+        let comments = [comment("Me", "Did you see https://contoso.com/a?b=1?")];
+        assert_eq!(open(&comments).0.as_deref(), Some("Did you see https://contoso.com/a?b=1?"));
+    }
+}
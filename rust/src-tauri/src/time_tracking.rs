@@ -0,0 +1,197 @@
+// Per-bug time tracking: local start/stop timers with daily totals, optionally written back to the
+// Completed Work and Remaining Work fields
+use crate::audit::{self, update_fields_reversible, WriteAction};
+use crate::azure_devops::AzureDevOpsClient;
+use crate::error::BuggerError;
+use crate::{storage, AppConfig};
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::AppHandle;
+
+const TIME_STORE: &str = "time_tracking";
+const COMPLETED_WORK: &str = "Microsoft.VSTS.Scheduling.CompletedWork";
+const REMAINING_WORK: &str = "Microsoft.VSTS.Scheduling.RemainingWork";
+/// Entries older than this are dropped.
+const HISTORY_DAYS: i64 = 365;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RunningTimer {
+    pub bug_id: u64,
+    pub started: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TimeEntry {
+    pub bug_id: u64,
+    pub started: DateTime<Utc>,
+    pub stopped: DateTime<Utc>,
+    /// Whether the hours were added to the work item's Completed Work.
+    #[serde(default)]
+    pub written_back: bool,
+    /// Why adding the hours failed, until `retry_time_write_back` succeeds.
+    #[serde(default)]
+    pub write_back_error: Option<String>,
+}
+
+impl TimeEntry {
+    pub fn hours(&self) -> f64 {
+        (self.stopped - self.started).num_seconds().max(0) as f64 / 3600.0
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TimeStore {
+    /// At most one timer runs at a time.
+    running: Option<RunningTimer>,
+    entries: Vec<TimeEntry>,
+}
+
+/// Hours spent on one bug on one (local) day.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DailyTotal {
+    pub date: NaiveDate,
+    pub bug_id: u64,
+    pub hours: f64,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TimeReport {
+    pub running: Option<RunningTimer>,
+    /// Newest day first, then by bug id.
+    pub totals: Vec<DailyTotal>,
+    /// Entries whose hours couldn't be added to Completed Work, oldest first.
+    pub failed_write_backs: Vec<TimeEntry>,
+}
+
+/// Sum entries per local start day and bug, for days on or after `since`.
+fn daily_totals(entries: &[TimeEntry], since: NaiveDate) -> Vec<DailyTotal> {
+    let mut totals: BTreeMap<(NaiveDate, u64), f64> = BTreeMap::new();
+    for entry in entries {
+        let date = entry.started.with_timezone(&Local).date_naive();
+        if date >= since {
+            *totals.entry((date, entry.bug_id)).or_default() += entry.hours();
+        }
+    }
+    let mut totals: Vec<DailyTotal> = totals.into_iter().map(|((date, bug_id), hours)| DailyTotal { date, bug_id, hours }).collect();
+    totals.sort_by(|a, b| b.date.cmp(&a.date).then(a.bug_id.cmp(&b.bug_id)));
+    totals
+}
+
+/// Add `hours` to Completed Work and take them off Remaining Work (not below zero, and only if it's set).
+fn log_to_work_item(app: &AppHandle, entry: &TimeEntry) -> Result<(), BuggerError> {
+    let config = AppConfig::from_env()?;
+    let actor = config.user_email.clone();
+    let client = AzureDevOpsClient::new(config);
+    let hours = entry.hours();
+    let current = client.fetch_fields(entry.bug_id, &[COMPLETED_WORK, REMAINING_WORK])?;
+    let value = |name: &str| current.iter().find(|(n, _)| n == name).and_then(|(_, v)| v.as_deref()?.parse::<f64>().ok());
+    let completed = format!("{:.2}", value(COMPLETED_WORK).unwrap_or(0.0) + hours);
+    let remaining = value(REMAINING_WORK).map(|r| format!("{:.2}", (r - hours).max(0.0)));
+    let mut updates = vec![(COMPLETED_WORK, completed.as_str())];
+    if let Some(remaining) = &remaining {
+        updates.push((REMAINING_WORK, remaining.as_str()));
+    }
+    audit::perform(app, &actor, entry.bug_id, WriteAction::LogTime, format!("{:.2} h", hours), false, || update_fields_reversible(&client, entry.bug_id, &updates))
+}
+
+/// Stop the running timer, if any, and store its entry.
+fn stop_running(store: &mut TimeStore, now: DateTime<Utc>) -> Option<TimeEntry> {
+    let timer = store.running.take()?;
+    let entry = TimeEntry { bug_id: timer.bug_id, started: timer.started, stopped: now, written_back: false, write_back_error: None };
+    store.entries.retain(|e| e.stopped > now - Duration::days(HISTORY_DAYS));
+    store.entries.push(entry.clone());
+    Some(entry)
+}
+
+#[tauri::command]
+/// Starts timing work on a bug, stopping the timer running on another bug first.
+pub fn start_timer(app: AppHandle, id: u64) -> Result<RunningTimer, String> {
+    let mut store: TimeStore = storage::load_json(&app, TIME_STORE)?;
+    if let Some(running) = store.running.as_ref().filter(|r| r.bug_id == id) {
+        return Ok(running.clone());
+    }
+    let now = Utc::now();
+    stop_running(&mut store, now);
+    let timer = RunningTimer { bug_id: id, started: now };
+    store.running = Some(timer.clone());
+    storage::save_json(&app, TIME_STORE, &store)?;
+    Ok(timer)
+}
+
+#[tauri::command]
+/// Stops the running timer and returns its entry (null if none was running). With `write_back` the
+/// time is also added to the bug's Completed Work and taken off its Remaining Work.
+pub fn stop_timer(app: AppHandle, write_back: bool) -> Result<Option<TimeEntry>, BuggerError> {
+    let mut store: TimeStore = storage::load_json(&app, TIME_STORE)?;
+    let Some(mut entry) = stop_running(&mut store, Utc::now()) else {
+        return Ok(None);
+    };
+    // The local entry is kept even if the write-back fails, and can be written back later.
+    let written = if write_back { log_to_work_item(&app, &entry) } else { Ok(()) };
+    entry.written_back = write_back && written.is_ok();
+    entry.write_back_error = written.as_ref().err().map(|e| e.to_string());
+    if let Some(last) = store.entries.last_mut() {
+        *last = entry.clone();
+    }
+    storage::save_json(&app, TIME_STORE, &store)?;
+    written.map(|_| Some(entry)).map_err(|e| e.context("The time was saved but not added to Completed Work"))
+}
+
+#[tauri::command]
+/// Adds the hours of an entry whose write-back failed to the bug's Completed Work again.
+pub fn retry_time_write_back(app: AppHandle, bug_id: u64, started: DateTime<Utc>) -> Result<TimeEntry, BuggerError> {
+    let store: TimeStore = storage::load_json(&app, TIME_STORE)?;
+    let mut entry = store
+        .entries
+        .into_iter()
+        .find(|e| e.bug_id == bug_id && e.started == started && e.write_back_error.is_some())
+        .ok_or_else(|| format!("No failed time write-back for #{} started {}", bug_id, started))?;
+    let written = log_to_work_item(&app, &entry);
+    entry.written_back = written.is_ok();
+    entry.write_back_error = written.as_ref().err().map(|e| e.to_string());
+    storage::update_json(&app, TIME_STORE, |store: &mut TimeStore| {
+        if let Some(saved) = store.entries.iter_mut().find(|e| e.bug_id == bug_id && e.started == started) {
+            *saved = entry.clone();
+        }
+    })?;
+    written.map(|_| entry)
+}
+
+#[tauri::command]
+/// The running timer and time spent per bug per day over the last `days` days (default 7).
+pub fn get_time_report(app: AppHandle, days: Option<i64>) -> Result<TimeReport, String> {
+    let store: TimeStore = storage::load_json(&app, TIME_STORE)?;
+    let since = Local::now().date_naive() - Duration::days(days.unwrap_or(7).max(1) - 1);
+    let failed_write_backs = store.entries.iter().filter(|e| e.write_back_error.is_some()).cloned().collect();
+    Ok(TimeReport { running: store.running, totals: daily_totals(&store.entries, since), failed_write_backs })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stopping_records_an_entry_and_clears_the_timer() {
+        let started = Utc::now() - Duration::minutes(90);
+        let mut store = TimeStore { running: Some(RunningTimer { bug_id: 7, started }), entries: Vec::new() };
+        let entry = stop_running(&mut store, started + Duration::minutes(90)).unwrap();
+        assert_eq!(entry.hours(), 1.5);
+        assert!(!entry.written_back && entry.write_back_error.is_none());
+        assert!(store.running.is_none());
+        assert_eq!(store.entries.len(), 1);
+        assert!(stop_running(&mut store, Utc::now()).is_none());
+    }
+
+    #[test]
+    fn totals_are_summed_per_day_and_bug() {
+        let entry = |bug_id, started: DateTime<Utc>, minutes| TimeEntry { bug_id, started, stopped: started + Duration::minutes(minutes), written_back: false, write_back_error: None };
+        let now = Utc::now();
+        let today = now.with_timezone(&Local).date_naive();
+        let entries = [entry(1, now, 30), entry(1, now, 60), entry(2, now, 15), entry(1, now - Duration::days(10), 60)];
+        let totals = daily_totals(&entries, today - Duration::days(6));
+        assert_eq!(totals.iter().map(|t| (t.bug_id, t.hours)).collect::<Vec<_>>(), [(1, 1.5), (2, 0.25)]);
+        assert!(totals.iter().all(|t| t.date == today));
+    }
+}
@@ -0,0 +1,73 @@
+// Windows toasts with quick-triage buttons (open, snooze a day, mark needs-info) for bug
+// notifications; elsewhere, and for other notifications, the plain plugin notification
+use crate::notifications::QueuedNotification;
+use log::warn;
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+#[cfg(windows)]
+mod actionable {
+    use crate::error::BuggerError;
+    use crate::notifications::QueuedNotification;
+    use crate::triage::{record_triage_decision, TriageDecision};
+    use crate::{needs_info, work_session, AppState};
+    use log::warn;
+    use tauri::{AppHandle, Manager};
+    use tauri_winrt_notification::Toast;
+
+    const OPEN: &str = "open";
+    const SNOOZE: &str = "snooze";
+    const NEEDS_INFO: &str = "needs-info";
+
+    /// Run the command behind a toast button; clicking the toast itself brings up the app.
+    fn handle_action(app: &AppHandle, bug_id: u64, action: Option<&str>) -> Result<(), BuggerError> {
+        match action {
+            Some(OPEN) => Ok(work_session::open_bug(app.clone(), bug_id)?),
+            Some(SNOOZE) => record_triage_decision(app.clone(), app.state::<AppState>(), bug_id, TriageDecision::Snooze { days: 1 }).map(|_| ()),
+            Some(NEEDS_INFO) => needs_info::mark_needs_info(app.clone(), app.state::<AppState>(), bug_id),
+            _ => {
+                if let Some(window) = app.webview_windows().into_values().next() {
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub fn show(app: &AppHandle, n: &QueuedNotification, bug_id: u64) -> Result<(), String> {
+        // Like the notification plugin: only the installed app has a registered app id.
+        let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+        let from_build_dir = exe.parent().is_some_and(|dir| dir.ends_with("target/debug") || dir.ends_with("target/release"));
+        let app_id = if from_build_dir { Toast::POWERSHELL_APP_ID.to_string() } else { app.config().identifier.clone() };
+        let handle = app.clone();
+        Toast::new(&app_id)
+            .title(&n.title)
+            .text1(&n.body)
+            .add_button("Open bug", OPEN)
+            .add_button("Snooze 1 day", SNOOZE)
+            .add_button("Mark needs-info", NEEDS_INFO)
+            .on_activated(move |action| {
+                if let Err(e) = handle_action(&handle, bug_id, action.as_deref()) {
+                    warn!("Toast action {:?} on #{} failed: {}", action, bug_id, e);
+                }
+                Ok(())
+            })
+            .show()
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Show one notification, with triage buttons on Windows when it's about a bug.
+pub fn show(app: &AppHandle, n: &QueuedNotification) {
+    #[cfg(windows)]
+    if let Some(bug_id) = n.bug_id {
+        match actionable::show(app, n, bug_id) {
+            Ok(()) => return,
+            Err(e) => warn!("Falling back to a plain notification for #{}: {}", bug_id, e),
+        }
+    }
+    if let Err(e) = app.notification().builder().title(&n.title).body(&n.body).show() {
+        warn!("Failed to show notification '{}': {}", n.title, e);
+    }
+}
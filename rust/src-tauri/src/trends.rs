@@ -0,0 +1,177 @@
+// Trend analysis: bug counts per found-in build/version with spike detection, and backlog burn-down forecast
+use crate::azure_devops::Bug;
+use crate::storage;
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+/// Builds need at least this many bugs before they can be flagged as a spike.
+const MIN_SPIKE_COUNT: usize = 3;
+/// A build is a spike when it has this many times the median bugs per build.
+const SPIKE_FACTOR: usize = 2;
+/// Daily actionable backlog snapshots, one per day (the last analysis of the day wins).
+const BACKLOG_STORE: &str = "backlog_history";
+/// Snapshots older than this are dropped; the forecast uses the most recent weeks only.
+const HISTORY_DAYS: i64 = 180;
+const FORECAST_WEEKS: i64 = 8;
+/// Fewer complete weeks of history than this gives no forecast.
+const MIN_FORECAST_WEEKS: usize = 2;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildStats {
+    pub build: String,
+    pub bug_ids: Vec<u64>,
+    pub spike: bool,
+}
+
+/// Group bugs by the value of `build_field` (bugs without it are skipped), most bugs first,
+/// flagging builds with an abnormal number of bugs compared to the median build.
+pub fn group_by_build(bugs: &[Bug], build_field: &str) -> Vec<BuildStats> {
+    let mut stats: Vec<BuildStats> = Vec::new();
+    for bug in bugs {
+        let Some(build) = bug.custom_fields.get(build_field).map(|b| b.trim()).filter(|b| !b.is_empty()) else {
+            continue;
+        };
+        match stats.iter_mut().find(|s| s.build == build) {
+            Some(s) => s.bug_ids.push(bug.id),
+            None => stats.push(BuildStats { build: build.to_string(), bug_ids: vec![bug.id], spike: false }),
+        }
+    }
+    let mut counts: Vec<usize> = stats.iter().map(|s| s.bug_ids.len()).collect();
+    counts.sort_unstable();
+    let median = counts.get(counts.len() / 2).copied().unwrap_or(0);
+    for s in &mut stats {
+        s.spike = is_spike(s.bug_ids.len(), median, counts.len());
+    }
+    stats.sort_by(|a, b| b.bug_ids.len().cmp(&a.bug_ids.len()).then(a.build.cmp(&b.build)));
+    stats
+}
+
+fn is_spike(count: usize, median: usize, builds: usize) -> bool {
+    // With a single build there is no baseline to compare against.
+    builds > 1 && count >= MIN_SPIKE_COUNT && count >= median * SPIKE_FACTOR
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacklogSnapshot {
+    pub date: NaiveDate,
+    pub actionable: Vec<u64>,
+}
+
+/// Store today's actionable bug ids and return the history, oldest first.
+pub fn record_backlog(app: &AppHandle, today: NaiveDate, actionable: Vec<u64>) -> Result<Vec<BacklogSnapshot>, String> {
+    let mut history: Vec<BacklogSnapshot> = storage::load_json(app, BACKLOG_STORE)?;
+    history.retain(|s| s.date != today && s.date > today - Duration::days(HISTORY_DAYS));
+    history.push(BacklogSnapshot { date: today, actionable });
+    history.sort_by_key(|s| s.date);
+    storage::save_json(app, BACKLOG_STORE, &history)?;
+    Ok(history)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Forecast {
+    pub backlog: usize,
+    pub inflow_per_week: f64,
+    pub outflow_per_week: f64,
+    /// Weeks until the backlog reaches zero at the mean net burn rate; None if it isn't shrinking.
+    pub weeks: Option<f64>,
+    /// Band from the mean burn rate plus/minus one standard deviation of the weekly rates.
+    pub weeks_optimistic: Option<f64>,
+    pub weeks_pessimistic: Option<f64>,
+    pub history_weeks: usize,
+}
+
+fn weeks_to_zero(backlog: usize, burn_per_week: f64) -> Option<f64> {
+    if backlog == 0 {
+        return Some(0.0);
+    }
+    (burn_per_week > 0.0).then(|| backlog as f64 / burn_per_week)
+}
+
+/// Forecast from weekly inflow (ids appearing) and outflow (ids disappearing) between snapshots over
+/// the last FORECAST_WEEKS weeks. None until there are MIN_FORECAST_WEEKS complete weeks of history.
+pub fn forecast(history: &[BacklogSnapshot]) -> Option<Forecast> {
+    let latest = history.last()?;
+    let start = latest.date - Duration::weeks(FORECAST_WEEKS);
+    let recent: Vec<&BacklogSnapshot> = history.iter().filter(|s| s.date >= start).collect();
+    let first = recent.first()?;
+    let weeks = ((latest.date - first.date).num_days() / 7) as usize;
+    if weeks < MIN_FORECAST_WEEKS {
+        return None;
+    }
+    // (inflow, outflow) per complete week, counted back from the latest snapshot.
+    let mut flows = vec![(0usize, 0usize); weeks];
+    for pair in recent.windows(2) {
+        let (before, after) = (pair[0], pair[1]);
+        let week = ((latest.date - after.date).num_days() / 7) as usize;
+        let Some(flow) = flows.get_mut(week) else { continue };
+        flow.0 += after.actionable.iter().filter(|id| !before.actionable.contains(id)).count();
+        flow.1 += before.actionable.iter().filter(|id| !after.actionable.contains(id)).count();
+    }
+    let n = weeks as f64;
+    let burns: Vec<f64> = flows.iter().map(|(inflow, outflow)| *outflow as f64 - *inflow as f64).collect();
+    let mean = burns.iter().sum::<f64>() / n;
+    let deviation = (burns.iter().map(|b| (b - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+    let backlog = latest.actionable.len();
+    Some(Forecast {
+        backlog,
+        inflow_per_week: flows.iter().map(|f| f.0 as f64).sum::<f64>() / n,
+        outflow_per_week: flows.iter().map(|f| f.1 as f64).sum::<f64>() / n,
+        weeks: weeks_to_zero(backlog, mean),
+        weeks_optimistic: weeks_to_zero(backlog, mean + deviation),
+        weeks_pessimistic: weeks_to_zero(backlog, mean - deviation),
+        history_weeks: weeks,
+    })
+}
+
+impl Forecast {
+    /// One-line summary, e.g. "at the current rate, the actionable backlog reaches zero in ~9 weeks (6–15 weeks)".
+    pub fn summary(&self) -> String {
+        let format_weeks = |w: f64| format!("{:.0}", w.ceil());
+        match self.weeks {
+            Some(weeks) => {
+                let band = match (self.weeks_optimistic, self.weeks_pessimistic) {
+                    (Some(low), Some(high)) => format!(" ({}–{} weeks)", format_weeks(low), format_weeks(high)),
+                    (Some(low), None) => format!(" ({} weeks, or never if inflow picks up)", format_weeks(low)),
+                    _ => String::new(),
+                };
+                format!("At the current rate, the actionable backlog reaches zero in ~{} weeks{}", format_weeks(weeks), band)
+            }
+            None => "At the current rate, the actionable backlog never reaches zero — bugs arrive as fast as they are fixed".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Weekly snapshots starting 2026-01-05, one backlog (as an id range) per week.
+    fn weekly(backlogs: &[std::ops::RangeInclusive<u64>]) -> Vec<BacklogSnapshot> {
+        let start = NaiveDate::from_ymd_opt(2026, 1, 5).unwrap();
+        backlogs.iter().enumerate().map(|(week, ids)| BacklogSnapshot { date: start + Duration::weeks(week as i64), actionable: ids.clone().collect() }).collect()
+    }
+
+    #[test]
+    fn a_steady_burn_down_is_forecast() {
+        let forecast = forecast(&weekly(&[1..=10, 3..=10, 5..=10, 7..=10])).unwrap();
+        assert_eq!((forecast.backlog, forecast.history_weeks), (4, 3));
+        assert_eq!((forecast.inflow_per_week, forecast.outflow_per_week), (0.0, 2.0));
+        assert_eq!((forecast.weeks, forecast.weeks_optimistic, forecast.weeks_pessimistic), (Some(2.0), Some(2.0), Some(2.0)));
+        assert_eq!(forecast.summary(), "At the current rate, the actionable backlog reaches zero in ~2 weeks (2–2 weeks)");
+    }
+
+    #[test]
+    fn a_growing_backlog_never_reaches_zero() {
+        let forecast = forecast(&weekly(&[1..=4, 1..=6, 2..=9])).unwrap();
+        assert_eq!((forecast.inflow_per_week, forecast.outflow_per_week), (2.5, 0.5));
+        assert_eq!(forecast.weeks, None);
+        assert!(forecast.summary().contains("never reaches zero"));
+    }
+
+    #[test]
+    fn short_histories_give_no_forecast() {
+        assert!(forecast(&[]).is_none());
+        assert!(forecast(&weekly(&[1..=10, 3..=10])).is_none());
+    }
+}
@@ -0,0 +1,319 @@
+// Triage queue: score un-triaged bugs and record keep/needs-info/duplicate/snooze decisions. With a
+// shared store the last decision wins, but replacing a teammate's is reported back
+use crate::azure_devops::Bug;
+use crate::bug_analysis::{categorize_bug, is_questionable, BugCategory, QuestionableCategory};
+use crate::error::BuggerError;
+use crate::roles::Role;
+use crate::{storage, AppConfig, AppState};
+use chrono::{DateTime, Duration, Utc};
+use log::info;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::{AppHandle, State};
+
+const TRIAGE_STORE: &str = "triage";
+/// Weights saved from the tuning UI.
+const WEIGHTS_STORE: &str = "triage_weights";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TriageDecision {
+    Keep,
+    NeedsInfo,
+    Duplicate { of: Option<u64> },
+    Snooze { days: u32 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriageRecord {
+    pub decision: TriageDecision,
+    pub decided_at: DateTime<Utc>,
+    /// Who decided; missing on decisions recorded before the triage store was shared.
+    #[serde(default)]
+    pub decided_by: Option<String>,
+}
+
+impl TriageRecord {
+    /// Whether this decision still keeps the bug out of the queue at `now` (snoozes expire).
+    pub fn is_settled(&self, now: DateTime<Utc>) -> bool {
+        match self.decision {
+            TriageDecision::Snooze { days } => now < self.decided_at + Duration::days(days as i64),
+            _ => true,
+        }
+    }
+}
+
+/// Persisted triage decisions keyed by work item id.
+pub type TriageStore = HashMap<u64, TriageRecord>;
+
+/// A teammate's decision on the same bug that a new decision replaced.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TriageConflict {
+    pub bug_id: u64,
+    pub decided_by: String,
+    pub decision: TriageDecision,
+    pub decided_at: DateTime<Utc>,
+}
+
+/// What recording a decision returns: the next bug, and the teammate's decision it replaced, if any.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TriageOutcome {
+    pub next: Option<TriageItem>,
+    pub conflict: Option<TriageConflict>,
+}
+
+/// The bug at the head of the triage queue, as shown to the frontend.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct TriageItem {
+    pub bug: Bug,
+    pub category: BugCategory,
+    pub questionable: Option<QuestionableCategory>,
+    pub score: f64,
+    /// What each weight contributed to the score.
+    pub breakdown: ScoreBreakdown,
+    /// Un-triaged bugs left in the queue, including this one.
+    pub remaining: usize,
+}
+
+fn severity_weight(category: &BugCategory) -> f64 {
+    match category {
+        BugCategory::Crash | BugCategory::Security => 5.0,
+        BugCategory::Memory | BugCategory::Boot => 4.0,
+        BugCategory::Driver | BugCategory::FileSystem | BugCategory::Performance => 3.0,
+        BugCategory::Network => 2.0,
+        BugCategory::UI | BugCategory::Other => 1.0,
+    }
+}
+
+/// Triage score weights. Defaults match the original fixed formula.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct TriageWeights {
+    /// Points per level of category severity (Crash/Security 5 … UI/Other 1).
+    pub category: f64,
+    /// Points per day of age...
+    pub age_per_day: f64,
+    /// ...counting at most this many days.
+    pub age_cap_days: i64,
+    /// Points for a questionable bug, which usually needs a quick keep/needs-info call.
+    pub questionable: f64,
+}
+
+impl Default for TriageWeights {
+    fn default() -> Self {
+        TriageWeights { category: 10.0, age_per_day: 0.5, age_cap_days: 60, questionable: 15.0 }
+    }
+}
+
+/// A triage score split into what each weight contributed.
+#[derive(Debug, Clone, Copy, Serialize, JsonSchema)]
+pub struct ScoreBreakdown {
+    pub category: f64,
+    pub age: f64,
+    pub questionable: f64,
+    pub total: f64,
+}
+
+/// Days since the bug was created, or 0 if the date is missing or unparseable.
+pub fn bug_age_days(bug: &Bug, now: DateTime<Utc>) -> i64 {
+    bug.created_date
+        .as_deref()
+        .and_then(|d| DateTime::parse_from_rfc3339(d).ok())
+        .map(|d| (now - d.with_timezone(&Utc)).num_days().max(0))
+        .unwrap_or(0)
+}
+
+/// Triage priority: severe categories first, then older bugs, with questionable bugs pulled forward
+/// since they usually need a quick keep/needs-info call.
+pub fn score_breakdown(bug: &Bug, now: DateTime<Utc>, weights: &TriageWeights) -> ScoreBreakdown {
+    let category = severity_weight(&categorize_bug(bug)) * weights.category;
+    let age = bug_age_days(bug, now).min(weights.age_cap_days.max(0)) as f64 * weights.age_per_day;
+    let questionable = if is_questionable(bug).is_some() { weights.questionable } else { 0.0 };
+    ScoreBreakdown { category, age, questionable, total: category + age + questionable }
+}
+
+/// Bugs ordered by score, highest first.
+pub fn ranked<'a>(bugs: &'a [Bug], now: DateTime<Utc>, weights: &TriageWeights) -> Vec<(&'a Bug, ScoreBreakdown)> {
+    let mut scored: Vec<(&Bug, ScoreBreakdown)> = bugs.iter().map(|b| (b, score_breakdown(b, now, weights))).collect();
+    scored.sort_by(|a, b| b.1.total.total_cmp(&a.1.total).then(a.0.id.cmp(&b.0.id)));
+    scored
+}
+
+/// One bug's score and rank under the current and the proposed weights.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct WhatIf {
+    pub id: u64,
+    pub title: String,
+    pub current: ScoreBreakdown,
+    pub proposed: ScoreBreakdown,
+    /// 1-based positions in the queue.
+    pub current_rank: usize,
+    pub proposed_rank: usize,
+}
+
+/// Score every bug under `current` and `proposed` weights, in proposed order.
+pub fn what_if(bugs: &[Bug], now: DateTime<Utc>, current: &TriageWeights, proposed: &TriageWeights) -> Vec<WhatIf> {
+    let before = ranked(bugs, now, current);
+    ranked(bugs, now, proposed)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, (bug, proposed))| {
+            let (current_rank, (_, current)) = before.iter().enumerate().find(|(_, (b, _))| b.id == bug.id)?;
+            Some(WhatIf { id: bug.id, title: bug.title.clone(), current: *current, proposed, current_rank: current_rank + 1, proposed_rank: i + 1 })
+        })
+        .collect()
+}
+
+/// Record `decision` on bug `id` by `actor`. A still-settled, different decision by someone else means
+/// a teammate triaged the bug meanwhile; it's replaced (the last writer wins) and returned.
+pub fn record(store: &mut TriageStore, id: u64, decision: TriageDecision, actor: &str, now: DateTime<Utc>) -> Option<TriageConflict> {
+    let previous = store.insert(id, TriageRecord { decision: decision.clone(), decided_at: now, decided_by: Some(actor.to_string()) })?;
+    let decided_by = previous.decided_by.clone().filter(|by| !by.eq_ignore_ascii_case(actor))?;
+    (previous.is_settled(now) && previous.decision != decision).then_some(TriageConflict { bug_id: id, decided_by, decision: previous.decision, decided_at: previous.decided_at })
+}
+
+/// Highest-scoring bug without a settled triage decision.
+pub fn next_untriaged(bugs: &[Bug], store: &TriageStore, now: DateTime<Utc>, weights: &TriageWeights) -> Option<TriageItem> {
+    let pending: Vec<Bug> = bugs.iter().filter(|b| !store.get(&b.id).is_some_and(|r| r.is_settled(now))).cloned().collect();
+    let remaining = pending.len();
+    ranked(&pending, now, weights).first().map(|(bug, breakdown)| TriageItem {
+        bug: (*bug).clone(),
+        category: categorize_bug(bug),
+        questionable: is_questionable(bug),
+        score: breakdown.total,
+        breakdown: *breakdown,
+        remaining,
+    })
+}
+
+#[tauri::command]
+/// Returns the next bug to triage, or null when the queue is empty.
+pub fn get_next_triage_bug(app: AppHandle, state: State<AppState>) -> Result<Option<TriageItem>, BuggerError> {
+    let bugs = state.bugs_or_fetch()?;
+    let store: TriageStore = storage::load_json(&app, TRIAGE_STORE)?;
+    Ok(next_untriaged(&bugs, &store, Utc::now(), &storage::load_json(&app, WEIGHTS_STORE)?))
+}
+
+#[tauri::command]
+/// Records a triage decision for a bug and advances, returning the next bug in the queue and the
+/// teammate's decision this one replaced, if any.
+pub fn record_triage_decision(app: AppHandle, state: State<AppState>, id: u64, decision: TriageDecision) -> Result<TriageOutcome, BuggerError> {
+    let config = AppConfig::from_env()?;
+    config.roles.check(&config.user_email, Role::Triager, "triage decisions")?;
+    let actor = config.user_email;
+    let mut conflict = None;
+    let store: TriageStore = storage::update_json(&app, TRIAGE_STORE, |store| conflict = record(store, id, decision, &actor, Utc::now()))?;
+    if let Some(c) = &conflict {
+        info!("Triage of #{} replaced {}'s decision ({:?})", id, c.decided_by, c.decision);
+    }
+    let bugs = state.bugs_or_fetch()?;
+    Ok(TriageOutcome { next: next_untriaged(&bugs, &store, Utc::now(), &storage::load_json(&app, WEIGHTS_STORE)?), conflict })
+}
+
+#[tauri::command]
+/// The triage score weights in use.
+pub fn get_triage_weights(app: AppHandle) -> Result<TriageWeights, String> {
+    storage::load_json(&app, WEIGHTS_STORE)
+}
+
+#[tauri::command]
+/// Saves new triage score weights; they apply to the queue from the next bug on.
+pub fn save_triage_weights(app: AppHandle, weights: TriageWeights) -> Result<TriageWeights, String> {
+    storage::save_json(&app, WEIGHTS_STORE, &weights)?;
+    Ok(weights)
+}
+
+#[tauri::command]
+/// What-if evaluation: how every current bug would score and rank under `weights` compared to the
+/// saved ones, with each score broken down by weight.
+pub fn evaluate_triage_weights(app: AppHandle, state: State<AppState>, weights: TriageWeights) -> Result<Vec<WhatIf>, BuggerError> {
+    let current: TriageWeights = storage::load_json(&app, WEIGHTS_STORE)?;
+    Ok(what_if(&state.bugs_or_fetch()?, Utc::now(), &current, &weights))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decided(store: &mut TriageStore, id: u64, decision: TriageDecision, by: Option<&str>, at: DateTime<Utc>) {
+        store.insert(id, TriageRecord { decision, decided_at: at, decided_by: by.map(str::to_string) });
+    }
+
+    #[test]
+    fn a_first_decision_replaces_nothing() {
+        let mut store = TriageStore::new();
+        assert!(record(&mut store, 1, TriageDecision::Keep, "me@contoso.com", Utc::now()).is_none());
+        assert_eq!(store[&1].decided_by.as_deref(), Some("me@contoso.com"));
+    }
+
+    #[test]
+    fn replacing_a_teammates_decision_is_reported() {
+        let now = Utc::now();
+        let mut store = TriageStore::new();
+        decided(&mut store, 1, TriageDecision::NeedsInfo, Some("jane@contoso.com"), now - Duration::hours(1));
+        let conflict = record(&mut store, 1, TriageDecision::Keep, "me@contoso.com", now).unwrap();
+        assert_eq!((conflict.bug_id, conflict.decided_by.as_str(), conflict.decision), (1, "jane@contoso.com", TriageDecision::NeedsInfo));
+        assert_eq!(store[&1].decision, TriageDecision::Keep);
+    }
+
+    #[test]
+    fn my_own_or_an_agreeing_or_expired_decision_is_no_conflict() {
+        let now = Utc::now();
+        let mut store = TriageStore::new();
+        decided(&mut store, 1, TriageDecision::NeedsInfo, Some("Me@Contoso.com"), now);
+        decided(&mut store, 2, TriageDecision::Keep, Some("jane@contoso.com"), now);
+        decided(&mut store, 3, TriageDecision::Snooze { days: 1 }, Some("jane@contoso.com"), now - Duration::days(2));
+        decided(&mut store, 4, TriageDecision::NeedsInfo, None, now);
+        for id in 1..=4 {
+            assert!(record(&mut store, id, TriageDecision::Keep, "me@contoso.com", now).is_none(), "#{}", id);
+        }
+    }
+
+    #[test]
+    fn settled_bugs_leave_the_queue_until_a_snooze_expires() {
+        let now = Utc::now();
+        let bugs = [Bug::sample(1, "App crashes on start"), Bug::sample(2, "Button misaligned")];
+        let mut store = TriageStore::new();
+        decided(&mut store, 1, TriageDecision::Snooze { days: 3 }, None, now);
+        let next = next_untriaged(&bugs, &store, now, &TriageWeights::default()).unwrap();
+        assert_eq!((next.bug.id, next.remaining), (2, 1));
+        let later = next_untriaged(&bugs, &store, now + Duration::days(4), &TriageWeights::default()).unwrap();
+        assert_eq!(later.remaining, 2);
+        decided(&mut store, 2, TriageDecision::Keep, None, now);
+        decided(&mut store, 1, TriageDecision::Duplicate { of: Some(2) }, None, now);
+        assert!(next_untriaged(&bugs, &store, now, &TriageWeights::default()).is_none());
+    }
+
+    /// A new, well-described crash and a 40-day-old questionable cosmetic bug.
+    fn crash_and_old_cosmetic_bug(now: DateTime<Utc>) -> [Bug; 2] {
+        let mut crash = Bug::sample(1, "App crashes on start");
+        crash.description = Some("Launch the app from the Start menu and it crashes before the window appears.".to_string());
+        crash.created_date = Some(now.to_rfc3339());
+        let mut cosmetic = Bug::sample(2, "Button misaligned");
+        cosmetic.created_date = Some((now - Duration::days(40)).to_rfc3339());
+        [crash, cosmetic]
+    }
+
+    #[test]
+    fn scores_are_broken_down_by_weight() {
+        let now = Utc::now();
+        let [crash, cosmetic] = crash_and_old_cosmetic_bug(now);
+        let weights = TriageWeights::default();
+        let crash = score_breakdown(&crash, now, &weights);
+        assert_eq!((crash.category, crash.age, crash.questionable, crash.total), (50.0, 0.0, 0.0, 50.0));
+        let cosmetic = score_breakdown(&cosmetic, now, &weights);
+        assert_eq!((cosmetic.category, cosmetic.age, cosmetic.questionable, cosmetic.total), (10.0, 20.0, 15.0, 45.0));
+        let capped = score_breakdown(&crash_and_old_cosmetic_bug(now)[1], now, &TriageWeights { age_cap_days: 10, ..weights });
+        assert_eq!(capped.age, 5.0);
+    }
+
+    #[test]
+    fn what_if_shows_how_ranks_would_change() {
+        let now = Utc::now();
+        let bugs = crash_and_old_cosmetic_bug(now);
+        let proposed = TriageWeights { category: 2.0, age_per_day: 1.0, ..TriageWeights::default() };
+        let ranks: Vec<_> = what_if(&bugs, now, &TriageWeights::default(), &proposed).iter().map(|w| (w.id, w.current_rank, w.proposed_rank, w.proposed.total)).collect();
+        assert_eq!(ranks, [(2, 2, 1, 57.0), (1, 1, 2, 10.0)]);
+    }
+}
@@ -0,0 +1,273 @@
+// One-off analysis of a single work item (e.g. one someone linked in chat) or of a bug not yet filed
+use crate::azure_devops::{AzureDevOpsClient, Bug};
+use crate::bug_analysis::{categorize_bug, is_questionable, BugCategory, QuestionableCategory};
+use crate::checklist::ChecklistScore;
+use crate::environment::Environment;
+use crate::error::BuggerError;
+use crate::error_codes::KnownCode;
+use crate::rules::{self, RuleSeverity};
+use crate::template::{self, TemplateCompliance};
+use crate::audit::{self, WriteAction, WriteOutcome};
+use crate::{openai, AppConfig};
+use log::warn;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::sync::OnceLock;
+use tauri::AppHandle;
+
+/// Everything the report would say about one bug.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Verdict {
+    /// Id, state and link are null for pasted text.
+    pub id: Option<u64>,
+    pub title: String,
+    pub state: Option<String>,
+    pub url: Option<String>,
+    pub category: BugCategory,
+    /// Why the bug isn't actionable, or null if it is.
+    pub questionable: Option<QuestionableCategory>,
+    /// Names of warning-severity rules the bug trips.
+    pub warnings: Vec<String>,
+    pub checklist: ChecklistScore,
+    /// Null when no bug template is configured or found.
+    pub template: Option<TemplateCompliance>,
+    pub error_codes: Vec<KnownCode>,
+}
+
+impl Verdict {
+    /// What keeps the bug from being a good report, in plain words; empty when nothing does.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems: Vec<String> = self.questionable.iter().map(|q| format!("Not actionable: {:?}", q)).collect();
+        problems.extend(self.warnings.iter().cloned());
+        problems.extend(self.checklist.missing.iter().map(|c| format!("Missing: {}", c.label())));
+        if let Some(template) = &self.template {
+            problems.extend(template.missing.iter().map(|s| format!("Missing template section: {}", s)));
+        }
+        problems
+    }
+}
+
+/// A bug not yet filed: its verdict and, when AI is configured and there are problems, a rewritten
+/// description that addresses them.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct PreFileReview {
+    pub verdict: Verdict,
+    pub problems: Vec<String>,
+    pub suggested_description: Option<String>,
+}
+
+/// A bug filed through the quality gate.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct FiledBug {
+    /// Null in simulation mode, where nothing is created.
+    pub id: Option<u64>,
+    pub url: Option<String>,
+    pub verdict: Verdict,
+}
+
+fn work_item_url_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?i)(?:/_?workitems/(?:edit/)?|[?&]workitem=)(\d+)").unwrap())
+}
+
+fn organization_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?i)^https?://(?:dev\.azure\.com/([^/?#]+)|([^./?#]+)\.visualstudio\.com)").unwrap())
+}
+
+/// Work item id from a pasted id (`123`, `#123`) or Azure DevOps URL (`…/_workitems/edit/123`,
+/// `…?workitem=123`). URLs from another organization are rejected since they can't be fetched.
+pub fn parse_work_item_ref(input: &str, org: &str) -> Result<u64, String> {
+    let input = input.trim();
+    if let Ok(id) = input.trim_start_matches('#').parse() {
+        return Ok(id);
+    }
+    if let Some(c) = organization_re().captures(input) {
+        let url_org = c.get(1).or_else(|| c.get(2)).map_or("", |m| m.as_str());
+        if !url_org.eq_ignore_ascii_case(org) {
+            return Err(format!("The link is to organization '{}', but Bugger is connected to '{}'", url_org, org));
+        }
+    }
+    work_item_url_re()
+        .captures(input)
+        .and_then(|c| c[1].parse().ok())
+        .ok_or_else(|| format!("'{}' is neither a work item id nor a work item link", input))
+}
+
+/// A not-yet-filed bug with just a title and description.
+fn draft_bug(title: String, description: String) -> Bug {
+    let environment = Environment::extract(&format!("{}\n{}", title, description));
+    Bug {
+        id: 0,
+        title,
+        state: String::new(),
+        created_date: None,
+        changed_date: None,
+        state_changed_date: None,
+        description: Some(description).filter(|d| !d.trim().is_empty()),
+        severity: None,
+        board_column: None,
+        parent_id: None,
+        comment_count: 0,
+        tags: Vec::new(),
+        assigned_to: None,
+        created_by: None,
+        area_path: None,
+        custom_fields: Default::default(),
+        environment,
+    }
+}
+
+/// Analyze one bug the way the report does, minus anything that needs the whole backlog.
+fn verdict(client: &AzureDevOpsClient, bug: &Bug, filed: bool) -> Verdict {
+    let config = &client.config;
+    let sections = config.bug_template.load(client);
+    let text = format!("{}\n{}", bug.title, bug.description.as_deref().unwrap_or(""));
+    Verdict {
+        id: filed.then_some(bug.id),
+        title: bug.title.clone(),
+        state: filed.then(|| bug.state.clone()),
+        url: filed.then(|| config.bug_url(bug.id)),
+        category: categorize_bug(bug),
+        questionable: is_questionable(bug),
+        warnings: rules::matching(bug, RuleSeverity::Warning).iter().map(|r| r.name().to_string()).collect(),
+        checklist: config.checklist.score(bug, false),
+        template: (!sections.is_empty()).then(|| template::check(bug, &sections)),
+        error_codes: config.error_codes.explain(&text),
+    }
+}
+
+/// Fetch and analyze one work item, whether or not it's in my bug list.
+pub fn work_item_verdict(id: u64) -> Result<Verdict, BuggerError> {
+    let config = AppConfig::from_env()?;
+    let client = AzureDevOpsClient::new(config);
+    let bug = client.fetch_bug_details(&[id])?.into_iter().next().ok_or_else(|| format!("Work item #{} not found", id))?;
+    Ok(verdict(&client, &bug, true))
+}
+
+#[tauri::command]
+/// Resolves a pasted work item link or id (whether or not it's in my bug list) and analyzes it.
+pub fn analyze_work_item(input: String) -> Result<Verdict, BuggerError> {
+    let config = AppConfig::from_env()?;
+    config.provider.require_azure_devops("Analyzing a work item")?;
+    work_item_verdict(parse_work_item_ref(&input, &config.org)?)
+}
+
+/// Ask the AI to rewrite `description` so it addresses `problems`, without inventing facts.
+fn suggest_description(config: &AppConfig, title: &str, description: &str, problems: &[String]) -> Option<String> {
+    let api_key = config.openai_api_key.as_deref()?;
+    let prompt = format!(
+        "Bug title: {}\nDescription:\n{}\n\nProblems found:\n- {}\n\nRewrite the description so it addresses these problems. Keep every fact from the original, don't invent any; where information is missing, add a clearly marked placeholder such as [OS build?]. Plain text only.",
+        title,
+        description,
+        problems.join("\n- ")
+    );
+    match openai::chat_completion(api_key, &config.openai_model, "You help people write clear, actionable bug reports.", &prompt, 600) {
+        Ok(suggestion) => Some(suggestion),
+        Err(e) => {
+            warn!("AI description suggestion failed: {}", e);
+            None
+        }
+    }
+}
+
+#[tauri::command]
+/// Runs the questionable/category/quality checks on a bug before it's filed, so reporters can
+/// fix what's missing first.
+pub fn analyze_text(title: String, description: String) -> Result<Verdict, BuggerError> {
+    let config = AppConfig::from_env()?;
+    config.provider.require_azure_devops("Analyzing a draft bug")?;
+    Ok(verdict(&AzureDevOpsClient::new(config), &draft_bug(title, description), false))
+}
+
+#[tauri::command]
+/// First step of filing a bug: the verdict on the draft plus an AI-improved description.
+pub fn review_new_bug(title: String, description: String) -> Result<PreFileReview, BuggerError> {
+    let config = AppConfig::from_env()?;
+    config.provider.require_azure_devops("Reviewing a new bug")?;
+    let client = AzureDevOpsClient::new(config);
+    let verdict = verdict(&client, &draft_bug(title.clone(), description.clone()), false);
+    let problems = verdict.problems();
+    let suggested_description = if problems.is_empty() { None } else { suggest_description(&client.config, &title, &description, &problems) };
+    Ok(PreFileReview { verdict, problems, suggested_description })
+}
+
+#[tauri::command]
+/// Creates the bug once it passes the quality gate. A draft that wouldn't be actionable is refused
+/// unless `file_anyway` is set.
+pub fn file_bug(app: AppHandle, title: String, description: String, file_anyway: Option<bool>) -> Result<FiledBug, BuggerError> {
+    let config = AppConfig::from_env()?;
+    config.provider.require_azure_devops("Filing a bug")?;
+    let actor = config.user_email.clone();
+    let client = AzureDevOpsClient::new(config);
+    let verdict = verdict(&client, &draft_bug(title.clone(), description.clone()), false);
+    if let Some(reason) = verdict.questionable.as_ref().filter(|_| !file_anyway.unwrap_or(false)) {
+        return Err(format!("Not filed: the bug wouldn't be actionable ({:?}). Improve the description or file it anyway.", reason).into());
+    }
+    let html = html_escape::encode_text(&description).replace('\n', "<br>");
+    let mut created = None;
+    // Nothing to point the audit entry at until the bug exists, so it's logged under id 0.
+    audit::perform(&app, &actor, 0, WriteAction::FileBug, title.clone(), false, || {
+        let response = client.create_work_item("Bug", &[("System.Title", &title), ("System.Description", &html)])?;
+        created = response["id"].as_u64();
+        Ok(WriteOutcome { response, undo: None })
+    })?;
+    let config = &client.config;
+    Ok(FiledBug {
+        id: created,
+        url: created.map(|id| config.bug_url(id)),
+        verdict,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checklist::Check;
+
+    #[test]
+    fn work_items_are_found_from_ids_and_links() {
+        assert_eq!(parse_work_item_ref(" #123 ", "contoso"), Ok(123));
+        assert_eq!(parse_work_item_ref("https://dev.azure.com/Contoso/Fabrikam/_workitems/edit/456/", "contoso"), Ok(456));
+        assert_eq!(parse_work_item_ref("https://contoso.visualstudio.com/Fabrikam/_boards/board?workitem=789", "contoso"), Ok(789));
+        assert!(parse_work_item_ref("https://dev.azure.com/northwind/Fabrikam/_workitems/edit/456", "contoso").unwrap_err().contains("'northwind'"));
+        assert!(parse_work_item_ref("the login bug", "contoso").is_err());
+    }
+
+    #[test]
+    fn a_blank_draft_has_no_description() {
+        assert_eq!(draft_bug("Crash on start".to_string(), "  \n".to_string()).description, None);
+        assert_eq!(draft_bug("Crash on start".to_string(), "Every time".to_string()).description.as_deref(), Some("Every time"));
+    }
+
+    #[test]
+    fn problems_list_everything_that_keeps_a_bug_from_being_good() {
+        let mut verdict = Verdict {
+            id: None,
+            title: "Crash".to_string(),
+            state: None,
+            url: None,
+            category: BugCategory::Crash,
+            questionable: None,
+            warnings: Vec::new(),
+            checklist: ChecklistScore { passed: vec![Check::Repro], missing: Vec::new(), score: 1, max: 1 },
+            template: None,
+            error_codes: Vec::new(),
+        };
+        assert!(verdict.problems().is_empty());
+        verdict.questionable = Some(QuestionableCategory::SingleWordDescription);
+        verdict.warnings = vec!["Vague title".to_string()];
+        verdict.checklist.missing = vec![Check::Logs];
+        verdict.template = Some(TemplateCompliance { missing: vec!["Impact".to_string()], total: 3 });
+        assert_eq!(
+            verdict.problems(),
+            [
+                "Not actionable: SingleWordDescription",
+                "Vague title",
+                "Missing: Logs, dump or screenshot",
+                "Missing template section: Impact",
+            ]
+        );
+    }
+}
@@ -0,0 +1,154 @@
+// Who owes a reply: threads where someone is waiting on me, and questions I asked that went unanswered
+use crate::azure_devops::{AzureDevOpsClient, Comment};
+use crate::bug_analysis::strip_html;
+use crate::audit::{self, WriteAction, WriteOutcome};
+use crate::error::BuggerError;
+use crate::{openai, AppConfig, AppState};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use tauri::{AppHandle, State};
+
+/// Longest excerpt of the last comment shown in the report.
+const EXCERPT_CHARS: usize = 160;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WaitingItem {
+    pub bug_id: u64,
+    /// The other person in the exchange: who asked me, or who I asked.
+    pub counterpart: String,
+    pub excerpt: String,
+    pub days_waiting: i64,
+}
+
+/// Whether I wrote `comment` under any of my identities (primary email first, then aliases).
+pub fn is_me(comment: &Comment, me: &[String]) -> bool {
+    me.iter().any(|m| comment.author_email.eq_ignore_ascii_case(m))
+}
+
+/// Whether `text` (plain) addresses me: a question, or an @mention by display name or email.
+fn addresses_me(text: &str, my_names: &[String]) -> bool {
+    let lower = text.to_lowercase();
+    lower.contains('?') || my_names.iter().any(|n| lower.contains(&format!("@{}", n.to_lowercase())))
+}
+
+fn days_since(date: Option<&str>, now: DateTime<Utc>) -> i64 {
+    date.and_then(|d| DateTime::parse_from_rfc3339(d).ok())
+        .map(|d| (now - d.with_timezone(&Utc)).num_days().max(0))
+        .unwrap_or(0)
+}
+
+fn excerpt(text: &str) -> String {
+    let mut excerpt: String = text.chars().take(EXCERPT_CHARS).collect();
+    if text.chars().count() > EXCERPT_CHARS {
+        excerpt.push('…');
+    }
+    excerpt
+}
+
+/// Most recent commenter other than me, if anyone else has commented.
+fn last_other_author(comments: &[Comment], me: &[String]) -> Option<String> {
+    comments.iter().rev().find(|c| !is_me(c, me)).map(|c| c.author.clone())
+}
+
+fn sort_longest_waiting(items: &mut [WaitingItem]) {
+    items.sort_by(|a, b| b.days_waiting.cmp(&a.days_waiting).then(a.bug_id.cmp(&b.bug_id)));
+}
+
+/// Bugs whose last comment is from someone else and asks me something, longest-waiting first.
+pub fn find_waiting_on_me(threads: &HashMap<u64, Vec<Comment>>, me: &[String], now: DateTime<Utc>) -> Vec<WaitingItem> {
+    let mut items: Vec<WaitingItem> = threads
+        .iter()
+        .filter_map(|(id, comments)| {
+            let last = comments.last()?;
+            if is_me(last, me) {
+                return None;
+            }
+            // Mentions use the display name, which we learn from my own comments in the thread.
+            let mut my_names: Vec<String> = comments.iter().filter(|c| is_me(c, me)).map(|c| c.author.clone()).collect();
+            my_names.extend(me.iter().cloned());
+            let text = strip_html(&last.text);
+            if !addresses_me(&text, &my_names) {
+                return None;
+            }
+            Some(WaitingItem {
+                bug_id: *id,
+                counterpart: last.author.clone(),
+                excerpt: excerpt(&text),
+                days_waiting: days_since(last.created_date.as_deref(), now),
+            })
+        })
+        .collect();
+    sort_longest_waiting(&mut items);
+    items
+}
+
+/// Bugs where my question is the last comment and nobody has answered for at least `min_days`.
+pub fn find_waiting_on_others(threads: &HashMap<u64, Vec<Comment>>, me: &[String], min_days: i64, now: DateTime<Utc>) -> Vec<WaitingItem> {
+    let mut items: Vec<WaitingItem> = threads
+        .iter()
+        .filter_map(|(id, comments)| {
+            let last = comments.last()?;
+            let text = strip_html(&last.text);
+            if !is_me(last, me) || !text.contains('?') {
+                return None;
+            }
+            let days_waiting = days_since(last.created_date.as_deref(), now);
+            (days_waiting >= min_days).then(|| WaitingItem {
+                bug_id: *id,
+                counterpart: last_other_author(comments, me).unwrap_or_else(|| "reporter".to_string()),
+                excerpt: excerpt(&text),
+                days_waiting,
+            })
+        })
+        .collect();
+    sort_longest_waiting(&mut items);
+    items
+}
+
+/// Follow-up text used when AI is disabled or unavailable.
+fn template_nudge(counterpart: &str, days: i64) -> String {
+    format!(
+        "Hi {}, following up on my question above from {} day(s) ago — could you take a look when you get a chance? Without that information I can't make progress on this bug.",
+        counterpart, days
+    )
+}
+
+#[tauri::command]
+/// Drafts a polite follow-up for a bug where I'm waiting on the reporter (AI when configured, template otherwise).
+pub fn draft_nudge_comment(state: State<AppState>, id: u64) -> Result<String, BuggerError> {
+    let config = AppConfig::from_env()?;
+    let title = state.bugs_or_fetch()?.into_iter().find(|b| b.id == id).map(|b| b.title).unwrap_or_default();
+    let api_key = config.openai_api_key.clone();
+    let model = config.openai_model.clone();
+    let me = config.my_identities();
+    let client = AzureDevOpsClient::new(config);
+    let comments = client.fetch_comments(id)?;
+    let counterpart = last_other_author(&comments, &me).unwrap_or_else(|| "there".to_string());
+    let question = comments.last().map(|c| strip_html(&c.text)).unwrap_or_default();
+    let days = days_since(comments.last().and_then(|c| c.created_date.as_deref()), Utc::now());
+    let Some(api_key) = api_key else {
+        return Ok(template_nudge(&counterpart, days));
+    };
+    let prompt = format!(
+        "Bug: {}\nMy unanswered question to {} ({} days ago): {}\n\nWrite a short, friendly follow-up comment (2-3 sentences) nudging them to answer. Plain text only.",
+        title, counterpart, days, question
+    );
+    match openai::chat_completion(&api_key, &model, "You help software engineers write concise, polite bug tracker comments.", &prompt, 150) {
+        Ok(draft) => Ok(draft),
+        Err(e) => {
+            log::warn!("AI nudge draft failed, using template: {}", e);
+            Ok(template_nudge(&counterpart, days))
+        }
+    }
+}
+
+#[tauri::command]
+/// Posts a (user-approved) comment on a work item.
+pub fn post_comment(app: AppHandle, id: u64, text: String) -> Result<(), BuggerError> {
+    let config = AppConfig::from_env()?;
+    let actor = config.user_email.clone();
+    let html = html_escape::encode_text(&text).replace('\n', "<br>");
+    let client = AzureDevOpsClient::new(config);
+    audit::perform(&app, &actor, id, WriteAction::Comment, text, false, || client.add_comment(id, &html).map(WriteOutcome::comment))
+}
@@ -0,0 +1,451 @@
+// Typed WIQL builder: composable filters instead of hand-written query strings
+use crate::azure_devops::AzureDevOpsClient;
+use crate::error::BuggerError;
+use crate::{env_list, AppConfig};
+use chrono::NaiveDate;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::env;
+
+/// Quote a value as a WIQL string literal, doubling embedded single quotes.
+pub fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// `=`/`<>` for a single value, `IN`/`NOT IN` for several.
+fn membership(field: &str, values: &[String], negate: bool) -> Option<String> {
+    match values {
+        [] => None,
+        [single] => Some(format!("[{}] {} {}", field, if negate { "<>" } else { "=" }, quote(single))),
+        many => Some(format!(
+            "[{}] {} ({})",
+            field,
+            if negate { "NOT IN" } else { "IN" },
+            many.iter().map(|v| quote(v)).collect::<Vec<_>>().join(", ")
+        )),
+    }
+}
+
+/// A flat `SELECT [System.Id] FROM WorkItems WHERE ... AND ...` query.
+#[derive(Debug, Clone, Default)]
+pub struct WiqlQuery {
+    conditions: Vec<String>,
+    order_by: Option<String>,
+}
+
+impl WiqlQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn with(mut self, condition: Option<String>) -> Self {
+        self.conditions.extend(condition);
+        self
+    }
+
+    pub fn work_item_type(self, work_item_type: &str) -> Self {
+        self.with(membership("System.WorkItemType", &[work_item_type.to_string()], false))
+    }
+
+    pub fn states_in(self, states: &[String]) -> Self {
+        self.with(membership("System.State", states, false))
+    }
+
+    pub fn states_not_in(self, states: &[String]) -> Self {
+        self.with(membership("System.State", states, true))
+    }
+
+    /// Assignee by unique name (email) or a macro such as `@Me`, which is left unquoted.
+    pub fn assigned_to(self, who: &str) -> Self {
+        let value = if who.starts_with('@') { who.to_string() } else { quote(who) };
+        self.with(Some(format!("[System.AssignedTo] = {}", value)))
+    }
+
+    /// Assigned to any of `identities` (OR-ed).
+    pub fn assigned_to_any(self, identities: &[String]) -> Self {
+        self.with(membership("System.AssignedTo", identities, false))
+    }
+
+    /// Resolved or closed by any of `identities`.
+    pub fn resolved_or_closed_by(self, identities: &[String]) -> Self {
+        let resolved = membership("Microsoft.VSTS.Common.ResolvedBy", identities, false);
+        let closed = membership("Microsoft.VSTS.Common.ClosedBy", identities, false);
+        self.with(resolved.zip(closed).map(|(r, c)| format!("({} OR {})", r, c)))
+    }
+
+    /// Changed on or after `date` (`YYYY-MM-DD`).
+    pub fn changed_since(self, date: &str) -> Self {
+        self.with(Some(format!("[System.ChangedDate] >= {}", quote(date))))
+    }
+
+    /// Area path including its children.
+    pub fn area_under(self, path: &str) -> Self {
+        self.with(Some(format!("[System.AreaPath] UNDER {}", quote(path))))
+    }
+
+    /// Under any of `paths` (OR-ed), e.g. a team's area paths.
+    pub fn area_under_any(self, paths: &[String]) -> Self {
+        let conditions: Vec<String> = paths.iter().map(|p| format!("[System.AreaPath] UNDER {}", quote(p))).collect();
+        self.with(match conditions.len() {
+            0 => None,
+            1 => conditions.into_iter().next(),
+            _ => Some(format!("({})", conditions.join(" OR "))),
+        })
+    }
+
+    /// Resolved within the last `days` days, relative to `@Today`.
+    pub fn resolved_within_days(self, days: u32) -> Self {
+        self.with(Some(format!("[Microsoft.VSTS.Common.ResolvedDate] >= @Today - {}", days)))
+    }
+
+    /// Iteration path including its children.
+    pub fn iteration_under(self, path: &str) -> Self {
+        self.with(Some(format!("[System.IterationPath] UNDER {}", quote(path))))
+    }
+
+    /// Work items carrying every one of `tags`.
+    pub fn tagged(self, tags: &[String]) -> Self {
+        let conditions: Vec<String> = tags.iter().map(|t| format!("[System.Tags] CONTAINS {}", quote(t))).collect();
+        self.with((!conditions.is_empty()).then(|| conditions.join(" AND ")))
+    }
+
+    /// Changed within the last `days` days, relative to `@Today`.
+    pub fn changed_within_days(self, days: u32) -> Self {
+        self.with(Some(format!("[System.ChangedDate] >= @Today - {}", days)))
+    }
+
+    pub fn order_by(mut self, field: &str, descending: bool) -> Self {
+        self.order_by = Some(format!("[{}] {}", field, if descending { "DESC" } else { "ASC" }));
+        self
+    }
+
+    pub fn build(&self) -> String {
+        let mut wiql = "SELECT [System.Id] FROM WorkItems".to_string();
+        if !self.conditions.is_empty() {
+            wiql.push_str(" WHERE ");
+            wiql.push_str(&self.conditions.join(" AND "));
+        }
+        if let Some(order) = &self.order_by {
+            wiql.push_str(" ORDER BY ");
+            wiql.push_str(order);
+        }
+        wiql
+    }
+}
+
+/// `wiql` without its ORDER BY and with `condition` ANDed to its conditions. Works on any flat query,
+/// including a custom BUGGER_WIQL.
+fn narrowed(wiql: &str, condition: &str) -> String {
+    let order_by = Regex::new(r"(?i)\s+ORDER\s+BY\s").expect("valid regex");
+    let query = order_by.find(wiql).map_or(wiql, |m| &wiql[..m.start()]).trim_end();
+    match Regex::new(r"(?i)\sWHERE\s").expect("valid regex").find(query) {
+        Some(m) => format!("{} WHERE {} AND ({})", query[..m.start()].trim_end(), condition, query[m.end()..].trim()),
+        None => format!("{} WHERE {}", query, condition),
+    }
+}
+
+/// `wiql` narrowed to ids above `after` and ordered by id, for paging through results past the WIQL
+/// limit.
+pub fn after_id(wiql: &str, after: u64) -> String {
+    format!("{} ORDER BY [System.Id] ASC", narrowed(wiql, &format!("[System.Id] > {}", after)))
+}
+
+/// `wiql` narrowed to work items changed on or after `since`'s day, for incremental syncs. WIQL
+/// compares dates by day, so changes from earlier that day come back again.
+pub fn changed_since(wiql: &str, since: NaiveDate) -> String {
+    narrowed(wiql, &format!("[System.ChangedDate] >= '{}'", since.format("%Y-%m-%d")))
+}
+
+fn quoted_list(values: &[String]) -> String {
+    format!("({})", values.iter().map(|v| quote(v)).collect::<Vec<_>>().join(", "))
+}
+
+/// Replace `@Macro` tokens outside string literals using `macros` (keys lower-case, without `@`).
+/// Unknown macros such as `@Today` or `@CurrentIteration` are left for Azure DevOps to resolve.
+pub fn expand_macros(wiql: &str, macros: &HashMap<String, String>) -> String {
+    let mut out = String::with_capacity(wiql.len());
+    let mut chars = wiql.chars().peekable();
+    let mut in_literal = false;
+    while let Some(c) = chars.next() {
+        if c == '\'' {
+            in_literal = !in_literal;
+        }
+        if c != '@' || in_literal {
+            out.push(c);
+            continue;
+        }
+        let mut name = String::new();
+        while let Some(&next) = chars.peek() {
+            if !next.is_alphanumeric() && next != '_' {
+                break;
+            }
+            name.push(next);
+            chars.next();
+        }
+        match macros.get(&name.to_lowercase()) {
+            Some(value) => out.push_str(value),
+            None => {
+                out.push('@');
+                out.push_str(&name);
+            }
+        }
+    }
+    out
+}
+
+/// Filters for the bug query (BUGGER_EXCLUDED_STATES, BUGGER_AREA_PATH, BUGGER_ITERATION_PATH,
+/// BUGGER_QUERY_TAGS, BUGGER_CHANGED_WITHIN_DAYS), or a custom query in BUGGER_WIQL. In team mode
+/// (BUGGER_TEAM_MODE) the bugs in BUGGER_TEAM_AREA_PATHS are fetched whoever they're assigned to.
+#[derive(Debug, Clone)]
+pub struct QueryFilters {
+    pub excluded_states: Vec<String>,
+    /// Whether BUGGER_EXCLUDED_STATES was set; otherwise the process's finished states replace the default.
+    pub excluded_states_configured: bool,
+    pub area_path: Option<String>,
+    pub iteration_path: Option<String>,
+    pub tags: Vec<String>,
+    pub changed_within_days: Option<u32>,
+    /// User-supplied WIQL replacing the built query; macros are expanded before it is run.
+    pub custom_wiql: Option<String>,
+    /// Area paths substituted for `@MyTeamAreaPaths` (BUGGER_TEAM_AREA_PATHS).
+    pub team_area_paths: Vec<String>,
+    pub team_mode: bool,
+}
+
+impl QueryFilters {
+    pub fn from_env() -> Self {
+        let excluded_states = env_list("BUGGER_EXCLUDED_STATES");
+        let non_empty = |name: &str| env::var(name).ok().filter(|v| !v.trim().is_empty());
+        QueryFilters {
+            excluded_states_configured: !excluded_states.is_empty(),
+            excluded_states: if excluded_states.is_empty() { vec!["Closed".to_string()] } else { excluded_states },
+            area_path: non_empty("BUGGER_AREA_PATH"),
+            iteration_path: non_empty("BUGGER_ITERATION_PATH"),
+            tags: env_list("BUGGER_QUERY_TAGS"),
+            changed_within_days: non_empty("BUGGER_CHANGED_WITHIN_DAYS").and_then(|v| v.trim().parse().ok()),
+            custom_wiql: non_empty("BUGGER_WIQL"),
+            team_area_paths: env_list("BUGGER_TEAM_AREA_PATHS"),
+            team_mode: crate::env_flag("BUGGER_TEAM_MODE", false),
+        }
+    }
+
+    /// Macros resolved from config, so saved queries work for whoever runs them. `@Me` is expanded
+    /// here because the API would otherwise resolve it to the PAT owner; `@MyIdentities` includes aliases.
+    pub fn macros(&self, identities: &[String], project: &str) -> HashMap<String, String> {
+        let mut macros = HashMap::from([
+            ("me".to_string(), quote(&identities[0])),
+            ("myidentities".to_string(), quoted_list(identities)),
+            ("project".to_string(), quote(project)),
+            ("myexcludedstates".to_string(), quoted_list(&self.excluded_states)),
+        ]);
+        if !self.team_area_paths.is_empty() {
+            macros.insert("myteamareapaths".to_string(), quoted_list(&self.team_area_paths));
+        }
+        if let Some(iteration) = &self.iteration_path {
+            macros.insert("myiteration".to_string(), quote(iteration));
+        }
+        macros
+    }
+
+    /// The WIQL to run: the custom query with macros expanded, or the built bug query.
+    pub fn wiql(&self, identities: &[String], project: &str) -> String {
+        match &self.custom_wiql {
+            Some(custom) => expand_macros(custom, &self.macros(identities, project)),
+            None => self.bug_query(identities).build(),
+        }
+    }
+
+    /// Whether the team's bugs are fetched instead of mine; needs team area paths to know who the team is.
+    pub fn is_team_mode(&self) -> bool {
+        self.team_mode && !self.team_area_paths.is_empty()
+    }
+
+    /// Active bugs assigned to any of `identities` (or, in team mode, in the team's area paths),
+    /// narrowed by the configured filters, newest first.
+    pub fn bug_query(&self, identities: &[String]) -> WiqlQuery {
+        let mut query = WiqlQuery::new().work_item_type("Bug").states_not_in(&self.excluded_states);
+        query = if self.is_team_mode() { query.area_under_any(&self.team_area_paths) } else { query.assigned_to_any(identities) };
+        if let Some(area) = &self.area_path {
+            query = query.area_under(area);
+        }
+        if let Some(iteration) = &self.iteration_path {
+            query = query.iteration_under(iteration);
+        }
+        query = query.tagged(&self.tags);
+        if let Some(days) = self.changed_within_days {
+            query = query.changed_within_days(days);
+        }
+        query.order_by("System.CreatedDate", true)
+    }
+}
+
+/// What the configured query would return, without fetching any work item details.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct QueryPreview {
+    pub wiql: String,
+    pub count: usize,
+}
+
+#[tauri::command]
+/// Runs the configured bug query for IDs only, so filters can be sanity-checked before a full analysis.
+pub fn preview_bug_query() -> Result<QueryPreview, BuggerError> {
+    let config = AppConfig::from_env()?;
+    let client = AzureDevOpsClient::new(config);
+    let wiql = client.active_bugs_wiql();
+    let count = client.run_wiql(&wiql)?.len();
+    Ok(QueryPreview { wiql, count })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    fn default_filters() -> QueryFilters {
+        QueryFilters {
+            excluded_states: strings(&["Closed"]),
+            excluded_states_configured: false,
+            area_path: None,
+            iteration_path: None,
+            tags: vec![],
+            changed_within_days: None,
+            custom_wiql: None,
+            team_area_paths: vec![],
+            team_mode: false,
+        }
+    }
+
+    #[test]
+    fn paging_narrows_by_id_and_keeps_the_conditions() {
+        assert_eq!(
+            after_id("SELECT [System.Id] FROM WorkItems WHERE [System.State] = 'Active' OR [System.State] = 'New'\nORDER BY [System.CreatedDate] DESC", 20000),
+            "SELECT [System.Id] FROM WorkItems WHERE [System.Id] > 20000 AND ([System.State] = 'Active' OR [System.State] = 'New') ORDER BY [System.Id] ASC"
+        );
+        assert_eq!(after_id("SELECT [System.Id] FROM WorkItems", 5), "SELECT [System.Id] FROM WorkItems WHERE [System.Id] > 5 ORDER BY [System.Id] ASC");
+    }
+
+    #[test]
+    fn delta_query_narrows_by_changed_date() {
+        assert_eq!(
+            changed_since("SELECT [System.Id] FROM WorkItems WHERE [System.State] <> 'Closed' ORDER BY [System.Id]", NaiveDate::from_ymd_opt(2026, 10, 14).unwrap()),
+            "SELECT [System.Id] FROM WorkItems WHERE [System.ChangedDate] >= '2026-10-14' AND ([System.State] <> 'Closed')"
+        );
+    }
+
+    #[test]
+    fn quote_doubles_single_quotes() {
+        assert_eq!(quote("O'Brien"), "'O''Brien'");
+        assert_eq!(quote("it''s"), "'it''''s'");
+        assert_eq!(quote(""), "''");
+    }
+
+    #[test]
+    fn single_state_uses_equality_operators() {
+        assert_eq!(WiqlQuery::new().states_in(&strings(&["Active"])).build(), "SELECT [System.Id] FROM WorkItems WHERE [System.State] = 'Active'");
+        assert_eq!(WiqlQuery::new().states_not_in(&strings(&["Closed"])).build(), "SELECT [System.Id] FROM WorkItems WHERE [System.State] <> 'Closed'");
+    }
+
+    #[test]
+    fn several_states_use_in_operators() {
+        assert_eq!(
+            WiqlQuery::new().states_in(&strings(&["New", "Active"])).build(),
+            "SELECT [System.Id] FROM WorkItems WHERE [System.State] IN ('New', 'Active')"
+        );
+        assert_eq!(
+            WiqlQuery::new().states_not_in(&strings(&["Closed", "Won't Fix"])).build(),
+            "SELECT [System.Id] FROM WorkItems WHERE [System.State] NOT IN ('Closed', 'Won''t Fix')"
+        );
+    }
+
+    #[test]
+    fn resolved_or_closed_by_groups_the_alternatives() {
+        let query = WiqlQuery::new().work_item_type("Bug").resolved_or_closed_by(&strings(&["me@contoso.com"])).changed_since("2026-10-01");
+        assert_eq!(
+            query.build(),
+            "SELECT [System.Id] FROM WorkItems WHERE [System.WorkItemType] = 'Bug' AND ([Microsoft.VSTS.Common.ResolvedBy] = 'me@contoso.com' \
+             OR [Microsoft.VSTS.Common.ClosedBy] = 'me@contoso.com') AND [System.ChangedDate] >= '2026-10-01'"
+        );
+        assert_eq!(WiqlQuery::new().resolved_or_closed_by(&[]).build(), "SELECT [System.Id] FROM WorkItems");
+    }
+
+    #[test]
+    fn empty_filters_add_no_conditions() {
+        let query = WiqlQuery::new().states_in(&[]).tagged(&[]);
+        assert_eq!(query.build(), "SELECT [System.Id] FROM WorkItems");
+    }
+
+    #[test]
+    fn assigned_to_leaves_macros_unquoted() {
+        assert!(WiqlQuery::new().assigned_to("@Me").build().ends_with("[System.AssignedTo] = @Me"));
+        assert!(WiqlQuery::new().assigned_to("jane@example.com").build().ends_with("[System.AssignedTo] = 'jane@example.com'"));
+    }
+
+    #[test]
+    fn paths_tags_and_dates_compose_with_and() {
+        let query = WiqlQuery::new()
+            .work_item_type("Bug")
+            .area_under("Proj\\Team's Area")
+            .iteration_under("Proj\\Sprint 1")
+            .tagged(&strings(&["regression", "ui"]))
+            .changed_within_days(14)
+            .order_by("System.ChangedDate", false);
+        assert_eq!(
+            query.build(),
+            "SELECT [System.Id] FROM WorkItems WHERE [System.WorkItemType] = 'Bug' AND [System.AreaPath] UNDER 'Proj\\Team''s Area' \
+             AND [System.IterationPath] UNDER 'Proj\\Sprint 1' AND [System.Tags] CONTAINS 'regression' AND [System.Tags] CONTAINS 'ui' \
+             AND [System.ChangedDate] >= @Today - 14 ORDER BY [System.ChangedDate] ASC"
+        );
+    }
+
+    #[test]
+    fn default_bug_query_matches_previous_hardcoded_query() {
+        let filters = default_filters();
+        assert_eq!(
+            filters.bug_query(&strings(&["me@example.com"])).build(),
+            "SELECT [System.Id] FROM WorkItems WHERE [System.WorkItemType] = 'Bug' AND [System.State] <> 'Closed' AND [System.AssignedTo] = 'me@example.com' ORDER BY [System.CreatedDate] DESC"
+        );
+    }
+
+    #[test]
+    fn team_mode_queries_team_area_paths_instead_of_assignee() {
+        let filters = QueryFilters { team_mode: true, team_area_paths: strings(&["Proj\\A", "Proj\\B"]), ..default_filters() };
+        assert_eq!(
+            filters.bug_query(&strings(&["me@example.com"])).build(),
+            "SELECT [System.Id] FROM WorkItems WHERE [System.WorkItemType] = 'Bug' AND [System.State] <> 'Closed' \
+             AND ([System.AreaPath] UNDER 'Proj\\A' OR [System.AreaPath] UNDER 'Proj\\B') ORDER BY [System.CreatedDate] DESC"
+        );
+        let without_paths = QueryFilters { team_mode: true, ..default_filters() };
+        assert!(without_paths.bug_query(&strings(&["me@example.com"])).build().contains("[System.AssignedTo] = 'me@example.com'"));
+    }
+
+    #[test]
+    fn custom_wiql_expands_config_macros() {
+        let filters = QueryFilters {
+            custom_wiql: Some("SELECT [System.Id] FROM WorkItems WHERE [System.AssignedTo] = @me AND [System.AreaPath] IN @MyTeamAreaPaths AND [System.ChangedDate] >= @Today - 7".to_string()),
+            team_area_paths: strings(&["Proj\\A", "Proj\\B's"]),
+            ..default_filters()
+        };
+        assert_eq!(
+            filters.wiql(&strings(&["me@example.com"]), "Proj"),
+            "SELECT [System.Id] FROM WorkItems WHERE [System.AssignedTo] = 'me@example.com' AND [System.AreaPath] IN ('Proj\\A', 'Proj\\B''s') AND [System.ChangedDate] >= @Today - 7"
+        );
+    }
+
+    #[test]
+    fn macros_inside_literals_and_unknown_macros_are_untouched() {
+        let macros = default_filters().macros(&strings(&["me@example.com"]), "Proj");
+        assert_eq!(expand_macros("[System.Title] CONTAINS '@Me' AND [System.IterationPath] = @CurrentIteration", &macros), "[System.Title] CONTAINS '@Me' AND [System.IterationPath] = @CurrentIteration");
+        assert_eq!(expand_macros("[System.TeamProject] = @Project", &macros), "[System.TeamProject] = 'Proj'");
+    }
+
+    #[test]
+    fn aliases_are_ored_in_the_bug_query() {
+        let query = default_filters().bug_query(&strings(&["me@example.com", "DOMAIN\\me"])).build();
+        assert!(query.contains("[System.AssignedTo] IN ('me@example.com', 'DOMAIN\\me')"));
+    }
+}
@@ -0,0 +1,130 @@
+// Pomodoro-style "work this bug" sessions: a timed, notification-free block on one bug that ends
+// with the choice to log the time or post a progress comment
+use crate::audit::{self, WriteAction, WriteOutcome};
+use crate::azure_devops::AzureDevOpsClient;
+use crate::notifications::deliver;
+use crate::time_tracking::{self, TimeEntry};
+use crate::error::BuggerError;
+use crate::{AppConfig, AppState};
+use chrono::{DateTime, Duration, Utc};
+use log::warn;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tauri::ipc::Channel;
+use tauri::{AppHandle, Manager, State, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_notification::NotificationExt;
+
+const DEFAULT_MINUTES: i64 = 25;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct WorkSession {
+    pub bug_id: u64,
+    pub title: String,
+    pub started: DateTime<Utc>,
+    pub ends: DateTime<Utc>,
+}
+
+impl WorkSession {
+    /// Until its time is up; after that it only waits for `finish_work_session`.
+    fn is_running(&self, now: DateTime<Utc>) -> bool {
+        now < self.ends
+    }
+}
+
+/// Whether a session is running; notifications are held back until its time is up.
+pub fn is_active(app: &AppHandle) -> bool {
+    app.state::<AppState>().work_session.lock().is_ok_and(|s| s.as_ref().is_some_and(|s| s.is_running(Utc::now())))
+}
+
+/// Open the work item in its own window, reusing the window if it's already open.
+fn open_detail_window(app: &AppHandle, config: &AppConfig, id: u64, title: &str) {
+    let label = format!("bug-{}", id);
+    if let Some(window) = app.get_webview_window(&label) {
+        let _ = window.set_focus();
+        return;
+    }
+    let Ok(url) = config.bug_url(id).parse() else {
+        return;
+    };
+    if let Err(e) = WebviewWindowBuilder::new(app, label, WebviewUrl::External(url)).title(format!("#{} {}", id, title)).inner_size(1100.0, 800.0).build() {
+        warn!("Failed to open work item window: {}", e);
+    }
+}
+
+#[tauri::command]
+/// Opens a bug in its own window (or focuses the window already showing it).
+pub fn open_bug(app: AppHandle, id: u64) -> Result<(), String> {
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    let title = app.state::<AppState>().bugs.lock().map_err(|e| e.to_string())?.iter().find(|b| b.id == id).map(|b| b.title.clone()).unwrap_or_default();
+    open_detail_window(&app, &config, id, &title);
+    Ok(())
+}
+
+#[tauri::command]
+/// Starts a focused session on a bug: starts its timer, holds notifications back and opens the work
+/// item. When the time is up (default 25 minutes) the session is sent to `on_end`; it lasts until
+/// `finish_work_session`.
+pub fn start_work_session(app: AppHandle, state: State<AppState>, id: u64, minutes: Option<i64>, on_end: Channel<WorkSession>) -> Result<WorkSession, String> {
+    let config = AppConfig::from_env().map_err(|e| e.to_string())?;
+    let title = state.bugs.lock().map_err(|e| e.to_string())?.iter().find(|b| b.id == id).map(|b| b.title.clone()).unwrap_or_default();
+    let timer = time_tracking::start_timer(app.clone(), id)?;
+    let session = WorkSession { bug_id: id, title, started: timer.started, ends: timer.started + Duration::minutes(minutes.unwrap_or(DEFAULT_MINUTES).max(1)) };
+    *state.work_session.lock().map_err(|e| e.to_string())? = Some(session.clone());
+    open_detail_window(&app, &config, id, &session.title);
+
+    let ended = session.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep((ended.ends - Utc::now()).to_std().unwrap_or_default());
+        // Finished early or replaced by another session.
+        if app.state::<AppState>().work_session.lock().ok().and_then(|s| s.clone()) != Some(ended.clone()) {
+            return;
+        }
+        if let Err(e) = app.notification().builder().title(format!("Session on #{} is over", ended.bug_id)).body("Log your time or add a progress comment.").show() {
+            warn!("Failed to show session notification: {}", e);
+        }
+        // The notifications held back during the session go out now, not when it's finished.
+        deliver(&app, AppConfig::from_env().ok().and_then(|c| c.quiet_hours).as_ref(), Vec::new());
+        if let Err(e) = on_end.send(ended) {
+            warn!("Failed to report session end: {}", e);
+        }
+    });
+    Ok(session)
+}
+
+#[tauri::command]
+/// Ends the session: posts `comment` on the bug if given, stops its timer (adding the time to
+/// Completed Work with `log_time`) and delivers the notifications held back meanwhile. The comment
+/// goes first: if it fails the session is kept so finishing can be retried, and a failed time
+/// write-back doesn't cost the comment.
+pub fn finish_work_session(app: AppHandle, state: State<AppState>, log_time: bool, comment: Option<String>) -> Result<Option<TimeEntry>, BuggerError> {
+    let Some(session) = state.work_session.lock().map_err(|e| e.to_string())?.clone() else {
+        return Ok(None);
+    };
+    let config = AppConfig::from_env()?;
+    let quiet_hours = config.quiet_hours.clone();
+    if let Some(text) = comment.filter(|c| !c.trim().is_empty()) {
+        let actor = config.user_email.clone();
+        let html = html_escape::encode_text(&text).replace('\n', "<br>");
+        let client = AzureDevOpsClient::new(config);
+        audit::perform(&app, &actor, session.bug_id, WriteAction::Comment, text, false, || client.add_comment(session.bug_id, &html).map(WriteOutcome::comment))
+            .map_err(|e| e.context("The comment wasn't posted"))?;
+    }
+    state.work_session.lock().map_err(|e| e.to_string())?.take();
+    deliver(&app, quiet_hours.as_ref(), Vec::new());
+    time_tracking::stop_timer(app.clone(), log_time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_session_stops_holding_notifications_when_its_time_is_up() {
+        let started = Utc::now();
+        let session = WorkSession { bug_id: 1, title: "Crash".to_string(), started, ends: started + Duration::minutes(25) };
+        assert!(session.is_running(started));
+        assert!(session.is_running(started + Duration::minutes(24)));
+        assert!(!session.is_running(started + Duration::minutes(25)));
+        assert!(!session.is_running(started + Duration::hours(3)));
+    }
+}
@@ -0,0 +1,217 @@
+// Opt-in write-back of Bugger's category and triage score to the work item (custom fields and/or a
+// category tag), so teammates see the triage signal without opening the app
+use crate::audit::{self, update_fields_reversible, WriteAction};
+use crate::azure_devops::{AzureDevOpsClient, Bug};
+use crate::error::BuggerError;
+use crate::bug_analysis::categorize_bug;
+use crate::policy::PolicyMode;
+use crate::roles::Role;
+use crate::triage::{self, ranked};
+use crate::{storage, AppConfig, AppState};
+use chrono::Utc;
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::env;
+use tauri::{AppHandle, State};
+
+/// Values last written per bug, so unchanged bugs aren't written again on every run.
+const WRITE_BACK_STORE: &str = "write_back";
+/// Scores are written rounded down to a multiple of this, so a bug ageing by a day isn't written again.
+const SCORE_STEP: f64 = 10.0;
+
+#[derive(Debug, Clone)]
+pub struct WriteBackPolicy {
+    pub mode: PolicyMode,
+    /// Field reference name receiving the category, e.g. `Custom.BuggerCategory`.
+    pub category_field: Option<String>,
+    /// Field reference name receiving the triage score rounded down to a multiple of 10, e.g. `Custom.BuggerScore`.
+    pub score_field: Option<String>,
+    /// Prefix of the category tag, e.g. `bugger:` for `bugger:network`.
+    pub tag_prefix: Option<String>,
+}
+
+impl WriteBackPolicy {
+    /// Load from BUGGER_WRITEBACK (off | dry-run | on), BUGGER_WRITEBACK_CATEGORY_FIELD,
+    /// BUGGER_WRITEBACK_SCORE_FIELD and BUGGER_WRITEBACK_TAG_PREFIX. Off unless explicitly enabled.
+    pub fn from_env() -> Self {
+        let mode = match env::var("BUGGER_WRITEBACK").unwrap_or_default().trim().to_lowercase().as_str() {
+            "on" | "true" => PolicyMode::On,
+            "dry-run" | "dryrun" => PolicyMode::DryRun,
+            _ => PolicyMode::Off,
+        };
+        let setting = |name: &str| env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        WriteBackPolicy {
+            mode,
+            category_field: setting("BUGGER_WRITEBACK_CATEGORY_FIELD"),
+            score_field: setting("BUGGER_WRITEBACK_SCORE_FIELD"),
+            tag_prefix: setting("BUGGER_WRITEBACK_TAG_PREFIX"),
+        }
+    }
+
+    /// Whether there is anywhere to write to.
+    pub fn has_target(&self) -> bool {
+        self.category_field.is_some() || self.score_field.is_some() || self.tag_prefix.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct WriteBackAction {
+    pub bug_id: u64,
+    /// Field reference names and the values written to them.
+    pub fields: Vec<(String, String)>,
+    pub dry_run: bool,
+    pub error: Option<String>,
+}
+
+/// The bug's tags with any earlier category tag replaced by `tag`; `None` if it's already there alone.
+fn retagged(bug: &Bug, prefix: &str, tag: &str) -> Option<String> {
+    let is_category_tag = |t: &String| t.get(..prefix.len()).is_some_and(|p| p.eq_ignore_ascii_case(prefix));
+    let existing: Vec<&String> = bug.tags.iter().filter(|t| is_category_tag(t)).collect();
+    if existing.len() == 1 && existing[0].eq_ignore_ascii_case(tag) {
+        return None;
+    }
+    let mut tags: Vec<String> = bug.tags.iter().filter(|t| !is_category_tag(t)).cloned().collect();
+    tags.push(tag.to_string());
+    Some(tags.join("; "))
+}
+
+/// Field updates bringing `bug` up to date with its category and `score`.
+fn updates(bug: &Bug, score: f64, policy: &WriteBackPolicy) -> Vec<(String, String)> {
+    let category = format!("{:?}", categorize_bug(bug)).to_lowercase();
+    let mut updates = Vec::new();
+    if let Some(field) = &policy.category_field {
+        updates.push((field.clone(), category.clone()));
+    }
+    if let Some(field) = &policy.score_field {
+        updates.push((field.clone(), format!("{:.0}", (score / SCORE_STEP).floor() * SCORE_STEP)));
+    }
+    if let Some(tags) = policy.tag_prefix.as_ref().and_then(|prefix| retagged(bug, prefix, &format!("{}{}", prefix, category))) {
+        updates.push(("System.Tags".to_string(), tags));
+    }
+    updates
+}
+
+/// Write each bug's category and score where they changed since the last run (or, in dry-run, only
+/// log it). Every write goes to the audit log.
+pub fn run_write_back(app: &AppHandle, client: &AzureDevOpsClient, bugs: &[Bug], policy: &WriteBackPolicy, dry_run: bool) -> Vec<WriteBackAction> {
+    let mut written: HashMap<u64, Vec<(String, String)>> = storage::load_json(app, WRITE_BACK_STORE).unwrap_or_else(|e| {
+        warn!("Ignoring write-back history: {}", e);
+        HashMap::new()
+    });
+    let weights = triage::get_triage_weights(app.clone()).unwrap_or_else(|e| {
+        warn!("Using default triage weights for write-back: {}", e);
+        Default::default()
+    });
+    let mut actions = Vec::new();
+    for (bug, score) in ranked(bugs, Utc::now(), &weights) {
+        let mut fields = updates(bug, score.total, policy);
+        // Tags are compared with the bug itself; custom fields with what was written last time.
+        let last = written.get(&bug.id);
+        fields.retain(|update| update.0 == "System.Tags" || last.is_none_or(|l| !l.contains(update)));
+        if fields.is_empty() {
+            continue;
+        }
+        let detail = fields.iter().map(|(name, value)| format!("{} = {}", name, value)).collect::<Vec<_>>().join(", ");
+        if dry_run && !audit::is_new_preview(app, bug.id, WriteAction::WriteBack, &detail) {
+            actions.push(WriteBackAction { bug_id: bug.id, fields, dry_run, error: None });
+            continue;
+        }
+        let result = audit::perform(app, &client.config.user_email, bug.id, WriteAction::WriteBack, detail, dry_run, || {
+            let refs: Vec<(&str, &str)> = fields.iter().map(|(name, value)| (name.as_str(), value.as_str())).collect();
+            update_fields_reversible(client, bug.id, &refs)
+        });
+        info!("Write-back #{}: {} field(s) (dry run: {})", bug.id, fields.len(), dry_run);
+        if result.is_ok() && !dry_run {
+            let entry = written.entry(bug.id).or_default();
+            for update in &fields {
+                entry.retain(|(name, _)| name != &update.0);
+                entry.push(update.clone());
+            }
+        }
+        actions.push(WriteBackAction { bug_id: bug.id, fields, dry_run, error: result.err().map(String::from) });
+    }
+    if !dry_run && !actions.is_empty() {
+        written.retain(|id, _| bugs.iter().any(|b| b.id == *id));
+        if let Err(e) = storage::save_json(app, WRITE_BACK_STORE, &written) {
+            warn!("Failed to save write-back history: {}", e);
+        }
+    }
+    actions
+}
+
+#[tauri::command]
+/// Writes categories and triage scores back to the work items now. `dry_run` defaults to the
+/// configured mode; running while write-back is off is always a dry run.
+pub fn run_write_back_policy(app: AppHandle, state: State<AppState>, dry_run: Option<bool>) -> Result<Vec<WriteBackAction>, BuggerError> {
+    let config = AppConfig::from_env()?;
+    config.provider.require_azure_devops("Write-back")?;
+    let policy = config.write_back.clone();
+    if !policy.has_target() {
+        return Err(BuggerError::Config("No write-back target configured (BUGGER_WRITEBACK_CATEGORY_FIELD, BUGGER_WRITEBACK_SCORE_FIELD or BUGGER_WRITEBACK_TAG_PREFIX)".to_string()));
+    }
+    let dry_run = policy.mode != PolicyMode::On || dry_run.unwrap_or(false);
+    if !dry_run {
+        config.roles.check(&config.user_email, Role::Admin, "running write-back")?;
+    }
+    let bugs = state.bugs_or_fetch()?;
+    Ok(run_write_back(&app, &AzureDevOpsClient::new(config), &bugs, &policy, dry_run))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> WriteBackPolicy {
+        WriteBackPolicy {
+            mode: PolicyMode::On,
+            category_field: Some("Custom.BuggerCategory".to_string()),
+            score_field: Some("Custom.BuggerScore".to_string()),
+            tag_prefix: Some("bugger:".to_string()),
+        }
+    }
+
+    fn tagged(tags: &[&str]) -> Bug {
+        let mut bug = Bug::sample(1, "Crash when opening settings");
+        bug.tags = tags.iter().map(|t| t.to_string()).collect();
+        bug
+    }
+
+    #[test]
+    fn a_category_tag_already_there_alone_is_left_alone() {
+        assert_eq!(retagged(&tagged(&["ui", "bugger:crash"]), "bugger:", "bugger:crash"), None);
+        assert_eq!(retagged(&tagged(&["Bugger:Crash"]), "bugger:", "bugger:crash"), None);
+    }
+
+    #[test]
+    fn an_earlier_category_tag_is_replaced() {
+        assert_eq!(retagged(&tagged(&["ui", "Bugger:network"]), "bugger:", "bugger:crash").as_deref(), Some("ui; bugger:crash"));
+        assert_eq!(retagged(&tagged(&["bugger:crash", "bugger:ui"]), "bugger:", "bugger:crash").as_deref(), Some("bugger:crash"));
+        assert_eq!(retagged(&tagged(&[]), "bugger:", "bugger:crash").as_deref(), Some("bugger:crash"));
+    }
+
+    #[test]
+    fn updates_cover_every_configured_target() {
+        let bug = tagged(&["ui"]);
+        let category = format!("{:?}", categorize_bug(&bug)).to_lowercase();
+        assert_eq!(
+            updates(&bug, 57.5, &policy()),
+            vec![
+                ("Custom.BuggerCategory".to_string(), category.clone()),
+                ("Custom.BuggerScore".to_string(), "50".to_string()),
+                ("System.Tags".to_string(), format!("ui; bugger:{}", category)),
+            ]
+        );
+        let fields_only = WriteBackPolicy { tag_prefix: None, category_field: None, ..policy() };
+        assert_eq!(updates(&bug, 9.9, &fields_only), vec![("Custom.BuggerScore".to_string(), "0".to_string())]);
+    }
+
+    #[test]
+    fn scores_in_the_same_step_are_written_the_same() {
+        let bug = tagged(&[]);
+        let score_only = WriteBackPolicy { tag_prefix: None, category_field: None, ..policy() };
+        assert_eq!(updates(&bug, 41.0, &score_only), updates(&bug, 49.5, &score_only));
+        assert_ne!(updates(&bug, 49.5, &score_only), updates(&bug, 50.0, &score_only));
+    }
+}
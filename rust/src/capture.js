@@ -0,0 +1,27 @@
+const { invoke } = window.__TAURI__.core;
+const { getCurrentWindow } = window.__TAURI__.window;
+
+// Quick note window: save with Ctrl+Enter, hide with Esc. It's hidden rather than closed so the
+// shortcut brings it back instantly.
+window.addEventListener("DOMContentLoaded", () => {
+  const text = document.getElementById("note-text");
+  const hint = document.querySelector(".capture-hint");
+  const hide = () => getCurrentWindow().hide();
+
+  window.addEventListener("focus", () => text.focus());
+  text.addEventListener("keydown", async (event) => {
+    if (event.key === "Escape") {
+      hide();
+    } else if (event.key === "Enter" && (event.ctrlKey || event.metaKey)) {
+      event.preventDefault();
+      try {
+        await invoke("save_quick_note", { text: text.value });
+        text.value = "";
+        hint.textContent = "Ctrl+Enter to save · Esc to close";
+        hide();
+      } catch (err) {
+        hint.textContent = `Not saved: ${err}`;
+      }
+    }
+  });
+});
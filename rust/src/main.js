@@ -1,8 +1,145 @@
-const { invoke } = window.__TAURI__.core;
+const { invoke, Channel } = window.__TAURI__.core;
+const { listen } = window.__TAURI__.event;
+
+// Setup guidance shown instead of an error when settings are missing or invalid.
+function renderSetup(status) {
+  const missing = status.missing.map((name) => `<li><code>${name}</code> is not set</li>`);
+  const invalid = status.invalid.map((s) => `<li><code>${s.name}</code>: ${s.reason}</li>`);
+  return `<div class="setup-needed">
+    <h2>⚙️ Setup needed</h2>
+    <p>Fill them in under <b>⚙️ Settings</b>, or add them to your <code>.env</code> file (see <code>.env.example</code>), and try again:</p>
+    <ul>${missing.concat(invalid).join("")}</ul>
+  </div>`;
+}
+
+// Settings form; saved settings take precedence over .env. Secrets are never shown: they're kept in
+// the OS keychain, and leaving them empty keeps the current ones.
+function renderSettings(s, credentials) {
+  const sources = { keychain: "stored in the OS keychain", environment: "from .env — removing it lasts until the next start" };
+  const text = (name, label, value, type = "text", placeholder = "") =>
+    `<label>${label}<input name="${name}" type="${type}" value="${escapeHtml(value)}" placeholder="${placeholder}"></label>
+     <div class="field-error" data-field="${name}"></div>`;
+  const secret = (name, label) => {
+    const source = credentials.find((c) => c.name === name)?.source;
+    const clear = source ? `<button type="button" class="clear-credential-btn" data-name="${name}">Remove</button>` : "";
+    return `${text(name, label, "", "password", source ? "unchanged" : "not set")}
+      <small>${sources[source] || "not set"}</small> ${clear}`;
+  };
+  const check = (name, label, value) => `<label class="settings-check"><input name="${name}" type="checkbox" ${value ? "checked" : ""}> ${label}</label>`;
+  return `<form id="settings-form" class="settings-form">
+    <h2>⚙️ Settings</h2>
+    ${text("org", "Organization", s.org)}
+    ${text("project", "Project", s.project)}
+    ${text("user_email", "Your email", s.user_email, "email")}
+    ${secret("azure_devops_pat", "Personal access token")}
+    ${secret("azure_devops_pat_secondary", "Second personal access token (used while rotating)")}
+    ${secret("openai_api_key", "OpenAI API key")}
+    ${text("openai_model", "OpenAI model", s.openai_model, "text", "gpt-4o")}
+    ${check("ai_triage", "AI triage (summary, category and verdict per bug)", s.ai_triage)}
+    ${check("ai_categorization", "AI categorization next to the keyword categories", s.ai_categorization)}
+    <div class="field-error" data-field="settings"></div>
+    <button type="submit" class="refresh-btn">💾 Save</button>
+  </form>`;
+}
+
+function escapeHtml(text) {
+  const div = document.createElement("div");
+  div.textContent = text;
+  return div.innerHTML.replace(/"/g, "&quot;");
+}
+
+function errorText(err) {
+  return err.message ?? err;
+}
+
+// Typed backend errors ({ kind, message, hint }) show their hint; other commands still reject with a string.
+function renderError(err) {
+  const message = `<span style='color:red;'>Error: ${escapeHtml(errorText(err))}</span>`;
+  return err.hint ? `${message}<div class="warning">💡 ${escapeHtml(err.hint)}</div>` : message;
+}
+
+// Notes captured with the quick note shortcut, each with buttons to file it as a bug or drop it.
+function renderNotes(notes) {
+  if (notes.length === 0) {
+    return `<div class="initial-message">No quick notes. Set <code>BUGGER_CAPTURE_SHORTCUT</code> (e.g. Alt+Shift+N) to jot them down from anywhere with a global shortcut.</div>`;
+  }
+  const items = notes.map(
+    (n) => `<div class="quick-note">
+      <div class="quick-note-text">${escapeHtml(n.text).replace(/\n/g, "<br>")}</div>
+      <small>${new Date(n.created).toLocaleString()}</small>
+      <button class="file-note-btn" data-note-id="${n.id}">🐞 File as bug</button>
+      <button class="delete-note-btn" data-note-id="${n.id}">🗑️ Delete</button>
+    </div>`,
+  );
+  return `<h2>📝 Quick notes</h2>${items.join("")}`;
+}
+
+// Attach panel: capture the screen or drop an image, pick the bug and say what the image shows.
+function renderAttach() {
+  return `<form id="attach-form" class="attach-form">
+    <h2>📷 Attach a screenshot</h2>
+    <div class="drop-zone">Drop an image here, or <button type="button" class="capture-btn">capture the screen</button></div>
+    <img class="attach-preview" hidden>
+    <label>Bug #<input name="bug_id" type="number" min="1" required></label>
+    <label>What does it show?<textarea name="comment" rows="3" placeholder="Optional, posted as a comment with the image"></textarea></label>
+    <div class="attach-status"></div>
+    <button type="submit" class="refresh-btn" disabled>📎 Attach</button>
+  </form>`;
+}
+
+// Must match EVENT_SCHEMA_VERSION in events.rs.
+const UPDATE_SCHEMA_VERSION = 1;
+
+// Follow bug updates from background refreshes and show the open bug count and the last change.
+// A gap in the sequence means batches were missed, so start over from a fresh snapshot.
+function subscribeUpdates(liveStatus) {
+  const bugs = new Map();
+  let sequence = null;
+  let current = true;
+  const onUpdate = new Channel();
+  onUpdate.onmessage = (batch) => {
+    if (!current) return;
+    if (batch.version !== UPDATE_SCHEMA_VERSION) {
+      liveStatus.textContent = "⚠️ Live updates are unavailable: the app and its window are different versions.";
+      current = false;
+      return;
+    }
+    if (!batch.snapshot && sequence !== null && batch.sequence !== sequence + 1) {
+      current = false;
+      subscribeUpdates(liveStatus);
+      return;
+    }
+    sequence = batch.sequence;
+    if (batch.snapshot) bugs.clear();
+    const counts = { added: 0, updated: 0, removed: 0 };
+    for (const event of batch.events) {
+      if (event.type === "added" || event.type === "updated") bugs.set(event.bug.id, event.bug);
+      if (event.type === "removed") bugs.delete(event.id);
+      if (event.type in counts) counts[event.type] += 1;
+    }
+    const changes = Object.entries(counts).filter(([, n]) => n > 0).map(([what, n]) => `${n} ${what}`);
+    liveStatus.textContent = `🟢 ${bugs.size} bug(s)`;
+    if (!batch.snapshot && changes.length) {
+      liveStatus.textContent += ` · ${new Date().toLocaleTimeString()}: ${changes.join(", ")}`;
+    }
+  };
+  invoke("subscribe_updates", { onUpdate }).catch((err) => {
+    liveStatus.textContent = `Live updates unavailable: ${err}`;
+  });
+}
 
 window.addEventListener("DOMContentLoaded", () => {
   const fetchBugsBtn = document.getElementById("fetch-bugs-btn");
+  const fullRefreshBtn = document.getElementById("full-refresh-btn");
+  const notesBtn = document.getElementById("notes-btn");
+  const settingsBtn = document.getElementById("settings-btn");
+  const attachBtn = document.getElementById("attach-btn");
   const reportArea = document.getElementById("report-area");
+  const liveStatus = document.getElementById("live-status");
+
+  if (liveStatus) {
+    subscribeUpdates(liveStatus);
+  }
 
   // Show initial message on load
   if (reportArea) {
@@ -15,11 +152,343 @@ window.addEventListener("DOMContentLoaded", () => {
     fetchBugsBtn.addEventListener("click", async () => {
       reportArea.innerHTML = `<div class="spinner"></div><em>Fetching and analyzing bugs...</em>`;
       try {
+        const status = await invoke("get_config_status");
+        if (!status.ready) {
+          reportArea.innerHTML = renderSetup(status);
+          return;
+        }
         const report = await invoke("fetch_and_analyze_bugs");
-        reportArea.innerHTML = report;
+        // Viewers can't change work items, so their report has no write buttons.
+        document.body.classList.toggle("read-only", (await invoke("get_role")) === "viewer");
+        const warnings = status.warnings.map((w) => `<div class="warning">⚠️ <code>${w.name}</code>: ${w.reason}</div>`);
+        reportArea.innerHTML = warnings.join("") + report;
+      } catch (err) {
+        reportArea.innerHTML = renderError(err);
+      }
+    });
+  }
+
+  if (fullRefreshBtn && fetchBugsBtn && reportArea) {
+    fullRefreshBtn.addEventListener("click", async () => {
+      fullRefreshBtn.disabled = true;
+      reportArea.innerHTML = `<div class="spinner"></div><em>Fetching every bug again...</em>`;
+      try {
+        await invoke("force_full_refresh");
+        fetchBugsBtn.click();
+      } catch (err) {
+        reportArea.innerHTML = renderError(err);
+      } finally {
+        fullRefreshBtn.disabled = false;
+      }
+    });
+  }
+
+  if (notesBtn && reportArea) {
+    const showNotes = async () => {
+      try {
+        reportArea.innerHTML = renderNotes(await invoke("list_quick_notes"));
       } catch (err) {
         reportArea.innerHTML = `<span style='color:red;'>Error: ${err}</span>`;
       }
+    };
+    notesBtn.addEventListener("click", showNotes);
+
+    reportArea.addEventListener("click", async (event) => {
+      const fileBtn = event.target.closest(".file-note-btn");
+      const deleteBtn = event.target.closest(".delete-note-btn");
+      const btn = fileBtn || deleteBtn;
+      if (!btn) return;
+      const id = Number(btn.dataset.noteId);
+      btn.disabled = true;
+      try {
+        if (deleteBtn) {
+          await invoke("delete_quick_note", { id });
+        } else {
+          let filed;
+          try {
+            filed = await invoke("file_quick_note", { id });
+          } catch (err) {
+            if (!window.confirm(`${errorText(err)}\n\nFile it anyway?`)) throw err;
+            filed = await invoke("file_quick_note", { id, fileAnyway: true });
+          }
+          alert(filed.id ? `Filed as #${filed.id}` : "Simulation mode: nothing was filed.");
+        }
+        await showNotes();
+      } catch (err) {
+        alert(`Quick note action failed: ${errorText(err)}`);
+        btn.disabled = false;
+      }
+    });
+  }
+
+  if (settingsBtn && reportArea) {
+    settingsBtn.addEventListener("click", async () => {
+      reportArea.innerHTML = renderSettings(await invoke("get_settings"), await invoke("get_credential_status"));
+    });
+
+    reportArea.addEventListener("click", async (event) => {
+      const btn = event.target.closest(".clear-credential-btn");
+      if (!btn) return;
+      try {
+        await invoke("clear_credential", { name: btn.dataset.name });
+        settingsBtn.click();
+      } catch (err) {
+        alert(`${err}`);
+      }
+    });
+
+    reportArea.addEventListener("submit", async (event) => {
+      const form = event.target.closest("#settings-form");
+      if (!form) return;
+      event.preventDefault();
+      const value = (name) => form.elements[name].value;
+      const settings = {
+        org: value("org"),
+        project: value("project"),
+        user_email: value("user_email"),
+        azure_devops_pat: value("azure_devops_pat"),
+        azure_devops_pat_secondary: value("azure_devops_pat_secondary"),
+        openai_api_key: value("openai_api_key"),
+        openai_model: value("openai_model"),
+        ai_triage: form.elements.ai_triage.checked,
+        ai_categorization: form.elements.ai_categorization.checked,
+      };
+      form.querySelectorAll(".field-error").forEach((el) => (el.textContent = ""));
+      const saveBtn = form.querySelector("button[type=submit]");
+      saveBtn.disabled = true;
+      saveBtn.textContent = "Checking…";
+      try {
+        await invoke("save_settings", { settings });
+        saveBtn.textContent = "✅ Saved";
+      } catch (errors) {
+        for (const e of Array.isArray(errors) ? errors : [{ field: "settings", message: String(errors) }]) {
+          const el = form.querySelector(`.field-error[data-field="${e.field}"]`);
+          if (el) el.textContent = e.message;
+        }
+        saveBtn.textContent = "💾 Save";
+      }
+      saveBtn.disabled = false;
+    });
+  }
+
+  if (attachBtn && reportArea) {
+    // The image waiting to be attached: base64 data and, for dropped files, the file name.
+    let pending = null;
+    const setImage = (form, image, fileName, type = "image/png") => {
+      pending = { image, fileName };
+      const preview = form.querySelector(".attach-preview");
+      preview.src = `data:${type};base64,${image}`;
+      preview.hidden = false;
+      form.querySelector("button[type=submit]").disabled = false;
+    };
+    attachBtn.addEventListener("click", () => {
+      pending = null;
+      reportArea.innerHTML = renderAttach();
+    });
+
+    reportArea.addEventListener("click", async (event) => {
+      const btn = event.target.closest(".capture-btn");
+      if (!btn) return;
+      const form = btn.closest("#attach-form");
+      const status = form.querySelector(".attach-status");
+      btn.disabled = true;
+      try {
+        setImage(form, await invoke("capture_screenshot"), null);
+        status.textContent = "";
+      } catch (err) {
+        status.textContent = `${err}`;
+      }
+      btn.disabled = false;
+    });
+
+    reportArea.addEventListener("dragover", (event) => {
+      if (event.target.closest(".drop-zone")) event.preventDefault();
+    });
+    reportArea.addEventListener("drop", (event) => {
+      const zone = event.target.closest(".drop-zone");
+      if (!zone) return;
+      event.preventDefault();
+      const file = [...event.dataTransfer.files].find((f) => f.type.startsWith("image/"));
+      const form = zone.closest("#attach-form");
+      if (!file) {
+        form.querySelector(".attach-status").textContent = "That isn't an image.";
+        return;
+      }
+      const reader = new FileReader();
+      reader.onload = () => setImage(form, reader.result.split(",")[1], file.name, file.type);
+      reader.readAsDataURL(file);
+    });
+
+    reportArea.addEventListener("submit", async (event) => {
+      const form = event.target.closest("#attach-form");
+      if (!form || !pending) return;
+      event.preventDefault();
+      const status = form.querySelector(".attach-status");
+      const submit = form.querySelector("button[type=submit]");
+      submit.disabled = true;
+      try {
+        const attached = await invoke("attach_image", {
+          id: Number(form.elements.bug_id.value),
+          image: pending.image,
+          fileName: pending.fileName,
+          comment: form.elements.comment.value || null,
+        });
+        status.textContent = attached.url ? `✅ Attached ${attached.file_name} to #${attached.bug_id}` : "Simulation mode: nothing was attached.";
+        if (attached.warning) status.textContent += ` ⚠️ ${attached.warning}`;
+        return;
+      } catch (err) {
+        status.innerHTML = renderError(err);
+      }
+      submit.disabled = false;
+    });
+  }
+
+  // Jump list and dock menu actions: refresh, or show the latest report (refreshing if there's none yet).
+  async function runQuickAction(quick) {
+    if (!quick || !fetchBugsBtn || !reportArea) return;
+    const report = quick.action === "open_report" ? await invoke("get_last_report") : null;
+    if (report) {
+      reportArea.innerHTML = report;
+    } else {
+      fetchBugsBtn.click();
+    }
+  }
+  listen("quick-action", (event) => runQuickAction(event.payload));
+  invoke("take_quick_action").then(runQuickAction);
+
+  // "Show full description" buttons on truncated descriptions: load the full HTML on demand.
+  if (reportArea) {
+    reportArea.addEventListener("click", async (event) => {
+      const btn = event.target.closest(".show-more-btn");
+      if (!btn) return;
+      btn.disabled = true;
+      try {
+        const html = await invoke("get_bug_description", { id: Number(btn.dataset.bugId) });
+        btn.closest(".description").innerHTML = html;
+      } catch (err) {
+        alert(`Could not load description: ${errorText(err)}`);
+        btn.disabled = false;
+      }
+    });
+  }
+
+  // "Ask for info" buttons: pick a needs-info template draft, let the user edit, then post.
+  if (reportArea) {
+    reportArea.addEventListener("click", async (event) => {
+      const btn = event.target.closest(".needs-info-btn");
+      if (!btn) return;
+      const id = Number(btn.dataset.bugId);
+      btn.disabled = true;
+      try {
+        const drafts = await invoke("draft_needs_info_comments", { id });
+        let draft = drafts[0];
+        if (!draft) {
+          alert(`Nothing seems to be missing on #${id}.`);
+        } else if (drafts.length > 1) {
+          const choices = drafts.map((d, i) => `${i + 1}. ${d.label}`).join("\n");
+          const choice = window.prompt(`Which comment for #${id}?\n${choices}`, "1");
+          draft = choice ? drafts[Number(choice) - 1] : null;
+        }
+        const text = draft && window.prompt(`Post this comment on #${id}?`, draft.text);
+        if (text) {
+          await invoke("post_comment", { id, text });
+          btn.textContent = "✅ Asked";
+          return;
+        }
+      } catch (err) {
+        alert(`Could not draft comment: ${errorText(err)}`);
+      }
+      btn.disabled = false;
+    });
+  }
+
+  // "Open in VS Code" buttons next to file paths found in a bug.
+  if (reportArea) {
+    reportArea.addEventListener("click", async (event) => {
+      const btn = event.target.closest(".open-editor-btn");
+      if (!btn) return;
+      try {
+        await invoke("open_in_editor", { path: btn.dataset.path, line: Number(btn.dataset.line) });
+      } catch (err) {
+        alert(`Could not open editor: ${err}`);
+      }
+    });
+  }
+
+  // "Nudge" buttons in the Waiting on Reporter section: draft, let the user edit, then post.
+  if (reportArea) {
+    reportArea.addEventListener("click", async (event) => {
+      const btn = event.target.closest(".nudge-btn");
+      if (!btn) return;
+      const id = Number(btn.dataset.bugId);
+      btn.disabled = true;
+      try {
+        const draft = await invoke("draft_nudge_comment", { id });
+        const text = window.prompt(`Post this comment on #${id}?`, draft);
+        if (text) {
+          await invoke("post_comment", { id, text });
+          btn.textContent = "✅ Nudged";
+          return;
+        }
+      } catch (err) {
+        alert(`Nudge failed: ${errorText(err)}`);
+      }
+      btn.disabled = false;
+    });
+  }
+
+  // "Reassign" buttons next to owner suggestions.
+  if (reportArea) {
+    reportArea.addEventListener("click", async (event) => {
+      const btn = event.target.closest(".reassign-btn");
+      if (!btn) return;
+      const id = Number(btn.dataset.bugId);
+      if (!window.confirm(`Assign #${id} to ${btn.dataset.to}?`)) return;
+      btn.disabled = true;
+      try {
+        await invoke("reassign_bug", { id, to: btn.dataset.to });
+        btn.textContent = "✅ Reassigned";
+        return;
+      } catch (err) {
+        btn.insertAdjacentHTML("afterend", renderError(err));
+      }
+      btn.disabled = false;
+    });
+  }
+
+  // "Work on this" buttons: start a focused session; when it's over, offer to log time and comment.
+  if (reportArea) {
+    reportArea.addEventListener("click", async (event) => {
+      const btn = event.target.closest(".work-session-btn");
+      if (!btn) return;
+      const onEnd = new Channel();
+      onEnd.onmessage = async (session) => {
+        const logTime = window.confirm(`Session on #${session.bug_id} is over. Add the time to Completed Work?`);
+        const comment = window.prompt(`Progress comment for #${session.bug_id} (leave empty to skip):`, "");
+        // A failed comment keeps the session, so finishing again posts it without retyping; a failed
+        // time write-back is retried on its own.
+        for (;;) {
+          try {
+            const report = await invoke("get_time_report", {});
+            const failed = report.failed_write_backs.find((e) => e.bug_id === session.bug_id && e.started === session.started);
+            if (failed) {
+              await invoke("retry_time_write_back", { bugId: failed.bug_id, started: failed.started });
+            } else {
+              await invoke("finish_work_session", { logTime, comment: comment || null });
+            }
+            break;
+          } catch (err) {
+            if (!window.confirm(`Could not finish session: ${errorText(err)}\n\nTry again?`)) break;
+          }
+        }
+        btn.textContent = "🍅 Work on this";
+      };
+      try {
+        await invoke("start_work_session", { id: Number(btn.dataset.bugId), onEnd });
+        btn.textContent = "🍅 In session…";
+      } catch (err) {
+        alert(`Could not start session: ${err}`);
+      }
     });
   }
 });